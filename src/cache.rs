@@ -0,0 +1,209 @@
+//! Cached inspection results.
+//!
+//! `inspect --cache <FILE>` saves the scan results as JSON once a scan
+//! finishes. A later run with the same file skips the counting pass, using the
+//! cached file count as its progress estimate, and reports which files were
+//! added, changed, or removed since. With `--offline` the cached results are
+//! shown without mounting or walking the source at all.
+//!
+//! A cache is only used when it has the current [`CACHE_VERSION`] and was
+//! written for the same sources, so results from another drive are never
+//! mistaken for this one.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::mount::is_device_path;
+use crate::scanner::ScanStats;
+
+/// Format version of the cache file; caches written with another version are ignored.
+pub const CACHE_VERSION: u32 = 2;
+
+/// Cache file contents as written to disk.
+#[derive(Serialize, Deserialize)]
+struct CacheFile<S> {
+    version: u32,
+    /// When the cache was written, in RFC 3339
+    written: String,
+    /// The sources the results were scanned from, as given by [`source_key`]
+    sources: Vec<String>,
+    stats: S,
+}
+
+/// Only the version, read first so files from other versions are rejected cleanly.
+#[derive(Deserialize)]
+struct CacheHeader {
+    version: u32,
+}
+
+/// Results loaded from a cache file.
+pub struct Cached {
+    /// When the cache was written, in RFC 3339
+    pub written: String,
+    /// The sources the results were scanned from
+    pub sources: Vec<String>,
+    pub stats: ScanStats,
+}
+
+/// How a new scan differs from the cached one, by file path.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheChanges {
+    pub added: usize,
+    /// Files whose size or modification time changed
+    pub changed: usize,
+    pub removed: usize,
+}
+
+/// Returns the name a source is recorded under in the cache.
+///
+/// Devices keep their path; directories are made absolute so the same
+/// directory matches however it was written on the command line.
+pub fn source_key(drive: &str) -> String {
+    if is_device_path(drive) {
+        return drive.to_string();
+    }
+    std::fs::canonicalize(drive)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| drive.to_string())
+}
+
+/// Reads the cache at `path` for `drives`.
+///
+/// With no `drives`, a cache for any sources is accepted. Returns why the
+/// cache cannot be used if it is missing, unreadable, from another format
+/// version, or written for different sources.
+pub fn load_cache(path: &Path, drives: &[String]) -> Result<Cached, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+
+    let header: CacheHeader = serde_json::from_str(&content)
+        .map_err(|e| format!("{} is not a tap cache: {}", path.display(), e))?;
+    if header.version != CACHE_VERSION {
+        return Err(format!(
+            "{} has format version {}, expected {}",
+            path.display(),
+            header.version,
+            CACHE_VERSION
+        ));
+    }
+
+    let cache: CacheFile<ScanStats> = serde_json::from_str(&content)
+        .map_err(|e| format!("{} is damaged: {}", path.display(), e))?;
+
+    let sources: Vec<String> = drives.iter().map(|d| source_key(d)).collect();
+    if !sources.is_empty() && sources != cache.sources {
+        return Err(format!(
+            "{} was written for {}, not {}",
+            path.display(),
+            cache.sources.join(", "),
+            sources.join(", ")
+        ));
+    }
+
+    Ok(Cached {
+        written: cache.written,
+        sources: cache.sources,
+        stats: cache.stats,
+    })
+}
+
+/// Writes `stats` for `drives` to the cache at `path`.
+///
+/// The file is written under a temporary name and renamed into place, so an
+/// interrupted write never leaves a truncated cache behind.
+pub fn save_cache(path: &Path, drives: &[String], stats: &ScanStats) -> color_eyre::Result<()> {
+    let cache = CacheFile {
+        version: CACHE_VERSION,
+        written: chrono::Local::now().to_rfc3339(),
+        sources: drives.iter().map(|d| source_key(d)).collect(),
+        stats,
+    };
+
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    std::fs::write(&partial, serde_json::to_string(&cache)?)?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Compares a new scan with the cached one by path, size, and modification time.
+pub fn compare(cached: &ScanStats, current: &ScanStats) -> CacheChanges {
+    let files = |stats: &ScanStats| -> HashMap<_, (u64, Option<SystemTime>)> {
+        stats
+            .files_by_category
+            .values()
+            .flatten()
+            .map(|file| (file.path.clone(), (file.size, file.modified)))
+            .collect()
+    };
+    let (before, after) = (files(cached), files(current));
+
+    let mut changes = CacheChanges::default();
+    for (path, state) in &after {
+        match before.get(path) {
+            None => changes.added += 1,
+            Some(previous) if previous != state => changes.changed += 1,
+            Some(_) => {}
+        }
+    }
+    changes.removed = before.keys().filter(|p| !after.contains_key(*p)).count();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::scanner::scan_directory;
+
+    #[tokio::test]
+    async fn test_cache_round_trip_and_changes() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("drive");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("report.pdf"), b"report").unwrap();
+        std::fs::write(source.join("notes.txt"), b"notes").unwrap();
+        let drives = vec![source.display().to_string()];
+        let cache_path = root.path().join("drive.cache.json");
+
+        let config = Config::default();
+        let stats = scan_directory(&source, &config, |_| {}).await.unwrap();
+        save_cache(&cache_path, &drives, &stats).unwrap();
+
+        let cached = load_cache(&cache_path, &drives).unwrap();
+        assert_eq!(cached.stats.total_files, 2);
+        assert_eq!(cached.stats.root, source);
+        assert_eq!(compare(&cached.stats, &stats), CacheChanges::default());
+
+        // Any sources are accepted when none are given, other sources are not
+        assert!(load_cache(&cache_path, &[]).is_ok());
+        let other = vec![root.path().display().to_string()];
+        assert!(load_cache(&cache_path, &other).is_err());
+
+        std::fs::write(source.join("notes.txt"), b"longer notes").unwrap();
+        std::fs::remove_file(source.join("report.pdf")).unwrap();
+        std::fs::write(source.join("photo.jpg"), b"jpg").unwrap();
+        let rescanned = scan_directory(&source, &config, |_| {}).await.unwrap();
+        assert_eq!(
+            compare(&cached.stats, &rescanned),
+            CacheChanges {
+                added: 1,
+                changed: 1,
+                removed: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_cache_rejects_other_versions() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let cache_path = root.path().join("old.json");
+        std::fs::write(&cache_path, r#"{"version": 0, "files": []}"#).unwrap();
+
+        let err = load_cache(&cache_path, &[]).err().unwrap();
+        assert!(err.contains("format version 0"));
+        assert!(load_cache(&root.path().join("missing.json"), &[]).is_err());
+    }
+}
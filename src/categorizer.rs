@@ -0,0 +1,254 @@
+//! Pluggable file categorization.
+//!
+//! The scanner decides which files to visit; a [`Categorizer`] decides which
+//! category each of them belongs to. [`ConfigCategorizer`] is the built-in
+//! policy used by the `inspect` and `export` commands. Library users can pass
+//! their own to [`scan_directory_with_categorizer`], for example to sniff file
+//! contents instead of trusting extensions.
+//!
+//! Whatever the categorizer returns, files in `scan.exclude_categories` are
+//! still left out. A category need not appear in `[categories]`; its files
+//! are exported into a directory of its name unless `[category_dirs]` renames it.
+//!
+//! `tap categories` lists the categories the configuration defines, with
+//! [`category_listing`] and [`list_categories`].
+//!
+//! [`scan_directory_with_categorizer`]: crate::scanner::scan_directory_with_categorizer
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+
+use crate::categories::get_extension;
+use crate::config::{CategoryMap, Config};
+use crate::mime::{Mime, MimeMap};
+use crate::rules::RuleSet;
+use crate::scanner::relative_base;
+use crate::tui::UI;
+
+/// Width extension lists are wrapped at by [`list_categories`].
+const LISTING_WIDTH: usize = 70;
+
+/// Decides the category of each file a scan records.
+///
+/// The scan runs on a blocking thread, so implementations must be `Send` and
+/// `Sync`. `categorize` is called for every file and bundle that passes the
+/// exclusion patterns, before the date filters are applied.
+pub trait Categorizer: Send + Sync {
+    /// Returns the category of the file at `path`, whose metadata is `meta`.
+    fn categorize(&self, path: &Path, meta: &Metadata) -> String;
+
+    /// Returns the category of a file whose MIME type was detected, with
+    /// `scan.detect_mime` set. Ignores the type unless overridden.
+    fn categorize_with_mime(&self, path: &Path, meta: &Metadata, _mime: &Mime) -> String {
+        self.categorize(path, meta)
+    }
+}
+
+/// Categorizes files by path rules, file name, and extension, as configured.
+///
+/// The first `[[rules]]` pattern matching the path relative to the scan root
+/// decides a file's category; otherwise its name in `[filenames]` does, and
+/// then its extension in `[categories]`. Unknown files are `misc`. With a
+/// detected MIME type, `[mime_categories]` is consulted as described in
+/// [`crate::mime`].
+#[derive(Debug, Clone)]
+pub struct ConfigCategorizer {
+    root: PathBuf,
+    categories: CategoryMap,
+    rules: RuleSet,
+    mime: MimeMap,
+}
+
+impl ConfigCategorizer {
+    /// Creates the categorizer for a scan of `root` with `config`.
+    pub fn new(root: &Path, config: &Config) -> Self {
+        Self {
+            root: relative_base(root).to_path_buf(),
+            categories: config.category_map(),
+            rules: RuleSet::new(&config.rules),
+            mime: MimeMap::new(&config.mime_categories),
+        }
+    }
+
+    /// Returns the category of the file at `path`; metadata is not needed.
+    pub fn category(&self, path: &Path) -> &str {
+        self.category_with_mime(path, None)
+    }
+
+    /// Returns the category of the file at `path`, whose MIME type is `mime` if detected.
+    pub fn category_with_mime(&self, path: &Path, mime: Option<&Mime>) -> &str {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        if let Some(category) = self.rules.category(relative) {
+            return category;
+        }
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        if let Some(category) = self.categories.filename_category(&file_name) {
+            return category;
+        }
+
+        let extension = get_extension(path);
+        let mime_category = match mime {
+            Some(Mime::Content(mime)) => self.mime.category(mime),
+            Some(Mime::Extension(mime)) if !self.categories.knows_extension(&extension) => {
+                self.mime.category(mime)
+            }
+            _ => None,
+        };
+        mime_category.unwrap_or_else(|| self.categories.category(&extension))
+    }
+}
+
+impl Categorizer for ConfigCategorizer {
+    fn categorize(&self, path: &Path, _meta: &Metadata) -> String {
+        self.category(path).to_string()
+    }
+
+    fn categorize_with_mime(&self, path: &Path, _meta: &Metadata, mime: &Mime) -> String {
+        self.category_with_mime(path, Some(mime)).to_string()
+    }
+}
+
+/// What files one category takes, as listed by `tap categories`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategoryInfo {
+    /// Extensions that resolve to this category, lowercase with their dot, in config order
+    pub extensions: Vec<String>,
+    /// File names from `[filenames]` assigned to this category, sorted
+    pub filenames: Vec<String>,
+    /// Directory the category is exported into
+    pub directory: String,
+}
+
+/// Lists the categories `config` defines, by name.
+///
+/// An extension listed under several categories appears only under the one
+/// that wins by `category_priority`, so the listing matches how files are
+/// actually categorized. Categories used only in `[filenames]` are included.
+pub fn category_listing(config: &Config) -> BTreeMap<String, CategoryInfo> {
+    let map = config.category_map();
+    let info = |name: &str| CategoryInfo {
+        extensions: Vec::new(),
+        filenames: Vec::new(),
+        directory: config.category_dir(name).to_string(),
+    };
+
+    let mut listing = BTreeMap::new();
+    for (name, extensions) in &config.categories {
+        let category = listing.entry(name.clone()).or_insert_with(|| info(name));
+        for extension in extensions {
+            let extension = extension.to_lowercase();
+            if map.category(&extension) == name && !category.extensions.contains(&extension) {
+                category.extensions.push(extension);
+            }
+        }
+    }
+    for (file_name, name) in &config.filenames {
+        listing
+            .entry(name.clone())
+            .or_insert_with(|| info(name))
+            .filenames
+            .push(file_name.clone());
+    }
+    for category in listing.values_mut() {
+        category.filenames.sort_by_key(|name| name.to_lowercase());
+    }
+    listing
+}
+
+/// Prints the categories of `config` with their extensions, or as a JSON
+/// object keyed by category name with `json`.
+pub fn list_categories(config: &Config, json: bool) -> color_eyre::Result<()> {
+    let listing = category_listing(config);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&listing)?);
+        return Ok(());
+    }
+
+    let (info_style, ..) = UI::get_static_status_styles(&config.ui.color.theme);
+    let dim = console::Style::new().dim();
+    for (name, category) in &listing {
+        let mut header = format!("{} ({} extensions)", name, category.extensions.len());
+        if category.directory != *name {
+            header.push_str(&format!(", exported to {}/", category.directory));
+        }
+        println!("{}", info_style.apply_to(header).bold());
+        for line in wrap_list(&category.extensions) {
+            println!("  {}", line);
+        }
+        if !category.filenames.is_empty() {
+            for line in wrap_list(&category.filenames) {
+                println!("  {}", dim.apply_to(line));
+            }
+        }
+        println!();
+    }
+    println!(
+        "{}",
+        dim.apply_to(
+            "Files matching no category are misc. Edit [categories] in config.toml to customize."
+        )
+    );
+    Ok(())
+}
+
+/// Joins `items` with commas into lines of at most [`LISTING_WIDTH`] characters.
+fn wrap_list(items: &[String]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for item in items {
+        if !line.is_empty() && line.len() + item.len() + 2 > LISTING_WIDTH {
+            lines.push(line.trim_end().to_string());
+            line.clear();
+        }
+        line.push_str(item);
+        line.push_str(", ");
+    }
+    if !line.is_empty() {
+        lines.push(line.trim_end_matches([',', ' ']).to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_listing_follows_config() {
+        let mut config = Config::default();
+        config.categories.insert(
+            "exhibits".to_string(),
+            vec![".E01".to_string(), ".pdf".to_string()],
+        );
+        config
+            .category_dirs
+            .insert("exhibits".to_string(), "Exhibits".to_string());
+        config
+            .filenames
+            .insert("CASE.txt".to_string(), "casefiles".to_string());
+
+        let listing = category_listing(&config);
+        // .pdf stays with documents, which ranks above the new category
+        assert_eq!(listing["exhibits"].extensions, [".e01"]);
+        assert_eq!(listing["exhibits"].directory, "Exhibits");
+        assert!(
+            listing["documents"]
+                .extensions
+                .contains(&".pdf".to_string())
+        );
+        assert_eq!(listing["casefiles"].filenames, ["CASE.txt"]);
+        assert!(listing["casefiles"].extensions.is_empty());
+
+        let items: Vec<String> = (0..30).map(|i| format!(".ext{}", i)).collect();
+        let lines = wrap_list(&items);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= LISTING_WIDTH));
+        // Lines that continue on the next end with a comma
+        assert_eq!(lines.join(" "), items.join(", "));
+    }
+}
@@ -3,9 +3,15 @@
 //! This module defines the CLI structure using clap, including all commands
 //! and their arguments.
 
+use crate::config::{ChartBy, CollisionStrategy, Config, SizeBase, check_rename_template};
+use crate::profile::Profile;
+use crate::rules::{RulesFile, load_rules_file};
+use crate::throttle::{parse_rate, parse_size};
 use crate::tui::BANNER;
-use clap::{Parser, Subcommand};
+use crate::zip::parse_split_size;
+use clap::{ArgGroup, Args as ClapArgs, Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 #[derive(Parser)]
 #[command(name = "tap")]
@@ -20,26 +26,489 @@ pub struct Args {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Inspect a drive and catalog its contents
+    #[command(group(ArgGroup::new("hashed").args(["out_manifest_only", "diff"]).multiple(true)))]
     Inspect {
-        /// Drive or path to inspect (e.g, /dev/sda or /mnt/evidence)
-        drive: Option<String>,
+        /// Drives or paths to inspect together (e.g, /dev/sda1 /dev/sdb1 /mnt/evidence)
+        drives: Vec<String>,
 
         /// Write a text log file summarizing the inspection results
         #[arg(long)]
         log: bool,
+
+        /// Gzip-compress the log file (written with a .gz suffix)
+        #[arg(long, requires = "log")]
+        compress_log: bool,
+
+        /// Write log timestamps and the log file name in UTC instead of local time
+        #[arg(long, requires = "log")]
+        utc: bool,
+
+        /// Write a self-contained HTML report with charts to this path
+        #[arg(long, value_name = "PATH")]
+        report_html: Option<PathBuf>,
+
+        /// Write manifest.csv for every scanned file to this path, without exporting anything
+        #[arg(long, value_name = "PATH")]
+        out_manifest_only: Option<PathBuf>,
+
+        /// Read each file in place to record its SHA-256 for --out-manifest-only and --diff
+        #[arg(long, requires = "hashed", conflicts_with = "offline")]
+        hash: bool,
+
+        /// Compare the results with another capture: a manifest.csv, an export, or a directory
+        #[arg(long, value_name = "PATH")]
+        diff: Option<PathBuf>,
+
+        /// Save results to this file and reuse them on later runs over the same sources
+        #[arg(long, value_name = "FILE")]
+        cache: Option<PathBuf>,
+
+        /// Show the results saved in --cache without mounting or scanning
+        #[arg(long, requires = "cache")]
+        offline: bool,
+
+        /// Mount disk images found on the drive (.img, .vmdk, .qcow2, .vhd, ...) read-only and scan them too
+        #[arg(long, conflicts_with = "offline")]
+        descend_images: bool,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+
+        #[command(flatten)]
+        mount: MountArgs,
+
+        #[command(flatten)]
+        display: DisplayArgs,
     },
     /// Export files from a drive organized by type
     Export {
         /// Drive or path to export from (e.g, /dev/sda or /mnt/evidence)
         drive: Option<String>,
 
-        /// Output directory for organized files
+        /// Output directory for organized files [default: ./tap_export_<label>_<timestamp>]
         #[arg(short, long)]
-        output_dir: PathBuf,
+        output_dir: Option<PathBuf>,
 
         /// Create a zip archive of the exported files
         #[arg(long)]
         zip: bool,
+
+        /// Write a self-contained HTML report with charts to this path
+        #[arg(long, value_name = "PATH")]
+        report_html: Option<PathBuf>,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+
+        #[command(flatten)]
+        mount: MountArgs,
+
+        #[command(flatten)]
+        display: DisplayArgs,
+
+        #[command(flatten)]
+        export: ExportArgs,
+    },
+    /// List the partitions the device picker would offer, without prompting
+    ListDevices {
+        /// Print the partitions as a JSON array instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the configured categories and the extensions each one takes
+    Categories {
+        /// Print the categories as a JSON object instead of a list
+        #[arg(long)]
+        json: bool,
+    },
+    /// Diagnose why a device would not mount, without mounting it
+    MountCheck {
+        /// Device to examine (e.g. /dev/sdb1)
+        device: String,
     },
     // TODO: Discover -- find eleigables and output what is most likely data not boot partitions
 }
+
+/// Scanning options shared by `inspect` and `export`.
+///
+/// Each flag overrides the matching `[scan]` setting from the config file.
+#[derive(ClapArgs, Debug, Clone, Default)]
+pub struct ScanArgs {
+    /// Only scan the categories of a built-in preset
+    #[arg(long, value_enum, value_name = "NAME")]
+    pub profile: Option<Profile>,
+
+    /// Do not descend into other filesystems mounted below the source
+    #[arg(long)]
+    pub same_filesystem: bool,
+
+    /// Also scan dotfiles and hidden directories (e.g. .ssh, .config)
+    #[arg(long)]
+    pub include_hidden: bool,
+
+    /// Skip entries matching this pattern, like a .tapignore line (repeatable)
+    #[arg(long = "exclude", value_name = "PATTERN")]
+    pub exclude_patterns: Vec<String>,
+
+    /// Leave out every file in this category (repeatable)
+    #[arg(long = "exclude-category", value_name = "CATEGORY")]
+    pub exclude_categories: Vec<String>,
+
+    /// Skip files with these extensions, whatever their category (e.g. .iso,.vmdk)
+    #[arg(long = "exclude-ext", value_name = "EXTENSIONS", value_delimiter = ',')]
+    pub exclude_extensions: Vec<String>,
+
+    /// Only scan files with these extensions, skipping all others (e.g. .pdf,.docx)
+    #[arg(long = "only-ext", value_name = "EXTENSIONS", value_delimiter = ',')]
+    pub only_extensions: Vec<String>,
+
+    /// Leave out unrecognized files (same as --exclude-category misc)
+    #[arg(long)]
+    pub skip_misc: bool,
+
+    /// List likely sensitive files (keys, credentials, wallets) in the summary and log
+    #[arg(long)]
+    pub flag_sensitive: bool,
+
+    /// List zero-byte files in the summary and log
+    #[arg(long)]
+    pub report_empty: bool,
+
+    /// Count bundle directories (.app, .framework, .rtfd, ...) as single files
+    #[arg(long)]
+    pub bundles_as_files: bool,
+
+    /// Detect each file's MIME type and categorize by it via [mime_categories]
+    #[arg(long)]
+    pub detect_mime: bool,
+
+    /// Stop scanning after this many files; the results are marked as partial
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_files: Option<u64>,
+
+    /// Only include files modified after this reference file was last modified
+    #[arg(long, value_name = "PATH", value_parser = modified_time)]
+    pub newer_than: Option<SystemTime>,
+
+    /// Only include files modified before this reference file was last modified
+    #[arg(long, value_name = "PATH", value_parser = modified_time)]
+    pub older_than: Option<SystemTime>,
+
+    /// Only include files modified since the last --since-last-run of this command on the drive
+    #[arg(long, conflicts_with = "newer_than")]
+    pub since_last_run: bool,
+
+    /// Read `pattern => category` rules from this file, checked before the config's rules
+    #[arg(long = "rules", value_name = "FILE", value_parser = load_rules_file)]
+    pub rules_file: Option<RulesFile>,
+}
+
+impl ScanArgs {
+    /// Applies the command-line overrides to the loaded configuration.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(profile) = self.profile {
+            profile.apply(config);
+        }
+        if self.same_filesystem {
+            config.scan.same_filesystem = true;
+        }
+        if self.include_hidden {
+            config.scan.include_hidden = true;
+        }
+        config
+            .scan
+            .exclude_patterns
+            .extend(self.exclude_patterns.iter().cloned());
+        config
+            .scan
+            .exclude_categories
+            .extend(self.exclude_categories.iter().cloned());
+        config
+            .scan
+            .exclude_extensions
+            .extend(self.exclude_extensions.iter().cloned());
+        config
+            .scan
+            .include_only_extensions
+            .extend(self.only_extensions.iter().cloned());
+        if self.skip_misc {
+            config.scan.exclude_categories.push("misc".to_string());
+        }
+        if self.flag_sensitive {
+            config.scan.flag_sensitive = true;
+        }
+        if self.report_empty {
+            config.scan.report_empty = true;
+        }
+        if self.bundles_as_files {
+            config.scan.bundles_as_files = true;
+        }
+        if self.detect_mime {
+            config.scan.detect_mime = true;
+        }
+        if let Some(max) = self.max_files {
+            config.scan.max_files = Some(max);
+        }
+        if let Some(time) = self.newer_than {
+            config.scan.modified_after = Some(time);
+        }
+        if let Some(time) = self.older_than {
+            config.scan.modified_before = Some(time);
+        }
+        if self.since_last_run {
+            config.scan.since_last_run = true;
+        }
+        if let Some(file) = &self.rules_file {
+            config.rules.splice(0..0, file.rules.iter().cloned());
+        }
+    }
+}
+
+/// Reads the modification time of a `--newer-than` or `--older-than` reference file.
+fn modified_time(path: &str) -> Result<SystemTime, String> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| format!("cannot read the modification time of {}: {}", path, e))
+}
+
+/// Display options shared by `inspect` and `export`.
+///
+/// Each flag overrides the matching `[ui]` setting from the config file.
+#[derive(ClapArgs, Debug, Clone, Default)]
+pub struct DisplayArgs {
+    /// Show sizes in binary (KiB, MiB, GiB) or decimal (KB, MB, GB) units
+    #[arg(long, value_enum, value_name = "BASE")]
+    pub format_size_base: Option<SizeBase>,
+
+    /// Fold categories with fewer than N files into an "other" row in the category chart
+    #[arg(long, value_name = "N")]
+    pub min_category_files: Option<usize>,
+
+    /// Size category chart bars by bytes, by file count, or show both
+    #[arg(long, value_enum, value_name = "BY")]
+    pub chart_by: Option<ChartBy>,
+
+    /// Print each scan and copy error to stderr as it happens
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Skip the file counting pass; the scan shows an open-ended progress bar
+    #[arg(long)]
+    pub no_count: bool,
+
+    /// Skip the interactive summary screens and just write the logs and outputs
+    #[arg(long)]
+    pub no_summary: bool,
+
+    /// Skip the one-line category summary printed at the end of the run
+    #[arg(long)]
+    pub no_compact_summary: bool,
+}
+
+impl DisplayArgs {
+    /// Applies the command-line overrides to the loaded configuration.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(base) = self.format_size_base {
+            config.ui.size_base = base;
+        }
+        if let Some(min) = self.min_category_files {
+            config.ui.min_category_files = min;
+        }
+        if let Some(chart_by) = self.chart_by {
+            config.ui.chart_by = chart_by;
+        }
+        if self.verbose {
+            config.ui.verbose = true;
+        }
+        if self.no_count {
+            config.ui.no_count = true;
+        }
+        if self.no_summary {
+            config.ui.no_summary = true;
+        }
+        if self.no_compact_summary {
+            config.ui.no_compact_summary = true;
+        }
+    }
+}
+
+/// Mount prompt options shared by `inspect` and `export`.
+#[derive(ClapArgs, Debug, Clone, Default)]
+pub struct MountArgs {
+    /// Accept the read-only mount and remount prompts without asking, and
+    /// export a source that looks like an earlier export
+    #[arg(short = 'y', long)]
+    pub assume_yes: bool,
+
+    /// Force-assemble a degraded RAID array without asking
+    #[arg(long)]
+    pub force_degraded: bool,
+
+    /// Mount the device at this path instead of a generated one; it must be empty or not exist
+    #[arg(long, value_name = "PATH")]
+    pub mount_point: Option<PathBuf>,
+
+    /// Leave a device tap mounted in place after the run and print how to unmount it
+    #[arg(long)]
+    pub no_unmount: bool,
+}
+
+impl MountArgs {
+    /// Applies the command-line overrides to the loaded configuration.
+    pub fn apply(&self, config: &mut Config) {
+        config.mount.assume_yes = self.assume_yes;
+        config.mount.force_degraded = self.force_degraded;
+        config.mount.mount_point = self.mount_point.clone();
+        config.mount.no_unmount = self.no_unmount;
+    }
+}
+
+/// Options specific to `export`.
+///
+/// Each flag overrides the matching `[export]` setting from the config file.
+#[derive(ClapArgs, Debug, Clone, Default)]
+pub struct ExportArgs {
+    /// Also compare files over 1 MiB by whole-file SHA-256, so identical large files already in the output are skipped instead of copied again under a new name
+    #[arg(long)]
+    pub checksum_source: bool,
+
+    /// Re-read each copy and its source after copying and fail the file if their SHA-256 differ
+    #[arg(long)]
+    pub verify_copies: bool,
+
+    /// Copy extended attributes and macOS resource forks onto each copy, where the filesystems support them
+    #[arg(long)]
+    pub preserve_xattrs: bool,
+
+    /// Write paths.tsv mapping each exported file back to its original source path
+    #[arg(long)]
+    pub path_map: bool,
+
+    /// Copy every file directly into the output directory, without category subdirectories
+    #[arg(long)]
+    pub flat: bool,
+
+    /// What to do when a different file already has a file's name in the output
+    #[arg(long, value_name = "STRATEGY", value_enum)]
+    pub on_collision: Option<CollisionStrategy>,
+
+    /// Name for renamed duplicates, from {stem}, {n}, and {ext}, e.g. "{stem} ({n}){ext}"
+    #[arg(long, value_name = "TEMPLATE", value_parser = check_rename_template)]
+    pub rename_template: Option<String>,
+
+    /// Write per-category file lists and inventory.csv instead of copying any files
+    #[arg(long, conflicts_with_all = ["checksum_source", "verify_copies", "preserve_xattrs", "path_map"])]
+    pub inventory_only: bool,
+
+    /// Only copy files added or changed since the export that wrote this manifest.csv
+    #[arg(long, value_name = "MANIFEST", conflicts_with = "inventory_only")]
+    pub since_manifest: Option<PathBuf>,
+
+    /// Merge into an existing output directory without asking
+    #[arg(long, conflicts_with = "clean")]
+    pub force: bool,
+
+    /// Delete an existing output directory before exporting, without asking
+    #[arg(long)]
+    pub clean: bool,
+
+    /// Write the --zip archive to this path instead of next to the output directory
+    #[arg(long, value_name = "PATH", requires = "zip")]
+    pub zip_path: Option<PathBuf>,
+
+    /// Split the --zip archive into volumes of at most this size, e.g. 4GB
+    #[arg(long, value_name = "SIZE", requires = "zip", value_parser = parse_split_size)]
+    pub zip_split: Option<u64>,
+
+    /// Gzip-compress tap.log into tap.log.gz
+    #[arg(long)]
+    pub compress_log: bool,
+
+    /// Write tap.log timestamps in UTC instead of local time
+    #[arg(long)]
+    pub utc: bool,
+
+    /// Append JSON progress lines to this file, to follow the run from another session
+    #[arg(long, value_name = "PATH")]
+    pub progress_file: Option<PathBuf>,
+
+    /// Cap combined copy throughput, e.g. 10MiB or 500KB/s
+    #[arg(long, value_name = "RATE", value_parser = parse_rate)]
+    pub rate_limit: Option<u64>,
+
+    /// Copy only the first SIZE bytes of larger files, e.g. 1MiB, for preview datasets
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, conflicts_with = "inventory_only")]
+    pub truncate_to: Option<u64>,
+
+    /// Export at most N randomly chosen files from each category
+    #[arg(long, value_name = "N")]
+    pub sample: Option<usize>,
+
+    /// Seed for --sample, so the same source and seed give the same files
+    #[arg(long, value_name = "N", requires = "sample")]
+    pub seed: Option<u64>,
+}
+
+impl ExportArgs {
+    /// Applies the command-line overrides to the loaded configuration.
+    pub fn apply(&self, config: &mut Config) {
+        if self.checksum_source {
+            config.export.checksum_source = true;
+        }
+        if self.verify_copies {
+            config.export.verify_copies = true;
+        }
+        if self.preserve_xattrs {
+            config.export.preserve_xattrs = true;
+        }
+        if self.path_map {
+            config.export.path_map = true;
+        }
+        if self.flat {
+            config.export.flat = true;
+        }
+        if let Some(strategy) = self.on_collision {
+            config.export.collision_strategy = strategy;
+        }
+        if let Some(template) = &self.rename_template {
+            config.export.rename_template = template.clone();
+        }
+        if self.inventory_only {
+            config.export.inventory_only = true;
+        }
+        if let Some(path) = &self.since_manifest {
+            config.export.since_manifest = Some(path.clone());
+        }
+        if self.force {
+            config.export.force = true;
+        }
+        if self.clean {
+            config.export.clean = true;
+        }
+        if let Some(path) = &self.zip_path {
+            config.zip.archive_path = Some(path.clone());
+        }
+        if let Some(size) = self.zip_split {
+            config.zip.split_size = Some(size);
+        }
+        if let Some(rate) = self.rate_limit {
+            config.export.rate_limit = Some(rate);
+        }
+        if let Some(size) = self.truncate_to {
+            config.export.truncate_to = Some(size);
+        }
+        if let Some(path) = &self.progress_file {
+            config.export.progress_file = Some(path.clone());
+        }
+        if let Some(per_category) = self.sample {
+            config.export.sample_per_category = Some(per_category);
+            config.export.sample_seed = self.seed;
+        }
+        if self.compress_log {
+            config.log.compress = true;
+        }
+        if self.utc {
+            config.log.utc = true;
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! System command execution.
+//!
+//! The mount and device modules shell out to tools such as `blkid`, `mdadm`,
+//! `findmnt`, and `lsblk`. Running them through [`CommandRunner`] keeps the
+//! output parsing testable against canned output instead of a live system.
+
+use std::process::Command;
+
+/// Captured result of running an external command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Whether the command exited with status 0
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs external programs and captures their output.
+pub trait CommandRunner {
+    /// Runs `program` with `args`, waiting for it to finish.
+    ///
+    /// Returns an error only if the program could not be started; a non-zero
+    /// exit status is reported through [`CommandOutput::success`].
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<CommandOutput>;
+}
+
+/// [`CommandRunner`] that executes commands on the host system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemCommands;
+
+impl CommandRunner for SystemCommands {
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<CommandOutput> {
+        let output = Command::new(program).args(args).output()?;
+
+        Ok(CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// [`CommandRunner`] that replays canned output, keyed by the full command line.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct FakeCommands {
+    responses: std::collections::HashMap<String, CommandOutput>,
+}
+
+#[cfg(test)]
+impl FakeCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a successful run of `command_line` printing `stdout`.
+    pub fn with(mut self, command_line: &str, stdout: &str) -> Self {
+        self.responses.insert(
+            command_line.to_string(),
+            CommandOutput {
+                success: true,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            },
+        );
+        self
+    }
+
+    /// Registers a failed run of `command_line` printing `stderr`.
+    pub fn with_failure(mut self, command_line: &str, stderr: &str) -> Self {
+        self.responses.insert(
+            command_line.to_string(),
+            CommandOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: stderr.to_string(),
+            },
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for FakeCommands {
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<CommandOutput> {
+        let command_line = std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        self.responses.get(&command_line).cloned().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("unexpected command: {}", command_line),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_commands_replays_output() {
+        let runner = FakeCommands::new()
+            .with("blkid -s TYPE -o value /dev/sdb1", "ext4\n")
+            .with_failure("findmnt -n /dev/sdb1", "");
+
+        let output = runner
+            .run("blkid", &["-s", "TYPE", "-o", "value", "/dev/sdb1"])
+            .unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout, "ext4\n");
+
+        assert!(!runner.run("findmnt", &["-n", "/dev/sdb1"]).unwrap().success);
+        assert!(runner.run("lsblk", &[]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_commands_reports_exit_status() {
+        let runner = SystemCommands;
+        assert!(runner.run("true", &[]).unwrap().success);
+        assert!(!runner.run("false", &[]).unwrap().success);
+    }
+}
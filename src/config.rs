@@ -5,10 +5,14 @@
 //! and more. On first run, a default configuration is automatically created.
 
 use color_eyre::Result;
+use color_eyre::eyre::eyre;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::rules::CategoryRule;
 
 /// Main configuration structure for TAP.
 ///
@@ -16,19 +20,242 @@ use std::path::PathBuf;
 /// compression settings, UI preferences, and mount configurations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
     pub categories: HashMap<String, Vec<String>>,
+    /// TOML file with shared `[categories]` and `[category_dirs]`, merged over the inline ones.
+    /// A relative path is resolved against the config directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories_file: Option<PathBuf>,
+    /// Optional mapping from category key to output directory name used by export
+    #[serde(default)]
+    pub category_dirs: HashMap<String, String>,
+    /// Categories that win when an extension is listed under more than one, highest first.
+    /// Categories not listed rank below these, alphabetically.
+    #[serde(default = "default_category_priority")]
+    pub category_priority: Vec<String>,
+    /// Exact file names (matched case-insensitively) mapped to a category, checked
+    /// before the extension lookup, e.g. `Dockerfile = "code"`
+    #[serde(default = "default_filenames")]
+    pub filenames: HashMap<String, String>,
+    /// MIME type prefixes mapped to a category, used with `scan.detect_mime`,
+    /// e.g. `"image/" = "images"`; the longest matching prefix wins
+    #[serde(default = "default_mime_categories")]
+    pub mime_categories: HashMap<String, String>,
+    /// Path-based category overrides, checked in order before the extension lookup
+    #[serde(default)]
+    pub rules: Vec<CategoryRule>,
     pub export: ExportConfig,
     pub zip: ZipConfig,
+    #[serde(default)]
+    pub log: LogConfig,
     pub ui: UIConfig,
     pub scan: ScanConfig,
     pub mount: MountConfig,
 }
 
+fn default_filenames() -> HashMap<String, String> {
+    [
+        ("Dockerfile", "code"),
+        ("Containerfile", "code"),
+        ("Makefile", "code"),
+        ("GNUmakefile", "code"),
+        ("CMakeLists.txt", "code"),
+        ("Jenkinsfile", "code"),
+        ("Vagrantfile", "code"),
+        ("Gemfile", "code"),
+        ("Rakefile", "code"),
+        ("Procfile", "code"),
+        ("requirements.txt", "code"),
+        (".gitignore", "config"),
+        (".gitattributes", "config"),
+        (".dockerignore", "config"),
+        (".editorconfig", "config"),
+    ]
+    .iter()
+    .map(|(name, category)| (name.to_string(), category.to_string()))
+    .collect()
+}
+
+fn default_mime_categories() -> HashMap<String, String> {
+    [
+        ("image/", "images"),
+        ("video/", "videos"),
+        ("audio/", "audio"),
+        ("font/", "fonts"),
+        ("application/pdf", "documents"),
+        ("application/epub+zip", "ebooks"),
+        ("application/x-sqlite3", "databases"),
+    ]
+    .iter()
+    .map(|(prefix, category)| (prefix.to_string(), category.to_string()))
+    .collect()
+}
+
+/// Contents of a shared `categories_file`.
+#[derive(Debug, Deserialize)]
+struct CategoriesFile {
+    #[serde(default)]
+    categories: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    category_dirs: HashMap<String, String>,
+}
+
+fn default_category_priority() -> Vec<String> {
+    [
+        "virtual",      // .iso, .img over archives
+        "web",          // .html, .url, .webloc over code and system
+        "code",         // .jar, .sh, .bat, .ts over executables and videos
+        "config",       // .ini, .plist over system
+        "certificates", // .key, .pub over presentations and publishing
+        "publishing",   // .indd over images
+        "executables",  // .msi, .dmg, .dll over archives and system
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
 /// Export operation configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportConfig {
     /// Maximum number of concurrent file copy operations
     pub max_concurrent_copies: usize,
+    /// Extra attempts for copies that fail with a transient I/O error
+    #[serde(default = "default_copy_retries")]
+    pub copy_retries: u32,
+    /// Compare whole-file SHA-256 checksums of files over 1 MiB too, when deciding whether an existing destination file is identical
+    #[serde(default)]
+    pub checksum_source: bool,
+    /// Re-read each copy and its source after copying and count a mismatch as a failed copy
+    #[serde(default)]
+    pub verify_copies: bool,
+    /// Copy extended attributes (and macOS resource forks) onto each copy, where supported
+    #[serde(default)]
+    pub preserve_xattrs: bool,
+    /// Cap on combined copy throughput in bytes per second, shared by all concurrent copies
+    #[serde(default)]
+    pub rate_limit: Option<u64>,
+    /// Copy only the first this many bytes of larger files, for bounded preview datasets
+    #[serde(default)]
+    pub truncate_to: Option<u64>,
+    /// Write `paths.tsv` mapping each exported file back to its source path
+    #[serde(default)]
+    pub path_map: bool,
+    /// Copy every file straight into the output directory instead of per-category subdirectories
+    #[serde(default)]
+    pub flat: bool,
+    /// What to do when a file's name is already taken in its destination directory
+    #[serde(default)]
+    pub collision_strategy: CollisionStrategy,
+    /// Name given to a renamed duplicate, from `{stem}`, the counter `{n}`, and
+    /// `{ext}` with its dot; see [`render_rename_template`]
+    #[serde(default = "default_rename_template")]
+    pub rename_template: String,
+    /// Write file listings and `inventory.csv` instead of copying files (CLI only)
+    #[serde(skip)]
+    pub inventory_only: bool,
+    /// Previous `manifest.csv`; files unchanged since it are not copied (CLI only)
+    #[serde(skip)]
+    pub since_manifest: Option<PathBuf>,
+    /// Merge into an existing output directory without asking (CLI only)
+    #[serde(skip)]
+    pub force: bool,
+    /// Delete an existing output directory before exporting (CLI only)
+    #[serde(skip)]
+    pub clean: bool,
+    /// File to append JSON progress heartbeats to (CLI only)
+    #[serde(skip)]
+    pub progress_file: Option<PathBuf>,
+    /// Export at most this many randomly chosen files per category (CLI only)
+    #[serde(skip)]
+    pub sample_per_category: Option<usize>,
+    /// Seed for the random choice of `sample_per_category`; a random one when
+    /// not set (CLI only)
+    #[serde(skip)]
+    pub sample_seed: Option<u64>,
+}
+
+fn default_copy_retries() -> u32 {
+    2
+}
+
+/// Most retries `export.copy_retries` accepts; with backoff capped at 30 seconds
+/// this still bounds the wait on one unreadable file to a few minutes.
+pub const MAX_COPY_RETRIES: u32 = 10;
+
+fn default_rename_template() -> String {
+    "{stem}_{n}{ext}".to_string()
+}
+
+/// Characters that are not allowed in a file name on at least one common filesystem.
+const UNSAFE_NAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Fills in a rename template, e.g. `"{stem} ({n}){ext}"` gives `report (2).pdf`.
+///
+/// `extension` is without its dot; `{ext}` is empty for files without one.
+pub fn render_rename_template(template: &str, stem: &str, counter: u32, extension: &str) -> String {
+    let ext = if extension.is_empty() {
+        String::new()
+    } else {
+        format!(".{}", extension)
+    };
+    template
+        .replace("{stem}", stem)
+        .replace("{n}", &counter.to_string())
+        .replace("{ext}", &ext)
+}
+
+/// Checks that a rename template gives distinct, filesystem-safe names.
+///
+/// It must contain `{n}` so each attempt differs, use no placeholders other
+/// than `{stem}`, `{n}`, and `{ext}`, and contain no path separators or other
+/// characters some filesystems reject. Also used as the clap value parser.
+pub fn check_rename_template(template: &str) -> std::result::Result<String, String> {
+    if !template.contains("{n}") {
+        return Err(format!(
+            "rename template '{}' must contain {{n}} so each renamed file gets a different name",
+            template
+        ));
+    }
+    let sample = template
+        .replace("{stem}", "")
+        .replace("{n}", "")
+        .replace("{ext}", "");
+    if sample.contains(['{', '}']) {
+        return Err(format!(
+            "rename template '{}' has an unknown placeholder; use {{stem}}, {{n}}, and {{ext}}",
+            template
+        ));
+    }
+    if let Some(c) = sample
+        .chars()
+        .find(|c| UNSAFE_NAME_CHARS.contains(c) || c.is_control())
+    {
+        return Err(format!(
+            "rename template '{}' contains {:?}, which is not allowed in file names",
+            template, c
+        ));
+    }
+    Ok(template.to_string())
+}
+
+/// How an export names a file whose name is already taken by a different file.
+///
+/// A file found identical to the one already there is not copied again,
+/// whichever strategy is chosen. Without `checksum_source` only files up to
+/// 1 MiB are compared; larger ones are always treated as different.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollisionStrategy {
+    /// Append `_1`, `_2`, ... to the name
+    #[default]
+    Rename,
+    /// Keep the file already there and leave the new one out
+    Skip,
+    /// Replace the file already there, so the last one copied wins
+    Overwrite,
+    /// Append a short hash of the contents, so copies of one file share a name
+    HashSuffix,
 }
 
 /// ZIP archive configuration.
@@ -38,6 +265,29 @@ pub struct ZipConfig {
     pub compression_level: u8,
     /// Buffer size in kilobytes for ZIP operations
     pub buffer_size_kb: usize,
+    /// Directory to write archives to; defaults to next to the export directory
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Exact archive path, overriding `output_dir`. Set from `--zip-path` only.
+    #[serde(skip)]
+    pub archive_path: Option<PathBuf>,
+    /// Split the archive into numbered volumes of at most this many bytes
+    #[serde(default)]
+    pub split_size: Option<u64>,
+}
+
+/// Log file configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogConfig {
+    /// Always write logs gzip-compressed, with a `.gz` suffix
+    #[serde(default)]
+    pub compress: bool,
+    /// Compress logs whose text exceeds this many bytes
+    #[serde(default)]
+    pub compress_above: Option<u64>,
+    /// Write log timestamps in UTC rather than local time
+    #[serde(default)]
+    pub utc: bool,
 }
 
 /// User interface configuration.
@@ -45,6 +295,68 @@ pub struct ZipConfig {
 pub struct UIConfig {
     pub max_recent_files: usize,
     pub color: ColorConfig,
+    /// Unit convention for displayed sizes
+    #[serde(default)]
+    pub size_base: SizeBase,
+    /// Categories with fewer files are folded into one "other" row in the category chart
+    #[serde(default)]
+    pub min_category_files: usize,
+    /// Whether category chart bars show each category's share of bytes, of files, or both
+    #[serde(default)]
+    pub chart_by: ChartBy,
+    /// Milliseconds between spinner redraws; 0 redraws only when progress is made
+    #[serde(default = "default_tick_ms")]
+    pub tick_ms: u64,
+    /// Least milliseconds between redraws of the recent-files list; 0 shows every file
+    #[serde(default = "default_recent_files_ms")]
+    pub recent_files_ms: u64,
+    /// Draw spinners and progress bars with ASCII characters only
+    #[serde(default)]
+    pub ascii_progress: bool,
+    /// Print each scan and copy error to stderr as it happens
+    #[serde(default)]
+    pub verbose: bool,
+    /// Skip the counting pass and scan with an open-ended progress bar (CLI only)
+    #[serde(skip)]
+    pub no_count: bool,
+    /// Skip the interactive summary screens (CLI only)
+    #[serde(skip)]
+    pub no_summary: bool,
+    /// Skip the one-line category summary printed at the end of a run (CLI only)
+    #[serde(skip)]
+    pub no_compact_summary: bool,
+}
+
+fn default_tick_ms() -> u64 {
+    100
+}
+
+fn default_recent_files_ms() -> u64 {
+    200
+}
+
+/// Unit convention used when formatting byte sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeBase {
+    /// Powers of 1024 labelled KiB, MiB, GiB
+    #[default]
+    Binary,
+    /// Powers of 1000 labelled KB, MB, GB, as drive vendors use
+    Decimal,
+}
+
+/// What the bars of the category chart are proportional to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartBy {
+    /// Bytes allocated by each category
+    #[default]
+    Size,
+    /// Number of files in each category
+    Count,
+    /// A size bar and a file count bar per category
+    Both,
 }
 
 /// Color theme configuration.
@@ -57,8 +369,143 @@ pub struct ColorConfig {
 /// Directory scanning configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanConfig {
-    /// Directory and file patterns to exclude from scanning
+    /// Directory and file patterns to exclude from scanning, in `.tapignore` syntax
     pub exclude_patterns: Vec<String>,
+    /// Stop at filesystem boundaries instead of descending into nested mounts
+    #[serde(default)]
+    pub same_filesystem: bool,
+    /// Filesystem types never descended into, matched against the mount table
+    /// (e.g. "proc", "sysfs"), so scanning a live root skips kernel pseudo-filesystems
+    #[serde(default = "default_pseudo_filesystems")]
+    pub pseudo_filesystems: Vec<String>,
+    /// Walk dotfiles and hidden directories such as `.ssh` and `.config`
+    #[serde(default)]
+    pub include_hidden: bool,
+    /// Categories whose files are left out of the scan entirely (e.g. "misc")
+    #[serde(default)]
+    pub exclude_categories: Vec<String>,
+    /// Extensions whose files are skipped whatever their category (e.g. ".iso")
+    #[serde(default)]
+    pub exclude_extensions: Vec<String>,
+    /// When not empty, only files with these extensions are scanned; all others are skipped
+    #[serde(default)]
+    pub include_only_extensions: Vec<String>,
+    /// List files matching `sensitive_patterns` separately in the summary and log
+    #[serde(default)]
+    pub flag_sensitive: bool,
+    /// Names of likely sensitive files (keys, credentials, wallets), in `.tapignore` syntax
+    #[serde(default = "default_sensitive_patterns")]
+    pub sensitive_patterns: Vec<String>,
+    /// Record directories with a `bundle_extensions` extension as single files instead of walking them
+    #[serde(default)]
+    pub bundles_as_files: bool,
+    /// Directory extensions treated as opaque bundles (e.g. ".app", ".rtfd")
+    #[serde(default = "default_bundle_extensions")]
+    pub bundle_extensions: Vec<String>,
+    /// Stop scanning after this many files and mark the results as partial
+    #[serde(default)]
+    pub max_files: Option<u64>,
+    /// List zero-byte files in the summary and log; they are always counted
+    #[serde(default)]
+    pub report_empty: bool,
+    /// Detect each file's MIME type and categorize by `[mime_categories]`
+    #[serde(default)]
+    pub detect_mime: bool,
+    /// Mount disk images found by `inspect` read-only and scan their contents
+    #[serde(default)]
+    pub descend_images: bool,
+    /// How many levels of images inside images `descend_images` opens
+    #[serde(default = "default_max_image_depth")]
+    pub max_image_depth: usize,
+    /// Only scan files modified after this time (set from `--newer-than`)
+    #[serde(skip)]
+    pub modified_after: Option<SystemTime>,
+    /// Only scan files modified before this time (set from `--older-than`)
+    #[serde(skip)]
+    pub modified_before: Option<SystemTime>,
+    /// Only include files modified since the last `--since-last-run` of this command on the drive
+    #[serde(skip)]
+    pub since_last_run: bool,
+    /// Leave out files whose modification time cannot be read when a date
+    /// filter is set; by default they are kept
+    #[serde(default)]
+    pub exclude_unknown_dates: bool,
+}
+
+fn default_pseudo_filesystems() -> Vec<String> {
+    [
+        "proc",
+        "sysfs",
+        "devtmpfs",
+        "devpts",
+        "cgroup",
+        "cgroup2",
+        "securityfs",
+        "debugfs",
+        "tracefs",
+        "pstore",
+        "bpf",
+        "configfs",
+        "fusectl",
+        "mqueue",
+        "hugetlbfs",
+        "binfmt_misc",
+        "autofs",
+        "efivarfs",
+        "selinuxfs",
+        "rpc_pipefs",
+        "nsfs",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+fn default_max_image_depth() -> usize {
+    2
+}
+
+fn default_bundle_extensions() -> Vec<String> {
+    [".app", ".bundle", ".framework", ".plugin", ".kext", ".rtfd"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_sensitive_patterns() -> Vec<String> {
+    [
+        // SSH and PuTTY private keys
+        "id_rsa",
+        "id_dsa",
+        "id_ecdsa",
+        "id_ed25519",
+        "*.ppk",
+        // Private keys and certificate bundles
+        "*.pem",
+        "*.key",
+        "*.pfx",
+        "*.p12",
+        // Credentials and secrets
+        ".env",
+        ".env.*",
+        "credentials",
+        "credentials.json",
+        ".netrc",
+        ".pgpass",
+        ".htpasswd",
+        "secrets.yml",
+        "secrets.yaml",
+        "*.ovpn",
+        // Password databases and wallets
+        "*.kdbx",
+        "*.kdb",
+        "*.keychain",
+        "*.keychain-db",
+        "wallet.dat",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
 }
 
 /// Drive mounting configuration.
@@ -67,6 +514,19 @@ pub struct MountConfig {
     pub mount_base_dir: String,
     pub mount_prefix: String,
     pub device_patterns: Vec<String>,
+    /// Accept the safe mount prompts (mount read-only, remount read-only) and the
+    /// prompt before exporting an earlier export without asking. Set from `--assume-yes` only.
+    #[serde(skip)]
+    pub assume_yes: bool,
+    /// Force-assemble degraded RAID arrays without asking. Set from `--force-degraded` only.
+    #[serde(skip)]
+    pub force_degraded: bool,
+    /// Mount devices here instead of a generated path. Set from `--mount-point` only.
+    #[serde(skip)]
+    pub mount_point: Option<PathBuf>,
+    /// Leave devices mounted after the operation. Set from `--no-unmount` only.
+    #[serde(skip)]
+    pub no_unmount: bool,
 }
 
 impl Default for Config {
@@ -105,6 +565,7 @@ impl Default for Config {
                 ".pdf",
                 // Rich Text & Plain Text
                 ".rtf",
+                ".rtfd",
                 ".txt",
                 ".text",
                 // Markdown
@@ -165,7 +626,7 @@ impl Default for Config {
         // Microsoft Access & Other Office Files
         categories.insert(
             "databases".to_string(),
-            vec![
+            [
                 // Microsoft Access
                 ".mdb", ".accdb", ".accde", ".accdt", ".accdr", // Database files
                 ".db", ".sqlite", ".sqlite3", ".sql", ".dbf", // FileMaker
@@ -179,7 +640,7 @@ impl Default for Config {
         // Microsoft Outlook & Email
         categories.insert(
             "email".to_string(),
-            vec![
+            [
                 // Outlook
                 ".msg", ".oft", ".ost", ".pst", // Email formats
                 ".eml", ".emlx", ".mbox", ".mbx", // Apple Mail
@@ -193,7 +654,7 @@ impl Default for Config {
         // Microsoft OneNote & Note-taking
         categories.insert(
             "notes".to_string(),
-            vec![
+            [
                 // Microsoft OneNote
                 ".one", ".onetoc2", ".onepkg", // Apple Notes (exported)
                 ".note",   // Evernote
@@ -208,7 +669,7 @@ impl Default for Config {
         // Microsoft Publisher & Design
         categories.insert(
             "publishing".to_string(),
-            vec![
+            [
                 // Microsoft Publisher
                 ".pub", // Adobe InDesign
                 ".indd", ".indt", // QuarkXPress
@@ -222,11 +683,11 @@ impl Default for Config {
         // Microsoft Visio & Diagrams
         categories.insert(
             "diagrams".to_string(),
-            vec![
+            [
                 // Microsoft Visio
                 ".vsd", ".vsdx", ".vsdm", ".vst", ".vstx", ".vstm", ".vss", ".vssx", ".vssm",
                 // Other diagram formats
-                ".drawio", ".vsdx",
+                ".drawio",
             ]
             .iter()
             .map(|s| s.to_string())
@@ -236,7 +697,7 @@ impl Default for Config {
         // Microsoft Project & Task Management
         categories.insert(
             "project_files".to_string(),
-            vec![
+            [
                 // Microsoft Project
                 ".mpp", ".mpt", // Other project formats
                 ".gan", ".planner",
@@ -343,9 +804,9 @@ impl Default for Config {
                 ".rs",    // Go
                 ".go",    // Ruby
                 ".rb", ".erb", // Perl
-                ".pl", ".pm", // R
-                ".r", ".R", // Matlab
-                ".m", ".mat", // Shell
+                ".pl", ".pm",  // R
+                ".r",   // Matlab
+                ".mat", // Shell
                 ".sh", ".bash", ".zsh", ".fish", // PowerShell
                 ".ps1", ".psm1", ".psd1", // Batch
                 ".bat", ".cmd", // Other
@@ -559,19 +1020,56 @@ impl Default for Config {
 
         Self {
             categories,
+            categories_file: None,
+            category_dirs: HashMap::new(),
+            category_priority: default_category_priority(),
+            filenames: default_filenames(),
+            mime_categories: default_mime_categories(),
+            rules: Vec::new(),
             export: ExportConfig {
                 max_concurrent_copies: 10,
+                copy_retries: default_copy_retries(),
+                checksum_source: false,
+                verify_copies: false,
+                preserve_xattrs: false,
+                rate_limit: None,
+                truncate_to: None,
+                path_map: false,
+                flat: false,
+                collision_strategy: CollisionStrategy::Rename,
+                rename_template: default_rename_template(),
+                inventory_only: false,
+                since_manifest: None,
+                force: false,
+                clean: false,
+                progress_file: None,
+                sample_per_category: None,
+                sample_seed: None,
             },
             zip: ZipConfig {
                 enabled: true,
                 compression_level: 6,
                 buffer_size_kb: 256,
+                output_dir: None,
+                archive_path: None,
+                split_size: None,
             },
+            log: LogConfig::default(),
             ui: UIConfig {
                 max_recent_files: 10,
                 color: ColorConfig {
                     theme: "default".to_string(),
                 },
+                size_base: SizeBase::Binary,
+                min_category_files: 0,
+                chart_by: ChartBy::Size,
+                tick_ms: default_tick_ms(),
+                recent_files_ms: default_recent_files_ms(),
+                ascii_progress: false,
+                verbose: false,
+                no_count: false,
+                no_summary: false,
+                no_compact_summary: false,
             },
             scan: ScanConfig {
                 exclude_patterns: vec![
@@ -579,7 +1077,33 @@ impl Default for Config {
                     "System Volume Information".to_string(),
                     "$RECYCLE.BIN".to_string(),
                     "node_modules".to_string(),
+                    // Filesystem and desktop metadata at the root of Unix and macOS volumes
+                    "/lost+found".to_string(),
+                    "/.Trash-*".to_string(),
+                    "/.Trashes".to_string(),
+                    "/.Spotlight-V100".to_string(),
+                    "/.fseventsd".to_string(),
+                    "/.DocumentRevisions-V100".to_string(),
                 ],
+                same_filesystem: false,
+                pseudo_filesystems: default_pseudo_filesystems(),
+                include_hidden: false,
+                exclude_categories: Vec::new(),
+                exclude_extensions: Vec::new(),
+                include_only_extensions: Vec::new(),
+                flag_sensitive: false,
+                sensitive_patterns: default_sensitive_patterns(),
+                bundles_as_files: false,
+                bundle_extensions: default_bundle_extensions(),
+                max_files: None,
+                report_empty: false,
+                detect_mime: false,
+                descend_images: false,
+                max_image_depth: default_max_image_depth(),
+                modified_after: None,
+                modified_before: None,
+                since_last_run: false,
+                exclude_unknown_dates: false,
             },
             mount: MountConfig {
                 mount_base_dir: "/mnt".to_string(),
@@ -590,25 +1114,195 @@ impl Default for Config {
                     "/dev/mmcblk".to_string(), // MMC
                     "/dev/vd".to_string(),     // Virtual
                 ],
+                assume_yes: false,
+                force_degraded: false,
+                mount_point: None,
+                no_unmount: false,
             },
         }
     }
 }
 
+/// An extension listed under more than one category, from [`Config::duplicate_extensions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateExtension {
+    /// The lowercase extension, e.g. ".iso"
+    pub extension: String,
+    /// The categories listing it, the winning one first
+    pub categories: Vec<String>,
+    /// The winner is set by `category_priority` rather than alphabetical order
+    pub by_priority: bool,
+}
+
+/// Category lookup built from `[filenames]` and `[categories]` by [`Config::category_map`].
+#[derive(Debug, Clone, Default)]
+pub struct CategoryMap {
+    by_extension: HashMap<String, String>,
+    /// Lowercase file names from `[filenames]`
+    by_filename: HashMap<String, String>,
+}
+
+impl CategoryMap {
+    /// Returns the category for a lowercase extension with its leading dot,
+    /// or `"misc"` if no category lists it.
+    pub fn category(&self, extension: &str) -> &str {
+        self.by_extension
+            .get(extension)
+            .map(String::as_str)
+            .unwrap_or("misc")
+    }
+
+    /// Returns the category for a file, looking up its whole name in
+    /// `[filenames]` before its extension.
+    pub fn category_for(&self, file_name: &str, extension: &str) -> &str {
+        self.filename_category(file_name)
+            .unwrap_or_else(|| self.category(extension))
+    }
+
+    /// Returns the category `[filenames]` gives a file name, if any.
+    pub fn filename_category(&self, file_name: &str) -> Option<&str> {
+        self.by_filename
+            .get(&file_name.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Returns true if some category lists the lowercase `extension`.
+    pub fn knows_extension(&self, extension: &str) -> bool {
+        self.by_extension.contains_key(extension)
+    }
+}
+
 impl Config {
+    /// Builds the extension lookup used to categorize scanned files.
+    ///
+    /// When an extension appears under several categories, the one listed
+    /// earliest in `category_priority` wins; categories missing from that list
+    /// rank below it in alphabetical order, so the result never depends on
+    /// `HashMap` iteration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tap::config::Config;
+    ///
+    /// let categories = Config::default().category_map();
+    ///
+    /// assert_eq!(categories.category(".iso"), "virtual");
+    /// assert_eq!(categories.category(".unknown"), "misc");
+    /// ```
+    pub fn category_map(&self) -> CategoryMap {
+        let mut by_extension = HashMap::new();
+        for name in self.ranked_categories() {
+            for extension in &self.categories[name] {
+                by_extension
+                    .entry(extension.to_lowercase())
+                    .or_insert_with(|| name.clone());
+            }
+        }
+
+        let by_filename = self
+            .filenames
+            .iter()
+            .map(|(name, category)| (name.to_lowercase(), category.clone()))
+            .collect();
+
+        CategoryMap {
+            by_extension,
+            by_filename,
+        }
+    }
+
+    /// Returns the category names in the order they win conflicts: by
+    /// `category_priority`, then alphabetically.
+    fn ranked_categories(&self) -> Vec<&String> {
+        let rank = |category: &str| {
+            self.category_priority
+                .iter()
+                .position(|p| p == category)
+                .unwrap_or(usize::MAX)
+        };
+
+        let mut names: Vec<&String> = self.categories.keys().collect();
+        names.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.cmp(b)));
+        names
+    }
+
+    /// Returns the extensions listed under more than one category, sorted.
+    pub fn duplicate_extensions(&self) -> Vec<DuplicateExtension> {
+        let mut by_extension: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for name in self.ranked_categories() {
+            for extension in &self.categories[name] {
+                let categories = by_extension.entry(extension.to_lowercase()).or_default();
+                if !categories.contains(name) {
+                    categories.push(name.clone());
+                }
+            }
+        }
+
+        by_extension
+            .into_iter()
+            .filter(|(_, categories)| categories.len() > 1)
+            .map(|(extension, categories)| DuplicateExtension {
+                by_priority: self.category_priority.contains(&categories[0]),
+                extension,
+                categories,
+            })
+            .collect()
+    }
+
+    /// Returns a warning for each extension listed under several categories
+    /// whose winner `category_priority` does not decide.
+    ///
+    /// Conflicts settled by `category_priority` are deliberate, as in the
+    /// default config, and are not reported.
+    pub fn duplicate_extension_warnings(&self) -> Vec<String> {
+        self.duplicate_extensions()
+            .into_iter()
+            .filter(|duplicate| !duplicate.by_priority)
+            .map(|duplicate| {
+                format!(
+                    "{} is listed under {}; {} wins by alphabetical order. Remove it from the others or add the category it belongs to to category_priority",
+                    duplicate.extension,
+                    duplicate.categories.join(", "),
+                    duplicate.categories[0]
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the output directory name for a category.
+    ///
+    /// Looks up the category in `[category_dirs]` and falls back to the
+    /// category key itself when no mapping is configured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tap::config::Config;
+    ///
+    /// let mut config = Config::default();
+    /// config.category_dirs.insert("images".to_string(), "Images".to_string());
+    ///
+    /// assert_eq!(config.category_dir("images"), "Images");
+    /// assert_eq!(config.category_dir("documents"), "documents");
+    /// ```
+    pub fn category_dir<'a>(&'a self, category: &'a str) -> &'a str {
+        self.category_dirs
+            .get(category)
+            .map(String::as_str)
+            .unwrap_or(category)
+    }
+
     /// Returns the configuration directory path.
     ///
-    /// Typically `~/.config/tap` on Unix systems or `%USERPROFILE%/.config/tap` on Windows.
+    /// `$XDG_CONFIG_HOME/tap` when that is set, otherwise typically
+    /// `~/.config/tap` on Unix systems or `%USERPROFILE%/.config/tap` on Windows.
     ///
     /// # Errors
     ///
     /// Returns an error if the home directory cannot be determined.
-    fn get_config_dir() -> Result<PathBuf> {
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .map_err(|_| color_eyre::eyre::eyre!("Could not determine home directory"))?;
-
-        Ok(PathBuf::from(home).join(".config").join("tap"))
+    pub(crate) fn get_config_dir() -> Result<PathBuf> {
+        config_dir_from(|name| std::env::var_os(name))
     }
 
     /// Returns the configuration file path.
@@ -633,7 +1327,8 @@ impl Config {
     ///
     /// # Errors
     ///
-    /// Returns an error if file I/O fails or if the TOML is malformed.
+    /// Returns an error if file I/O fails, if the TOML is malformed, or if a
+    /// path setting names an environment variable that is not set.
     ///
     /// # Examples
     ///
@@ -658,11 +1353,112 @@ impl Config {
         }
 
         let contents = fs::read_to_string(&config_path)?;
-        let config: Config = toml::from_str(&contents)?;
+        let mut config: Config = toml::from_str(&contents)?;
+        config
+            .validate()
+            .and_then(|()| config.expand_paths_with(|name| std::env::var_os(name)))
+            .map_err(|e| eyre!("Invalid config {}: {}", config_path.display(), e))?;
+        if let Some(config_dir) = config_path.parent() {
+            config.merge_categories_file(config_dir)?;
+        }
+        // On stderr, so they never end up in JSON output
+        for warning in config.duplicate_extension_warnings() {
+            eprintln!("WARNING: {}", warning);
+        }
 
         Ok(config)
     }
 
+    /// Checks settings that would otherwise only fail partway through a run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the setting if `zip.compression_level` is above
+    /// 9, `zip.buffer_size_kb` or `export.max_concurrent_copies` is zero,
+    /// `zip.split_size` is below [`crate::zip::MIN_SPLIT_SIZE`], or
+    /// `export.rename_template` cannot produce distinct, valid file names.
+    pub fn validate(&self) -> Result<()> {
+        if self.zip.compression_level > 9 {
+            return Err(eyre!(
+                "zip.compression_level must be 0-9 for Deflate, found {}",
+                self.zip.compression_level
+            ));
+        }
+        if self.zip.buffer_size_kb == 0 {
+            return Err(eyre!("zip.buffer_size_kb must be greater than 0"));
+        }
+        if let Some(size) = self.zip.split_size {
+            crate::zip::check_split_size(size).map_err(|e| eyre!("zip.split_size: {}", e))?;
+        }
+        if self.export.max_concurrent_copies == 0 {
+            return Err(eyre!("export.max_concurrent_copies must be greater than 0"));
+        }
+        if self.export.copy_retries > MAX_COPY_RETRIES {
+            return Err(eyre!(
+                "export.copy_retries must be at most {}, found {}",
+                MAX_COPY_RETRIES,
+                self.export.copy_retries
+            ));
+        }
+        check_rename_template(&self.export.rename_template).map_err(|e| eyre!(e))?;
+        Ok(())
+    }
+
+    /// Merges the categories from `categories_file`, if one is set.
+    ///
+    /// Categories and directory names from the shared file replace inline
+    /// ones with the same key; inline categories it does not define are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid TOML.
+    pub fn merge_categories_file(&mut self, config_dir: &Path) -> Result<()> {
+        let Some(file) = &self.categories_file else {
+            return Ok(());
+        };
+        let path = config_dir.join(file);
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| eyre!("Cannot read categories file {}: {}", path.display(), e))?;
+        let shared: CategoriesFile = toml::from_str(&contents)
+            .map_err(|e| eyre!("Invalid categories file {}: {}", path.display(), e))?;
+
+        self.categories.extend(shared.categories);
+        self.category_dirs.extend(shared.category_dirs);
+        Ok(())
+    }
+
+    /// Expands `~` and environment variables in the path settings read from
+    /// the config file: `categories_file`, `zip.output_dir`, and
+    /// `mount.mount_base_dir`. See [`expand_path_with`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the setting if it uses a variable `var` does not return.
+    fn expand_paths_with(
+        &mut self,
+        var: impl Fn(&str) -> Option<std::ffi::OsString>,
+    ) -> Result<()> {
+        let expand = |setting: &str, path: &Path| match path.to_str() {
+            Some(path) => expand_path_with(path, &var)
+                .map(PathBuf::from)
+                .map_err(|e| eyre!("{}: {}", setting, e)),
+            None => Ok(path.to_path_buf()),
+        };
+        if let Some(file) = &self.categories_file {
+            self.categories_file = Some(expand("categories_file", file)?);
+        }
+        if let Some(dir) = &self.zip.output_dir {
+            self.zip.output_dir = Some(expand("zip.output_dir", dir)?);
+        }
+        self.mount.mount_base_dir = expand(
+            "mount.mount_base_dir",
+            Path::new(&self.mount.mount_base_dir),
+        )?
+        .to_string_lossy()
+        .into_owned();
+        Ok(())
+    }
+
     /// Saves the configuration to file.
     ///
     /// Creates the configuration directory if it doesn't exist.
@@ -682,6 +1478,78 @@ impl Config {
     }
 }
 
+/// Picks the configuration directory from the environment variables `var` returns.
+///
+/// Following the XDG base directory spec, `XDG_CONFIG_HOME` is only used when
+/// it is an absolute path; otherwise `$HOME/.config`, then
+/// `$USERPROFILE/.config`, is the base.
+fn config_dir_from(var: impl Fn(&str) -> Option<std::ffi::OsString>) -> Result<PathBuf> {
+    if let Some(xdg) = var("XDG_CONFIG_HOME").map(PathBuf::from)
+        && xdg.is_absolute()
+    {
+        return Ok(xdg.join("tap"));
+    }
+
+    let home = var("HOME")
+        .or_else(|| var("USERPROFILE"))
+        .ok_or_else(|| eyre!("Could not determine home directory"))?;
+    Ok(PathBuf::from(home).join(".config").join("tap"))
+}
+
+/// Expands a leading `~` or `~/` to the home directory, and `$NAME` or
+/// `${NAME}` anywhere to the value of that environment variable, as `var`
+/// returns them.
+///
+/// `~user` and a `$` not followed by a name are left as written. The home
+/// directory is `$HOME`, or `$USERPROFILE` on Windows.
+///
+/// # Errors
+///
+/// Returns an error naming the variable if `var` returns nothing for it.
+pub(crate) fn expand_path_with(
+    path: &str,
+    var: impl Fn(&str) -> Option<std::ffi::OsString>,
+) -> Result<String> {
+    let lookup = |name: &str| {
+        var(name)
+            .map(|value| value.to_string_lossy().into_owned())
+            .ok_or_else(|| eyre!("environment variable {} is not set", name))
+    };
+
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        let home = lookup("HOME").or_else(|_| lookup("USERPROFILE"))?;
+        expanded.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remainder) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        if name.is_empty() {
+            expanded.push('$');
+            rest = after;
+        } else {
+            expanded.push_str(&lookup(name)?);
+            rest = remainder;
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -698,9 +1566,10 @@ mod tests {
 
         // Test export config
         assert_eq!(config.export.max_concurrent_copies, 10);
+        assert_eq!(config.export.copy_retries, 2);
 
         // Test zip config
-        assert_eq!(config.zip.enabled, true);
+        assert!(config.zip.enabled);
         assert_eq!(config.zip.compression_level, 6);
         assert_eq!(config.zip.buffer_size_kb, 256);
 
@@ -820,13 +1689,242 @@ mod tests {
         assert!(code.contains(&".css".to_string()));
     }
 
+    #[test]
+    fn test_category_dir_mapping() {
+        let mut config = Config::default();
+        config
+            .category_dirs
+            .insert("code".to_string(), "Src".to_string());
+
+        assert_eq!(config.category_dir("code"), "Src");
+        assert_eq!(config.category_dir("images"), "images");
+        assert_eq!(config.category_dir("misc"), "misc");
+    }
+
+    #[test]
+    fn test_category_dirs_optional_in_toml() {
+        let mut config = Config::default();
+        config
+            .category_dirs
+            .insert("documents".to_string(), "Docs".to_string());
+
+        let contents = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(parsed.category_dir("documents"), "Docs");
+
+        // Configs written before [category_dirs] existed must still load
+        let without: String = contents
+            .lines()
+            .filter(|line| {
+                !line.starts_with("[category_dirs]") && !line.starts_with("documents = \"Docs\"")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let parsed: Config = toml::from_str(&without).unwrap();
+        assert!(parsed.category_dirs.is_empty());
+    }
+
+    #[test]
+    fn test_rules_round_trip_in_order() {
+        let config = Config {
+            rules: vec![
+                CategoryRule {
+                    pattern: "/Evidence/Exhibits/**".to_string(),
+                    category: "exhibits".to_string(),
+                },
+                CategoryRule {
+                    pattern: "*.eml".to_string(),
+                    category: "email".to_string(),
+                },
+            ],
+            ..Config::default()
+        };
+
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(contents.contains("[[rules]]"));
+        let parsed: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(parsed.rules, config.rules);
+    }
+
     #[test]
     fn test_export_config() {
         let config = ExportConfig {
             max_concurrent_copies: 20,
+            copy_retries: 5,
+            checksum_source: true,
+            verify_copies: true,
+            preserve_xattrs: true,
+            rate_limit: Some(10 * 1024 * 1024),
+            truncate_to: Some(1024 * 1024),
+            path_map: true,
+            flat: true,
+            collision_strategy: CollisionStrategy::HashSuffix,
+            rename_template: "{stem} ({n}){ext}".to_string(),
+            inventory_only: false,
+            since_manifest: None,
+            force: false,
+            clean: false,
+            progress_file: None,
+            sample_per_category: Some(100),
+            sample_seed: Some(42),
         };
 
         assert_eq!(config.max_concurrent_copies, 20);
+        assert_eq!(config.copy_retries, 5);
+        assert!(config.checksum_source);
+        assert!(config.verify_copies);
+        assert!(config.preserve_xattrs);
+        assert!(config.path_map);
+        assert!(config.flat);
+        assert_eq!(config.collision_strategy, CollisionStrategy::HashSuffix);
+    }
+
+    #[test]
+    fn test_config_dir_prefers_xdg_config_home() {
+        let xdg = std::env::temp_dir().join("xdg");
+        let env = |vars: Vec<(&'static str, std::ffi::OsString)>| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.clone())
+            }
+        };
+
+        let dir = config_dir_from(env(vec![
+            ("XDG_CONFIG_HOME", xdg.clone().into()),
+            ("HOME", "home".into()),
+        ]));
+        assert_eq!(dir.unwrap(), xdg.join("tap"));
+
+        // A relative XDG_CONFIG_HOME is ignored, as the spec requires
+        let dir = config_dir_from(env(vec![
+            ("XDG_CONFIG_HOME", "relative".into()),
+            ("HOME", "home".into()),
+        ]));
+        assert_eq!(dir.unwrap(), Path::new("home").join(".config").join("tap"));
+
+        let dir = config_dir_from(env(vec![("USERPROFILE", "profile".into())]));
+        assert_eq!(
+            dir.unwrap(),
+            Path::new("profile").join(".config").join("tap")
+        );
+        assert!(config_dir_from(env(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_expand_paths() {
+        let var = |name: &str| match name {
+            "HOME" => Some("/home/examiner".into()),
+            "CASE" => Some("case42".into()),
+            _ => None,
+        };
+        let expand = |path: &str| expand_path_with(path, var).unwrap();
+
+        assert_eq!(expand("~/mnt"), "/home/examiner/mnt");
+        assert_eq!(expand("~"), "/home/examiner");
+        assert_eq!(
+            expand("$HOME/mounts/${CASE}_x"),
+            "/home/examiner/mounts/case42_x"
+        );
+        // Only a leading ~ is the home directory, and a lone $ is kept
+        assert_eq!(expand("/mnt/~user/$/a$"), "/mnt/~user/$/a$");
+        assert_eq!(expand("/mnt/${CASE"), "/mnt/${CASE");
+        assert!(expand_path_with("$MISSING/mnt", var).is_err());
+
+        let mut config = Config::default();
+        config.mount.mount_base_dir = "~/mnt".to_string();
+        config.zip.output_dir = Some(PathBuf::from("$HOME/archives/$CASE"));
+        config.expand_paths_with(var).unwrap();
+        assert_eq!(config.mount.mount_base_dir, "/home/examiner/mnt");
+        assert_eq!(
+            config.zip.output_dir,
+            Some(PathBuf::from("/home/examiner/archives/case42"))
+        );
+
+        config.categories_file = Some(PathBuf::from("$NOPE/categories.toml"));
+        let err = config.expand_paths_with(var).unwrap_err();
+        assert!(err.to_string().contains("categories_file"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.zip.compression_level = 9;
+        assert!(config.validate().is_ok());
+        config.zip.compression_level = 50;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("zip.compression_level"));
+
+        let mut config = Config::default();
+        config.zip.buffer_size_kb = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.zip.split_size = Some(1024);
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.export.max_concurrent_copies = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.export.copy_retries = MAX_COPY_RETRIES;
+        assert!(config.validate().is_ok());
+        config.export.copy_retries = 40;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("export.copy_retries"));
+
+        let mut config = Config::default();
+        config.export.rename_template = "{stem}{ext}".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_rename_template() {
+        let default = default_rename_template();
+        assert_eq!(
+            render_rename_template(&default, "report", 1, "pdf"),
+            "report_1.pdf"
+        );
+        assert_eq!(
+            render_rename_template(&default, "Makefile", 2, ""),
+            "Makefile_2"
+        );
+        assert_eq!(
+            render_rename_template("{stem} ({n}){ext}", "report", 3, "pdf"),
+            "report (3).pdf"
+        );
+        assert_eq!(
+            render_rename_template("{stem}.{n}{ext}", "report", 1, "pdf"),
+            "report.1.pdf"
+        );
+
+        assert!(check_rename_template(&default).is_ok());
+        assert!(check_rename_template("{stem} ({n}){ext}").is_ok());
+        assert!(check_rename_template("{stem}{ext}").is_err());
+        assert!(check_rename_template("{stem}_{count}{ext}").is_err());
+        assert!(check_rename_template("{n}/{stem}{ext}").is_err());
+        assert!(check_rename_template("{stem}:{n}{ext}").is_err());
+    }
+
+    #[test]
+    fn test_copy_retries_optional_in_toml() {
+        let parsed: ExportConfig = toml::from_str("max_concurrent_copies = 4").unwrap();
+        assert_eq!(parsed.copy_retries, 2);
+        assert!(!parsed.checksum_source);
+        assert!(!parsed.verify_copies);
+        assert!(!parsed.path_map);
+        assert!(!parsed.flat);
+        assert_eq!(parsed.collision_strategy, CollisionStrategy::Rename);
+        assert_eq!(parsed.rename_template, "{stem}_{n}{ext}");
+        assert!(!parsed.inventory_only);
+
+        let parsed: ExportConfig =
+            toml::from_str("max_concurrent_copies = 4\ncollision_strategy = \"hash-suffix\"")
+                .unwrap();
+        assert_eq!(parsed.collision_strategy, CollisionStrategy::HashSuffix);
     }
 
     #[test]
@@ -835,11 +1933,15 @@ mod tests {
             enabled: true,
             compression_level: 9,
             buffer_size_kb: 512,
+            output_dir: Some(PathBuf::from("/archives")),
+            archive_path: None,
+            split_size: None,
         };
 
-        assert_eq!(config.enabled, true);
+        assert!(config.enabled);
         assert_eq!(config.compression_level, 9);
         assert_eq!(config.buffer_size_kb, 512);
+        assert_eq!(config.output_dir, Some(PathBuf::from("/archives")));
     }
 
     #[test]
@@ -849,20 +1951,83 @@ mod tests {
             color: ColorConfig {
                 theme: "cyan".to_string(),
             },
+            size_base: SizeBase::Decimal,
+            min_category_files: 3,
+            chart_by: ChartBy::Both,
+            tick_ms: 500,
+            recent_files_ms: 50,
+            ascii_progress: true,
+            verbose: true,
+            no_count: true,
+            no_summary: true,
+            no_compact_summary: true,
         };
 
         assert_eq!(config.max_recent_files, 20);
         assert_eq!(config.color.theme, "cyan");
+        assert_eq!(config.size_base, SizeBase::Decimal);
+        assert_eq!(config.min_category_files, 3);
+        assert_eq!(config.chart_by, ChartBy::Both);
+        assert_eq!(config.tick_ms, 500);
+        assert_eq!(config.recent_files_ms, 50);
+        assert!(config.ascii_progress);
+        assert!(config.verbose);
+        assert!(config.no_count);
+        assert!(config.no_summary);
+        assert!(config.no_compact_summary);
+    }
+
+    #[test]
+    fn test_progress_settings_optional_in_toml() {
+        let parsed: UIConfig =
+            toml::from_str("max_recent_files = 5\n[color]\ntheme = \"default\"").unwrap();
+        assert_eq!(parsed.tick_ms, 100);
+        assert_eq!(parsed.recent_files_ms, 200);
+        assert!(!parsed.ascii_progress);
+        assert!(!parsed.verbose);
+        assert!(!parsed.no_count);
+        assert_eq!(parsed.chart_by, ChartBy::Size);
     }
 
     #[test]
     fn test_scan_config() {
         let config = ScanConfig {
             exclude_patterns: vec![".*".to_string(), "node_modules".to_string()],
+            same_filesystem: true,
+            pseudo_filesystems: vec!["proc".to_string()],
+            include_hidden: true,
+            exclude_categories: vec!["misc".to_string()],
+            exclude_extensions: vec![".iso".to_string()],
+            include_only_extensions: vec![".pdf".to_string()],
+            flag_sensitive: true,
+            sensitive_patterns: vec!["*.kdbx".to_string()],
+            bundles_as_files: true,
+            bundle_extensions: vec![".app".to_string()],
+            max_files: Some(1_000_000),
+            report_empty: true,
+            detect_mime: true,
+            descend_images: true,
+            max_image_depth: 3,
+            modified_after: None,
+            modified_before: Some(SystemTime::UNIX_EPOCH),
+            since_last_run: false,
+            exclude_unknown_dates: true,
         };
 
         assert_eq!(config.exclude_patterns.len(), 2);
         assert!(config.exclude_patterns.contains(&".*".to_string()));
+        assert!(config.same_filesystem);
+        assert!(config.include_hidden);
+        assert_eq!(config.exclude_categories, vec!["misc".to_string()]);
+        assert_eq!(config.exclude_extensions, vec![".iso".to_string()]);
+        assert_eq!(config.include_only_extensions, vec![".pdf".to_string()]);
+        assert!(config.flag_sensitive);
+        assert!(config.bundles_as_files);
+        assert!(config.report_empty);
+        assert!(config.detect_mime);
+        assert!(config.descend_images);
+        assert_eq!(config.max_image_depth, 3);
+        assert_eq!(config.modified_before, Some(SystemTime::UNIX_EPOCH));
     }
 
     #[test]
@@ -871,10 +2036,151 @@ mod tests {
             mount_base_dir: "/mnt".to_string(),
             mount_prefix: "tap_".to_string(),
             device_patterns: vec!["/dev/sd".to_string(), "/dev/nvme".to_string()],
+            assume_yes: false,
+            force_degraded: false,
+            mount_point: None,
+            no_unmount: false,
         };
 
         assert_eq!(config.mount_base_dir, "/mnt");
         assert_eq!(config.mount_prefix, "tap_");
         assert_eq!(config.device_patterns.len(), 2);
     }
+
+    #[test]
+    fn test_mount_prompt_flags_are_not_persisted() {
+        let mut config = Config::default();
+        config.mount.assume_yes = true;
+        config.mount.force_degraded = true;
+        config.mount.mount_point = Some(PathBuf::from("/mnt/case42"));
+        config.mount.no_unmount = true;
+
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(!contents.contains("assume_yes"));
+        assert!(!contents.contains("force_degraded"));
+        assert!(!contents.contains("case42"));
+        assert!(!contents.contains("no_unmount"));
+
+        let parsed: Config = toml::from_str(&contents).unwrap();
+        assert!(!parsed.mount.assume_yes);
+        assert!(!parsed.mount.force_degraded);
+        assert!(!parsed.mount.no_unmount);
+    }
+
+    #[test]
+    fn test_category_map_file_names() {
+        let mut config = Config::default();
+        config
+            .filenames
+            .insert("Notes.TXT".to_string(), "journal".to_string());
+        let categories = config.category_map();
+
+        assert_eq!(categories.category_for("Dockerfile", ""), "code");
+        assert_eq!(categories.category_for("makefile", ""), "code");
+        // A listed name wins over its extension
+        assert_eq!(categories.category_for("CMakeLists.txt", ".txt"), "code");
+        assert_eq!(categories.category_for("notes.txt", ".txt"), "journal");
+        assert_eq!(categories.category_for("readme.txt", ".txt"), "documents");
+        assert_eq!(categories.category_for("LICENSE", ""), "misc");
+
+        let parsed: HashMap<String, String> = toml::from_str("Justfile = \"code\"").unwrap();
+        assert_eq!(parsed["Justfile"], "code");
+    }
+
+    #[test]
+    fn test_category_map_resolves_conflicts() {
+        let categories = Config::default().category_map();
+
+        // Listed under archives and virtual
+        assert_eq!(categories.category(".iso"), "virtual");
+        // Listed under archives, executables, and code
+        assert_eq!(categories.category(".jar"), "code");
+        assert_eq!(categories.category(".m"), "code");
+        assert_eq!(categories.category(".msi"), "executables");
+        assert_eq!(categories.category(".html"), "web");
+        assert_eq!(categories.category(".pdf"), "documents");
+        assert_eq!(categories.category(".nope"), "misc");
+    }
+
+    #[test]
+    fn test_category_map_priority_override() {
+        let mut config = Config {
+            category_priority: vec!["archives".to_string()],
+            ..Config::default()
+        };
+        let categories = config.category_map();
+        assert_eq!(categories.category(".iso"), "archives");
+        assert_eq!(categories.category(".jar"), "archives");
+
+        // Without a priority, ties fall back to alphabetical order
+        config.category_priority.clear();
+        let categories = config.category_map();
+        assert_eq!(categories.category(".iso"), "archives");
+        assert_eq!(categories.category(".sh"), "code");
+        assert_eq!(categories.category(".dll"), "executables");
+    }
+
+    #[test]
+    fn test_duplicate_extension_warnings() {
+        // The default conflicts are all settled by category_priority
+        let mut config = Config::default();
+        assert!(config.duplicate_extension_warnings().is_empty());
+        let iso = config
+            .duplicate_extensions()
+            .into_iter()
+            .find(|duplicate| duplicate.extension == ".iso")
+            .unwrap();
+        assert_eq!(iso.categories, ["virtual", "archives"]);
+        assert!(iso.by_priority);
+
+        config
+            .categories
+            .insert("legal".to_string(), vec![".PDF".to_string()]);
+        let warnings = config.duplicate_extension_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with(
+            ".pdf is listed under documents, legal; documents wins by alphabetical order"
+        ));
+
+        config.category_priority.push("legal".to_string());
+        assert!(config.duplicate_extension_warnings().is_empty());
+        assert_eq!(config.category_map().category(".pdf"), "legal");
+    }
+
+    #[test]
+    fn test_merge_categories_file() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("team")).unwrap();
+        std::fs::write(
+            dir.path().join("team/categories.toml"),
+            "[categories]\n\
+             documents = [\".pdf\", \".docx\"]\n\
+             exhibits = [\".e01\"]\n\
+             [category_dirs]\n\
+             exhibits = \"Exhibits\"\n",
+        )
+        .unwrap();
+
+        let mut config: Config =
+            toml::from_str(&toml::to_string(&Config::default()).unwrap().replace(
+                "[categories]",
+                "categories_file = \"team/categories.toml\"\n\n[categories]",
+            ))
+            .unwrap();
+        assert_eq!(
+            config.categories_file,
+            Some(PathBuf::from("team/categories.toml"))
+        );
+        config.merge_categories_file(dir.path()).unwrap();
+
+        // The shared file wins for categories it defines; the rest stay
+        assert_eq!(config.categories["documents"], vec![".pdf", ".docx"]);
+        assert_eq!(config.categories["exhibits"], vec![".e01"]);
+        assert!(config.categories.contains_key("images"));
+        assert_eq!(config.category_dir("exhibits"), "Exhibits");
+
+        config.categories_file = Some(PathBuf::from("missing.toml"));
+        let err = config.merge_categories_file(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("missing.toml"));
+    }
 }
@@ -0,0 +1,300 @@
+//! Pause and cancel controls for long-running phases.
+//!
+//! A [`Control`] is shared between the scan, copy, and zip loops and a
+//! [`KeyListener`] that drives it from the keyboard: `p` pauses or resumes and
+//! `q` cancels. The loops only check the control between files, so cancelling
+//! always lets the file in progress finish.
+
+use indicatif::ProgressBar;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often paused loops and the key reader check for a state change.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct State {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+    /// Progress bar of the running phase and its original message
+    bar: Mutex<Option<(ProgressBar, String)>>,
+}
+
+/// Shared pause/cancel state for a long-running operation.
+///
+/// Cloning a `Control` yields a handle to the same state.
+#[derive(Clone, Default)]
+pub struct Control {
+    state: Arc<State>,
+}
+
+impl Control {
+    /// Creates a control that is neither paused nor cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Pauses if running or resumes if paused, returning whether it is now paused.
+    ///
+    /// Has no effect once cancelled.
+    pub fn toggle_pause(&self) -> bool {
+        if self.is_cancelled() {
+            return false;
+        }
+        let paused = !self.state.paused.fetch_xor(true, Ordering::SeqCst);
+        if paused {
+            self.show("paused, press p to resume");
+        } else {
+            self.show("");
+        }
+        paused
+    }
+
+    /// Requests cancellation; paused loops wake up and stop.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.paused.store(false, Ordering::SeqCst);
+        self.show("cancelling after current file");
+    }
+
+    /// Attaches the progress bar of the current phase so it reflects pause and cancel.
+    pub fn attach(&self, pb: &ProgressBar) {
+        let mut bar = self.state.bar.lock().unwrap();
+        *bar = Some((pb.clone(), pb.message()));
+    }
+
+    /// Runs `f` with the attached bar hidden, so that anything it prints does
+    /// not tear the bar, then draws the bar again.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        let bar = self
+            .state
+            .bar
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(pb, _)| pb.clone());
+        match bar {
+            Some(pb) => pb.suspend(f),
+            None => f(),
+        }
+    }
+
+    /// Appends `status` to the attached bar's message, or restores it when empty.
+    fn show(&self, status: &str) {
+        if let Some((pb, message)) = self.state.bar.lock().unwrap().as_ref() {
+            if status.is_empty() {
+                pb.set_message(message.clone());
+            } else {
+                pb.set_message(format!("{} ({})", message, status));
+            }
+        }
+    }
+
+    /// Blocks the calling thread while paused.
+    ///
+    /// Returns `false` if the operation was cancelled and the caller should stop.
+    pub fn wait_blocking(&self) -> bool {
+        while self.is_paused() {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        !self.is_cancelled()
+    }
+
+    /// Waits while paused without blocking the runtime.
+    ///
+    /// Returns `false` if the operation was cancelled and the caller should stop.
+    pub async fn wait(&self) -> bool {
+        while self.is_paused() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        !self.is_cancelled()
+    }
+
+    /// Applies a key press: `p` toggles pause and `q` cancels.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn handle_key(&self, key: char) {
+        match key.to_ascii_lowercase() {
+            'p' => {
+                self.toggle_pause();
+            }
+            'q' => self.cancel(),
+            _ => {}
+        }
+    }
+}
+
+/// Reads pause and cancel keys from the terminal on a background thread.
+///
+/// Keys are only read while the listener is alive, so drop it before showing
+/// any prompt. Dropping restores the terminal mode and joins the thread.
+pub struct KeyListener {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl KeyListener {
+    /// Starts listening for keys on behalf of `control`.
+    ///
+    /// Does nothing when stdin is not a terminal or on platforms without
+    /// terminal mode support.
+    pub fn start(control: &Control) -> Self {
+        use std::io::IsTerminal;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = (cfg!(unix) && std::io::stdin().is_terminal()).then(|| {
+            let control = control.clone();
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || read_keys(&control, &stop))
+        });
+
+        Self { stop, handle }
+    }
+
+    /// Key usage hint to append to a phase message, or empty when keys are not read.
+    pub fn hint(&self) -> &'static str {
+        if self.handle.is_some() {
+            " (p: pause, q: cancel)"
+        } else {
+            ""
+        }
+    }
+}
+
+impl Drop for KeyListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Reads single bytes from stdin until `stop` is set.
+///
+/// `console::Term::read_key` blocks until a key arrives, which would leave the
+/// thread holding stdin after the phase ends. Instead the terminal is switched
+/// to non-canonical mode with a read timeout so `stop` is checked regularly.
+#[cfg(unix)]
+fn read_keys(control: &Control, stop: &AtomicBool) {
+    use std::io::Read;
+
+    let Some(_mode) = KeyMode::enable() else {
+        return;
+    };
+
+    let mut stdin = std::io::stdin().lock();
+    let mut byte = [0u8; 1];
+    while !stop.load(Ordering::SeqCst) {
+        match stdin.read(&mut byte) {
+            Ok(1) => control.handle_key(byte[0] as char),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn read_keys(_control: &Control, _stop: &AtomicBool) {}
+
+/// Terminal mode with line buffering and echo disabled, restored on drop.
+///
+/// Output processing and signals are left alone so progress bars still render
+/// and Ctrl-C still interrupts.
+#[cfg(unix)]
+struct KeyMode {
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl KeyMode {
+    fn enable() -> Option<Self> {
+        // SAFETY: termios is plain old data and is fully written by tcgetattr
+        // before use; both calls only touch the stdin descriptor.
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+
+            let mut mode = original;
+            mode.c_lflag &= !(libc::ICANON | libc::ECHO);
+            mode.c_cc[libc::VMIN] = 0;
+            // Tenths of a second; matches POLL_INTERVAL
+            mode.c_cc[libc::VTIME] = 1;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &mode) != 0 {
+                return None;
+            }
+
+            Some(Self { original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for KeyMode {
+    fn drop(&mut self) {
+        // SAFETY: restores the attributes read in `enable`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_toggle_pause_and_cancel() {
+        let control = Control::new();
+        let pb = ProgressBar::hidden();
+        pb.set_message("Copying");
+        control.attach(&pb);
+
+        control.handle_key('p');
+        assert!(control.is_paused());
+        assert_eq!(pb.message(), "Copying (paused, press p to resume)");
+
+        control.handle_key('P');
+        assert!(!control.is_paused());
+        assert_eq!(pb.message(), "Copying");
+        assert!(control.wait_blocking());
+
+        control.handle_key('p');
+        control.handle_key('q');
+        assert!(control.is_cancelled());
+        assert!(!control.is_paused());
+        assert!(!control.wait_blocking());
+
+        // Pausing is ignored once cancelled
+        control.handle_key('p');
+        assert!(!control.is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_wait_resumes_when_unpaused() {
+        let control = Control::new();
+        control.toggle_pause();
+
+        let waiter = tokio::spawn({
+            let control = control.clone();
+            async move { control.wait().await }
+        });
+        tokio::time::sleep(POLL_INTERVAL).await;
+        assert!(!waiter.is_finished());
+
+        control.toggle_pause();
+        assert!(waiter.await.unwrap());
+    }
+}
@@ -2,37 +2,53 @@
 //!
 //! This module provides an interactive UI for selecting block devices (partitions)
 //! from available system storage, filtering out system partitions and encrypted volumes.
+//! Devices are enumerated with `lsblk` on Linux and `diskutil` on macOS. The same
+//! enumeration backs `tap list-devices`, which prints it without prompting.
 
-use crate::tui::{BANNER, UI};
+#[cfg(target_os = "linux")]
+use crate::command::{CommandRunner, SystemCommands};
+use crate::tui::{BANNER, UI, format_size};
 use console::Term;
 use dialoguer::Select;
-use std::collections::HashSet;
+use serde::Serialize;
+#[cfg(target_os = "linux")]
+use std::collections::{HashMap, HashSet};
+#[cfg(target_os = "linux")]
 use std::fs;
+#[cfg(target_os = "linux")]
 use std::path::Path;
-use std::process::Command;
 
-#[derive(Debug)]
+/// A partition that can be inspected or exported.
+///
+/// Fields other than `path` are best-effort: they are `None` when `lsblk`
+/// is unavailable or does not report them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 pub struct BlockDevice {
     pub path: String,
-    pub display_name: String,
+    /// Partition size in bytes
+    pub size_bytes: Option<u64>,
+    /// Filesystem type (e.g. `ext4`, `vfat`, `crypto_LUKS`)
+    pub fstype: Option<String>,
+    /// Filesystem label
+    pub label: Option<String>,
+    /// Whether the partition is encrypted (LUKS on Linux, FileVault or APFS encryption on macOS)
+    pub encrypted: bool,
+    /// Where the partition is currently mounted, if anywhere
+    pub mountpoint: Option<String>,
 }
 
 /// Get list of partitions that are part of the Linux system
-fn get_linux_system_partitions() -> HashSet<String> {
+#[cfg(target_os = "linux")]
+fn get_linux_system_partitions(runner: &dyn CommandRunner) -> HashSet<String> {
     let mut system_partitions = HashSet::new();
 
     // Use findmnt to get all mounted partitions
-    if let Ok(output) = Command::new("findmnt")
-        .args(["-n", "-o", "SOURCE"])
-        .output()
-    {
-        if let Ok(stdout) = String::from_utf8(output.stdout) {
-            for line in stdout.lines() {
-                let source = line.trim();
-                // Skip pseudo-filesystems (tmpfs, devtmpfs, etc.)
-                if source.starts_with("/dev/") {
-                    system_partitions.insert(source.to_string());
-                }
+    if let Ok(output) = runner.run("findmnt", &["-n", "-o", "SOURCE"]) {
+        for line in output.stdout.lines() {
+            let source = line.trim();
+            // Skip pseudo-filesystems (tmpfs, devtmpfs, etc.)
+            if source.starts_with("/dev/") {
+                system_partitions.insert(source.to_string());
             }
         }
     }
@@ -40,12 +56,36 @@ fn get_linux_system_partitions() -> HashSet<String> {
     system_partitions
 }
 
+/// Returns true if a `/dev` entry name looks like a partition (not a whole disk).
+#[cfg(target_os = "linux")]
+pub fn is_partition_name(name: &str) -> bool {
+    let is_sata_partition = name.starts_with("sd")
+        && name.len() > 3
+        && name.chars().nth(3).is_some_and(|c| c.is_ascii_digit()); // sda1, sdb2, etc.
+    let is_nvme_partition = name.starts_with("nvme")
+        && name.contains("p")
+        && name.chars().last().is_some_and(|c| c.is_ascii_digit()); // nvme0n1p1, etc.
+    let is_mmc_partition = name.starts_with("mmcblk")
+        && name.contains("p")
+        && name.chars().last().is_some_and(|c| c.is_ascii_digit()); // mmcblk0p1, etc.
+    let is_virtual_partition = name.starts_with("vd")
+        && name.len() > 3
+        && name.chars().nth(3).is_some_and(|c| c.is_ascii_digit()); // vda1, vdb2, etc.
+
+    is_sata_partition || is_nvme_partition || is_mmc_partition || is_virtual_partition
+}
+
 /// Enumerate available block devices from /dev/
+///
+/// Returns every partition that is not already mounted as part of the running
+/// system, including encrypted ones (see [`BlockDevice::encrypted`]).
+#[cfg(target_os = "linux")]
 pub fn enumerate_block_devices() -> color_eyre::Result<Vec<BlockDevice>> {
+    let runner = SystemCommands;
     let mut devices = Vec::new();
 
     // Get Linux system partitions to filter out
-    let system_partitions = get_linux_system_partitions();
+    let system_partitions = get_linux_system_partitions(&runner);
 
     // Read /dev/ directory
     let dev_dir = fs::read_dir("/dev")?;
@@ -54,119 +94,234 @@ pub fn enumerate_block_devices() -> color_eyre::Result<Vec<BlockDevice>> {
         let entry = entry?;
         let path = entry.path();
         let file_name = entry.file_name();
-        let name = file_name.to_string_lossy();
 
         // Look for partitions only (keep it simple - no whole disks)
-        let is_sata_partition = name.starts_with("sd")
-            && name.len() > 3
-            && name.chars().nth(3).is_some_and(|c| c.is_ascii_digit()); // sda1, sdb2, etc.
-        let is_nvme_partition = name.starts_with("nvme")
-            && name.contains("p")
-            && name.chars().last().is_some_and(|c| c.is_ascii_digit()); // nvme0n1p1, etc.
-        let is_mmc_partition = name.starts_with("mmcblk")
-            && name.contains("p")
-            && name.chars().last().is_some_and(|c| c.is_ascii_digit()); // mmcblk0p1, etc.
-        let is_virtual_partition = name.starts_with("vd")
-            && name.len() > 3
-            && name.chars().nth(3).is_some_and(|c| c.is_ascii_digit()); // vda1, vdb2, etc.
-
-        // Check if we should include this device
-        let should_include =
-            is_sata_partition || is_nvme_partition || is_mmc_partition || is_virtual_partition;
-
-        if should_include {
-            let path_str = path.to_string_lossy().to_string();
-
-            // Skip if this is a Linux system partition
-            if system_partitions.contains(&path_str) {
-                continue;
-            }
-
-            // Skip if this is an encrypted partition
-            if is_encrypted(&path) {
-                continue;
-            }
-
-            // Get size info
-            let size_info = get_device_size(&path);
-
-            let display_name = if let Some(size) = size_info {
-                format!("{} ({})", path.display(), size)
-            } else {
-                format!("{}", path.display())
-            };
+        if !is_partition_name(&file_name.to_string_lossy()) {
+            continue;
+        }
 
-            devices.push(BlockDevice {
-                path: path_str,
-                display_name,
-            });
+        // Skip if this is a Linux system partition
+        if system_partitions.contains(path.to_string_lossy().as_ref()) {
+            continue;
         }
+
+        devices.push(device_info(&runner, &path));
     }
 
     // Sort by device name
     devices.sort_by(|a, b| a.path.cmp(&b.path));
 
-    if devices.is_empty() {
-        return Err(color_eyre::eyre::eyre!(
-            "No removable partitions found. All partitions appear to be part of the Linux system."
-        ));
+    Ok(devices)
+}
+
+/// Enumerate partitions on external disks using `diskutil`.
+#[cfg(target_os = "macos")]
+pub fn enumerate_block_devices() -> color_eyre::Result<Vec<BlockDevice>> {
+    crate::diskutil::list_devices()
+}
+
+/// Device enumeration is not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn enumerate_block_devices() -> color_eyre::Result<Vec<BlockDevice>> {
+    Err(color_eyre::eyre::eyre!(
+        "Device selection is not supported on this platform; pass a path to scan instead"
+    ))
+}
+
+/// Queries `lsblk` for the size, filesystem, label, and mountpoint of a partition.
+#[cfg(target_os = "linux")]
+pub fn device_info(runner: &dyn CommandRunner, path: &Path) -> BlockDevice {
+    let mut device = BlockDevice {
+        path: path.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    let output = runner.run(
+        "lsblk",
+        &[
+            "-b",
+            "-d",
+            "-n",
+            "-P",
+            "-o",
+            "SIZE,FSTYPE,LABEL,MOUNTPOINT",
+            &device.path,
+        ],
+    );
+
+    if let Ok(output) = output
+        && output.success
+        && let Some(line) = output.stdout.lines().next()
+    {
+        let fields = parse_lsblk_pairs(line);
+        let non_empty = |key: &str| fields.get(key).filter(|v| !v.is_empty()).cloned();
+
+        device.size_bytes = fields.get("SIZE").and_then(|s| s.parse().ok());
+        device.fstype = non_empty("FSTYPE");
+        device.label = non_empty("LABEL");
+        device.mountpoint = non_empty("MOUNTPOINT");
+        device.encrypted = device.fstype.as_deref() == Some("crypto_LUKS");
     }
 
-    Ok(devices)
+    device
 }
 
-/// Check if a device is LUKS encrypted
-fn is_encrypted(path: &Path) -> bool {
-    use std::process::Command;
+/// Returns a name for `drive` to label its export with.
+///
+/// For a device this is its filesystem label, if it has one; for a directory,
+/// the directory's name.
+pub fn drive_label(drive: &str) -> Option<String> {
+    if !crate::mount::is_device_path(drive) {
+        let path = std::fs::canonicalize(drive).ok()?;
+        return path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+    }
+    device_label(drive)
+}
 
-    let output = Command::new("lsblk")
-        .args(["-n", "-o", "FSTYPE", path.to_str().unwrap_or("")])
-        .output();
+#[cfg(target_os = "linux")]
+fn device_label(drive: &str) -> Option<String> {
+    device_info(&SystemCommands, Path::new(drive)).label
+}
 
-    if let Ok(output) = output {
-        if let Ok(stdout) = String::from_utf8(output.stdout) {
-            // Check if any line contains crypto_LUKS
-            return stdout.lines().any(|line| line.trim() == "crypto_LUKS");
-        }
+#[cfg(target_os = "macos")]
+fn device_label(drive: &str) -> Option<String> {
+    crate::diskutil::disk_info(drive)
+        .ok()
+        .flatten()?
+        .volume_name
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn device_label(_drive: &str) -> Option<String> {
+    None
+}
+
+/// Parses one line of `lsblk -P` output (`KEY="value" KEY="value"`) into a map.
+///
+/// `lsblk` escapes unsafe bytes as `\xNN`; these are decoded.
+#[cfg(target_os = "linux")]
+pub fn parse_lsblk_pairs(line: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let mut rest = line.trim();
+
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let Some(end) = after.find('"') else {
+            break;
+        };
+        fields.insert(key.trim().to_string(), unescape_lsblk(&after[..end]));
+        rest = &after[end + 1..];
     }
 
-    false
+    fields
 }
 
-/// Get device size information using lsblk
-fn get_device_size(path: &Path) -> Option<String> {
-    use std::process::Command;
+/// Decodes `\xNN` escapes produced by `lsblk`.
+#[cfg(target_os = "linux")]
+fn unescape_lsblk(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let raw = value.as_bytes();
+    let mut i = 0;
+
+    while i < raw.len() {
+        if raw[i] == b'\\'
+            && raw.get(i + 1) == Some(&b'x')
+            && let Some(byte) = value
+                .get(i + 2..i + 4)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            bytes.push(byte);
+            i += 4;
+        } else {
+            bytes.push(raw[i]);
+            i += 1;
+        }
+    }
 
-    let output = Command::new("lsblk")
-        .args(["-b", "-d", "-n", "-o", "SIZE", path.to_str()?])
-        .output()
-        .ok()?;
+    String::from_utf8_lossy(&bytes).into_owned()
+}
 
-    if !output.status.success() {
-        return None;
+/// Formats a device for the picker list, e.g. `/dev/sdb1 (14.91 GiB, vfat, "USB")`.
+fn display_name(device: &BlockDevice) -> String {
+    let mut details = Vec::new();
+    if let Some(size) = device.size_bytes {
+        details.push(format_size(size));
+    }
+    if let Some(fstype) = &device.fstype {
+        details.push(fstype.clone());
+    }
+    if let Some(label) = &device.label {
+        details.push(format!("\"{}\"", label));
     }
 
-    let size_bytes = String::from_utf8(output.stdout)
-        .ok()?
-        .trim()
-        .parse::<u64>()
-        .ok()?;
+    if details.is_empty() {
+        device.path.clone()
+    } else {
+        format!("{} ({})", device.path, details.join(", "))
+    }
+}
+
+/// Formats partitions as a table for `tap list-devices`, header row first.
+fn device_table(devices: &[BlockDevice]) -> Vec<String> {
+    let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+    let mut lines = vec![format!(
+        "{:<16} {:<12} {:<12} {:<16} {:<9} {}",
+        "Device", "Size", "Filesystem", "Label", "Encrypted", "Mounted at"
+    )];
+    lines.push("-".repeat(80));
+
+    for device in devices {
+        lines.push(format!(
+            "{:<16} {:<12} {:<12} {:<16} {:<9} {}",
+            device.path,
+            device
+                .size_bytes
+                .map_or_else(|| "-".to_string(), format_size),
+            or_dash(device.fstype.as_deref()),
+            or_dash(device.label.as_deref()),
+            if device.encrypted { "yes" } else { "no" },
+            or_dash(device.mountpoint.as_deref()),
+        ));
+    }
 
-    Some(human_readable_size(size_bytes))
+    lines
 }
 
-/// Convert bytes to human-readable size
-fn human_readable_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_idx = 0;
+/// Print the available partitions without prompting, as a table or as JSON.
+///
+/// Unlike [`pick_device`], encrypted partitions are listed too, so scripts can
+/// decide for themselves what to do with them.
+pub fn list_devices(json: bool, theme: &str) -> color_eyre::Result<()> {
+    let devices = enumerate_block_devices()?;
 
-    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&devices)?);
+        return Ok(());
+    }
+
+    if devices.is_empty() {
+        println!(
+            "{}",
+            console::Style::new()
+                .white()
+                .bold()
+                .apply_to("No partitions found outside the running system.")
+        );
+        return Ok(());
     }
 
-    format!("{:.2} {}", size, UNITS[unit_idx])
+    let (info_style, ..) = UI::get_static_status_styles(theme);
+    let mut lines = device_table(&devices).into_iter();
+    if let Some(header) = lines.next() {
+        println!("{}", info_style.apply_to(header).bold());
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+
+    Ok(())
 }
 
 /// Show interactive device picker and return selected device path
@@ -201,9 +356,18 @@ pub fn pick_device(theme: &str) -> color_eyre::Result<String> {
     );
     println!();
 
-    let devices = enumerate_block_devices()?;
+    let devices: Vec<BlockDevice> = enumerate_block_devices()?
+        .into_iter()
+        .filter(|d| !d.encrypted)
+        .collect();
 
-    let items: Vec<&str> = devices.iter().map(|d| d.display_name.as_str()).collect();
+    if devices.is_empty() {
+        return Err(color_eyre::eyre::eyre!(
+            "No removable partitions found. All partitions appear to be part of the Linux system."
+        ));
+    }
+
+    let items: Vec<String> = devices.iter().map(display_name).collect();
 
     let colorful_theme = UI::get_colorful_theme(theme);
     let selection = Select::with_theme(&colorful_theme)
@@ -216,3 +380,103 @@ pub fn pick_device(theme: &str) -> color_eyre::Result<String> {
 
     Ok(devices[selection].path.clone())
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::command::FakeCommands;
+
+    #[test]
+    fn test_is_partition_name() {
+        assert!(is_partition_name("sdb1"));
+        assert!(is_partition_name("nvme0n1p2"));
+        assert!(is_partition_name("mmcblk0p1"));
+        assert!(is_partition_name("vda3"));
+        assert!(!is_partition_name("sdb"));
+        assert!(!is_partition_name("nvme0n1"));
+        assert!(!is_partition_name("loop0"));
+    }
+
+    #[test]
+    fn test_device_info() {
+        let runner = FakeCommands::new().with(
+            "lsblk -b -d -n -P -o SIZE,FSTYPE,LABEL,MOUNTPOINT /dev/sdc1",
+            "SIZE=\"500107862016\" FSTYPE=\"crypto_LUKS\" LABEL=\"\" MOUNTPOINT=\"\"\n",
+        );
+
+        let device = device_info(&runner, Path::new("/dev/sdc1"));
+        assert_eq!(device.path, "/dev/sdc1");
+        assert_eq!(device.size_bytes, Some(500107862016));
+        assert_eq!(device.fstype.as_deref(), Some("crypto_LUKS"));
+        assert!(device.encrypted);
+        assert_eq!(device.label, None);
+        assert_eq!(device.mountpoint, None);
+
+        // lsblk missing or failing leaves only the path
+        let device = device_info(&FakeCommands::new(), Path::new("/dev/sdd1"));
+        assert_eq!(device.size_bytes, None);
+        assert!(!device.encrypted);
+    }
+
+    #[test]
+    fn test_get_linux_system_partitions() {
+        let runner = FakeCommands::new().with(
+            "findmnt -n -o SOURCE",
+            "/dev/nvme0n1p2\ntmpfs\n/dev/nvme0n1p1\nproc\n",
+        );
+
+        let partitions = get_linux_system_partitions(&runner);
+        assert_eq!(partitions.len(), 2);
+        assert!(partitions.contains("/dev/nvme0n1p1"));
+        assert!(!partitions.contains("tmpfs"));
+    }
+
+    #[test]
+    fn test_parse_lsblk_pairs() {
+        let fields = parse_lsblk_pairs(
+            r#"SIZE="16008609792" FSTYPE="vfat" LABEL="MY\x20USB" MOUNTPOINT="""#,
+        );
+
+        assert_eq!(fields["SIZE"], "16008609792");
+        assert_eq!(fields["FSTYPE"], "vfat");
+        assert_eq!(fields["LABEL"], "MY USB");
+        assert_eq!(fields["MOUNTPOINT"], "");
+    }
+
+    #[test]
+    fn test_list_devices_output() {
+        let devices = vec![
+            BlockDevice {
+                path: "/dev/sdb1".to_string(),
+                size_bytes: Some(1024),
+                fstype: Some("vfat".to_string()),
+                label: Some("USB".to_string()),
+                encrypted: false,
+                mountpoint: Some("/media/usb".to_string()),
+            },
+            BlockDevice {
+                path: "/dev/sdc1".to_string(),
+                ..BlockDevice::default()
+            },
+        ];
+
+        let table = device_table(&devices);
+        assert_eq!(table.len(), 4);
+        assert!(table[0].starts_with("Device"));
+        assert!(table[2].starts_with("/dev/sdb1"));
+        assert!(table[2].ends_with("no        /media/usb"));
+        assert!(
+            table[3]
+                .split_whitespace()
+                .skip(1)
+                .all(|f| f == "-" || f == "no")
+        );
+
+        let json: serde_json::Value = serde_json::to_value(&devices).unwrap();
+        assert_eq!(json[0]["path"], "/dev/sdb1");
+        assert_eq!(json[0]["size_bytes"], 1024);
+        assert_eq!(json[0]["mountpoint"], "/media/usb");
+        assert_eq!(json[1]["fstype"], serde_json::Value::Null);
+        assert_eq!(json[1]["encrypted"], false);
+    }
+}
@@ -0,0 +1,214 @@
+//! Comparing two captures for `inspect --diff`.
+//!
+//! Both sides are reduced to manifest entries keyed by their path relative to
+//! the source, so a drive can be compared with another drive, with an earlier
+//! export, or with a `manifest.csv` on its own. A file present on both sides
+//! differs when its size does, or when both sides recorded a SHA-256 and they
+//! do not match; modification times are not compared, since copying a drive
+//! rarely preserves them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::control::Control;
+use crate::manifest::{MANIFEST_FILE, Manifest, ManifestEntry, scan_manifest};
+use crate::mount::is_device_path;
+use crate::scanner::scan_directory;
+
+/// Files found on only one side, or on both with different contents.
+///
+/// Each list is sorted by path.
+#[derive(Debug, Default)]
+pub struct Diff {
+    /// Files only in the inspected sources
+    pub only_here: Vec<ManifestEntry>,
+    /// Files only in the other capture
+    pub only_there: Vec<ManifestEntry>,
+    /// Files in both whose size or hash differ, as (here, there)
+    pub differing: Vec<(ManifestEntry, ManifestEntry)>,
+    /// Files in both that match
+    pub same: usize,
+}
+
+/// Counts of differences within one category.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CategoryDiff {
+    pub only_here: usize,
+    pub only_there: usize,
+    pub differing: usize,
+}
+
+impl Diff {
+    /// Compares the entries of the inspected sources with those of another capture.
+    pub fn compare(here: &[ManifestEntry], there: Vec<ManifestEntry>) -> Self {
+        let mut there: HashMap<String, ManifestEntry> = there
+            .into_iter()
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+
+        let mut diff = Self::default();
+        for entry in here {
+            match there.remove(&entry.path) {
+                None => diff.only_here.push(entry.clone()),
+                Some(other) => {
+                    let hashes_differ = entry.sha256.is_some()
+                        && other.sha256.is_some()
+                        && entry.sha256 != other.sha256;
+                    if entry.size != other.size || hashes_differ {
+                        diff.differing.push((entry.clone(), other));
+                    } else {
+                        diff.same += 1;
+                    }
+                }
+            }
+        }
+        diff.only_there = there.into_values().collect();
+
+        diff.only_here.sort_by(|a, b| a.path.cmp(&b.path));
+        diff.only_there.sort_by(|a, b| a.path.cmp(&b.path));
+        diff.differing.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+        diff
+    }
+
+    /// Returns true if both sides hold the same files.
+    pub fn is_empty(&self) -> bool {
+        self.only_here.is_empty() && self.only_there.is_empty() && self.differing.is_empty()
+    }
+
+    /// Counts the differences in each category, by name.
+    ///
+    /// Files that differ are counted under the category they have here.
+    pub fn by_category(&self) -> BTreeMap<&str, CategoryDiff> {
+        let mut categories: BTreeMap<&str, CategoryDiff> = BTreeMap::new();
+        for entry in &self.only_here {
+            categories.entry(&entry.category).or_default().only_here += 1;
+        }
+        for entry in &self.only_there {
+            categories.entry(&entry.category).or_default().only_there += 1;
+        }
+        for (entry, _) in &self.differing {
+            categories.entry(&entry.category).or_default().differing += 1;
+        }
+        categories
+    }
+}
+
+/// Reads the other side of a `--diff`.
+///
+/// `path` may be a manifest file, an export directory holding a
+/// `manifest.csv`, or any other directory, which is scanned with `config`.
+/// Files of a scanned directory are not hashed, so they are compared by size.
+///
+/// # Errors
+///
+/// Returns an error if `path` is a device or does not exist, the manifest
+/// cannot be read, or the scan fails.
+pub async fn load_other(path: &Path, config: &Config) -> color_eyre::Result<Vec<ManifestEntry>> {
+    if is_device_path(&path.to_string_lossy()) {
+        return Err(color_eyre::eyre::eyre!(
+            "Cannot compare with {}: it is a device; mount it read-only and pass the mount point",
+            path.display()
+        ));
+    }
+    if path.is_file() {
+        return Ok(Manifest::load(path).await?.into_entries());
+    }
+    if path.join(MANIFEST_FILE).is_file() {
+        return Ok(Manifest::load(&path.join(MANIFEST_FILE))
+            .await?
+            .into_entries());
+    }
+    if !path.is_dir() {
+        return Err(color_eyre::eyre::eyre!(
+            "Cannot compare with {}: no such file or directory",
+            path.display()
+        ));
+    }
+
+    let scan_stats = scan_directory(path, config, |_| {}).await?;
+    scan_manifest(&scan_stats, false, &Control::new(), || {}).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: u64, category: &str, sha256: Option<&str>) -> ManifestEntry {
+        ManifestEntry {
+            path: path.to_string(),
+            size,
+            modified: None,
+            category: category.to_string(),
+            sha256: sha256.map(str::to_string),
+            exported_path: None,
+            truncated_to: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_diff_against_directory_and_manifest() {
+        let here = vec![
+            entry("a/report.pdf", 10, "documents", None),
+            entry("photo.jpg", 5, "images", Some("aa")),
+            entry("notes.txt", 3, "documents", Some("bb")),
+            entry("same.txt", 4, "documents", None),
+        ];
+        let there = vec![
+            entry("a/report.pdf", 12, "documents", None),
+            entry("photo.jpg", 5, "images", Some("cc")),
+            entry("notes.txt", 3, "documents", None),
+            entry("same.txt", 4, "documents", None),
+            entry("song.mp3", 7, "audio", None),
+        ];
+
+        let diff = Diff::compare(&here, there);
+        let differing: Vec<&str> = diff
+            .differing
+            .iter()
+            .map(|(a, _)| a.path.as_str())
+            .collect();
+        assert_eq!(differing, ["a/report.pdf", "photo.jpg"]);
+        // A hash on only one side is not compared
+        assert_eq!(diff.same, 2);
+        assert_eq!(diff.only_there[0].path, "song.mp3");
+        assert!(diff.only_here.is_empty());
+
+        let categories = diff.by_category();
+        assert_eq!(categories["documents"].differing, 1);
+        assert_eq!(categories["audio"].only_there, 1);
+        assert!(Diff::compare(&here, here.clone()).is_empty());
+
+        // A plain directory is scanned, an export is read from its manifest
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("a")).unwrap();
+        std::fs::write(root.path().join("a/report.pdf"), b"0123456789").unwrap();
+        let config = Config::default();
+        let scanned = load_other(root.path(), &config).await.unwrap();
+        assert_eq!(scanned.len(), 1);
+        assert_eq!(scanned[0].path, "a/report.pdf");
+        assert_eq!(scanned[0].size, 10);
+
+        std::fs::write(
+            root.path().join(MANIFEST_FILE),
+            "path,size,modified,category\nsong.mp3,7,,audio\n",
+        )
+        .unwrap();
+        let exported = load_other(root.path(), &config).await.unwrap();
+        assert_eq!(exported[0].path, "song.mp3");
+        assert!(
+            load_other(&root.path().join("missing"), &config)
+                .await
+                .is_err()
+        );
+
+        // A device is refused with a hint, not reported as missing
+        #[cfg(target_os = "linux")]
+        {
+            let err = load_other(Path::new("/dev/null"), &config)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("it is a device"));
+        }
+    }
+}
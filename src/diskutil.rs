@@ -0,0 +1,663 @@
+//! macOS disk management via `diskutil`.
+//!
+//! This module enumerates external partitions and mounts them read-only using
+//! `diskutil`, providing the macOS side of the [`crate::mount`] and
+//! [`crate::device_picker`] workflows.
+
+use crate::config::MountConfig;
+use crate::device_picker::BlockDevice;
+use crate::mount::{check_mount_point, register_mount, take_mount};
+use crate::tui::UI;
+use dialoguer::Confirm;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Partition types that hold other volumes rather than a mountable filesystem.
+const CONTAINER_CONTENT: &[&str] = &[
+    "EFI",
+    "Apple_APFS",
+    "Apple_APFS_ISC",
+    "Apple_APFS_Recovery",
+    "Apple_CoreStorage",
+    "Apple_Boot",
+    "GUID_partition_scheme",
+    "FDisk_partition_scheme",
+];
+
+/// Top level of `diskutil list -plist`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DiskList {
+    all_disks_and_partitions: Vec<DiskEntry>,
+}
+
+/// A disk, partition, or APFS volume from `diskutil list -plist`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DiskEntry {
+    device_identifier: String,
+    size: Option<u64>,
+    content: Option<String>,
+    volume_name: Option<String>,
+    mount_point: Option<String>,
+    #[serde(default)]
+    partitions: Vec<DiskEntry>,
+    #[serde(default, rename = "APFSVolumes")]
+    apfs_volumes: Vec<DiskEntry>,
+}
+
+impl DiskEntry {
+    fn is_container(&self) -> bool {
+        self.content
+            .as_deref()
+            .is_some_and(|c| CONTAINER_CONTENT.contains(&c))
+    }
+
+    fn to_block_device(&self) -> BlockDevice {
+        let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.is_empty());
+
+        BlockDevice {
+            path: format!("/dev/{}", self.device_identifier),
+            size_bytes: self.size,
+            fstype: non_empty(&self.content),
+            label: non_empty(&self.volume_name),
+            encrypted: false,
+            mountpoint: non_empty(&self.mount_point),
+        }
+    }
+}
+
+/// Details for a single device from `diskutil info -plist`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DiskInfo {
+    /// Filesystem personality (e.g. `msdos`, `exfat`, `apfs`, `hfs`, `ntfs`)
+    pub filesystem_type: Option<String>,
+    /// Empty when the volume is not mounted
+    pub mount_point: Option<String>,
+    #[serde(default)]
+    pub writable_volume: bool,
+    #[serde(default)]
+    pub encryption: bool,
+    #[serde(default, rename = "FileVault")]
+    pub file_vault: bool,
+    /// Volume label; empty or missing for unnamed volumes
+    pub volume_name: Option<String>,
+}
+
+impl DiskInfo {
+    /// Returns the mount point, treating an empty string as unmounted.
+    pub fn mount_point(&self) -> Option<PathBuf> {
+        self.mount_point
+            .as_deref()
+            .filter(|m| !m.is_empty())
+            .map(PathBuf::from)
+    }
+}
+
+/// Parses `diskutil list -plist` output into mountable volumes.
+///
+/// Container partitions (EFI, APFS stores, partition maps) are skipped; a disk
+/// with no partitions or volumes is returned as a single device.
+pub fn parse_disk_list(xml: &[u8]) -> color_eyre::Result<Vec<BlockDevice>> {
+    let list: DiskList = plist::from_bytes(xml)?;
+    let mut devices = Vec::new();
+
+    for disk in &list.all_disks_and_partitions {
+        let children: Vec<&DiskEntry> = disk
+            .partitions
+            .iter()
+            .chain(&disk.apfs_volumes)
+            .filter(|entry| !entry.is_container())
+            .collect();
+
+        if !children.is_empty() {
+            devices.extend(children.iter().map(|entry| entry.to_block_device()));
+        } else if disk.partitions.is_empty() && disk.apfs_volumes.is_empty() && !disk.is_container()
+        {
+            devices.push(disk.to_block_device());
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Runs `diskutil info -plist` for a device or mount point.
+pub fn disk_info(device: &str) -> color_eyre::Result<Option<DiskInfo>> {
+    let output = Command::new("diskutil")
+        .args(["info", "-plist", device])
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(plist::from_bytes(&output.stdout)?))
+}
+
+/// Enumerate partitions and volumes on external disks.
+///
+/// Internal disks (including the boot volume) are never listed.
+pub fn list_devices() -> color_eyre::Result<Vec<BlockDevice>> {
+    let output = Command::new("diskutil")
+        .args(["list", "-plist", "external"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(color_eyre::eyre::eyre!(
+            "diskutil list failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut devices = parse_disk_list(&output.stdout)?;
+    for device in &mut devices {
+        if let Ok(Some(info)) = disk_info(&device.path) {
+            device.encrypted = info.encryption || info.file_vault;
+            if let Some(fstype) = info.filesystem_type.filter(|f| !f.is_empty()) {
+                device.fstype = Some(fstype);
+            }
+        }
+    }
+
+    devices.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(devices)
+}
+
+/// Extracts the mount point from `df -P <path>` output.
+///
+/// The mount point is everything after the capacity column (`NN%`), so
+/// filesystem names and mount points containing spaces are handled.
+pub fn parse_df_mount_point(df_output: &str) -> Option<PathBuf> {
+    let line = df_output.lines().nth(1)?;
+    let mut rest = line;
+
+    while let Some((field, tail)) = rest.trim_start().split_once(' ') {
+        if field.ends_with('%') {
+            return Some(PathBuf::from(tail.trim()));
+        }
+        rest = tail;
+    }
+
+    None
+}
+
+/// Parses `mount` output and reports whether `mount_point` is mounted read-only.
+///
+/// Lines look like `/dev/disk4s1 on /Volumes/USB (msdos, local, read-only)`.
+/// Returns `None` if `mount_point` is not listed.
+pub fn parse_mount_readonly(mount_output: &str, mount_point: &Path) -> Option<bool> {
+    mount_output.lines().find_map(|line| {
+        let (_, rest) = line.split_once(" on ")?;
+        let (target, options) = rest.rsplit_once(" (")?;
+        (Path::new(target) == mount_point).then(|| {
+            options
+                .trim_end_matches(')')
+                .split(',')
+                .any(|opt| opt.trim() == "read-only" || opt.trim() == "rdonly")
+        })
+    })
+}
+
+/// Check whether the filesystem containing `path` is mounted read-only.
+///
+/// `df` resolves the owning mount (following firmlinks such as `/Users` into
+/// the data volume) before the options are looked up in `mount`.
+pub fn is_mounted_readonly(path: &Path) -> color_eyre::Result<bool> {
+    let df = Command::new("df").arg("-P").arg(path).output()?;
+    let Some(mount_point) = parse_df_mount_point(&String::from_utf8_lossy(&df.stdout)) else {
+        return Ok(false);
+    };
+
+    let output = Command::new("mount").output()?;
+    Ok(
+        parse_mount_readonly(&String::from_utf8_lossy(&output.stdout), &mount_point)
+            .unwrap_or(false),
+    )
+}
+
+/// Mount `device` read-only with `diskutil mount readOnly`, returning its mount point.
+fn diskutil_mount_readonly(
+    device: &str,
+    mount_point: Option<&Path>,
+) -> color_eyre::Result<PathBuf> {
+    let mut command = Command::new("diskutil");
+    command.args(["mount", "readOnly"]);
+    if let Some(mount_point) = mount_point {
+        command.arg("-mountPoint").arg(mount_point);
+    }
+    let output = command.arg(device).output()?;
+
+    if !output.status.success() {
+        return Err(color_eyre::eyre::eyre!(
+            "{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    disk_info(device)?
+        .and_then(|info| info.mount_point())
+        .ok_or_else(|| color_eyre::eyre::eyre!("{} mounted but has no mount point", device))
+}
+
+/// Mount a device read-only, prompting before remounting or mounting unless
+/// `options.assume_yes` is set.
+pub fn mount_readonly(
+    device: &str,
+    options: &MountConfig,
+    theme: &str,
+) -> color_eyre::Result<PathBuf> {
+    let colorful_theme = UI::get_colorful_theme(theme);
+    let (info_style, warning_style, error_style, success_style) =
+        UI::get_static_status_styles(theme);
+    let white_bold = console::Style::new().white().bold();
+
+    let Some(info) = disk_info(device)? else {
+        println!(
+            "{} {}",
+            error_style.apply_to("[!] ERROR:").bold(),
+            white_bold.apply_to(format!("diskutil does not recognise {}", device))
+        );
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
+    };
+
+    // Check if already mounted
+    if let Some(existing_mount) = info.mount_point() {
+        println!(
+            "{} {}",
+            info_style.apply_to("[*]").bold(),
+            white_bold.apply_to(format!(
+                "Drive already mounted at: {}",
+                existing_mount.display()
+            ))
+        );
+
+        if !info.writable_volume {
+            println!(
+                "{} {}",
+                success_style.apply_to("[✓]").bold(),
+                white_bold.apply_to("Drive is mounted read-only")
+            );
+            return Ok(existing_mount);
+        }
+
+        println!(
+            "{} {}",
+            warning_style.apply_to("[!] WARNING:").bold(),
+            white_bold.apply_to("Drive is mounted READ-WRITE!")
+        );
+        println!(
+            "{}",
+            white_bold.apply_to("   For safety, the drive should be remounted read-only.")
+        );
+
+        let remount = options.assume_yes
+            || Confirm::with_theme(&colorful_theme)
+                .with_prompt("Remount as read-only?")
+                .default(true)
+                .interact()?;
+
+        if !remount {
+            println!(
+                "{} {}",
+                warning_style.apply_to("[!] WARNING:").bold(),
+                white_bold.apply_to("Continuing with read-write mount (NOT RECOMMENDED)")
+            );
+            return Ok(existing_mount);
+        }
+
+        println!(
+            "{} {}",
+            info_style.apply_to("[*]").bold(),
+            white_bold.apply_to(format!("Remounting {} as read-only...", device))
+        );
+
+        let output = Command::new("diskutil")
+            .args(["unmount", device])
+            .output()?;
+        let remounted = if output.status.success() {
+            diskutil_mount_readonly(device, None)
+        } else {
+            Err(color_eyre::eyre::eyre!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        };
+
+        return match remounted {
+            Ok(mount_point) => {
+                println!(
+                    "{} {}",
+                    success_style.apply_to("[✓]").bold(),
+                    white_bold.apply_to("Remounted as read-only")
+                );
+                Ok(mount_point)
+            }
+            Err(e) => {
+                println!(
+                    "{} {}",
+                    error_style.apply_to("[!] ERROR:").bold(),
+                    white_bold.apply_to("Failed to remount read-only")
+                );
+                println!("{}", white_bold.apply_to(e));
+                return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
+            }
+        };
+    }
+
+    // Drive not mounted - mount it
+    println!(
+        "{} {}",
+        info_style.apply_to("[*]").bold(),
+        white_bold.apply_to(format!("Drive {} is not mounted", device))
+    );
+
+    // A requested mount point must not shadow existing data
+    if let Some(path) = &options.mount_point
+        && let Err(e) = check_mount_point(path)
+    {
+        println!(
+            "{} {}",
+            error_style.apply_to("[!] ERROR:").bold(),
+            white_bold.apply_to(e)
+        );
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
+    }
+
+    let should_mount = options.assume_yes
+        || Confirm::with_theme(&colorful_theme)
+            .with_prompt("Mount as read-only?")
+            .default(true)
+            .interact()?;
+
+    if !should_mount {
+        println!(
+            "{} {}",
+            error_style.apply_to("[!] ERROR:").bold(),
+            white_bold.apply_to("Drive must be mounted to proceed")
+        );
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
+    }
+
+    println!(
+        "{} {}",
+        info_style.apply_to("[*]").bold(),
+        white_bold.apply_to(format!("Mounting {} (read-only)...", device))
+    );
+
+    // diskutil needs an existing directory; one created here is removed after unmounting
+    let remove_dir = match &options.mount_point {
+        Some(path) if !path.exists() => {
+            std::fs::create_dir_all(path)?;
+            true
+        }
+        _ => false,
+    };
+
+    match diskutil_mount_readonly(device, options.mount_point.as_deref()) {
+        Ok(mount_point) => {
+            register_mount(mount_point.clone(), remove_dir);
+
+            println!(
+                "{} {}",
+                success_style.apply_to("[✓]").bold(),
+                white_bold.apply_to(format!(
+                    "Drive mounted successfully at {}",
+                    mount_point.display()
+                ))
+            );
+            Ok(mount_point)
+        }
+        Err(e) => {
+            println!(
+                "{} {}",
+                error_style.apply_to("[!] ERROR:").bold(),
+                white_bold.apply_to("Failed to mount drive")
+            );
+            println!("{}", white_bold.apply_to(e));
+
+            println!();
+            println!("{}", white_bold.apply_to("TROUBLESHOOTING:"));
+            println!(
+                "{}",
+                white_bold.apply_to("  1. Check if device exists: diskutil list external")
+            );
+            println!(
+                "{}",
+                white_bold.apply_to(format!("  2. Check filesystem: diskutil info {}", device))
+            );
+            println!(
+                "{}",
+                white_bold.apply_to(format!(
+                    "  3. Try manual mount: diskutil mount readOnly {}",
+                    device
+                ))
+            );
+
+            Err(color_eyre::eyre::eyre!("Failed to mount {}", device))
+        }
+    }
+}
+
+/// Mount a device read-only without printing or prompting, for library use.
+///
+/// A volume already mounted read-only is used where it is. One mounted
+/// read-write is remounted read-only only with `options.assume_yes`, and is
+/// refused otherwise.
+pub fn mount_device(device: &str, options: &MountConfig) -> color_eyre::Result<PathBuf> {
+    let info = disk_info(device)?
+        .ok_or_else(|| color_eyre::eyre::eyre!("diskutil does not recognise {}", device))?;
+
+    if let Some(existing_mount) = info.mount_point() {
+        if !info.writable_volume {
+            return Ok(existing_mount);
+        }
+        if !options.assume_yes {
+            return Err(color_eyre::eyre::eyre!(
+                "{} is mounted read-write at {}; remount it read-only or set assume_yes",
+                device,
+                existing_mount.display()
+            ));
+        }
+        let output = Command::new("diskutil")
+            .args(["unmount", device])
+            .output()?;
+        if !output.status.success() {
+            return Err(color_eyre::eyre::eyre!(
+                "Failed to remount {} read-only: {}",
+                device,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        return diskutil_mount_readonly(device, None);
+    }
+
+    if let Some(path) = &options.mount_point {
+        check_mount_point(path)?;
+    }
+    let remove_dir = match &options.mount_point {
+        Some(path) if !path.exists() => {
+            std::fs::create_dir_all(path)?;
+            true
+        }
+        _ => false,
+    };
+
+    let mount_point = diskutil_mount_readonly(device, options.mount_point.as_deref())
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to mount {}: {}", device, e))?;
+    register_mount(mount_point.clone(), remove_dir);
+    Ok(mount_point)
+}
+
+/// Unmount a volume mounted by [`mount_device`] without printing. Volumes tap
+/// did not mount are left in place.
+pub fn unmount_device(mount_point: &Path) -> color_eyre::Result<()> {
+    let Some(remove_dir) = take_mount(mount_point) else {
+        return Ok(());
+    };
+
+    let output = Command::new("diskutil")
+        .arg("unmount")
+        .arg(mount_point)
+        .output()?;
+    if !output.status.success() {
+        // Still mounted, so a later attempt may unmount it
+        register_mount(mount_point.to_path_buf(), remove_dir);
+        return Err(color_eyre::eyre::eyre!(
+            "Failed to unmount {}: {}",
+            mount_point.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    if remove_dir {
+        let _ = std::fs::remove_dir(mount_point);
+    }
+    Ok(())
+}
+
+/// Unmount a volume with `diskutil unmount`, if this process mounted it.
+pub fn unmount(mount_point: &Path, theme: &str) -> color_eyre::Result<()> {
+    let (info_style, warning_style, _, success_style) = UI::get_static_status_styles(theme);
+    let white_bold = console::Style::new().white().bold();
+
+    let Some(remove_dir) = take_mount(mount_point) else {
+        println!(
+            "{} {}",
+            info_style.apply_to("[*]").bold(),
+            white_bold.apply_to("Skipping unmount - not a tap-managed mount point")
+        );
+        return Ok(());
+    };
+
+    println!(
+        "{} {}",
+        info_style.apply_to("[*]").bold(),
+        white_bold.apply_to(format!("Unmounting {}...", mount_point.display()))
+    );
+
+    let output = Command::new("diskutil")
+        .arg("unmount")
+        .arg(mount_point)
+        .output()?;
+
+    if !output.status.success() {
+        println!(
+            "{} {}",
+            warning_style.apply_to("[!] WARNING:").bold(),
+            white_bold.apply_to("Failed to unmount drive")
+        );
+        println!(
+            "{}",
+            white_bold.apply_to(String::from_utf8_lossy(&output.stderr))
+        );
+        return Err(color_eyre::eyre::eyre!("Failed to unmount drive"));
+    }
+
+    println!(
+        "{} {}",
+        success_style.apply_to("[✓]").bold(),
+        white_bold.apply_to("Drive unmounted successfully")
+    );
+
+    if remove_dir && std::fs::remove_dir(mount_point).is_ok() {
+        println!(
+            "{} {}",
+            success_style.apply_to("[✓]").bold(),
+            white_bold.apply_to("Mount point removed")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISK_LIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>AllDisksAndPartitions</key>
+    <array>
+        <dict>
+            <key>Content</key><string>GUID_partition_scheme</string>
+            <key>DeviceIdentifier</key><string>disk4</string>
+            <key>Size</key><integer>32015679488</integer>
+            <key>Partitions</key>
+            <array>
+                <dict>
+                    <key>Content</key><string>EFI</string>
+                    <key>DeviceIdentifier</key><string>disk4s1</string>
+                    <key>Size</key><integer>209715200</integer>
+                    <key>VolumeName</key><string>EFI</string>
+                </dict>
+                <dict>
+                    <key>Content</key><string>Microsoft Basic Data</string>
+                    <key>DeviceIdentifier</key><string>disk4s2</string>
+                    <key>MountPoint</key><string>/Volumes/EVIDENCE</string>
+                    <key>Size</key><integer>31804047360</integer>
+                    <key>VolumeName</key><string>EVIDENCE</string>
+                </dict>
+            </array>
+        </dict>
+        <dict>
+            <key>Content</key><string>Windows_FAT_32</string>
+            <key>DeviceIdentifier</key><string>disk5</string>
+            <key>Size</key><integer>4026531840</integer>
+            <key>VolumeName</key><string>CARD</string>
+        </dict>
+    </array>
+</dict>
+</plist>"#;
+
+    #[test]
+    fn test_parse_disk_list() {
+        let devices = parse_disk_list(DISK_LIST.as_bytes()).unwrap();
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].path, "/dev/disk4s2");
+        assert_eq!(devices[0].label.as_deref(), Some("EVIDENCE"));
+        assert_eq!(devices[0].mountpoint.as_deref(), Some("/Volumes/EVIDENCE"));
+        assert_eq!(devices[1].path, "/dev/disk5");
+        assert_eq!(devices[1].size_bytes, Some(4026531840));
+    }
+
+    #[test]
+    fn test_parse_df_mount_point() {
+        let df_output = "\
+Filesystem   512-blocks     Used Available Capacity  Mounted on
+/dev/disk4s2   62117280 20480000  41637280    33%    /Volumes/MY EVIDENCE
+";
+
+        assert_eq!(
+            parse_df_mount_point(df_output),
+            Some(PathBuf::from("/Volumes/MY EVIDENCE"))
+        );
+        assert_eq!(parse_df_mount_point(""), None);
+    }
+
+    #[test]
+    fn test_parse_mount_readonly() {
+        let mount_output = "\
+/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)
+/dev/disk3s5 on /System/Volumes/Data (apfs, local, journaled, nobrowse)
+/dev/disk4s2 on /Volumes/EVIDENCE (msdos, local, nodev, nosuid, read-only, noowners)
+";
+
+        assert_eq!(
+            parse_mount_readonly(mount_output, Path::new("/Volumes/EVIDENCE")),
+            Some(true)
+        );
+        assert_eq!(
+            parse_mount_readonly(mount_output, Path::new("/System/Volumes/Data")),
+            Some(false)
+        );
+        assert_eq!(
+            parse_mount_readonly(mount_output, Path::new("/Volumes/OTHER")),
+            None
+        );
+    }
+}
@@ -5,29 +5,84 @@
 //! performance and provides detailed progress tracking.
 
 use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::io::{ErrorKind, IsTerminal};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::sync::Mutex;
+use tokio::task;
 use walkdir::WalkDir;
 
 use dialoguer::Confirm;
 
-use crate::config::Config;
-use crate::log::write_log_file;
-use crate::mount::{mount_drive_readonly, unmount_drive, validate_source_path};
-use crate::scanner::{ScanStats, count_files, scan_directory};
-use crate::tui::{Mode, UI};
-use crate::zip::zip_directory;
+use crate::config::{CollisionStrategy, Config, ExportConfig, render_rename_template};
+use crate::control::{Control, KeyListener};
+use crate::hash::{sha256_file, sha256_head};
+use crate::last_run;
+use crate::log::{ErrorEcho, write_log_file};
+use crate::manifest::{
+    Change, INVENTORY_FILE, MANIFEST_FILE, Manifest, ManifestEntry, PATH_MAP_FILE, write_inventory,
+    write_manifest, write_path_map,
+};
+use crate::mount::{
+    is_device_path, mount_drive_readonly, release_drive, validate_source_path,
+    verify_still_readonly,
+};
+use crate::progress::ProgressFile;
+use crate::report::write_html_report;
+use crate::scanner::{
+    ScanStats, bundle_size, count_files, relative_base, scan_directory_with_control,
+};
+use crate::throttle::{RateLimiter, copy_head, copy_throttled};
+use crate::tui::{Mode, RedrawThrottle, Throughput, UI, format_duration, format_rate, format_size};
+use crate::zip::{archive_path, join_volumes_command, zip_directory};
 
 /// Statistics about an export operation.
 ///
-/// Tracks the number of files successfully copied, failed copies,
-/// and detailed error messages.
+/// Tracks the number of files successfully copied, files skipped because an
+/// identical copy already existed, failed copies, sources that only copied
+/// after a retry, detailed error messages, how long the copy took, and where
+/// each source file ended up.
 pub struct ExportStats {
     pub copied: usize,
+    /// Files left alone because an identical file was already at the destination
+    pub skipped: usize,
     pub failed: usize,
+    /// Source files that copied successfully after one or more retries
+    pub retried: Vec<PathBuf>,
+    /// Source files not copied because a different file already had their name (`skip` strategy)
+    pub collided: Vec<PathBuf>,
+    /// Source files whose copy a later file with the same name replaced (`overwrite` strategy)
+    pub overwritten: Vec<PathBuf>,
     pub errors: Vec<String>,
+    /// Bytes written to the destination (excludes files already present)
+    pub bytes_copied: u64,
+    /// Size of the source files that failed to copy
+    pub bytes_failed: u64,
+    /// `(path relative to the output directory, source path)` for every file copied or already present
+    pub exported: Vec<(PathBuf, PathBuf)>,
+    /// Wall-clock time spent copying
+    pub duration: Duration,
+    /// The export was cancelled before every file was copied
+    pub cancelled: bool,
+    /// With `--since-manifest`: files not in the previous manifest
+    pub added: usize,
+    /// With `--since-manifest`: files whose size, modification time, or hash changed
+    pub changed: usize,
+    /// With `--since-manifest`: files left out because they match the previous manifest
+    pub unchanged: usize,
+    /// Files exported only up to `export.truncate_to` bytes
+    pub truncated: usize,
+    /// Copies re-read and confirmed to match their source (`--verify-copies`)
+    pub verified: usize,
+    /// Source files whose extended attributes were copied onto the copy (`--preserve-xattrs`)
+    pub xattrs_preserved: Vec<PathBuf>,
+    /// Files copied without some or all of their extended attributes, with the reason
+    pub xattr_failures: Vec<String>,
+    /// Entries for `manifest.csv`: every file copied, already present, or unchanged
+    pub manifest: Vec<ManifestEntry>,
 }
 
 impl Default for ExportStats {
@@ -41,20 +96,356 @@ impl ExportStats {
     pub fn new() -> Self {
         Self {
             copied: 0,
+            skipped: 0,
             failed: 0,
+            retried: Vec::new(),
+            collided: Vec::new(),
+            overwritten: Vec::new(),
             errors: Vec::new(),
+            bytes_copied: 0,
+            bytes_failed: 0,
+            exported: Vec::new(),
+            duration: Duration::ZERO,
+            cancelled: false,
+            added: 0,
+            changed: 0,
+            unchanged: 0,
+            truncated: 0,
+            verified: 0,
+            xattrs_preserved: Vec::new(),
+            xattr_failures: Vec::new(),
+            manifest: Vec::new(),
         }
     }
 }
 
+/// Delay before the first retry; doubled for each subsequent attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Longest wait between two attempts, however many retries are configured.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Backoff before retry number `attempt + 1`, doubling up to [`RETRY_MAX_DELAY`].
+fn retry_delay(attempt: u32) -> Duration {
+    2u32.checked_pow(attempt)
+        .and_then(|factor| RETRY_BASE_DELAY.checked_mul(factor))
+        .map_or(RETRY_MAX_DELAY, |delay| delay.min(RETRY_MAX_DELAY))
+}
+
+/// Returns true for I/O errors that are worth retrying on flaky media.
+fn is_retryable(error: &std::io::Error) -> bool {
+    // EIO has no stable `ErrorKind`, so match the raw code (5 on Linux and macOS)
+    const EIO: i32 = 5;
+
+    matches!(
+        error.kind(),
+        ErrorKind::Interrupted | ErrorKind::TimedOut | ErrorKind::Other
+    ) || (cfg!(unix) && error.raw_os_error() == Some(EIO))
+}
+
+/// Copies a single file, paced by `limiter` when one is given.
+async fn copy_file(src: &Path, dest: &Path, limiter: Option<&RateLimiter>) -> std::io::Result<u64> {
+    match limiter {
+        Some(limiter) => copy_throttled(src, dest, limiter).await,
+        None => fs::copy(src, dest).await,
+    }
+}
+
+/// Copies a bundle directory and everything inside it to `dest`.
+async fn copy_tree(src: &Path, dest: &Path, limiter: Option<&RateLimiter>) -> std::io::Result<u64> {
+    let root = src.to_path_buf();
+    let entries = task::spawn_blocking(move || {
+        WalkDir::new(&root)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    let mut copied = 0;
+    for entry in entries {
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(std::io::Error::other)?;
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).await?;
+        } else if entry.file_type().is_symlink() {
+            copy_symlink(entry.path(), &target).await?;
+        } else {
+            copied += copy_file(entry.path(), &target, limiter).await?;
+        }
+    }
+    Ok(copied)
+}
+
+/// Recreates the symlink at `src` as `dest`, replacing one left by an earlier attempt.
+#[cfg(unix)]
+async fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let target = fs::read_link(src).await?;
+    let _ = fs::remove_file(dest).await;
+    fs::symlink(target, dest).await
+}
+
+/// Symlinks inside bundles are only recreated on Unix; elsewhere they are left out.
+#[cfg(not(unix))]
+async fn copy_symlink(_src: &Path, _dest: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Copies `src` to `dest`, retrying transient failures up to `retries` times.
+///
+/// Returns the number of retries that were needed. A directory source is a
+/// bundle recorded by the scanner and is copied with all of its contents.
+/// Files larger than `truncate_to` are copied only up to that many bytes.
+///
+/// When `limiter` is given the copy is paced by it instead of running at full speed.
+async fn copy_with_retry(
+    src: &Path,
+    dest: &Path,
+    retries: u32,
+    truncate_to: Option<u64>,
+    limiter: Option<&RateLimiter>,
+) -> std::io::Result<u32> {
+    // Files within the limit are copied whole, the usual way
+    let truncate_to = match truncate_to {
+        Some(limit) if !src.is_dir() && fs::metadata(src).await?.len() > limit => Some(limit),
+        _ => None,
+    };
+    let mut attempt = 0;
+    loop {
+        let result = if src.is_dir() {
+            copy_tree(src, dest, limiter).await
+        } else if let Some(limit) = truncate_to {
+            copy_head(src, dest, limit, limiter).await
+        } else {
+            copy_file(src, dest, limiter).await
+        };
+        match result {
+            Ok(_) => return Ok(attempt),
+            Err(e) if attempt < retries && is_retryable(&e) => {
+                tokio::time::sleep(retry_delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Result of exporting a single file.
+enum CopyOutcome {
+    /// The file was written to `dest` after `retries` retried attempts
+    Copied { dest: PathBuf, retries: u32 },
+    /// An identical file was already present at `dest`, the target name or one of its `_N` variants
+    AlreadyPresent { dest: PathBuf },
+    /// A different file already had the name and was kept
+    NameTaken,
+}
+
+/// Largest file compared by SHA-256 when `checksum_source` is off.
+const QUICK_HASH_BYTES: u64 = 1024 * 1024;
+
+/// Returns true if `dest` holds the same content as `src`: equal size and
+/// SHA-256 of the whole file.
+///
+/// Without `checksum`, files larger than [`QUICK_HASH_BYTES`] are not read
+/// and never count as identical, so they are copied under another name
+/// rather than risk dropping a different file that shares a size and header.
+///
+/// Bundle directories are compared by the total size of their contents only,
+/// and files larger than `truncate_to` against that much of the source.
+async fn is_identical(
+    src: &Path,
+    dest: &Path,
+    checksum: bool,
+    truncate_to: Option<u64>,
+) -> std::io::Result<bool> {
+    if src.is_dir() {
+        let (src, dest) = (src.to_path_buf(), dest.to_path_buf());
+        return task::spawn_blocking(move || {
+            dest.is_dir() && bundle_size(&src).0 == bundle_size(&dest).0
+        })
+        .await
+        .map_err(std::io::Error::other);
+    }
+    let src_len = fs::metadata(src).await?.len();
+    let expected = truncate_to.map_or(src_len, |limit| src_len.min(limit));
+    if expected != fs::metadata(dest).await?.len() {
+        return Ok(false);
+    }
+
+    if !checksum && expected > QUICK_HASH_BYTES {
+        return Ok(false);
+    }
+    let (src, dest) = (src.to_path_buf(), dest.to_path_buf());
+    task::spawn_blocking(move || Ok(sha256_head(&src, expected)? == sha256_head(&dest, expected)?))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+/// Flushes the copy at `path` to the device and, on Linux, drops its pages
+/// from the page cache, so that reading it back reads what reached the disk.
+///
+/// Other platforms have no portable way to drop the cache, so there the copy
+/// may still be verified as cached after the flush.
+fn drop_cached_copy(path: &Path) -> std::io::Result<()> {
+    let file = std::fs::File::open(path)?;
+    // Windows only flushes handles opened for writing, so a refusal there is not fatal
+    if let Err(e) = file.sync_all()
+        && cfg!(unix)
+    {
+        return Err(e);
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        // SAFETY: the descriptor belongs to `file`, which outlives the call;
+        // the advice only affects caching. A refusal leaves the pages cached,
+        // so its result is ignored.
+        unsafe {
+            libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+        }
+    }
+    Ok(())
+}
+
+/// Re-reads a fresh copy and its source for `--verify-copies`.
+///
+/// Bundle directories are compared by the total size of their contents, files
+/// by size and SHA-256; a copy truncated to `truncated_to` bytes is compared
+/// with that much of the source. The copy is flushed and dropped from the page
+/// cache first (see [`drop_cached_copy`]). A copy that does not match is
+/// removed, so the next export writes it again instead of taking it for
+/// already present.
+async fn verify_copy(src: &Path, dest: &Path, truncated_to: Option<u64>) -> color_eyre::Result<()> {
+    let (src, copy) = (src.to_path_buf(), dest.to_path_buf());
+    let mismatch = task::spawn_blocking(move || -> std::io::Result<Option<String>> {
+        let dest = copy;
+        if src.is_dir() {
+            let (expected, found) = (bundle_size(&src).0, bundle_size(&dest).0);
+            return Ok((expected != found)
+                .then(|| format!("bundle holds {} bytes, source {}", found, expected)));
+        }
+        let limit = truncated_to.unwrap_or(u64::MAX);
+        let expected = std::fs::metadata(&src)?.len().min(limit);
+        let found = std::fs::metadata(&dest)?.len();
+        if expected != found {
+            return Ok(Some(format!(
+                "copy is {} bytes, source {}",
+                found, expected
+            )));
+        }
+        drop_cached_copy(&dest)?;
+        if sha256_head(&src, limit)? != sha256_file(&dest)? {
+            return Ok(Some(
+                "SHA-256 of the copy differs from the source".to_string(),
+            ));
+        }
+        Ok(None)
+    })
+    .await??;
+
+    match mismatch {
+        None => Ok(()),
+        Some(reason) => {
+            let removed = if dest.is_dir() {
+                fs::remove_dir_all(dest).await
+            } else {
+                fs::remove_file(dest).await
+            };
+            Err(color_eyre::eyre::eyre!(
+                "verification failed, {}{}",
+                reason,
+                if removed.is_ok() {
+                    " (copy removed)"
+                } else {
+                    ""
+                }
+            ))
+        }
+    }
+}
+
+/// Copies the extended attributes of `src`, macOS resource forks included, onto `dest`.
+///
+/// Returns how many were copied; a source filesystem without extended
+/// attributes has none. On Linux only the namespaces [`copies_xattr`] allows
+/// are copied. Attributes the destination rejects, as FAT and many network
+/// shares do, or that need privileges to set, are named in the error once the
+/// others have been copied.
+#[cfg(unix)]
+fn copy_xattrs(src: &Path, dest: &Path) -> std::io::Result<usize> {
+    let names = match xattr::list(src) {
+        Ok(names) => names,
+        Err(e) if e.raw_os_error() == Some(libc::ENOTSUP) => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let root = unsafe { libc::geteuid() } == 0;
+
+    let mut copied = 0;
+    let mut failed = Vec::new();
+    for name in names.filter(|name| copies_xattr(name, root)) {
+        let result = xattr::get(src, &name).and_then(|value| match value {
+            Some(value) => xattr::set(dest, &name, &value).map(|()| 1),
+            // Removed since it was listed
+            None => Ok(0),
+        });
+        match result {
+            Ok(n) => copied += n,
+            Err(e) => failed.push(format!("{}: {}", name.to_string_lossy(), e)),
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(copied)
+    } else {
+        Err(std::io::Error::other(format!(
+            "{} of {} extended attribute(s) not copied ({})",
+            failed.len(),
+            copied + failed.len(),
+            failed.join("; ")
+        )))
+    }
+}
+
+/// Whether the extended attribute `name` belongs on the copy.
+///
+/// On Linux, `security.*` (SELinux labels) and `system.*` (ACLs) describe the
+/// source host's policy: they fail on every file, or as root stamp the source's
+/// labels onto the export. Only `user.*` is copied, and `trusted.*` as root,
+/// who alone can read it. Elsewhere, as with macOS's `com.apple.*`, every
+/// attribute is copied.
+#[cfg(unix)]
+fn copies_xattr(name: &std::ffi::OsStr, root: bool) -> bool {
+    if !cfg!(target_os = "linux") {
+        return true;
+    }
+    let name = name.as_encoded_bytes();
+    name.starts_with(b"user.") || (root && name.starts_with(b"trusted."))
+}
+
+/// Extended attributes are only read on Unix; elsewhere there are none to copy.
+#[cfg(not(unix))]
+fn copy_xattrs(_src: &Path, _dest: &Path) -> std::io::Result<usize> {
+    Ok(0)
+}
+
+/// Copies `src` into `dest_dir` as `filename`, giving it a name from
+/// `options.rename_template` if that is taken by a different file.
 async fn copy_file_with_rename(
     src: &Path,
     dest_dir: &Path,
     filename: &str,
-) -> color_eyre::Result<PathBuf> {
+    options: &ExportConfig,
+    limiter: Option<&RateLimiter>,
+) -> color_eyre::Result<CopyOutcome> {
+    let (template, checksum) = (options.rename_template.as_str(), options.checksum_source);
+    let truncate_to = options.truncate_to;
     let mut dest_path = dest_dir.join(filename);
 
-    // Handle duplicate filenames
+    // Handle duplicate filenames, skipping the copy if any existing variant is identical
     if dest_path.exists() {
         let stem = Path::new(filename)
             .file_stem()
@@ -67,12 +458,11 @@ async fn copy_file_with_rename(
 
         let mut counter = 1;
         loop {
-            let new_filename = if extension.is_empty() {
-                format!("{}_{}", stem, counter)
-            } else {
-                format!("{}_{}.{}", stem, counter, extension)
-            };
+            if is_identical(src, &dest_path, checksum, truncate_to).await? {
+                return Ok(CopyOutcome::AlreadyPresent { dest: dest_path });
+            }
 
+            let new_filename = render_rename_template(template, stem, counter, extension);
             dest_path = dest_dir.join(new_filename);
 
             if !dest_path.exists() {
@@ -81,39 +471,138 @@ async fn copy_file_with_rename(
             counter += 1;
         }
     }
-    fs::copy(src, &dest_path).await?;
-    Ok(dest_path)
+    let retries =
+        copy_with_retry(src, &dest_path, options.copy_retries, truncate_to, limiter).await?;
+    Ok(CopyOutcome::Copied {
+        dest: dest_path,
+        retries,
+    })
+}
+
+/// Copies `src` into `dest_dir` as `filename`, resolving a name already
+/// taken by a different file with the configured collision strategy.
+async fn copy_file_on_collision(
+    src: &Path,
+    dest_dir: &Path,
+    filename: &str,
+    options: &ExportConfig,
+    limiter: Option<&RateLimiter>,
+) -> color_eyre::Result<CopyOutcome> {
+    let retries = options.copy_retries;
+    let checksum = options.checksum_source;
+    let truncate_to = options.truncate_to;
+    let dest = dest_dir.join(filename);
+    if !dest.exists() {
+        let retries = copy_with_retry(src, &dest, retries, truncate_to, limiter).await?;
+        return Ok(CopyOutcome::Copied { dest, retries });
+    }
+
+    match options.collision_strategy {
+        CollisionStrategy::Rename => {
+            copy_file_with_rename(src, dest_dir, filename, options, limiter).await
+        }
+        CollisionStrategy::Skip => {
+            if is_identical(src, &dest, checksum, truncate_to).await? {
+                Ok(CopyOutcome::AlreadyPresent { dest })
+            } else {
+                Ok(CopyOutcome::NameTaken)
+            }
+        }
+        CollisionStrategy::Overwrite => {
+            if is_identical(src, &dest, checksum, truncate_to).await? {
+                return Ok(CopyOutcome::AlreadyPresent { dest });
+            }
+            // A bundle copied over another would otherwise keep the old one's extra files
+            if dest.is_dir() {
+                fs::remove_dir_all(&dest).await?;
+            }
+            let retries = copy_with_retry(src, &dest, retries, truncate_to, limiter).await?;
+            Ok(CopyOutcome::Copied { dest, retries })
+        }
+        CollisionStrategy::HashSuffix => {
+            // Bundles have no single content hash, so they fall back to `_N` names
+            if src.is_dir() {
+                return copy_file_with_rename(src, dest_dir, filename, options, limiter).await;
+            }
+            if is_identical(src, &dest, true, truncate_to).await? {
+                return Ok(CopyOutcome::AlreadyPresent { dest });
+            }
+
+            let path = src.to_path_buf();
+            let digest = task::spawn_blocking(move || sha256_file(&path)).await??;
+            let name = Path::new(filename);
+            let stem = name.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+            let hashed = match name.extension().and_then(|s| s.to_str()) {
+                Some(extension) => format!("{}_{}.{}", stem, &digest[..8], extension),
+                None => format!("{}_{}", stem, &digest[..8]),
+            };
+            // Same hash, same contents: another copy of this file is already there
+            let dest = dest_dir.join(hashed);
+            if dest.exists() && is_identical(src, &dest, true, truncate_to).await? {
+                return Ok(CopyOutcome::AlreadyPresent { dest });
+            }
+            let retries = copy_with_retry(src, &dest, retries, truncate_to, limiter).await?;
+            Ok(CopyOutcome::Copied { dest, retries })
+        }
+    }
 }
 
+/// Files tap writes into the output directory itself.
+const RESERVED_NAMES: [&str; 5] = [
+    "tap.log",
+    "tap.log.gz",
+    MANIFEST_FILE,
+    PATH_MAP_FILE,
+    INVENTORY_FILE,
+];
+
 pub async fn export_files<F, Fut>(
     scan_stats: &ScanStats,
     dest_base: &Path,
+    config: &Config,
+    previous: Option<&Manifest>,
+    control: &Control,
+    throughput: Option<&Throughput>,
     progress_callback: F,
 ) -> color_eyre::Result<ExportStats>
 where
     F: Fn(String) -> Fut + Send + Sync + 'static,
     Fut: std::future::Future<Output = ()> + Send,
 {
+    let started = Instant::now();
     let export_stats = Arc::new(Mutex::new(ExportStats::new()));
     let callback = Arc::new(progress_callback);
+    let checksum = config.export.checksum_source;
+    let limiter = config.export.rate_limit.map(RateLimiter::new);
+    let limiter = limiter.as_ref();
+    let flat = config.export.flat;
+    let echo = ErrorEcho::new(config.ui.verbose, control);
+    let echo = &echo;
 
     // Create base destination directiory
     fs::create_dir_all(dest_base).await?;
 
     // Create category directory
-    for category in scan_stats.files_by_category.keys() {
-        let category_dir = dest_base.join(category);
-        fs::create_dir_all(&category_dir).await?;
+    if !flat {
+        for category in scan_stats.files_by_category.keys() {
+            let category_dir = dest_base.join(config.category_dir(category));
+            fs::create_dir_all(&category_dir).await?;
+        }
     }
 
-    // Collect all files to copy
+    // Collect all files to copy, with the directory each goes to; flat exports use the base
     let all_files: Vec<_> = scan_stats
         .files_by_category
         .iter()
         .flat_map(|(category, files)| {
+            let dest_dir = if flat {
+                dest_base.to_path_buf()
+            } else {
+                dest_base.join(config.category_dir(category))
+            };
             files
                 .iter()
-                .map(move |file| (category.clone(), file.clone()))
+                .map(move |file| (dest_dir.clone(), file.clone()))
         })
         .collect();
 
@@ -121,36 +610,172 @@ where
     // Note: This could be configurable via Config in the future
     const MAX_CONCURRENT_COPIES: usize = 10;
 
+    // One lock per destination name, so files sharing a name are copied one at
+    // a time and each sees the copies made before it
+    let name_locks: std::sync::Mutex<HashMap<PathBuf, Arc<Mutex<()>>>> = Default::default();
+    let name_locks = &name_locks;
+
     stream::iter(all_files)
-        .map(|(category, file_info)| {
+        .map(|(category_dir, file_info)| {
             let dest_base = dest_base.to_path_buf();
             let export_stats = Arc::clone(&export_stats);
             let callback = Arc::clone(&callback);
 
             async move {
-                let category_dir = dest_base.join(&category);
+                // Copies already in flight finish; the rest are never started
+                if !control.wait().await {
+                    return;
+                }
+
                 let filename = file_info
                     .path
                     .file_name()
                     .and_then(|s| s.to_str())
                     .unwrap_or("unknown");
+                // A flat export shares its directory with the files tap writes at the end
+                let filename = if flat && RESERVED_NAMES.contains(&filename) {
+                    format!("source_{}", filename)
+                } else {
+                    filename.to_string()
+                };
+                let name_lock = name_locks
+                    .lock()
+                    .map(|mut locks| {
+                        Arc::clone(locks.entry(category_dir.join(&filename)).or_default())
+                    })
+                    .unwrap_or_default();
+                let _same_name = name_lock.lock().await;
 
                 callback(file_info.path.display().to_string()).await;
 
-                match copy_file_with_rename(&file_info.path, &category_dir, filename).await {
-                    Ok(_) => {
-                        let mut stats = export_stats.lock().await;
+                let mut entry = ManifestEntry::new(&file_info, relative_base(&scan_stats.root));
+                if checksum {
+                    let path = file_info.path.clone();
+                    entry.sha256 = task::spawn_blocking(move || sha256_file(&path).ok())
+                        .await
+                        .ok()
+                        .flatten();
+                }
+
+                let change = previous.map(|manifest| manifest.classify(&entry));
+                if change == Some(Change::Unchanged) {
+                    let mut stats = export_stats.lock().await;
+                    stats.unchanged += 1;
+                    stats.manifest.push(entry);
+                    return;
+                }
+
+                // Bundles are never truncated, only files over the limit
+                let truncated = config
+                    .export
+                    .truncate_to
+                    .filter(|&limit| file_info.size > limit && !file_info.path.is_dir());
+
+                let mut outcome = copy_file_on_collision(
+                    &file_info.path,
+                    &category_dir,
+                    &filename,
+                    &config.export,
+                    limiter,
+                )
+                .await;
+                let verify = config.export.verify_copies;
+                if verify
+                    && let Ok(CopyOutcome::Copied { dest, .. }) = &outcome
+                    && let Err(e) = verify_copy(&file_info.path, dest, truncated).await
+                {
+                    outcome = Err(e);
+                }
+                let xattrs = match &outcome {
+                    Ok(CopyOutcome::Copied { dest, .. }) if config.export.preserve_xattrs => {
+                        let (src, dest) = (file_info.path.clone(), dest.clone());
+                        Some(
+                            task::spawn_blocking(move || copy_xattrs(&src, &dest))
+                                .await
+                                .map_err(std::io::Error::other)
+                                .and_then(|result| result),
+                        )
+                    }
+                    _ => None,
+                };
+
+                let mut stats = export_stats.lock().await;
+                let copied = matches!(outcome, Ok(CopyOutcome::Copied { .. }));
+                let dest = match outcome {
+                    Ok(CopyOutcome::Copied { dest, retries }) => {
                         stats.copied += 1;
+                        let bytes = truncated.unwrap_or(file_info.size);
+                        stats.bytes_copied += bytes;
+                        if let Some(throughput) = throughput {
+                            throughput.add(bytes);
+                        }
+                        if retries > 0 {
+                            stats.retried.push(file_info.path.clone());
+                        }
+                        if verify {
+                            stats.verified += 1;
+                        }
+                        match xattrs {
+                            Some(Ok(0)) | None => {}
+                            Some(Ok(_)) => stats.xattrs_preserved.push(file_info.path.clone()),
+                            Some(Err(e)) => {
+                                let message = format!(
+                                    "Extended attributes of {} not preserved: {}",
+                                    file_info.path.display(),
+                                    e
+                                );
+                                echo.echo(&message);
+                                stats.xattr_failures.push(message);
+                            }
+                        }
+                        dest
+                    }
+                    Ok(CopyOutcome::AlreadyPresent { dest }) => {
+                        stats.skipped += 1;
+                        dest
+                    }
+                    Ok(CopyOutcome::NameTaken) => {
+                        // Not exported, so left out of the manifest like a failed copy
+                        stats.collided.push(file_info.path.clone());
+                        return;
                     }
                     Err(e) => {
-                        let mut stats = export_stats.lock().await;
+                        let message = format!("Failed to copy {}: {}", file_info.path.display(), e);
+                        echo.echo(&message);
                         stats.failed += 1;
-                        stats.errors.push(format!(
-                            "Failed to copy {}: {}",
-                            file_info.path.display(),
-                            e
-                        ));
+                        stats.bytes_failed += file_info.size;
+                        stats.errors.push(message);
+                        // Left out of the manifest so the next incremental export retries it
+                        return;
                     }
+                };
+
+                let relative = dest.strip_prefix(&dest_base).unwrap_or(&dest);
+                entry.set_exported_path(relative);
+                // Only `overwrite` copies onto a file exported earlier in this run,
+                // which then no longer has a copy of its own
+                if copied
+                    && let Some(index) =
+                        stats.exported.iter().position(|(path, _)| path == relative)
+                {
+                    let (_, replaced) = stats.exported.remove(index);
+                    stats
+                        .manifest
+                        .retain(|earlier| earlier.exported_path != entry.exported_path);
+                    stats.overwritten.push(replaced);
+                }
+                stats
+                    .exported
+                    .push((relative.to_path_buf(), file_info.path.clone()));
+                if truncated.is_some() {
+                    stats.truncated += 1;
+                    entry.truncated_to = truncated;
+                }
+                stats.manifest.push(entry);
+                match change {
+                    Some(Change::Added) => stats.added += 1,
+                    Some(Change::Changed) => stats.changed += 1,
+                    _ => {}
                 }
             }
         })
@@ -158,21 +783,231 @@ where
         .collect::<Vec<_>>()
         .await;
 
-    let export_stats = Arc::try_unwrap(export_stats)
+    let mut export_stats = Arc::try_unwrap(export_stats)
         .map_err(|_| color_eyre::eyre::eyre!("Failed to unwrap export stats"))?
         .into_inner();
+    export_stats.duration = started.elapsed();
+    export_stats.cancelled = control.is_cancelled();
 
     Ok(export_stats)
 }
 
+/// Files written next to an export's copies, as returned by [`write_export_outputs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOutputs {
+    /// `manifest.csv`, unless the export was inventory only
+    pub manifest: Option<PathBuf>,
+    /// `inventory.csv`, for inventory-only exports
+    pub inventory: Option<PathBuf>,
+    /// `paths.tsv`, when `export.path_map` is set
+    pub path_map: Option<PathBuf>,
+    /// `tap.log`, or `tap.log.gz` when compressed
+    pub log: PathBuf,
+}
+
+/// Writes the manifest or inventory, the path map, and `tap.log` to `output_dir`.
+///
+/// The directory is created first, since a cancelled scan never reaches the
+/// copy phase that would create it.
+pub async fn write_export_outputs(
+    output_dir: &Path,
+    scan_stats: &ScanStats,
+    export_stats: &ExportStats,
+    config: &Config,
+) -> color_eyre::Result<ExportOutputs> {
+    fs::create_dir_all(output_dir).await?;
+
+    let (manifest, inventory) = if config.export.inventory_only {
+        let inventory = write_inventory(output_dir, scan_stats, config).await?;
+        (None, Some(inventory))
+    } else {
+        let manifest =
+            write_manifest(output_dir, &export_stats.manifest, scan_stats.sample).await?;
+        (Some(manifest), None)
+    };
+
+    let path_map = if config.export.path_map {
+        Some(write_path_map(output_dir, &export_stats.exported).await?)
+    } else {
+        None
+    };
+
+    let log = write_log_file(output_dir, scan_stats, export_stats, &config.log).await?;
+
+    Ok(ExportOutputs {
+        manifest,
+        inventory,
+        path_map,
+        log,
+    })
+}
+
+/// Returns true if `source` looks like the output directory of an earlier export.
+///
+/// That is a `tap.log` (possibly compressed) or `manifest.csv` at the top level
+/// alongside at least one directory named after a category.
+fn looks_like_export(source: &Path, config: &Config) -> bool {
+    if !["tap.log", "tap.log.gz", MANIFEST_FILE]
+        .iter()
+        .any(|name| source.join(name).is_file())
+    {
+        return false;
+    }
+
+    config
+        .categories
+        .keys()
+        .map(String::as_str)
+        .chain(std::iter::once("misc"))
+        .any(|category| source.join(config.category_dir(category)).is_dir())
+}
+
+/// Top-level system directories `--clean` never deletes, nor anything inside them.
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib64",
+    "/opt",
+    "/proc",
+    "/root",
+    "/sbin",
+    "/srv",
+    "/sys",
+    "/usr",
+    "/var",
+    "/private/etc",
+    "/System",
+    "/Library",
+    "/Applications",
+];
+
+/// Checks that `--clean` may delete `output_dir` before exporting `drive` into it.
+///
+/// Refuses filesystem roots and their direct children (`/home`, `C:\\Windows`),
+/// system directories, and any directory holding the home directory, the
+/// working directory, or the source.
+fn check_clean_target(output_dir: &Path, drive: &str) -> color_eyre::Result<()> {
+    let target = std::fs::canonicalize(output_dir)?;
+    let refuse = |reason: &str| {
+        Err(color_eyre::eyre::eyre!(
+            "Refusing to delete {} for --clean: {}",
+            target.display(),
+            reason
+        ))
+    };
+
+    if !target.is_dir() {
+        return refuse("it is not a directory");
+    }
+    if target
+        .parent()
+        .is_none_or(|parent| parent.parent().is_none())
+    {
+        return refuse("it is a filesystem root or top-level directory");
+    }
+    if SYSTEM_DIRS.iter().any(|dir| target.starts_with(dir)) {
+        return refuse("it is a system directory");
+    }
+    let holds = |path: Option<PathBuf>| {
+        path.and_then(|p| std::fs::canonicalize(p).ok())
+            .is_some_and(|p| p.starts_with(&target))
+    };
+    if holds(std::env::home_dir()) {
+        return refuse("it contains the home directory");
+    }
+    if holds(std::env::current_dir().ok()) {
+        return refuse("it contains the working directory");
+    }
+    if !is_device_path(drive) && holds(Some(PathBuf::from(drive))) {
+        return refuse("it contains the source");
+    }
+    Ok(())
+}
+
+/// Returns the output directory used when `--output-dir` is not given:
+/// `tap_export_<label>_<timestamp>` in the working directory.
+///
+/// `label` is the drive's filesystem label or directory name, if it has one;
+/// characters unsafe in a file name are replaced with `_`. The timestamp is
+/// local time, or UTC with a `Z` suffix when `utc` is set.
+pub fn default_output_dir(label: Option<&str>, utc: bool) -> PathBuf {
+    let timestamp = if utc {
+        chrono::Utc::now().format("%Y%m%d_%H%M%SZ").to_string()
+    } else {
+        chrono::Local::now().format("%Y%m%d_%H%M%S").to_string()
+    };
+    PathBuf::from(output_dir_name(label, &timestamp))
+}
+
+fn output_dir_name(label: Option<&str>, timestamp: &str) -> String {
+    let label: String = label
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let label = label.trim_matches(|c| c == '_' || c == '.');
+    if label.is_empty() {
+        format!("tap_export_{}", timestamp)
+    } else {
+        format!("tap_export_{}_{}", label, timestamp)
+    }
+}
+
+/// Narrows `scan_stats` to a random sample when `export.sample_per_category`
+/// is set, drawing a seed if `export.sample_seed` is not.
+pub fn sample_scanned(scan_stats: ScanStats, config: &Config) -> ScanStats {
+    match config.export.sample_per_category {
+        Some(per_category) => {
+            let seed = config.export.sample_seed.unwrap_or_else(rand::random);
+            scan_stats.sampled(per_category, seed)
+        }
+        None => scan_stats,
+    }
+}
+
 pub async fn handle_export(
     drive: &str,
     output_dir: &Path,
     should_zip: bool,
+    report_html: Option<&Path>,
     config: &Config,
 ) -> color_eyre::Result<()> {
-    // Check if output directory already exists
-    if output_dir.exists() {
+    // The output directory is zipped and then deleted, so the archive must live elsewhere
+    let archive = archive_path(output_dir, &config.zip);
+    if should_zip && std::path::absolute(&archive)?.starts_with(std::path::absolute(output_dir)?) {
+        return Err(color_eyre::eyre::eyre!(
+            "Archive path {} is inside the output directory {}",
+            archive.display(),
+            output_dir.display()
+        ));
+    }
+
+    let mut config = config.clone();
+    let since_last_run = last_run::begin("export", &[drive.to_string()], &mut config)?;
+    let config = &config;
+
+    // Load the previous manifest before mounting so a bad path fails fast
+    let previous = match &config.export.since_manifest {
+        Some(path) => Some(Manifest::load(path).await?),
+        None => None,
+    };
+    let progress = Arc::new(ProgressFile::new(config.export.progress_file.as_deref())?);
+
+    // Check if output directory already exists; --clean only deletes it once the source is open
+    let clean = output_dir.exists() && config.export.clean;
+    if clean {
+        check_clean_target(output_dir, drive)?;
+    } else if output_dir.exists() && !config.export.force {
         use console::Style;
         let white_bold = Style::new().white().bold();
 
@@ -194,15 +1029,84 @@ pub async fn handle_export(
     }
 
     // Check if it's a device or a path
-    let is_device = drive.starts_with("/dev/");
+    let is_device = is_device_path(drive);
     let source_path = if is_device {
-        mount_drive_readonly(drive, &config.ui.color.theme).await?
+        mount_drive_readonly(drive, &config.mount, &config.ui.color.theme).await?
     } else {
-        validate_source_path(drive, &config.ui.color.theme)?
+        validate_source_path(drive, &config.mount, &config.ui.color.theme)?
     };
 
+    // Exporting an earlier export would sort its category folders into themselves
+    if looks_like_export(&source_path, config) {
+        use console::Style;
+        let white_bold = Style::new().white().bold();
+        let (_, warning_style, _, _) = UI::get_static_status_styles(&config.ui.color.theme);
+
+        println!(
+            "{} {}",
+            warning_style.apply_to("[!] WARNING:").bold(),
+            white_bold.apply_to(format!(
+                "{} looks like a previous tap export (log or manifest plus category folders)",
+                source_path.display()
+            ))
+        );
+        println!(
+            "{}",
+            white_bold.apply_to("   Exporting it again nests files as images/images/...")
+        );
+
+        // Scripted runs cannot answer, so they need --assume-yes to go ahead
+        let interactive = std::io::stdin().is_terminal();
+        let theme = UI::get_colorful_theme(&config.ui.color.theme);
+        let should_continue = config.mount.assume_yes
+            || (interactive
+                && Confirm::with_theme(&theme)
+                    .with_prompt("Export it anyway?")
+                    .default(false)
+                    .interact()?);
+
+        if !should_continue {
+            if is_device {
+                release_drive(&source_path, drive, &config.mount, &config.ui.color.theme)?;
+            }
+            if !interactive {
+                return Err(color_eyre::eyre::eyre!(
+                    "{} looks like a previous tap export; pass --assume-yes to export it anyway",
+                    source_path.display()
+                ));
+            }
+            println!("{}", white_bold.apply_to("Operation cancelled."));
+            std::process::exit(0);
+        }
+    }
+
+    if clean {
+        use console::Style;
+        let white_bold = Style::new().white().bold();
+
+        println!(
+            "{}",
+            white_bold.apply_to(format!(
+                "Removing existing output directory: {}",
+                output_dir.display()
+            ))
+        );
+        if let Err(e) = fs::remove_dir_all(output_dir).await {
+            if is_device {
+                let _ = release_drive(&source_path, drive, &config.mount, &config.ui.color.theme);
+            }
+            return Err(e.into());
+        }
+    }
+
     // Create UI with color theme from config
-    let ui = UI::new()?.with_color_theme(config.ui.color.theme.clone());
+    let ui = UI::new()?
+        .with_color_theme(config.ui.color.theme.clone())
+        .with_min_category_files(config.ui.min_category_files)
+        .with_chart_by(config.ui.chart_by)
+        .with_progress_style(config.ui.tick_ms, config.ui.ascii_progress)
+        .with_summary(!config.ui.no_summary)
+        .with_compact_summary(!config.ui.no_compact_summary);
 
     let mode_message = format!(
         "Source: {} → Destination: {}",
@@ -211,44 +1115,57 @@ pub async fn handle_export(
     );
 
     ui.init(&Mode::Export, &mode_message)?;
+    if let Some(run) = &since_last_run {
+        ui.print_info(&run.describe())?;
+    }
+
+    let control = Control::new();
 
     // Phase 1: Scan and categorize (with counting in background)
-    ui.print_info("Phase 1/3: Scanning and categorizing source files")?;
+    let keys = KeyListener::start(&control);
+    ui.print_info(&format!(
+        "Phase 1/3: Scanning and categorizing source files{}",
+        keys.hint()
+    ))?;
 
     // First, do a quick estimate without progress to get a rough count for progress bar
-    let estimated_files = count_files(&source_path).await;
+    let estimated_files = if config.ui.no_count {
+        None
+    } else {
+        Some(count_files(&source_path, config).await)
+    };
 
     ui.draw_recent_files()?;
-    let pb = ui.create_progress_bar(estimated_files, "Analyzing");
+    let pb = ui.create_scan_bar(estimated_files, "Analyzing");
+    control.attach(&pb);
+    progress.start("scan", estimated_files.unwrap_or(0));
 
     let ui_arc = Arc::new(Mutex::new(ui));
-    let counter = Arc::new(Mutex::new(0u64));
+    let throttle = Arc::new(RedrawThrottle::new(config.ui.recent_files_ms));
 
-    let scan_stats = scan_directory(&source_path, {
+    let mut scan_stats = scan_directory_with_control(&source_path, config, &control, {
         let pb = pb.clone();
         let ui_arc = Arc::clone(&ui_arc);
-        let counter = Arc::clone(&counter);
+        let throttle = Arc::clone(&throttle);
+        let progress = Arc::clone(&progress);
 
         move |path| {
             pb.inc(1);
+            progress.inc();
 
             // Rate limit UI updates to prevent screen overflow
-            // Only update every 100 files
             // Use try_lock to avoid blocking in the scanning thread
-            if let Ok(mut count) = counter.try_lock() {
-                *count += 1;
-
-                if *count % 100 == 0 {
-                    if let Ok(mut ui) = ui_arc.try_lock() {
-                        let _ = ui.update_recent_files(path);
-                    }
-                }
+            if throttle.ready()
+                && let Ok(mut ui) = ui_arc.try_lock()
+            {
+                let _ = ui.update_recent_files(path);
             }
         }
     })
     .await?;
 
     pb.finish_and_clear();
+    drop(keys);
 
     // Get UI back
     let mut ui = Arc::try_unwrap(ui_arc)
@@ -268,99 +1185,214 @@ pub async fn handle_export(
     ui.print_banner_with_mode(&Mode::Export)?;
 
     // Display scan results
-    let summary = scan_stats.get_summary();
-    let all_files = scan_stats.get_all_files();
-    ui.print_summary(
-        &Mode::Export,
-        "SCAN RESULTS",
-        &summary,
-        &all_files,
-        None,
-        false,
-    )?;
-
-    // Clear screen before starting copy phase
-    ui.term.clear_screen()?;
-
-    // Show banner with mode again for context
-    ui.print_banner_with_mode(&Mode::Export)?;
-
-    // Phase 2: Export
-    ui.print_info("Phase 2/3: Copying files to destination")?;
-    ui.draw_recent_files()?;
-    let pb = ui.create_progress_bar(scan_stats.total_files as u64, "Copying");
-
-    let ui_arc = Arc::new(Mutex::new(ui));
-    let counter = Arc::new(Mutex::new(0u64));
-
-    let export_stats = export_files(&scan_stats, output_dir, {
-        let pb = pb.clone();
-        let ui_arc = Arc::clone(&ui_arc);
-        let counter = Arc::clone(&counter);
+    let title = if scan_stats.cancelled {
+        "SCAN CANCELLED"
+    } else {
+        "SCAN RESULTS"
+    };
+    scan_stats = sample_scanned(scan_stats, config);
+    ui.print_summary(&Mode::Export, title, &scan_stats, None, false)?;
+    if let Some(sample) = scan_stats.sample {
+        ui.print_info(&format!(
+            "Sampled up to {} files per category with seed {}; pass --seed {} to draw the same files again",
+            sample.per_category, sample.seed, sample.seed
+        ))?;
+    }
 
-        move |path| {
-            let pb = pb.clone();
-            let ui_arc = Arc::clone(&ui_arc);
-            let counter = Arc::clone(&counter);
+    // A cancelled scan skips the copy phase entirely
+    let export_stats = if scan_stats.cancelled {
+        ExportStats {
+            cancelled: true,
+            ..ExportStats::new()
+        }
+    } else if config.export.inventory_only {
+        ExportStats::new()
+    } else {
+        // Clear screen before starting copy phase
+        ui.term.clear_screen()?;
 
-            async move {
-                pb.inc(1);
+        // Show banner with mode again for context
+        ui.print_banner_with_mode(&Mode::Export)?;
 
-                // Rate limit UI updates to prevent screen overflow
-                // Only update every 100 files
-                let mut count = counter.lock().await;
-                *count += 1;
+        // Phase 2: Export
+        let keys = KeyListener::start(&control);
+        ui.print_info(&format!(
+            "Phase 2/3: Copying files to destination{}",
+            keys.hint()
+        ))?;
+        ui.draw_recent_files()?;
+        let (pb, throughput) =
+            ui.create_progress_bar_with_throughput(scan_stats.total_files as u64, "Copying");
+        control.attach(&pb);
+        progress.start("copy", scan_stats.total_files as u64);
 
-                if *count % 100 == 0 {
-                    let mut ui = ui_arc.lock().await;
-                    let _ = ui.update_recent_files(path);
+        let ui_arc = Arc::new(Mutex::new(ui));
+        let throttle = Arc::new(RedrawThrottle::new(config.ui.recent_files_ms));
+
+        let export_stats = export_files(
+            &scan_stats,
+            output_dir,
+            config,
+            previous.as_ref(),
+            &control,
+            Some(&throughput),
+            {
+                let pb = pb.clone();
+                let ui_arc = Arc::clone(&ui_arc);
+                let throttle = Arc::clone(&throttle);
+                let progress = Arc::clone(&progress);
+
+                move |path| {
+                    let pb = pb.clone();
+                    let ui_arc = Arc::clone(&ui_arc);
+                    let throttle = Arc::clone(&throttle);
+                    progress.inc();
+
+                    async move {
+                        pb.inc(1);
+
+                        // Rate limit UI updates to prevent screen overflow
+                        if throttle.ready() {
+                            let mut ui = ui_arc.lock().await;
+                            let _ = ui.update_recent_files(path);
+                        }
+                    }
                 }
-            }
-        }
-    })
-    .await?;
+            },
+        )
+        .await?;
 
-    pb.finish_and_clear();
+        pb.finish_and_clear();
+        drop(keys);
 
-    // Get UI back
-    ui = Arc::try_unwrap(ui_arc)
-        .map_err(|_| color_eyre::eyre::eyre!("Failed to unwrap UI"))?
-        .into_inner();
+        // Get UI back
+        ui = Arc::try_unwrap(ui_arc)
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to unwrap UI"))?
+            .into_inner();
 
-    // Wait for user to see final copy files
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        // Wait for user to see final copy files
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
-    // Clear the recent files section
-    ui.term.clear_last_lines(ui.max_recent + 2)?;
+        // Clear the recent files section
+        ui.term.clear_last_lines(ui.max_recent + 2)?;
 
-    // Clear screen and show clean copy results
-    ui.term.clear_screen()?;
+        // Clear screen and show clean copy results
+        ui.term.clear_screen()?;
 
-    // Show banner with mode again for context
-    ui.print_banner_with_mode(&Mode::Export)?;
+        // Show banner with mode again for context
+        ui.print_banner_with_mode(&Mode::Export)?;
+
+        // Display scan results using the same format as inspect
+        let title = if export_stats.cancelled {
+            "COPY CANCELLED"
+        } else {
+            "COPY COMPLETE"
+        };
+        ui.print_summary(&Mode::Export, title, &scan_stats, None, false)?;
+
+        export_stats
+    };
 
-    // Display scan results using the same format as inspect
-    let summary = scan_stats.get_summary();
-    let all_files = scan_stats.get_all_files();
-    ui.print_summary(
-        &Mode::Export,
-        "COPY COMPLETE",
-        &summary,
-        &all_files,
-        None,
-        false,
-    )?;
+    // The read-only guarantee has to have held through scanning and copying
+    if is_device && let Some(warning) = verify_still_readonly(&source_path, drive) {
+        scan_stats.warnings.push(warning);
+    }
 
     // Clear screen for post-summary messages
     ui.term.clear_screen()?;
     ui.print_banner_with_mode(&Mode::Export)?;
     println!();
 
+    for warning in &scan_stats.warnings {
+        ui.print_error(warning)?;
+        println!();
+    }
+
+    if scan_stats.limit_reached {
+        ui.print_warning(&format!(
+            "Scan limit reached after {} files; only those files were exported",
+            scan_stats.total_files
+        ))?;
+        println!();
+    }
+
     // Display export errors if any
     if export_stats.failed > 0 {
         ui.print_error(&format!(
-            "{} file(s) failed to copy (permission denied or I/O error)",
-            export_stats.failed
+            "{} file(s) ({}) failed to copy (permission denied or I/O error)",
+            export_stats.failed,
+            format_size(export_stats.bytes_failed)
+        ))?;
+        println!();
+    }
+
+    if export_stats.skipped > 0 {
+        ui.print_info(&format!(
+            "{} file(s) already present in the output and left unchanged",
+            export_stats.skipped
+        ))?;
+        println!();
+    }
+
+    if export_stats.verified > 0 {
+        ui.print_success(&format!(
+            "{} copied file(s) re-read and verified against their source",
+            export_stats.verified
+        ))?;
+        println!();
+    }
+
+    if export_stats.truncated > 0 {
+        ui.print_info(&format!(
+            "{} file(s) exported only up to their first {} (--truncate-to)",
+            export_stats.truncated,
+            format_size(config.export.truncate_to.unwrap_or(0))
+        ))?;
+        println!();
+    }
+
+    if config.export.preserve_xattrs {
+        ui.print_info(&format!(
+            "Extended attributes preserved on {} file(s)",
+            export_stats.xattrs_preserved.len()
+        ))?;
+        if !export_stats.xattr_failures.is_empty() {
+            ui.print_warning(&format!(
+                "{} file(s) copied without some extended attributes (see log)",
+                export_stats.xattr_failures.len()
+            ))?;
+        }
+        println!();
+    }
+
+    if !export_stats.retried.is_empty() {
+        ui.print_warning(&format!(
+            "{} file(s) copied only after retrying (unreliable source)",
+            export_stats.retried.len()
+        ))?;
+        println!();
+    }
+
+    if !scan_stats.special_entries.is_empty() {
+        ui.print_warning(&format!(
+            "{} symlink(s) and special file(s) not exported (see log)",
+            scan_stats.special_entries.len()
+        ))?;
+        println!();
+    }
+
+    if !export_stats.collided.is_empty() {
+        ui.print_warning(&format!(
+            "{} file(s) not copied because a different file already had the name (see log)",
+            export_stats.collided.len()
+        ))?;
+        println!();
+    }
+
+    if !export_stats.overwritten.is_empty() {
+        ui.print_warning(&format!(
+            "{} copied file(s) overwritten by a later file with the same name (see log)",
+            export_stats.overwritten.len()
         ))?;
         println!();
     }
@@ -370,21 +1402,71 @@ pub async fn handle_export(
         println!();
     }
 
-    // Write log file
-    write_log_file(output_dir, &scan_stats, &export_stats).await?;
-    let log_path = output_dir.join("tap.log");
-    ui.print_info(&format!("Log file: {}", log_path.display()))?;
+    let outputs = write_export_outputs(output_dir, &scan_stats, &export_stats, config).await?;
+
+    if let Some(inventory_path) = &outputs.inventory {
+        ui.print_info(&format!("Inventory: {}", inventory_path.display()))?;
+        println!();
+    }
+
+    if previous.is_some() {
+        ui.print_info(&format!(
+            "Since previous manifest: {} added, {} changed, {} unchanged (not copied)",
+            export_stats.added, export_stats.changed, export_stats.unchanged
+        ))?;
+        println!();
+    }
+
+    if let Some(run) = since_last_run {
+        // Files that were never copied would be older than the next run's cutoff
+        if scan_stats.cancelled || export_stats.cancelled || export_stats.failed > 0 {
+            ui.print_warning(
+                "Last run not recorded because the export was cancelled or files failed to copy",
+            )?;
+        } else if scan_stats.limit_reached || scan_stats.sample.is_some() {
+            ui.print_warning(
+                "Last run not recorded because --max-files or --sample left files out of the export",
+            )?;
+        } else {
+            match run.record() {
+                Ok(()) => ui.print_success("Last run recorded for --since-last-run")?,
+                Err(e) => ui.print_warning(&format!("Failed to record last run: {}", e))?,
+            }
+        }
+        println!();
+    }
+
+    if let Some(map_path) = &outputs.path_map {
+        ui.print_info(&format!("Path map: {}", map_path.display()))?;
+        println!();
+    }
+
+    ui.print_info(&format!("Log file: {}", outputs.log.display()))?;
     println!();
 
-    // Conditionally zip the exported directory
-    if should_zip {
+    if let Some(report_path) = report_html {
+        match write_html_report(report_path, &source_path, &scan_stats, Some(&export_stats)).await {
+            Ok(()) => {
+                ui.print_info(&format!("Report file: {}", report_path.display()))?;
+                println!();
+            }
+            Err(e) => {
+                ui.print_warning(&format!("Failed to write HTML report: {}", e))?;
+                println!();
+            }
+        }
+    }
+
+    // Conditionally zip the exported directory; a partial export is never archived
+    if should_zip && !export_stats.cancelled {
         // Clear screen before starting zip phase
         ui.term.clear_screen()?;
 
         // Show banner with mode again for context
         ui.print_banner_with_mode(&Mode::Export)?;
 
-        ui.print_info("Phase 3/3: Compressing to archive")?;
+        let keys = KeyListener::start(&control);
+        ui.print_info(&format!("Phase 3/3: Compressing to archive{}", keys.hint()))?;
 
         // Count files to zip
         let total_files = WalkDir::new(output_dir)
@@ -394,30 +1476,41 @@ pub async fn handle_export(
             .count();
 
         ui.draw_recent_files()?;
-        let pb = ui.create_progress_bar(total_files as u64, "Archiving");
+        let (pb, throughput) =
+            ui.create_progress_bar_with_throughput(total_files as u64, "Archiving");
+        control.attach(&pb);
+        progress.start("archive", total_files as u64);
 
         let ui_arc = Arc::new(Mutex::new(ui));
-        let counter = Arc::new(Mutex::new(0u64));
-
-        let zip_path = zip_directory(output_dir, pb, {
-            let ui_arc = Arc::clone(&ui_arc);
-            let counter = Arc::clone(&counter);
-            move |path| {
-                // Rate limit UI updates to prevent screen overflow
-                // Only update every 100 files
-                // Use try_lock to avoid blocking in the zip thread
-                if let Ok(mut count) = counter.try_lock() {
-                    *count += 1;
-
-                    if *count % 100 == 0 {
-                        if let Ok(mut ui) = ui_arc.try_lock() {
-                            let _ = ui.update_recent_files(path);
-                        }
+        let throttle = Arc::new(RedrawThrottle::new(config.ui.recent_files_ms));
+
+        let zip_started = Instant::now();
+        let zip_result = zip_directory(
+            output_dir,
+            &archive,
+            config.zip.split_size,
+            pb,
+            Some(throughput),
+            &control,
+            {
+                let ui_arc = Arc::clone(&ui_arc);
+                let throttle = Arc::clone(&throttle);
+                let progress = Arc::clone(&progress);
+                move |path| {
+                    progress.inc();
+                    // Rate limit UI updates to prevent screen overflow
+                    // Use try_lock to avoid blocking in the zip thread
+                    if throttle.ready()
+                        && let Ok(mut ui) = ui_arc.try_lock()
+                    {
+                        let _ = ui.update_recent_files(path);
                     }
                 }
-            }
-        })
-        .await?;
+            },
+        )
+        .await;
+        let zip_duration = zip_started.elapsed();
+        drop(keys);
 
         // Get UI back
         ui = Arc::try_unwrap(ui_arc)
@@ -437,41 +1530,748 @@ pub async fn handle_export(
         ui.print_banner_with_mode(&Mode::Export)?;
 
         // Display scan results using the same format as inspect
-        let summary = scan_stats.get_summary();
-        let all_files = scan_stats.get_all_files();
-        ui.print_summary(
-            &Mode::Export,
-            "ZIP COMPLETE",
-            &summary,
-            &all_files,
-            None,
-            false,
-        )?;
+        let title = match &zip_result {
+            Ok(Some(_)) => "ZIP COMPLETE",
+            Ok(None) => "ZIP CANCELLED",
+            Err(_) => "ZIP FAILED",
+        };
+        ui.print_summary(&Mode::Export, title, &scan_stats, None, false)?;
 
         // Clear screen for final messages
         ui.term.clear_screen()?;
         ui.print_banner_with_mode(&Mode::Export)?;
         println!();
 
-        ui.print_success(&format!("Archive created: {}", zip_path.display()))?;
-        println!();
+        progress.finish(match &zip_result {
+            Ok(Some(_)) => "complete",
+            Ok(None) => "cancelled",
+            Err(_) => "failed",
+        });
+        match zip_result {
+            Ok(Some(zip_paths)) => {
+                ui.print_success(&format!(
+                    "Archive created and verified: {} (compressed in {})",
+                    zip_paths[0].display(),
+                    format_duration(zip_duration)
+                ))?;
+                if zip_paths.len() > 1 {
+                    ui.print_info(&format!(
+                        "Split into {} volumes; join them in order to open: {}",
+                        zip_paths.len(),
+                        join_volumes_command(&archive)
+                    ))?;
+                }
+                println!();
 
-        // Remove the original directory
-        ui.print_info("Removing temporary directory")?;
-        tokio::fs::remove_dir_all(output_dir).await?;
-        ui.print_success("Cleanup complete")?;
+                // Only reached once every file is confirmed in the archive
+                ui.print_info("Removing temporary directory")?;
+                tokio::fs::remove_dir_all(output_dir).await?;
+                ui.print_success("Cleanup complete")?;
+                println!();
+            }
+            Ok(None) => {
+                ui.print_warning(&format!(
+                    "Archiving cancelled; partial archive removed, files kept in {}",
+                    output_dir.display()
+                ))?;
+                println!();
+            }
+            Err(e) => {
+                ui.print_error(&format!(
+                    "Archive failed: {}. The partial archive was removed; your exported files are safe in {}",
+                    e,
+                    output_dir.display()
+                ))?;
+                println!();
+            }
+        }
+    } else if export_stats.cancelled {
+        progress.finish("cancelled");
+        ui.print_warning(&format!(
+            "Export cancelled after {} file(s); partial results in {}",
+            export_stats.copied,
+            output_dir.display()
+        ))?;
+        println!();
+    } else if config.export.inventory_only {
+        progress.finish("complete");
+        ui.print_success(&format!("Inventory complete: {}", output_dir.display()))?;
         println!();
     } else {
+        progress.finish("complete");
         ui.print_success(&format!("Export complete: {}", output_dir.display()))?;
         println!();
     }
 
+    if config.export.inventory_only {
+        ui.print_info(&format!(
+            "Scan took {}",
+            format_duration(scan_stats.duration)
+        ))?;
+    } else {
+        ui.print_info(&format!(
+            "Scan took {}, copy took {} at {}; {} of {} scanned copied",
+            format_duration(scan_stats.duration),
+            format_duration(export_stats.duration),
+            format_rate(export_stats.bytes_copied, export_stats.duration),
+            format_size(export_stats.bytes_copied),
+            format_size(scan_stats.total_apparent_size)
+        ))?;
+    }
+    println!();
+
     ui.cleanup()?;
 
-    // Unmount drive if we mounted it
+    // Unmount drive if we mounted it, unless asked to leave it in place
     if is_device {
-        unmount_drive(&source_path, drive, &config.ui.color.theme)?;
+        release_drive(&source_path, drive, &config.mount, &config.ui.color.theme)?;
     }
 
+    ui.print_compact_summary(&scan_stats)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT_TEMPLATE: &str = "{stem}_{n}{ext}";
+
+    /// Export settings for a copy without retries, with a rename template and checksum setting.
+    fn options(template: &str, checksum_source: bool) -> ExportConfig {
+        ExportConfig {
+            rename_template: template.to_string(),
+            copy_retries: 0,
+            checksum_source,
+            ..Config::default().export
+        }
+    }
+
+    #[test]
+    fn test_looks_like_export() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let mut config = Config::default();
+
+        std::fs::create_dir_all(root.path().join("images")).unwrap();
+        assert!(!looks_like_export(root.path(), &config));
+
+        std::fs::write(root.path().join("tap.log"), b"TAP EXPORT LOG").unwrap();
+        assert!(looks_like_export(root.path(), &config));
+
+        // Category folders are matched by their configured names
+        config
+            .category_dirs
+            .insert("images".to_string(), "Pictures".to_string());
+        assert!(!looks_like_export(root.path(), &config));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&std::io::Error::from(ErrorKind::Interrupted)));
+        assert!(is_retryable(&std::io::Error::from(ErrorKind::TimedOut)));
+        assert!(is_retryable(&std::io::Error::other("device hiccup")));
+        #[cfg(unix)]
+        assert!(is_retryable(&std::io::Error::from_raw_os_error(5)));
+        assert!(!is_retryable(&std::io::Error::from(ErrorKind::NotFound)));
+        assert!(!is_retryable(&std::io::Error::from(
+            ErrorKind::PermissionDenied
+        )));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(0), Duration::from_millis(200));
+        assert_eq!(retry_delay(3), Duration::from_millis(1600));
+        assert_eq!(retry_delay(10), RETRY_MAX_DELAY);
+        // Large attempt counts neither overflow nor wait longer
+        assert_eq!(retry_delay(40), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_retry() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let src = dir.path().join("a.txt");
+        std::fs::write(&src, b"data").unwrap();
+
+        let attempts = copy_with_retry(&src, &dir.path().join("b.txt"), 2, None, None)
+            .await
+            .unwrap();
+        assert_eq!(attempts, 0);
+
+        // Permanent errors fail immediately without waiting on backoff
+        let missing = dir.path().join("missing.txt");
+        let err = copy_with_retry(&missing, &dir.path().join("c.txt"), 2, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_copy_file_with_rename_skips_identical() {
+        let src_dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let dest_dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let src = src_dir.path().join("notes.txt");
+        std::fs::write(&src, b"same").unwrap();
+
+        let first = copy_file_with_rename(
+            &src,
+            dest_dir.path(),
+            "notes.txt",
+            &options(DEFAULT_TEMPLATE, true),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            matches!(first, CopyOutcome::Copied { ref dest, retries: 0 } if *dest == dest_dir.path().join("notes.txt"))
+        );
+
+        let again = copy_file_with_rename(
+            &src,
+            dest_dir.path(),
+            "notes.txt",
+            &options(DEFAULT_TEMPLATE, true),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(again, CopyOutcome::AlreadyPresent { .. }));
+
+        // Same size, different content is another file, with or without full checksums
+        std::fs::write(&src, b"diff").unwrap();
+        let quick = copy_file_with_rename(
+            &src,
+            dest_dir.path(),
+            "notes.txt",
+            &options(DEFAULT_TEMPLATE, false),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            matches!(quick, CopyOutcome::Copied { ref dest, .. } if *dest == dest_dir.path().join("notes_1.txt"))
+        );
+        assert_eq!(
+            std::fs::read(dest_dir.path().join("notes_1.txt")).unwrap(),
+            b"diff"
+        );
+
+        let checked = copy_file_with_rename(
+            &src,
+            dest_dir.path(),
+            "notes.txt",
+            &options(DEFAULT_TEMPLATE, true),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            matches!(checked, CopyOutcome::AlreadyPresent { ref dest } if *dest == dest_dir.path().join("notes_1.txt"))
+        );
+
+        // A truncated copy matches only the same head of the source
+        std::fs::write(&src, b"abcdef").unwrap();
+        let truncated = dest_dir.path().join("head.txt");
+        std::fs::write(&truncated, b"abc").unwrap();
+        assert!(
+            is_identical(&src, &truncated, false, Some(3))
+                .await
+                .unwrap()
+        );
+        std::fs::write(&truncated, b"xyz").unwrap();
+        assert!(
+            !is_identical(&src, &truncated, false, Some(3))
+                .await
+                .unwrap()
+        );
+
+        // Past the quick limit a shared head is not enough; only full checksums skip
+        let mut large = vec![0u8; QUICK_HASH_BYTES as usize + 1];
+        std::fs::write(&src, &large).unwrap();
+        let other = dest_dir.path().join("disk.img");
+        *large.last_mut().unwrap() = 1;
+        std::fs::write(&other, &large).unwrap();
+        assert!(!is_identical(&src, &other, false, None).await.unwrap());
+        assert!(!is_identical(&src, &other, true, None).await.unwrap());
+        std::fs::copy(&src, &other).unwrap();
+        assert!(!is_identical(&src, &other, false, None).await.unwrap());
+        assert!(is_identical(&src, &other, true, None).await.unwrap());
+
+        // A custom template names the next different file after it
+        std::fs::write(&src, b"third").unwrap();
+        let templated = copy_file_with_rename(
+            &src,
+            dest_dir.path(),
+            "notes.txt",
+            &options("{stem} ({n}){ext}", false),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            matches!(templated, CopyOutcome::Copied { ref dest, .. } if *dest == dest_dir.path().join("notes (1).txt"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_copy_file_with_rename_copies_bundle() {
+        let src_dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let dest_dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let bundle = src_dir.path().join("Notes.rtfd");
+        std::fs::create_dir_all(bundle.join("Resources")).unwrap();
+        std::fs::write(bundle.join("TXT.rtf"), b"{\\rtf1}").unwrap();
+        std::fs::write(bundle.join("Resources/image.png"), b"png").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("TXT.rtf", bundle.join("current")).unwrap();
+
+        let first = copy_file_with_rename(
+            &bundle,
+            dest_dir.path(),
+            "Notes.rtfd",
+            &options(DEFAULT_TEMPLATE, false),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(first, CopyOutcome::Copied { .. }));
+        let copied = dest_dir.path().join("Notes.rtfd");
+        assert_eq!(
+            std::fs::read(copied.join("Resources/image.png")).unwrap(),
+            b"png"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_link(copied.join("current")).unwrap(),
+            Path::new("TXT.rtf")
+        );
+
+        let again = copy_file_with_rename(
+            &bundle,
+            dest_dir.path(),
+            "Notes.rtfd",
+            &options(DEFAULT_TEMPLATE, false),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(again, CopyOutcome::AlreadyPresent { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_copy_file_on_collision() {
+        let src_dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let dest_dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let first = src_dir.path().join("a.txt");
+        let second = src_dir.path().join("b.txt");
+        std::fs::write(&first, b"first").unwrap();
+        std::fs::write(&second, b"other").unwrap();
+        let copy = |src: PathBuf, strategy| {
+            let dest_dir = dest_dir.path().to_path_buf();
+            let options = ExportConfig {
+                collision_strategy: strategy,
+                copy_retries: 0,
+                ..Config::default().export
+            };
+            async move {
+                copy_file_on_collision(&src, &dest_dir, "notes.txt", &options, None)
+                    .await
+                    .unwrap()
+            }
+        };
+        let notes = dest_dir.path().join("notes.txt");
+
+        let outcome = copy(first.clone(), CollisionStrategy::Skip).await;
+        assert!(matches!(outcome, CopyOutcome::Copied { ref dest, .. } if *dest == notes));
+        // Same size but different content is a different file for every strategy
+        let outcome = copy(first.clone(), CollisionStrategy::Skip).await;
+        assert!(matches!(outcome, CopyOutcome::AlreadyPresent { ref dest } if *dest == notes));
+        let outcome = copy(second.clone(), CollisionStrategy::Skip).await;
+        assert!(matches!(outcome, CopyOutcome::NameTaken));
+        assert_eq!(std::fs::read(&notes).unwrap(), b"first");
+
+        let outcome = copy(second.clone(), CollisionStrategy::Overwrite).await;
+        assert!(matches!(outcome, CopyOutcome::Copied { ref dest, .. } if *dest == notes));
+        assert_eq!(std::fs::read(&notes).unwrap(), b"other");
+        std::fs::write(&second, b"longer other").unwrap();
+        let outcome = copy(second.clone(), CollisionStrategy::Overwrite).await;
+        assert!(matches!(outcome, CopyOutcome::Copied { ref dest, .. } if *dest == notes));
+        assert_eq!(std::fs::read(&notes).unwrap(), b"longer other");
+
+        let outcome = copy(first.clone(), CollisionStrategy::HashSuffix).await;
+        let hashed = match outcome {
+            CopyOutcome::Copied { dest, .. } => dest,
+            _ => panic!("expected a copy under a hashed name"),
+        };
+        let digest = sha256_file(&first).unwrap();
+        assert_eq!(
+            hashed,
+            dest_dir.path().join(format!("notes_{}.txt", &digest[..8]))
+        );
+        // Copies of the same file share the hashed name instead of fanning out
+        let outcome = copy(first.clone(), CollisionStrategy::HashSuffix).await;
+        assert!(matches!(outcome, CopyOutcome::AlreadyPresent { ref dest } if *dest == hashed));
+        assert_eq!(std::fs::read_dir(dest_dir.path()).unwrap().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_export_files_overwrite_drops_replaced_copy() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(source.join("a")).unwrap();
+        std::fs::create_dir_all(source.join("b")).unwrap();
+        std::fs::write(source.join("a/notes.txt"), b"first").unwrap();
+        std::fs::write(source.join("b/notes.txt"), b"other").unwrap();
+
+        let mut config = Config::default();
+        config.export.collision_strategy = CollisionStrategy::Overwrite;
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let out = root.path().join("out");
+        let stats = export_files(
+            &scan_stats,
+            &out,
+            &config,
+            None,
+            &Control::new(),
+            None,
+            |_| async {},
+        )
+        .await
+        .unwrap();
+
+        // One copy survives, and only its source still claims it
+        assert_eq!(stats.overwritten.len(), 1);
+        assert_eq!(stats.exported.len(), 1);
+        assert_eq!(stats.manifest.len(), 1);
+        let (copy, source_path) = &stats.exported[0];
+        assert_eq!(
+            std::fs::read(out.join(copy)).unwrap(),
+            std::fs::read(source_path).unwrap()
+        );
+        assert_ne!(&stats.overwritten[0], source_path);
+    }
+
+    #[tokio::test]
+    async fn test_export_files_since_manifest() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(source.join("docs")).unwrap();
+        std::fs::write(source.join("docs/report.pdf"), b"report").unwrap();
+        std::fs::write(source.join("photo.jpg"), b"photo").unwrap();
+
+        let config = Config::default();
+        let control = Control::new();
+
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let first_out = root.path().join("first");
+        let first = export_files(
+            &scan_stats,
+            &first_out,
+            &config,
+            None,
+            &control,
+            None,
+            |_| async {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.copied, 2);
+        assert_eq!(first.added + first.changed + first.unchanged, 0);
+        let manifest_path = write_manifest(&first_out, &first.manifest, None)
+            .await
+            .unwrap();
+
+        std::fs::write(source.join("notes.txt"), b"new").unwrap();
+        std::fs::write(source.join("photo.jpg"), b"photo, edited").unwrap();
+
+        let previous = Manifest::load(&manifest_path).await.unwrap();
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let second_out = root.path().join("second");
+        let second = export_files(
+            &scan_stats,
+            &second_out,
+            &config,
+            Some(&previous),
+            &control,
+            None,
+            |_| async {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!((second.added, second.changed, second.unchanged), (1, 1, 1));
+        assert_eq!(second.copied, 2);
+        assert_eq!(second.manifest.len(), 3);
+        assert!(!second_out.join("documents/report.pdf").exists());
+
+        let unchanged = second
+            .manifest
+            .iter()
+            .find(|e| e.path == "docs/report.pdf")
+            .unwrap();
+        assert_eq!(unchanged.exported_path, None);
+    }
+
+    #[tokio::test]
+    async fn test_export_files_flat() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(source.join("docs")).unwrap();
+        std::fs::write(source.join("docs/report.pdf"), b"report").unwrap();
+        std::fs::write(source.join("photo.jpg"), b"photo").unwrap();
+        std::fs::write(source.join("manifest.csv"), b"a,b").unwrap();
+
+        let config = Config {
+            export: ExportConfig {
+                flat: true,
+                ..Config::default().export
+            },
+            ..Config::default()
+        };
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let out = root.path().join("out");
+        let stats = export_files(
+            &scan_stats,
+            &out,
+            &config,
+            None,
+            &Control::new(),
+            None,
+            |_| async {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.copied, 3);
+        let mut names: Vec<_> = std::fs::read_dir(&out)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["photo.jpg", "report.pdf", "source_manifest.csv"]);
+
+        // Categories are still recorded for each file
+        let report = stats
+            .manifest
+            .iter()
+            .find(|e| e.path == "docs/report.pdf")
+            .unwrap();
+        assert_eq!(report.category, "documents");
+        assert_eq!(report.exported_path.as_deref(), Some("report.pdf"));
+    }
+
+    #[tokio::test]
+    async fn test_export_files_truncate_to() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("server.log"), b"0123456789").unwrap();
+        std::fs::write(source.join("notes.txt"), b"0123").unwrap();
+
+        let config = Config {
+            export: ExportConfig {
+                truncate_to: Some(4),
+                ..Config::default().export
+            },
+            ..Config::default()
+        };
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let out = root.path().join("out");
+        let control = Control::new();
+        let export = || {
+            export_files(
+                &scan_stats,
+                &out,
+                &config,
+                None,
+                &control,
+                None,
+                |_| async {},
+            )
+        };
+
+        let stats = export().await.unwrap();
+        assert_eq!((stats.copied, stats.truncated), (2, 1));
+        assert_eq!(stats.bytes_copied, 8);
+        let entry = |path: &str| stats.manifest.iter().find(|e| e.path == path).unwrap();
+        let exported = |path: &str| {
+            std::fs::read(out.join(entry(path).exported_path.as_deref().unwrap())).unwrap()
+        };
+        assert_eq!(exported("server.log"), b"0123");
+        assert_eq!(exported("notes.txt"), b"0123");
+        assert_eq!(entry("server.log").truncated_to, Some(4));
+        assert_eq!(entry("notes.txt").truncated_to, None);
+
+        // The truncated copy counts as already present on the next run
+        let again = export().await.unwrap();
+        assert_eq!((again.copied, again.skipped, again.truncated), (0, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_verify_copies() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("server.log"), b"0123456789").unwrap();
+        std::fs::write(source.join("notes.txt"), b"0123").unwrap();
+        let config = Config {
+            export: ExportConfig {
+                verify_copies: true,
+                truncate_to: Some(6),
+                ..Config::default().export
+            },
+            ..Config::default()
+        };
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let out = root.path().join("out");
+        let stats = export_files(
+            &scan_stats,
+            &out,
+            &config,
+            None,
+            &Control::new(),
+            None,
+            |_| async {},
+        )
+        .await
+        .unwrap();
+        assert_eq!((stats.copied, stats.verified, stats.failed), (2, 2, 0));
+
+        // A copy corrupted in place, at the same size, fails and is removed
+        let copy = out.join("documents/notes.txt");
+        std::fs::write(&copy, b"01x3").unwrap();
+        let err = verify_copy(&source.join("notes.txt"), &copy, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("SHA-256"));
+        assert!(!copy.exists());
+
+        // A truncated copy is checked against the head of its source
+        let entry = stats
+            .manifest
+            .iter()
+            .find(|e| e.path == "server.log")
+            .unwrap();
+        let head = out.join(entry.exported_path.as_deref().unwrap());
+        assert!(
+            verify_copy(&source.join("server.log"), &head, Some(6))
+                .await
+                .is_ok()
+        );
+        assert!(
+            verify_copy(&source.join("server.log"), &head, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_preserve_xattrs() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("tagged.pdf"), b"pdf").unwrap();
+        std::fs::write(source.join("plain.pdf"), b"pdf").unwrap();
+        // Not every filesystem a test runs on takes user attributes
+        if xattr::set(source.join("tagged.pdf"), "user.tap.tag", b"Red").is_err() {
+            return;
+        }
+
+        let config = Config {
+            export: ExportConfig {
+                preserve_xattrs: true,
+                ..Config::default().export
+            },
+            ..Config::default()
+        };
+        let scan_stats = crate::scanner::scan_directory(&source, &config, |_| {})
+            .await
+            .unwrap();
+        let out = root.path().join("out");
+        let stats = export_files(
+            &scan_stats,
+            &out,
+            &config,
+            None,
+            &Control::new(),
+            None,
+            |_| async {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.copied, 2);
+        assert_eq!(stats.xattrs_preserved, [source.join("tagged.pdf")]);
+        assert!(stats.xattr_failures.is_empty());
+        assert_eq!(
+            xattr::get(out.join("documents/tagged.pdf"), "user.tap.tag").unwrap(),
+            Some(b"Red".to_vec())
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_copies_xattr() {
+        use std::ffi::OsStr;
+        assert!(copies_xattr(OsStr::new("user.tap.tag"), false));
+        assert!(!copies_xattr(OsStr::new("security.selinux"), true));
+        assert!(!copies_xattr(OsStr::new("system.posix_acl_access"), true));
+        assert!(!copies_xattr(OsStr::new("trusted.overlay.opaque"), false));
+        assert!(copies_xattr(OsStr::new("trusted.overlay.opaque"), true));
+    }
+
+    #[test]
+    fn test_output_dir_name() {
+        let timestamp = "20261017_091203";
+        assert_eq!(
+            output_dir_name(Some("EVIDENCE"), timestamp),
+            "tap_export_EVIDENCE_20261017_091203"
+        );
+        assert_eq!(
+            output_dir_name(Some(" Case 42/Disk:1 "), timestamp),
+            "tap_export_Case_42_Disk_1_20261017_091203"
+        );
+        assert_eq!(
+            output_dir_name(Some(".."), timestamp),
+            "tap_export_20261017_091203"
+        );
+        assert_eq!(
+            output_dir_name(None, timestamp),
+            "tap_export_20261017_091203"
+        );
+    }
+
+    #[test]
+    fn test_check_clean_target() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let output = root.path().join("out");
+        let source = root.path().join("source");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::create_dir_all(&source).unwrap();
+
+        let drive = source.display().to_string();
+        assert!(check_clean_target(&output, &drive).is_ok());
+        // A directory holding the source is never deleted
+        assert!(check_clean_target(root.path(), &drive).is_err());
+        assert!(check_clean_target(Path::new("."), "/dev/sdb1").is_err());
+        assert!(check_clean_target(Path::new("/"), &drive).is_err());
+        #[cfg(unix)]
+        assert!(check_clean_target(Path::new("/usr/share"), &drive).is_err());
+        #[cfg(target_os = "linux")]
+        assert!(check_clean_target(Path::new("/var/lib"), &drive).is_err());
+        assert!(check_clean_target(&root.path().join("missing"), &drive).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! File hashing utilities.
+//!
+//! This module computes content digests used to compare source and
+//! destination files during export.
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// Size of the read buffer used while hashing.
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Computes the SHA-256 digest of a file as a lowercase hex string.
+///
+/// The file is streamed in fixed-size chunks, so memory use does not grow
+/// with file size. This is a blocking call; run it on a blocking thread
+/// from async code.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use tap::hash::sha256_file;
+///
+/// let digest = sha256_file(Path::new("/mnt/evidence/report.pdf")).unwrap();
+/// println!("{}", digest);
+/// ```
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    sha256_head(path, u64::MAX)
+}
+
+/// Computes the SHA-256 digest of the first `max_bytes` bytes of a file, or
+/// of the whole file if it is shorter.
+///
+/// Used to check a copy made with `--truncate-to` against its source.
+pub fn sha256_head(path: &Path, max_bytes: u64) -> std::io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?).take(max_bytes);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        std::fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        std::fs::write(dir.path().join("abcdef.txt"), b"abcdef").unwrap();
+        assert_eq!(
+            sha256_head(&dir.path().join("abcdef.txt"), 3).unwrap(),
+            sha256_file(&path).unwrap()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Exclusion patterns.
+//!
+//! Patterns come from `scan.exclude_patterns`, `--exclude`, and a `.tapignore`
+//! file in the scanned root. They follow a small subset of `.gitignore` rules:
+//!
+//! - blank lines and lines starting with `#` are ignored
+//! - a pattern without a `/` matches an entry's name at any depth
+//! - a pattern containing a `/` is matched against the path relative to the
+//!   scan root; a leading `/` only marks it as anchored
+//! - a trailing `/` restricts the pattern to directories
+//! - `*` matches within one path component, `?` matches one character, and a
+//!   `**` component matches any number of directories
+
+use std::path::{Component, Path};
+
+/// Name of the ignore file read from the scan root.
+pub const TAPIGNORE_FILE: &str = ".tapignore";
+
+/// A single parsed exclusion pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// Path components to match, `**` included verbatim
+    segments: Vec<String>,
+    /// Matched against the whole relative path instead of just the name
+    anchored: bool,
+    /// Only matches directories
+    dir_only: bool,
+}
+
+impl Pattern {
+    /// Parses one pattern, returning `None` for blank lines and comments.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let anchored = line.contains('/');
+        let segments: Vec<String> = line
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        if segments.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            segments,
+            anchored,
+            dir_only,
+        })
+    }
+
+    /// Returns true if the entry at `relative` (from the scan root) matches.
+    pub fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let components: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        if self.anchored {
+            match_segments(&self.segments, &components)
+        } else {
+            components
+                .last()
+                .is_some_and(|name| wildcard_match(&self.segments[0], name))
+        }
+    }
+}
+
+/// Parses every pattern in the contents of an ignore file.
+pub fn parse_patterns(content: &str) -> Vec<Pattern> {
+    content.lines().filter_map(Pattern::parse).collect()
+}
+
+/// Reads `.tapignore` from `root`, returning no patterns if it does not exist.
+pub fn load_tapignore(root: &Path) -> Vec<Pattern> {
+    std::fs::read_to_string(root.join(TAPIGNORE_FILE))
+        .map(|content| parse_patterns(&content))
+        .unwrap_or_default()
+}
+
+/// Matches path components against pattern segments, where `**` spans zero or more components.
+fn match_segments(segments: &[String], components: &[String]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=components.len()).any(|skip| match_segments(rest, &components[skip..]))
+        }
+        Some((first, rest)) => components
+            .split_first()
+            .is_some_and(|(name, tail)| wildcard_match(first, name) && match_segments(rest, tail)),
+    }
+}
+
+/// Matches a single component against a pattern with `*` and `?` wildcards.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.tmp", "cache.tmp"));
+        assert!(wildcard_match("IMG_????.JPG", "IMG_0042.JPG"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("*.tmp", "cache.tmp.bak"));
+        assert!(!wildcard_match("IMG_????.JPG", "IMG_42.JPG"));
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let patterns = parse_patterns(
+            "# build output\n\
+             \n\
+             *.tmp\n\
+             target/\n\
+             /Backups/old\n\
+             **/cache/**\n",
+        );
+        assert_eq!(patterns.len(), 4);
+        let matches =
+            |path: &str, is_dir| patterns.iter().any(|p| p.matches(Path::new(path), is_dir));
+
+        // Unanchored patterns match names at any depth
+        assert!(matches("notes.tmp", false));
+        assert!(matches("a/b/notes.tmp", false));
+
+        // Trailing slash only matches directories
+        assert!(matches("rust/target", true));
+        assert!(!matches("rust/target", false));
+
+        // Anchored patterns match from the root only
+        assert!(matches("Backups/old", true));
+        assert!(!matches("Users/Backups/old", true));
+
+        // `**` spans any number of directories
+        assert!(matches("cache/thumb.db", false));
+        assert!(matches("Users/me/cache/x/thumb.db", false));
+        assert!(matches("Users/me/cache", true));
+
+        assert!(!matches("Documents/report.pdf", false));
+    }
+}
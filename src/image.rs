@@ -0,0 +1,516 @@
+//! Disk images found during a scan, for `inspect --descend-images`.
+//!
+//! A `.vmdk`, `.qcow2`, `.vhd`, or `.img` on a drive often holds the data that
+//! matters. Raw images and ISOs are attached read-only to a loop device with
+//! `losetup`; the other formats are connected read-only to an NBD device with
+//! `qemu-nbd`. Each partition with a filesystem is then mounted read-only
+//! under `mount.mount_base_dir` and scanned as a source of its own, labelled
+//! with the image it came from. Images found inside are opened in turn, at
+//! most `scan.max_image_depth` levels deep.
+//!
+//! Opening images needs Linux and root, like mounting devices.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use crate::categories::get_extension;
+use crate::command::{CommandRunner, SystemCommands};
+use crate::config::{Config, MountConfig};
+use crate::control::Control;
+use crate::scanner::{ScanStats, scan_directory_with_control};
+
+/// Disk image formats that can be opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// A plain sector copy (`.img`, `.dd`) or an ISO, attached to a loop device
+    Raw,
+    Qcow2,
+    Vmdk,
+    Vhd,
+    Vhdx,
+    Vdi,
+    /// Parallels `.hdd`
+    Parallels,
+}
+
+impl ImageFormat {
+    /// Returns the format of a disk image by its extension, or `None` for other files.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match get_extension(path).as_str() {
+            ".img" | ".dd" | ".iso" => Some(Self::Raw),
+            ".qcow" | ".qcow2" => Some(Self::Qcow2),
+            ".vmdk" => Some(Self::Vmdk),
+            ".vhd" => Some(Self::Vhd),
+            ".vhdx" => Some(Self::Vhdx),
+            ".vdi" => Some(Self::Vdi),
+            ".hdd" => Some(Self::Parallels),
+            _ => None,
+        }
+    }
+
+    /// The `qemu-nbd --format` of the image, or `None` for raw images.
+    fn qemu_format(self) -> Option<&'static str> {
+        match self {
+            Self::Raw => None,
+            Self::Qcow2 => Some("qcow2"),
+            Self::Vmdk => Some("vmdk"),
+            Self::Vhd => Some("vpc"),
+            Self::Vhdx => Some("vhdx"),
+            Self::Vdi => Some("vdi"),
+            Self::Parallels => Some("parallels"),
+        }
+    }
+}
+
+/// Returns the disk images among the scanned files, sorted by path.
+pub fn find_images(stats: &ScanStats) -> Vec<PathBuf> {
+    let mut images: Vec<PathBuf> = stats
+        .files_by_category
+        .values()
+        .flatten()
+        .filter(|file| file.size > 0 && ImageFormat::from_path(&file.path).is_some())
+        .map(|file| file.path.clone())
+        // Bundles are directories and never images
+        .filter(|path| path.is_file())
+        .collect();
+    images.sort();
+    images
+}
+
+/// A disk image attached to a block device, with its partitions mounted.
+#[derive(Debug)]
+pub struct OpenedImage {
+    pub image: PathBuf,
+    /// The loop or NBD device the image is attached to
+    pub device: String,
+    format: ImageFormat,
+    /// Partitions mounted from the image, as (partition, mount point)
+    pub mounts: Vec<(String, PathBuf)>,
+    /// Partitions that were not mounted, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Returns the first NBD device with no image connected, from `lsblk -dnbpo NAME,SIZE`.
+#[cfg(target_os = "linux")]
+fn first_free_nbd(listing: &str) -> Option<String> {
+    listing.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let name = fields.next()?;
+        let size = fields.next()?;
+        (name.starts_with("/dev/nbd") && size == "0").then(|| name.to_string())
+    })
+}
+
+/// Returns the partitions of `device` from `lsblk -lnpo NAME,TYPE`, or the
+/// device itself when it holds no partition table.
+#[cfg(target_os = "linux")]
+fn parse_partitions(device: &str, listing: &str) -> Vec<String> {
+    let partitions: Vec<String> = listing
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            (fields.next() == Some("part")).then(|| name.to_string())
+        })
+        .collect();
+    if partitions.is_empty() {
+        vec![device.to_string()]
+    } else {
+        partitions
+    }
+}
+
+/// Attaches `image` read-only and returns the device it is attached to.
+#[cfg(target_os = "linux")]
+fn attach(
+    runner: &dyn CommandRunner,
+    image: &Path,
+    format: ImageFormat,
+) -> color_eyre::Result<String> {
+    let image = image.to_string_lossy();
+    let device = match format.qemu_format() {
+        None => {
+            let output = runner.run(
+                "sudo",
+                &[
+                    "losetup",
+                    "--find",
+                    "--show",
+                    "--read-only",
+                    "--partscan",
+                    &image,
+                ],
+            )?;
+            if !output.success {
+                return Err(color_eyre::eyre::eyre!(
+                    "losetup failed: {}",
+                    output.stderr.trim()
+                ));
+            }
+            output.stdout.trim().to_string()
+        }
+        Some(qemu_format) => {
+            // The module may already be loaded, or built in
+            let _ = runner.run("sudo", &["modprobe", "nbd", "max_part=16"]);
+            let listing = runner.run("lsblk", &["-dnbpo", "NAME,SIZE"])?;
+            let device = first_free_nbd(&listing.stdout).ok_or_else(|| {
+                color_eyre::eyre::eyre!("no free /dev/nbdN device (is qemu-nbd installed?)")
+            })?;
+            let output = runner.run(
+                "sudo",
+                &[
+                    "qemu-nbd",
+                    "--read-only",
+                    &format!("--format={}", qemu_format),
+                    &format!("--connect={}", device),
+                    &image,
+                ],
+            )?;
+            if !output.success {
+                return Err(color_eyre::eyre::eyre!(
+                    "qemu-nbd failed: {}",
+                    output.stderr.trim()
+                ));
+            }
+            device
+        }
+    };
+
+    // Partition devices appear asynchronously
+    let _ = runner.run("sudo", &["udevadm", "settle"]);
+    Ok(device)
+}
+
+/// Attaches `image` and mounts each of its partitions read-only.
+///
+/// # Errors
+///
+/// Returns an error if the image cannot be attached or none of its
+/// partitions holds a filesystem that mounts; the image is detached again.
+#[cfg(target_os = "linux")]
+pub fn open_image(
+    runner: &dyn CommandRunner,
+    image: &Path,
+    format: ImageFormat,
+    mount: &MountConfig,
+) -> color_eyre::Result<OpenedImage> {
+    use crate::mount::{get_filesystem_type, mount_command, ntfs_driver};
+
+    let device = attach(runner, image, format)?;
+    let mut opened = OpenedImage {
+        image: image.to_path_buf(),
+        device,
+        format,
+        mounts: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    let listing = runner.run("lsblk", &["-lnpo", "NAME,TYPE", &opened.device])?;
+    for partition in parse_partitions(&opened.device, &listing.stdout) {
+        let fs_type = match get_filesystem_type(runner, &partition)? {
+            Some(fs_type) if fs_type != "swap" => fs_type,
+            Some(_) => continue,
+            None => {
+                opened
+                    .skipped
+                    .push(format!("{}: no filesystem found", partition));
+                continue;
+            }
+        };
+
+        let name = partition.trim_start_matches("/dev/");
+        let mount_point =
+            Path::new(&mount.mount_base_dir).join(format!("{}{}", mount.mount_prefix, name));
+        let mount_point_str = mount_point.to_string_lossy();
+        let output = runner.run("sudo", &["mkdir", "-p", &mount_point_str])?;
+        if !output.success {
+            opened.skipped.push(format!(
+                "{}: cannot create {}: {}",
+                partition,
+                mount_point.display(),
+                output.stderr.trim()
+            ));
+            continue;
+        }
+
+        // Journal replay would write to the image, and fails on a read-only device
+        let args = match fs_type.as_str() {
+            "ext3" | "ext4" => vec!["mount", "-o", "ro,noload", &partition, &mount_point_str],
+            "xfs" => vec!["mount", "-o", "ro,norecovery", &partition, &mount_point_str],
+            "ntfs" => mount_command(Some(ntfs_driver(runner)), &partition, &mount_point_str),
+            _ => mount_command(None, &partition, &mount_point_str),
+        };
+        let output = runner.run("sudo", &args)?;
+        if output.success {
+            opened.mounts.push((partition.clone(), mount_point.clone()));
+        } else {
+            let _ = runner.run("sudo", &["rmdir", &mount_point_str]);
+            opened.skipped.push(format!(
+                "{} ({}): mount failed: {}",
+                partition,
+                fs_type,
+                output.stderr.trim()
+            ));
+        }
+    }
+
+    if opened.mounts.is_empty() {
+        let reasons = opened.skipped.join("; ");
+        close_image(runner, opened)?;
+        return Err(color_eyre::eyre::eyre!(
+            "no partition could be mounted{}",
+            if reasons.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", reasons)
+            }
+        ));
+    }
+    Ok(opened)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_image(
+    _runner: &dyn CommandRunner,
+    _image: &Path,
+    _format: ImageFormat,
+    _mount: &MountConfig,
+) -> color_eyre::Result<OpenedImage> {
+    Err(color_eyre::eyre::eyre!(
+        "disk images can only be opened on Linux"
+    ))
+}
+
+/// Unmounts the partitions of an opened image and detaches it.
+///
+/// Every step is tried; the first failure is returned.
+pub fn close_image(runner: &dyn CommandRunner, opened: OpenedImage) -> color_eyre::Result<()> {
+    let mut result = Ok(());
+    for (_, mount_point) in opened.mounts.iter().rev() {
+        let mount_point = mount_point.to_string_lossy();
+        match runner.run("sudo", &["umount", &mount_point]) {
+            Ok(output) if output.success => {
+                let _ = runner.run("sudo", &["rmdir", &mount_point]);
+            }
+            Ok(output) => {
+                if result.is_ok() {
+                    result = Err(color_eyre::eyre::eyre!(
+                        "Failed to unmount {}: {}",
+                        mount_point,
+                        output.stderr.trim()
+                    ));
+                }
+            }
+            Err(e) => {
+                if result.is_ok() {
+                    result = Err(e.into());
+                }
+            }
+        }
+    }
+
+    let args: &[&str] = match opened.format.qemu_format() {
+        None => &["losetup", "--detach", &opened.device],
+        Some(_) => &["qemu-nbd", "--disconnect", &opened.device],
+    };
+    match runner.run("sudo", args) {
+        Ok(output) if output.success => result,
+        Ok(output) => result.and(Err(color_eyre::eyre::eyre!(
+            "Failed to detach {} from {}: {}",
+            opened.image.display(),
+            opened.device,
+            output.stderr.trim()
+        ))),
+        Err(e) => result.and(Err(e.into())),
+    }
+}
+
+/// Opens the disk images found in `stats`, scans their partitions, and merges
+/// the results in, each partition as a source labelled after its image.
+///
+/// Images found inside are opened too, down to `scan.max_image_depth`
+/// levels. Images that cannot be opened, and images nested deeper, are
+/// reported as warnings in `stats`. The returned images stay mounted so their
+/// files can still be read; pass them to [`close_images`] when done.
+pub async fn scan_images<F>(
+    stats: &mut ScanStats,
+    config: &Config,
+    control: &Control,
+    progress: F,
+) -> color_eyre::Result<Vec<OpenedImage>>
+where
+    F: Fn(String) + Clone + Send + Sync + 'static,
+{
+    let runner = SystemCommands;
+    let mut pending: VecDeque<(PathBuf, usize)> = find_images(stats)
+        .into_iter()
+        .map(|image| (image, 1))
+        .collect();
+    let mut opened = Vec::new();
+    let mut too_deep = 0;
+
+    while let Some((image, depth)) = pending.pop_front() {
+        if control.is_cancelled() {
+            break;
+        }
+        let Some(format) = ImageFormat::from_path(&image) else {
+            continue;
+        };
+
+        // Images inside an image are named by the partition they were found on
+        let source = stats.source_of(&image).to_path_buf();
+        let image_label = match stats.source_labels.get(&source) {
+            Some(label) => format!(
+                "{} › {}",
+                label,
+                image.strip_prefix(&source).unwrap_or(&image).display()
+            ),
+            None => image.display().to_string(),
+        };
+
+        let image_mounts = match open_image(&runner, &image, format, &config.mount) {
+            Ok(image_mounts) => image_mounts,
+            Err(e) => {
+                stats
+                    .warnings
+                    .push(format!("Disk image {} not opened: {}", image_label, e));
+                continue;
+            }
+        };
+        for reason in &image_mounts.skipped {
+            stats
+                .warnings
+                .push(format!("Disk image {}: {}", image_label, reason));
+        }
+
+        for (partition, mount_point) in image_mounts.mounts.clone() {
+            let mut nested =
+                match scan_directory_with_control(&mount_point, config, control, progress.clone())
+                    .await
+                {
+                    Ok(nested) => nested,
+                    Err(e) => {
+                        // Left attached, the loop and NBD devices would outlive the run
+                        opened.push(image_mounts);
+                        close_images(opened);
+                        return Err(e);
+                    }
+                };
+            let inner = find_images(&nested);
+            if depth < config.scan.max_image_depth {
+                pending.extend(inner.into_iter().map(|image| (image, depth + 1)));
+            } else {
+                too_deep += inner.len();
+            }
+
+            let label = match partition.strip_prefix(&image_mounts.device) {
+                Some("") | None => image_label.clone(),
+                Some(suffix) => format!("{} › {}", image_label, suffix),
+            };
+            nested.source_labels.insert(mount_point, label);
+            stats.merge(nested);
+        }
+        opened.push(image_mounts);
+    }
+
+    if too_deep > 0 {
+        stats.warnings.push(format!(
+            "{} disk image(s) nested deeper than scan.max_image_depth ({}) were not opened",
+            too_deep, config.scan.max_image_depth
+        ));
+    }
+    Ok(opened)
+}
+
+/// Closes images opened by [`scan_images`], innermost first, returning a
+/// message for each that could not be closed.
+pub fn close_images(opened: Vec<OpenedImage>) -> Vec<String> {
+    let runner = SystemCommands;
+    opened
+        .into_iter()
+        .rev()
+        .filter_map(|image| close_image(&runner, image).err())
+        .map(|e| e.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_formats() {
+        assert_eq!(
+            ImageFormat::from_path(Path::new("/evidence/VM/Disk.VMDK")),
+            Some(ImageFormat::Vmdk)
+        );
+        assert_eq!(
+            ImageFormat::from_path(Path::new("sdb.dd")),
+            Some(ImageFormat::Raw)
+        );
+        assert_eq!(ImageFormat::Vhd.qemu_format(), Some("vpc"));
+        assert_eq!(ImageFormat::from_path(Path::new("photo.raw")), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_device_listings() {
+        let listing = "/dev/nbd0 10737418240\n/dev/nbd1 0\n/dev/nbd2 0\n/dev/sda 500107862016\n";
+        assert_eq!(first_free_nbd(listing), Some("/dev/nbd1".to_string()));
+        assert_eq!(first_free_nbd("/dev/nbd0 512\n"), None);
+
+        let listing = "/dev/loop3 loop\n/dev/loop3p1 part\n/dev/loop3p2 part\n";
+        assert_eq!(
+            parse_partitions("/dev/loop3", listing),
+            ["/dev/loop3p1", "/dev/loop3p2"]
+        );
+        assert_eq!(
+            parse_partitions("/dev/loop4", "/dev/loop4 loop\n"),
+            ["/dev/loop4"]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_open_and_close_raw_image() {
+        use crate::command::FakeCommands;
+
+        let runner = FakeCommands::new()
+            .with(
+                "sudo losetup --find --show --read-only --partscan /cases/disk.img",
+                "/dev/loop3\n",
+            )
+            .with("sudo udevadm settle", "")
+            .with(
+                "lsblk -lnpo NAME,TYPE /dev/loop3",
+                "/dev/loop3 loop\n/dev/loop3p1 part\n/dev/loop3p2 part\n/dev/loop3p3 part\n",
+            )
+            .with("blkid -s TYPE -o value /dev/loop3p1", "ext4\n")
+            .with("blkid -s TYPE -o value /dev/loop3p2", "swap\n")
+            .with_failure("blkid -s TYPE -o value /dev/loop3p3", "")
+            .with("sudo mkdir -p /mnt/tap_loop3p1", "")
+            .with("sudo mount -o ro,noload /dev/loop3p1 /mnt/tap_loop3p1", "")
+            .with("sudo umount /mnt/tap_loop3p1", "")
+            .with("sudo rmdir /mnt/tap_loop3p1", "")
+            .with("sudo losetup --detach /dev/loop3", "");
+
+        let opened = open_image(
+            &runner,
+            Path::new("/cases/disk.img"),
+            ImageFormat::Raw,
+            &Config::default().mount,
+        )
+        .unwrap();
+        assert_eq!(opened.device, "/dev/loop3");
+        assert_eq!(
+            opened.mounts,
+            [(
+                "/dev/loop3p1".to_string(),
+                PathBuf::from("/mnt/tap_loop3p1")
+            )]
+        );
+        // Swap is passed over silently; an unknown partition is reported
+        assert_eq!(opened.skipped, ["/dev/loop3p3: no filesystem found"]);
+        close_image(&runner, opened).unwrap();
+    }
+}
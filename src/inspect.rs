@@ -1,79 +1,278 @@
 //! Drive inspection workflow.
 //!
 //! This module implements the inspect command, which mounts a drive, scans
-//! its contents, and displays categorized file statistics.
+//! its contents, and displays categorized file statistics. Several drives or
+//! paths can be inspected together; their results are merged into one summary.
+//! With `--cache` the results are saved for later runs, and `--offline` shows
+//! saved results without touching the source. `--out-manifest-only` writes the
+//! manifest an export would, without copying anything, and `--diff` compares
+//! the results with another drive, export, or manifest. With
+//! `--descend-images`, disk images found on the drive are opened and scanned
+//! too; see [`crate::image`].
 
+use dialoguer::{Confirm, Input, MultiSelect};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::cache::{compare, load_cache, save_cache};
 use crate::config::Config;
-use crate::log::write_inspect_log;
-use crate::mount::{mount_drive_readonly, unmount_drive, validate_source_path};
-use crate::scanner::{count_files, scan_directory};
-use crate::tui::{Mode, UI};
+use crate::control::{Control, KeyListener};
+use crate::diff::{Diff, load_other};
+use crate::image::{close_images, find_images, scan_images};
+use crate::last_run;
+use crate::log::{write_diff_log, write_inspect_log};
+use crate::manifest::{ManifestEntry, scan_manifest, write_manifest_to};
+use crate::mount::{
+    DriveSpace, drive_space, ensure_mount_privileges, is_device_path, mount_drive_readonly,
+    overlapping_sources, release_drive, validate_source_path, verify_still_readonly,
+};
+use crate::report::write_html_report;
+use crate::scanner::{ScanStats, count_files};
+use crate::tui::{Mode, RedrawThrottle, UI, format_duration, format_size};
+use crate::workflow::{export_scanned, scan_sources};
+
+/// Files written after an inspection, as requested on the command line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InspectOutputs<'a> {
+    /// Write a text log (`--log`)
+    pub log: bool,
+    /// Write an HTML report here (`--report-html`)
+    pub report_html: Option<&'a Path>,
+    /// Write a manifest of every scanned file here (`--out-manifest-only`)
+    pub manifest: Option<&'a Path>,
+    /// Read each file to record its SHA-256 in the manifest (`--hash`)
+    pub hash: bool,
+    /// Compare the results with this capture (`--diff`)
+    pub diff: Option<&'a Path>,
+}
 
 pub async fn handle_inspect(
-    drive: &str,
-    write_log: bool,
+    drives: &[String],
+    outputs: InspectOutputs<'_>,
+    cache: Option<&Path>,
+    offline: bool,
     config: &Config,
 ) -> color_eyre::Result<()> {
-    // Check if it's a device or a path
-    let is_device = drive.starts_with("/dev/");
-    let source_path = if is_device {
-        mount_drive_readonly(drive, &config.ui.color.theme).await?
-    } else {
-        validate_source_path(drive, &config.ui.color.theme)?
-    };
+    if offline {
+        let cache_path =
+            cache.ok_or_else(|| color_eyre::eyre::eyre!("--offline requires --cache"))?;
+        return inspect_offline(drives, outputs, cache_path, config).await;
+    }
+
+    let mut config = config.clone();
+    let since_last_run = last_run::begin("inspect", drives, &mut config)?;
+    let config = &config;
 
+    // A single mount point cannot hold more than one device
+    if config.mount.mount_point.is_some() && drives.iter().filter(|d| is_device_path(d)).count() > 1
+    {
+        return Err(color_eyre::eyre::eyre!(
+            "--mount-point can only be used when inspecting a single device"
+        ));
+    }
+
+    // A source given twice, or inside another source, would be counted twice
+    let repeated = overlapping_sources(drives);
+    let skipped: Vec<String> = repeated
+        .iter()
+        .map(|&i| format!("Skipping {}: already covered by another source", drives[i]))
+        .collect();
+    let distinct: Vec<&str> = (0..drives.len())
+        .filter(|i| !repeated.contains(i))
+        .map(|i| drives[i].as_str())
+        .collect();
+
+    let sources = open_sources(&distinct, config).await?;
+    let result = inspect_sources(
+        drives,
+        &sources,
+        skipped,
+        since_last_run,
+        outputs,
+        cache,
+        config,
+    )
+    .await;
+
+    match result {
+        Ok((ui, scan_stats)) => {
+            // Unmount drives if we mounted them, unless asked to leave them in place
+            for (drive, source_path, is_device) in &sources {
+                if *is_device {
+                    release_drive(source_path, drive, &config.mount, &config.ui.color.theme)?;
+                }
+            }
+            ui.print_compact_summary(&scan_stats)?;
+            Ok(())
+        }
+        Err(e) => {
+            release_sources(&sources, config);
+            Err(e)
+        }
+    }
+}
+
+/// Mounts each device and checks each path in turn. If one of them fails, the
+/// devices already mounted are released again before the error is returned.
+async fn open_sources<'a>(
+    drives: &[&'a str],
+    config: &Config,
+) -> color_eyre::Result<Vec<(&'a str, PathBuf, bool)>> {
+    let mut sources = Vec::new();
+    for &drive in drives {
+        let is_device = is_device_path(drive);
+        let opened = if is_device {
+            mount_drive_readonly(drive, &config.mount, &config.ui.color.theme).await
+        } else {
+            validate_source_path(drive, &config.mount, &config.ui.color.theme)
+        };
+        match opened {
+            Ok(source_path) => sources.push((drive, source_path, is_device)),
+            Err(e) => {
+                release_sources(&sources, config);
+                return Err(e);
+            }
+        }
+    }
+    Ok(sources)
+}
+
+/// Releases the devices mounted for an inspection that failed part way. The
+/// original error is what gets reported, so unmount failures are not.
+fn release_sources(sources: &[(&str, PathBuf, bool)], config: &Config) {
+    for (drive, source_path, is_device) in sources {
+        if *is_device {
+            let _ = release_drive(source_path, drive, &config.mount, &config.ui.color.theme);
+        }
+    }
+}
+
+/// Scans the opened sources and shows and writes the results. Returns the UI
+/// and the statistics so the caller can finish once the drives are released.
+async fn inspect_sources(
+    drives: &[String],
+    sources: &[(&str, PathBuf, bool)],
+    mut skipped: Vec<String>,
+    since_last_run: Option<last_run::SinceLastRun>,
+    outputs: InspectOutputs<'_>,
+    cache: Option<&Path>,
+    config: &Config,
+) -> color_eyre::Result<(UI, ScanStats)> {
+    // A drive mounted inside a path source is also reached through that path
+    let nested = overlapping_sources(&sources.iter().map(|(_, path, _)| path).collect::<Vec<_>>());
+    skipped.extend(nested.iter().map(|&i| {
+        format!(
+            "Skipping {}: already covered by another source",
+            sources[i].0
+        )
+    }));
+    let source_paths: Vec<PathBuf> = (0..sources.len())
+        .filter(|i| !nested.contains(i))
+        .map(|i| sources[i].1.clone())
+        .collect();
     // Create UI with color theme from config
-    let ui = UI::new()?.with_color_theme(config.ui.color.theme.clone());
-    let inspect_msg = format!("Source: {}", source_path.display());
+    let ui = UI::new()?
+        .with_color_theme(config.ui.color.theme.clone())
+        .with_min_category_files(config.ui.min_category_files)
+        .with_chart_by(config.ui.chart_by)
+        .with_progress_style(config.ui.tick_ms, config.ui.ascii_progress)
+        .with_summary(!config.ui.no_summary)
+        .with_compact_summary(!config.ui.no_compact_summary);
+    let inspect_msg = format!(
+        "{}: {}",
+        if source_paths.len() > 1 {
+            "Sources"
+        } else {
+            "Source"
+        },
+        source_paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
     ui.init(&Mode::Inspect, &inspect_msg)?;
+    for message in &skipped {
+        ui.print_warning(message)?;
+    }
+    if let Some(run) = &since_last_run {
+        ui.print_info(&run.describe())?;
+    }
+
+    let cached = match cache.map(|path| load_cache(path, drives)) {
+        Some(Ok(cached)) => Some(cached),
+        Some(Err(reason)) => {
+            ui.print_warning(&format!("Cache not used: {}", reason))?;
+            None
+        }
+        None => None,
+    };
 
-    // Phase 1: Count files
-    ui.print_info("Phase 1/2: Counting filesystem entries")?;
-    let spinner = ui.create_spinner("Walking directory tree...");
+    // Phase 1: Count files, unless a cache already knows roughly how many there are
+    let total_files = if let Some(cached) = &cached {
+        ui.print_info(&format!(
+            "Phase 1/2: Using file count from the cache written {}",
+            cached.written
+        ))?;
+        Some(cached.stats.total_files as u64)
+    } else if config.ui.no_count {
+        ui.print_info("Phase 1/2: Counting skipped (--no-count)")?;
+        None
+    } else {
+        ui.print_info("Phase 1/2: Counting filesystem entries")?;
+        let spinner = ui.create_spinner("Walking directory tree...");
 
-    let total_files = count_files(&source_path).await;
+        let mut total_files = 0;
+        for source_path in &source_paths {
+            total_files += count_files(source_path, config).await;
+        }
 
-    spinner.finish_and_clear();
-    ui.print_success(&format!("Discovered {} files", total_files))?;
+        spinner.finish_and_clear();
+        Some(total_files)
+    };
+    if let Some(total_files) = total_files {
+        ui.print_success(&format!("Discovered {} files", total_files))?;
+    }
 
     // Phase 2: Scan and categorize
-    ui.print_info("Phase 2/2: Analyzing and categorizing files")?;
+    let control = Control::new();
+    let keys = KeyListener::start(&control);
+    ui.print_info(&format!(
+        "Phase 2/2: Analyzing and categorizing files{}",
+        keys.hint()
+    ))?;
 
     // Draw the recent files section first, then create progress bar below it
     ui.draw_recent_files()?;
-    let pb = ui.create_progress_bar(total_files, "Analyzing");
+    let pb = ui.create_scan_bar(total_files, "Analyzing");
+    control.attach(&pb);
 
     let ui_arc = Arc::new(Mutex::new(ui));
-    let counter = Arc::new(Mutex::new(0u64));
+    let throttle = Arc::new(RedrawThrottle::new(config.ui.recent_files_ms));
 
-    let scan_stats = scan_directory(&source_path, {
+    let mut scan_stats = scan_sources(&source_paths, config, &control, {
         let pb = pb.clone();
         let ui_arc = Arc::clone(&ui_arc);
-        let counter = Arc::clone(&counter);
+        let throttle = Arc::clone(&throttle);
 
         move |path| {
             pb.inc(1);
 
             // Rate limit UI updates to prevent screen overflow
-            // Only update every 100 files
             // Use try_lock to avoid blocking in the scanning thread
-            if let Ok(mut count) = counter.try_lock() {
-                *count += 1;
-
-                if *count % 100 == 0 {
-                    if let Ok(mut ui) = ui_arc.try_lock() {
-                        let _ = ui.update_recent_files(path);
-                    }
-                }
+            if throttle.ready()
+                && let Ok(mut ui) = ui_arc.try_lock()
+            {
+                let _ = ui.update_recent_files(path);
             }
         }
     })
     .await?;
 
     pb.finish_and_clear();
+    drop(keys);
 
     // Get UI back
     let ui = Arc::try_unwrap(ui_arc)
@@ -86,6 +285,181 @@ pub async fn handle_inspect(
     // Clear the recent files section after scan completes
     ui.term.clear_last_lines(ui.max_recent + 2)?;
 
+    // Disk images on the drive often hold the data that matters
+    let images = find_images(&scan_stats);
+    let opened_images =
+        if !scan_stats.cancelled && !images.is_empty() && offer_images(&ui, &images, config)? {
+            // Checked before the run already when --descend-images was given
+            if !config.scan.descend_images {
+                ensure_mount_privileges(&config.ui.color.theme)?;
+            }
+            let keys = KeyListener::start(&control);
+            ui.print_info(&format!(
+                "Opening {} disk image(s) read-only{}",
+                images.len(),
+                keys.hint()
+            ))?;
+            let pb = ui.create_scan_bar(None, "Analyzing disk images");
+            control.attach(&pb);
+            let opened = scan_images(&mut scan_stats, config, &control, {
+                let pb = pb.clone();
+                move |_| pb.inc(1)
+            })
+            .await?;
+            pb.finish_and_clear();
+            drop(keys);
+            opened
+        } else {
+            Vec::new()
+        };
+
+    // The images stay open until reporting is done, and are closed even if it fails
+    let reported = async {
+        // The read-only guarantee has to have held for the whole scan, not just at mount time
+        for (drive, source_path, is_device) in sources {
+            if *is_device && let Some(warning) = verify_still_readonly(source_path, drive) {
+                scan_stats.warnings.push(warning);
+            }
+        }
+
+        // Drive figures only make sense for a single source that is a whole filesystem
+        let drive = match source_paths.as_slice() {
+            [source] => drive_space(source),
+            _ => None,
+        };
+        show_summary(&ui, &scan_stats, drive, None)?;
+
+        if let Some(cached) = &cached {
+            let changes = compare(&cached.stats, &scan_stats);
+            ui.print_info(&format!(
+                "Since the cached scan: {} added, {} changed, {} removed",
+                changes.added, changes.changed, changes.removed
+            ))?;
+            println!();
+        }
+
+        if let Some(cache_path) = cache {
+            // A partial scan would make the next run report missing files as removed
+            if scan_stats.cancelled || scan_stats.limit_reached {
+                ui.print_warning(
+                    "Cache not updated because the inspection was cancelled or stopped at --max-files",
+                )?;
+            } else if since_last_run.as_ref().is_some_and(|run| run.since.is_some()) {
+                ui.print_warning(
+                    "Cache not updated because --since-last-run left out files unchanged since the last run",
+                )?;
+            } else {
+                match save_cache(cache_path, drives, &scan_stats) {
+                    Ok(()) => {
+                        ui.print_success(&format!("Cache written to: {}", cache_path.display()))?
+                    }
+                    Err(e) => ui.print_warning(&format!("Failed to write cache: {}", e))?,
+                }
+            }
+            println!();
+        }
+
+        if let Some(run) = since_last_run {
+            // Files the scan never reached would be left out of the next run
+            if scan_stats.cancelled || scan_stats.limit_reached {
+                ui.print_warning(
+                    "Last run not recorded because the inspection was cancelled or stopped at --max-files",
+                )?;
+            } else {
+                match run.record() {
+                    Ok(()) => ui.print_success("Last run recorded for --since-last-run")?,
+                    Err(e) => ui.print_warning(&format!("Failed to record last run: {}", e))?,
+                }
+            }
+            println!();
+        }
+
+        write_outputs(&ui, &source_paths, &scan_stats, outputs, config).await?;
+
+        // Only offered to someone at the terminal, never in scripted runs
+        if !scan_stats.cancelled
+            && scan_stats.total_files > 0
+            && std::io::stdin().is_terminal()
+            && std::io::stdout().is_terminal()
+        {
+            offer_export(&ui, &source_paths, &scan_stats, config).await?;
+        }
+        Ok::<(), color_eyre::Report>(())
+    }
+    .await;
+
+    // Images live on the drives, so they are closed before the drives are unmounted
+    for message in close_images(opened_images) {
+        ui.print_warning(&message)?;
+    }
+    reported?;
+    ui.cleanup()?;
+
+    Ok((ui, scan_stats))
+}
+
+/// Decides whether to open the disk images found in the scan: always with
+/// `scan.descend_images`, otherwise when someone at the terminal agrees.
+fn offer_images(ui: &UI, images: &[PathBuf], config: &Config) -> color_eyre::Result<bool> {
+    if config.scan.descend_images {
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(false);
+    }
+
+    ui.print_info(&format!("Found {} disk image(s):", images.len()))?;
+    for image in images.iter().take(5) {
+        println!("    {}", image.display());
+    }
+    if images.len() > 5 {
+        println!("    ... and {} more", images.len() - 5);
+    }
+    let theme = UI::get_colorful_theme(&config.ui.color.theme);
+    Ok(Confirm::with_theme(&theme)
+        .with_prompt("Mount them read-only and scan their contents?")
+        .default(false)
+        .interact()?)
+}
+
+/// Shows results saved by an earlier `--cache` run without mounting or scanning.
+async fn inspect_offline(
+    drives: &[String],
+    outputs: InspectOutputs<'_>,
+    cache_path: &Path,
+    config: &Config,
+) -> color_eyre::Result<()> {
+    let cached = load_cache(cache_path, drives)
+        .map_err(|e| color_eyre::eyre::eyre!("Cannot inspect offline: {}", e))?;
+
+    let ui = UI::new()?
+        .with_color_theme(config.ui.color.theme.clone())
+        .with_min_category_files(config.ui.min_category_files)
+        .with_chart_by(config.ui.chart_by)
+        .with_progress_style(config.ui.tick_ms, config.ui.ascii_progress)
+        .with_summary(!config.ui.no_summary)
+        .with_compact_summary(!config.ui.no_compact_summary);
+    ui.init(
+        &Mode::Inspect,
+        &format!("Cached: {}", cached.sources.join(", ")),
+    )?;
+
+    show_summary(&ui, &cached.stats, None, Some(&cached.written))?;
+    write_outputs(&ui, &cached.stats.sources, &cached.stats, outputs, config).await?;
+    ui.cleanup()?;
+    ui.print_compact_summary(&cached.stats)?;
+    Ok(())
+}
+
+/// Shows the summary screen, then the warnings and completion status below a fresh banner.
+///
+/// `cached_at` is when the results were cached, for results shown without scanning.
+fn show_summary(
+    ui: &UI,
+    scan_stats: &ScanStats,
+    drive: Option<DriveSpace>,
+    cached_at: Option<&str>,
+) -> color_eyre::Result<()> {
     // Clear screen and show clean output
     ui.term.clear_screen()?;
 
@@ -93,16 +467,12 @@ pub async fn handle_inspect(
     ui.print_banner_with_mode(&Mode::Inspect)?;
 
     // Display scan results
-    let summary = scan_stats.get_summary();
-    let all_files = scan_stats.get_all_files();
-    ui.print_summary(
-        &Mode::Inspect,
-        "INSPECTION COMPLETE",
-        &summary,
-        &all_files,
-        None,
-        false,
-    )?;
+    let title = if scan_stats.cancelled {
+        "INSPECTION CANCELLED"
+    } else {
+        "INSPECTION COMPLETE"
+    };
+    ui.print_summary(&Mode::Inspect, title, scan_stats, drive, false)?;
 
     // Clear screen for final messages
     ui.term.clear_screen()?;
@@ -110,20 +480,69 @@ pub async fn handle_inspect(
     println!();
 
     if !scan_stats.errors.is_empty() {
+        let kinds: Vec<String> = scan_stats
+            .get_error_summary(0)
+            .iter()
+            .map(|group| format!("{} {}", group.count, group.kind.label().to_lowercase()))
+            .collect();
         ui.print_warning(&format!(
-            "{} file(s) skipped due to permission errors or I/O failures",
-            scan_stats.errors.len()
+            "{} path(s) could not be scanned{}; see the Errors section for details",
+            scan_stats.errors.len(),
+            if kinds.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", kinds.join(", "))
+            }
+        ))?;
+        println!();
+    }
+
+    for warning in &scan_stats.warnings {
+        ui.print_error(warning)?;
+        println!();
+    }
+
+    if scan_stats.limit_reached {
+        ui.print_warning(&format!(
+            "Scan limit reached after {} files; results are partial",
+            scan_stats.total_files
         ))?;
         println!();
     }
 
-    ui.print_success("Inspection complete")?;
+    if let Some(written) = cached_at {
+        ui.print_success(&format!(
+            "Showing cached results written {}; the source was not scanned",
+            written
+        ))?;
+    } else if scan_stats.cancelled {
+        ui.print_warning(&format!(
+            "Inspection cancelled after {}; results are partial",
+            format_duration(scan_stats.duration)
+        ))?;
+    } else {
+        ui.print_success(&format!(
+            "Inspection complete in {}",
+            format_duration(scan_stats.duration)
+        ))?;
+    }
     println!();
 
+    Ok(())
+}
+
+/// Writes the log, HTML report, manifest, and diff if requested; failures are shown as warnings.
+async fn write_outputs(
+    ui: &UI,
+    source_paths: &[PathBuf],
+    scan_stats: &ScanStats,
+    outputs: InspectOutputs<'_>,
+    config: &Config,
+) -> color_eyre::Result<()> {
     // Write log file if requested
-    if write_log {
+    if outputs.log {
         ui.print_info("Writing log file...")?;
-        match write_inspect_log(&source_path, &scan_stats).await {
+        match write_inspect_log(source_paths, scan_stats, &config.log).await {
             Ok(log_path) => {
                 ui.print_success(&format!("Log written to: {}", log_path.display()))?;
                 println!();
@@ -135,12 +554,252 @@ pub async fn handle_inspect(
         }
     }
 
-    ui.cleanup()?;
+    if let Some(report_path) = outputs.report_html
+        && let Some(root) = source_paths.first()
+    {
+        match write_html_report(report_path, root, scan_stats, None).await {
+            Ok(()) => {
+                ui.print_success(&format!("Report written to: {}", report_path.display()))?;
+                println!();
+            }
+            Err(e) => {
+                ui.print_warning(&format!("Failed to write HTML report: {}", e))?;
+                println!();
+            }
+        }
+    }
+
+    if outputs.manifest.is_none() && outputs.diff.is_none() {
+        return Ok(());
+    }
+    let entries = scan_entries(ui, scan_stats, outputs.hash).await?;
+    if let Some(manifest_path) = outputs.manifest {
+        write_scan_manifest(ui, manifest_path, &entries, outputs.hash).await?;
+    }
+    if let Some(other) = outputs.diff {
+        show_diff(ui, source_paths, &entries, other, config).await?;
+    }
+
+    Ok(())
+}
+
+/// Builds the manifest entries of the scanned files, hashing them in place with `--hash`.
+async fn scan_entries(
+    ui: &UI,
+    scan_stats: &ScanStats,
+    hash: bool,
+) -> color_eyre::Result<Vec<ManifestEntry>> {
+    let control = Control::new();
+    let entries = if hash {
+        let keys = KeyListener::start(&control);
+        ui.print_info(&format!(
+            "Hashing {} files in place{}",
+            scan_stats.total_files,
+            keys.hint()
+        ))?;
+        let pb = ui.create_progress_bar(scan_stats.total_files as u64, "Hashing");
+        control.attach(&pb);
+        let entries = scan_manifest(scan_stats, true, &control, || pb.inc(1)).await?;
+        pb.finish_and_clear();
+        entries
+    } else {
+        scan_manifest(scan_stats, false, &control, || {}).await?
+    };
+    Ok(entries)
+}
+
+/// Writes the manifest for `--out-manifest-only`.
+async fn write_scan_manifest(
+    ui: &UI,
+    path: &Path,
+    entries: &[ManifestEntry],
+    hash: bool,
+) -> color_eyre::Result<()> {
+    match write_manifest_to(path, entries).await {
+        Ok(()) => ui.print_success(&format!("Manifest written to: {}", path.display()))?,
+        Err(e) => ui.print_warning(&format!("Failed to write manifest: {}", e))?,
+    }
+    let unhashed = entries
+        .iter()
+        .filter(|entry| entry.sha256.is_none())
+        .count();
+    if hash && unhashed > 0 {
+        ui.print_warning(&format!(
+            "{} file(s) have no checksum (unreadable or hashing cancelled)",
+            unhashed
+        ))?;
+    }
+    println!();
+    Ok(())
+}
+
+/// Compares the scanned files with `other` for `--diff`, shows the differences
+/// by category, and writes them to the diff log.
+async fn show_diff(
+    ui: &UI,
+    source_paths: &[PathBuf],
+    entries: &[ManifestEntry],
+    other: &Path,
+    config: &Config,
+) -> color_eyre::Result<()> {
+    ui.print_info(&format!("Comparing with {}...", other.display()))?;
+    let there = match load_other(other, config).await {
+        Ok(there) => there,
+        Err(e) => {
+            ui.print_warning(&format!("Failed to compare: {}", e))?;
+            println!();
+            return Ok(());
+        }
+    };
 
-    // Unmount drive if we mounted it
-    if is_device {
-        unmount_drive(&source_path, drive, &config.ui.color.theme)?;
+    let diff = Diff::compare(entries, there);
+    if diff.is_empty() {
+        ui.print_success(&format!(
+            "No differences: all {} files match {}",
+            diff.same,
+            other.display()
+        ))?;
+    } else {
+        ui.print_warning(&format!(
+            "{} only in source, {} only in {}, {} different, {} identical",
+            diff.only_here.len(),
+            diff.only_there.len(),
+            other.display(),
+            diff.differing.len(),
+            diff.same
+        ))?;
+        for (category, counts) in diff.by_category() {
+            ui.print_info(&format!(
+                "{}: {} only in source, {} only in other, {} different",
+                category, counts.only_here, counts.only_there, counts.differing
+            ))?;
+        }
+    }
+
+    match write_diff_log(source_paths, other, &diff, &config.log).await {
+        Ok(log_path) => ui.print_success(&format!("Diff written to: {}", log_path.display()))?,
+        Err(e) => ui.print_warning(&format!("Failed to write diff log: {}", e))?,
+    }
+    println!();
+    Ok(())
+}
+
+/// Offers to export chosen categories straight from the inspection results.
+///
+/// Copies from the files already scanned, so the sources are not walked a
+/// second time as a separate `tap export` would.
+async fn offer_export(
+    ui: &UI,
+    source_paths: &[PathBuf],
+    scan_stats: &ScanStats,
+    config: &Config,
+) -> color_eyre::Result<()> {
+    let theme = UI::get_colorful_theme(&config.ui.color.theme);
+    if !Confirm::with_theme(&theme)
+        .with_prompt("Export selected categories now?")
+        .default(false)
+        .interact()?
+    {
+        return Ok(());
     }
 
+    let mut categories: Vec<&String> = scan_stats.files_by_category.keys().collect();
+    categories.sort();
+    let labels: Vec<String> = categories
+        .iter()
+        .map(|category| {
+            let files = &scan_stats.files_by_category[*category];
+            let size: u64 = files.iter().map(|file| file.size).sum();
+            format!(
+                "{} ({} files, {})",
+                category,
+                files.len(),
+                format_size(size)
+            )
+        })
+        .collect();
+    let chosen = MultiSelect::with_theme(&theme)
+        .with_prompt("Categories to export (space to toggle, enter to confirm)")
+        .items(&labels)
+        .defaults(&vec![true; labels.len()])
+        .interact()?;
+    if chosen.is_empty() {
+        ui.print_info("No categories selected; nothing exported")?;
+        println!();
+        return Ok(());
+    }
+    let selected: Vec<String> = chosen.iter().map(|&i| categories[i].clone()).collect();
+
+    let output_dir: String = Input::with_theme(&theme)
+        .with_prompt("Output directory")
+        .interact_text()?;
+    let output_dir = PathBuf::from(output_dir.trim());
+    let absolute_output = std::path::absolute(&output_dir)?;
+    if let Some(source) = source_paths
+        .iter()
+        .find(|source| std::path::absolute(source).is_ok_and(|s| absolute_output.starts_with(s)))
+    {
+        ui.print_error(&format!(
+            "Output directory {} is inside the source {}; nothing exported",
+            output_dir.display(),
+            source.display()
+        ))?;
+        println!();
+        return Ok(());
+    }
+    if output_dir.exists()
+        && !Confirm::with_theme(&theme)
+            .with_prompt("Merge with existing directory?")
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    let selection = scan_stats.with_categories(&selected);
+    let control = Control::new();
+    let keys = KeyListener::start(&control);
+    ui.print_info(&format!(
+        "Copying {} files to {}{}",
+        selection.total_files,
+        output_dir.display(),
+        keys.hint()
+    ))?;
+    let pb = ui.create_progress_bar(selection.total_files as u64, "Copying");
+    control.attach(&pb);
+
+    let (export_stats, outputs) =
+        export_scanned(&selection, &output_dir, config, None, &control, {
+            let pb = pb.clone();
+            move |_| pb.inc(1)
+        })
+        .await?;
+    pb.finish_and_clear();
+    drop(keys);
+
+    if export_stats.cancelled {
+        ui.print_warning(&format!(
+            "Export cancelled after copying {} file(s)",
+            export_stats.copied
+        ))?;
+    } else {
+        ui.print_success(&format!(
+            "Exported {} file(s) from {} categories to {}",
+            export_stats.copied,
+            selected.len(),
+            output_dir.display()
+        ))?;
+    }
+    println!();
+    if export_stats.failed > 0 {
+        ui.print_error(&format!(
+            "{} file(s) failed to copy (permission denied or I/O error)",
+            export_stats.failed
+        ))?;
+        println!();
+    }
+    ui.print_info(&format!("Log file: {}", outputs.log.display()))?;
+    println!();
+
     Ok(())
 }
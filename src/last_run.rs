@@ -0,0 +1,207 @@
+//! Last-run markers for `--since-last-run`.
+//!
+//! With `--since-last-run`, `inspect` and `export` only include files modified
+//! since the previous `--since-last-run` of the same command on the same drive,
+//! and record the new run once it has covered every file. The first run of a
+//! drive includes everything.
+//!
+//! Markers are kept in [`STATE_FILE`] in the config directory, keyed by the
+//! command and the filesystem UUID `blkid` reports for a device, so a drive is
+//! recognized even when it shows up under another device name, and an
+//! `inspect` never moves the cutoff of the next `export`. Directories, and
+//! devices whose UUID cannot be read, are keyed by path as for `--cache`.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::cache::source_key;
+use crate::command::{CommandRunner, SystemCommands};
+use crate::config::Config;
+use crate::mount::is_device_path;
+
+/// Name of the state file in the config directory.
+pub const STATE_FILE: &str = "last_run.json";
+
+/// Returns the key a drive's last run is recorded under: `uuid:<UUID>` for a
+/// device with a filesystem UUID, otherwise its path.
+pub fn drive_key(runner: &dyn CommandRunner, drive: &str) -> String {
+    if is_device_path(drive)
+        && let Ok(output) = runner.run("blkid", &["-s", "UUID", "-o", "value", drive])
+        && output.success
+        && let Some(uuid) = output.stdout.lines().map(str::trim).find(|l| !l.is_empty())
+    {
+        return format!("uuid:{}", uuid);
+    }
+    source_key(drive)
+}
+
+/// Starts a `--since-last-run` run of `command` on `drives` if
+/// `scan.since_last_run` is set.
+///
+/// Narrows `config` to files modified since the drives' last run of the same
+/// command, if there was one. Returns `None` when the option is not set.
+///
+/// # Errors
+///
+/// Returns an error if the config directory or the state file cannot be read.
+pub fn begin(
+    command: &str,
+    drives: &[String],
+    config: &mut Config,
+) -> color_eyre::Result<Option<SinceLastRun>> {
+    if !config.scan.since_last_run {
+        return Ok(None);
+    }
+    let path = Config::get_config_dir()?.join(STATE_FILE);
+    let run = SinceLastRun::start(&path, command, drives, &SystemCommands)
+        .map_err(|e| color_eyre::eyre::eyre!("Cannot use --since-last-run: {}", e))?;
+    config.scan.modified_after = run.since;
+    Ok(Some(run))
+}
+
+/// Last-run times by command and drive key, as stored in the state file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LastRuns {
+    /// RFC 3339 start time of the last completed run of each command and
+    /// drive, keyed `<command>:<drive key>`
+    runs: BTreeMap<String, String>,
+}
+
+/// Tracks one `--since-last-run` run from start to completion.
+pub struct SinceLastRun {
+    path: PathBuf,
+    keys: Vec<String>,
+    runs: LastRuns,
+    started: SystemTime,
+    /// Only files modified after this are included; `None` when a drive has
+    /// no recorded run yet, so everything is
+    pub since: Option<SystemTime>,
+}
+
+impl SinceLastRun {
+    /// Reads the state file at `path` for `command` on `drives`.
+    ///
+    /// With several drives, the oldest of their last runs is used. A missing
+    /// state file counts as no runs yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the state file cannot be read if it exists but is not valid.
+    pub fn start(
+        path: &Path,
+        command: &str,
+        drives: &[String],
+        runner: &dyn CommandRunner,
+    ) -> Result<Self, String> {
+        let runs: LastRuns = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("{} is damaged: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => LastRuns::default(),
+            Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+        };
+
+        let keys: Vec<String> = drives
+            .iter()
+            .map(|d| format!("{}:{}", command, drive_key(runner, d)))
+            .collect();
+        let since = keys
+            .iter()
+            .map(|key| {
+                runs.runs
+                    .get(key)
+                    .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+                    .map(SystemTime::from)
+            })
+            .collect::<Option<Vec<_>>>()
+            .and_then(|times| times.into_iter().min());
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            keys,
+            runs,
+            started: SystemTime::now(),
+            since,
+        })
+    }
+
+    /// Describes what the run includes, for display before scanning.
+    pub fn describe(&self) -> String {
+        match self.since {
+            Some(since) => format!(
+                "Only including files modified since the last run ({})",
+                DateTime::<chrono::Local>::from(since).format("%Y-%m-%d %H:%M:%S")
+            ),
+            None => "No earlier run recorded for this drive; including all files".to_string(),
+        }
+    }
+
+    /// Records this run's start time for each drive.
+    ///
+    /// The start rather than the end is recorded, so files changed while the
+    /// scan ran are included next time. The file is written under a temporary
+    /// name and renamed into place.
+    pub fn record(mut self) -> color_eyre::Result<()> {
+        let started =
+            DateTime::<Utc>::from(self.started).to_rfc3339_opts(SecondsFormat::Nanos, true);
+        for key in self.keys {
+            self.runs.runs.insert(key, started.clone());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut partial = self.path.as_os_str().to_owned();
+        partial.push(".partial");
+        std::fs::write(&partial, serde_json::to_string_pretty(&self.runs)?)?;
+        std::fs::rename(&partial, &self.path)?;
+        Ok(())
+    }
+}
+
+// Device paths are only recognized where devices can be mounted
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+mod tests {
+    use super::*;
+    use crate::command::FakeCommands;
+
+    #[test]
+    fn test_since_last_run_by_uuid() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let state = root.path().join("config/last_run.json");
+        let runner = FakeCommands::new()
+            .with("blkid -s UUID -o value /dev/sdb1", "4a1c-9f2e\n")
+            .with("blkid -s UUID -o value /dev/sdc1", "4a1c-9f2e\n")
+            .with_failure("blkid -s UUID -o value /dev/sdd1", "");
+
+        assert_eq!(drive_key(&runner, "/dev/sdb1"), "uuid:4a1c-9f2e");
+        assert_eq!(drive_key(&runner, "/dev/sdd1"), "/dev/sdd1");
+
+        // The first run includes everything
+        let first =
+            SinceLastRun::start(&state, "export", &["/dev/sdb1".to_string()], &runner).unwrap();
+        assert_eq!(first.since, None);
+        let started = first.started;
+        first.record().unwrap();
+
+        // The same filesystem under another device name picks up the recorded run
+        let second =
+            SinceLastRun::start(&state, "export", &["/dev/sdc1".to_string()], &runner).unwrap();
+        assert_eq!(second.since, Some(started));
+
+        // Inspecting the drive does not share the export's marker
+        let inspect =
+            SinceLastRun::start(&state, "inspect", &["/dev/sdb1".to_string()], &runner).unwrap();
+        assert_eq!(inspect.since, None);
+
+        // A drive without a recorded run makes the whole run include everything
+        let drives = ["/dev/sdc1".to_string(), "/dev/sdd1".to_string()];
+        let both = SinceLastRun::start(&state, "export", &drives, &runner).unwrap();
+        assert_eq!(both.since, None);
+
+        std::fs::write(&state, "not json").unwrap();
+        assert!(SinceLastRun::start(&state, "export", &drives, &runner).is_err());
+    }
+}
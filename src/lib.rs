@@ -57,7 +57,7 @@
 //!     let config = Config::load()?;
 //!     let path = Path::new("/mnt/evidence");
 //!
-//!     let stats = scan_directory(path, |file_path| {
+//!     let stats = scan_directory(path, &config, |file_path| {
 //!         println!("Scanning: {}", file_path);
 //!     }).await?;
 //!
@@ -68,10 +68,41 @@
 //! }
 //! ```
 //!
+//! The [`workflow`] module runs whole inspections and exports the same way,
+//! from mounting a device to writing the log, without printing, prompting, or
+//! exiting:
+//!
+//! ```rust,no_run
+//! use tap::config::Config;
+//! use tap::control::Control;
+//! use tap::workflow;
+//! use std::path::Path;
+//!
+//! #[tokio::main]
+//! async fn main() -> color_eyre::Result<()> {
+//!     let config = Config::load()?;
+//!     let stats = workflow::export(
+//!         Path::new("/mnt/evidence"),
+//!         Path::new("./extracted"),
+//!         &config,
+//!         &Control::new(),
+//!         |_| {},
+//!     )
+//!     .await?;
+//!
+//!     println!("Copied {} files", stats.copied);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! To classify files by something other than their names, for example by
+//! sniffing their contents, implement [`categorizer::Categorizer`] and scan with
+//! [`scanner::scan_directory_with_categorizer`].
+//!
 //! ## Configuration
 //!
-//! TAP uses a TOML configuration file located at `~/.config/tap/config.toml`.
-//! On first run, a default configuration is created automatically.
+//! TAP uses a TOML configuration file located at `~/.config/tap/config.toml`,
+//! or under `$XDG_CONFIG_HOME` when that is set. On first run, a default configuration is created automatically.
 //!
 //! ### Configuration Options
 //!
@@ -84,28 +115,53 @@
 //!
 //! ## Module Organization
 //!
+//! - [`cache`]: Cached inspection results
 //! - [`categories`]: File categorization and extension mapping
+//! - [`categorizer`]: Pluggable file categorization
 //! - [`cli`]: Command-line argument parsing
 //! - [`config`]: Configuration management
 //! - [`device_picker`]: Interactive device selection
 //! - [`export`]: File export and copy operations
 //! - [`inspect`]: Drive inspection workflows
+//! - [`last_run`]: Last-run markers for `--since-last-run`
 //! - [`log`]: Log file generation
 //! - [`mount`]: Drive mounting and validation
+//! - [`profile`]: Built-in category presets
+//! - [`rules`]: Path-based category rules
 //! - [`scanner`]: File system scanning and analysis
 //! - [`tui`]: Terminal user interface components
+//! - [`workflow`]: Terminal-free inspect and export workflows for library use
 //! - [`zip`]: Archive creation utilities
 
+pub mod cache;
 pub mod categories;
+pub mod categorizer;
 pub mod cli;
+pub mod command;
 pub mod config;
+pub mod control;
 pub mod device_picker;
+pub mod diff;
+#[cfg(target_os = "macos")]
+pub mod diskutil;
 pub mod export;
+pub mod hash;
+pub mod ignore;
+pub mod image;
 pub mod inspect;
+pub mod last_run;
 pub mod log;
+pub mod manifest;
+pub mod mime;
 pub mod mount;
+pub mod profile;
+pub mod progress;
+pub mod report;
+pub mod rules;
 pub mod scanner;
+pub mod throttle;
 pub mod tui;
+pub mod workflow;
 pub mod zip;
 
 // Re-export commonly used types
@@ -3,80 +3,363 @@
 //! This module creates detailed log files documenting scan and export operations,
 //! including statistics, errors, and file categorization summaries.
 
-use std::path::Path;
-use tokio::io::AsyncWriteExt;
+use console::Style;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
+use crate::config::LogConfig;
+use crate::control::Control;
+use crate::diff::Diff;
 use crate::export::ExportStats;
 use crate::scanner::ScanStats;
-use crate::tui::format_size;
+use crate::tui::{
+    directory_label, extension_label, format_duration, format_rate, format_size,
+    format_special_entries,
+};
+
+/// Most errors [`ErrorEcho`] prints per second; the rest are only counted.
+const ECHO_PER_SECOND: usize = 10;
+
+/// Prints errors to stderr as they happen, for `--verbose`.
+///
+/// Errors are still collected for the log; this only gives early warning that
+/// a run is failing. Output is throttled, so a tree of thousands of unreadable
+/// files shows a handful of errors and a count of the rest each second. Each
+/// line is printed with the progress bar attached to the [`Control`] hidden.
+pub struct ErrorEcho {
+    enabled: bool,
+    control: Control,
+    state: Mutex<EchoState>,
+}
+
+struct EchoState {
+    /// Start of the current one-second window
+    window: Instant,
+    printed: usize,
+    /// Errors not printed in the current window
+    suppressed: usize,
+}
+
+impl ErrorEcho {
+    /// Creates an echo that prints nothing unless `enabled`.
+    pub fn new(enabled: bool, control: &Control) -> Self {
+        Self {
+            enabled,
+            control: control.clone(),
+            state: Mutex::new(EchoState {
+                window: Instant::now(),
+                printed: 0,
+                suppressed: 0,
+            }),
+        }
+    }
+
+    /// Prints `message` to stderr unless this second's allowance is used up.
+    pub fn echo(&self, message: &str) {
+        if !self.enabled {
+            return;
+        }
+        let (print, suppressed) = self.admit(Instant::now());
+        let style = Style::new().red().for_stderr();
+        self.control.suspend(|| {
+            if suppressed > 0 {
+                eprintln!(
+                    "{}",
+                    style.apply_to(format!("... {} more error(s) not shown", suppressed))
+                );
+            }
+            if print {
+                eprintln!("{}", style.apply_to(message));
+            }
+        });
+    }
+
+    /// Decides whether an error at `now` is printed.
+    ///
+    /// Also returns how many errors were held back in the window that just
+    /// ended, once, so their count can be printed.
+    fn admit(&self, now: Instant) -> (bool, usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut suppressed = 0;
+        if now.duration_since(state.window) >= Duration::from_secs(1) {
+            suppressed = state.suppressed;
+            *state = EchoState {
+                window: now,
+                printed: 0,
+                suppressed: 0,
+            };
+        }
+
+        if state.printed < ECHO_PER_SECOND {
+            state.printed += 1;
+            (true, suppressed)
+        } else {
+            state.suppressed += 1;
+            (false, suppressed)
+        }
+    }
+}
+
+impl Drop for ErrorEcho {
+    fn drop(&mut self) {
+        let suppressed = self
+            .state
+            .get_mut()
+            .map(|state| state.suppressed)
+            .unwrap_or(0);
+        if self.enabled && suppressed > 0 {
+            self.control.suspend(|| {
+                eprintln!(
+                    "{}",
+                    Style::new()
+                        .red()
+                        .for_stderr()
+                        .apply_to(format!("... {} more error(s) not shown", suppressed))
+                )
+            });
+        }
+    }
+}
+
+/// Returns the command line TAP was started with, quoting arguments that
+/// contain spaces or quotes so it can be pasted back into a shell.
+fn invocation() -> String {
+    quote_args(std::env::args_os())
+}
+
+/// Joins `args` into one shell line. Arguments that are not valid UTF-8 are
+/// shown with replacement characters rather than aborting the log.
+fn quote_args(args: impl IntoIterator<Item = std::ffi::OsString>) -> String {
+    args.into_iter()
+        .map(|arg| {
+            let arg = arg.to_string_lossy().into_owned();
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"\\$".contains(c)) {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats the current time for log content, marked `Z` in UTC or with the
+/// local offset such as `+02:00`.
+fn log_timestamp(utc: bool) -> String {
+    if utc {
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%SZ").to_string()
+    } else {
+        chrono::Local::now()
+            .format("%Y-%m-%d %H:%M:%S %:z")
+            .to_string()
+    }
+}
+
+/// Formats the title and provenance block that opens every log: the TAP
+/// version, the command line, each source, and the time with its timezone.
+fn log_header(title: &str, sources: &[&Path], options: &LogConfig) -> String {
+    let mut content = String::new();
+    content.push_str(title);
+    content.push('\n');
+    content.push_str(&"═".repeat(70));
+    content.push_str("\n\n");
+
+    content.push_str(&format!("TAP version: {}\n", env!("CARGO_PKG_VERSION")));
+    content.push_str(&format!("Command: {}\n", invocation()));
+    for source in sources {
+        content.push_str(&format!("Source: {}\n", source.display()));
+    }
+    content.push_str(&format!("Timestamp: {}\n", log_timestamp(options.utc)));
+    if options.utc {
+        content.push_str("Timezone: UTC\n\n");
+    } else {
+        content.push_str(&format!(
+            "Timezone: local (UTC{})\n\n",
+            chrono::Local::now().format("%:z")
+        ));
+    }
+    content
+}
 
 /// Writes a log file for inspection results.
 ///
 /// Creates a detailed text log of the inspection, including:
+/// - The TAP version, command line, sources, and time it was written
 /// - Total files and size
-/// - Files organized by category
+/// - Files organized by category, and by source when several were inspected
 /// - Any errors encountered during scanning
 ///
 /// # Arguments
 ///
-/// * `source` - The source path that was inspected
+/// * `sources` - The source paths that were inspected; the log is named after the first
 /// * `scan_stats` - Statistics from the scan operation
+/// * `options` - Whether to gzip the log, see [`write_log_content`]
 ///
 /// # Returns
 ///
 /// The path where the log file was written
 pub async fn write_inspect_log(
-    source: &Path,
+    sources: &[PathBuf],
     scan_stats: &ScanStats,
+    options: &LogConfig,
 ) -> color_eyre::Result<std::path::PathBuf> {
-    // Create log file in current directory with timestamp
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let source_name = source
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
-    let log_filename = format!("tap_inspect_{}_{}.txt", source_name, timestamp);
-    let log_path = std::path::PathBuf::from(&log_filename);
-
-    let mut file = tokio::fs::File::create(&log_path).await?;
-
-    let mut content = String::new();
-    content.push_str("TAP INSPECTION LOG\n");
-    content.push_str(&"═".repeat(70));
-    content.push_str("\n\n");
+    let log_path = log_file_name("inspect", sources, options);
+    let sources: Vec<&Path> = sources.iter().map(PathBuf::as_path).collect();
+    let mut content = log_header("TAP INSPECTION LOG", &sources, options);
 
-    content.push_str(&format!("Source: {}\n", source.display()));
-    content.push_str(&format!(
-        "Timestamp: {}\n\n",
-        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
-    ));
+    if scan_stats.cancelled {
+        content.push_str("Status: cancelled by user, results are partial\n\n");
+    }
+    if scan_stats.limit_reached {
+        content.push_str(&format!(
+            "Status: scan limit reached after {} files, results are partial\n\n",
+            scan_stats.total_files
+        ));
+    }
+    for warning in &scan_stats.warnings {
+        content.push_str(&format!("WARNING: {}\n\n", warning));
+    }
 
     content.push_str(&format!(
         "Total files scanned: {}\n",
         scan_stats.total_files
     ));
     content.push_str(&format!(
-        "Total size: {}\n\n",
+        "Total size: {}\n",
         format_size(scan_stats.total_size)
     ));
+    if scan_stats.sparse_files > 0 {
+        content.push_str(&format!(
+            "Apparent size: {} ({} sparse files)\n",
+            format_size(scan_stats.total_apparent_size),
+            scan_stats.sparse_files
+        ));
+    }
+    if scan_stats.empty_files > 0 {
+        content.push_str(&format!("Empty files: {}\n", scan_stats.empty_files));
+    }
+    content.push_str(&format!(
+        "Total directories: {} ({} empty)\n",
+        scan_stats.total_dirs, scan_stats.empty_dirs
+    ));
+    if scan_stats.excluded_files > 0 || scan_stats.excluded_dirs > 0 {
+        content.push_str(&format!(
+            "Skipped by exclusion rules: {} files ({}), {} directories not walked\n",
+            scan_stats.excluded_files,
+            format_size(scan_stats.excluded_size),
+            scan_stats.excluded_dirs
+        ));
+    }
+    if !scan_stats.special_entries.is_empty() {
+        content.push_str(&format!(
+            "Special entries: {}\n",
+            format_special_entries(scan_stats)
+        ));
+    }
+
+    content.push_str(&format!(
+        "Scan time: {}\n\n",
+        format_duration(scan_stats.duration)
+    ));
 
     content.push_str("FILES BY CATEGORY\n");
     content.push_str(&"─".repeat(70));
     content.push('\n');
+    content.push_str(&category_lines(scan_stats));
 
-    for (category, count, size) in scan_stats.get_summary() {
+    content.push_str("\nFILES BY EXTENSION\n");
+    content.push_str(&"─".repeat(70));
+    content.push('\n');
+
+    for (extension, count, size) in scan_stats.get_extension_summary() {
         content.push_str(&format!(
             "{}: {} files ({})\n",
-            category,
+            extension_label(&extension),
             count,
             format_size(size)
         ));
     }
 
+    if scan_stats.sources.len() > 1 {
+        content.push_str("\nFILES BY SOURCE\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+
+        for (source, count, size) in scan_stats.get_source_summary() {
+            content.push_str(&format!(
+                "{}: {} files ({})\n",
+                scan_stats.source_label(&source),
+                count,
+                format_size(size)
+            ));
+        }
+    }
+
+    content.push_str("\nFILES BY TOP-LEVEL DIRECTORY\n");
+    content.push_str(&"─".repeat(70));
+    content.push('\n');
+
+    for (directory, count, size) in scan_stats.get_directory_summary() {
+        content.push_str(&format!(
+            "{}: {} files ({})\n",
+            directory_label(&directory),
+            count,
+            format_size(size)
+        ));
+    }
+
+    if scan_stats.excluded_by_category > 0 {
+        content.push_str(&format!(
+            "\nFiles in excluded categories: {}\n",
+            scan_stats.excluded_by_category
+        ));
+    }
+
+    if scan_stats.excluded_by_date > 0 {
+        content.push_str(&format!(
+            "\nFiles outside the date filter: {}\n",
+            scan_stats.excluded_by_date
+        ));
+    }
+
+    if !scan_stats.sensitive_files.is_empty() {
+        content.push_str("\nSENSITIVE FILES\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for file in &scan_stats.sensitive_files {
+            content.push_str(&format!("{}\n", file.display()));
+        }
+    }
+
+    if !scan_stats.zero_byte_files.is_empty() {
+        content.push_str("\nEMPTY FILES\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for file in &scan_stats.zero_byte_files {
+            content.push_str(&format!("{}\n", file.display()));
+        }
+    }
+
+    content.push_str(&special_entries_section(scan_stats, "SPECIAL ENTRIES"));
+
+    if !scan_stats.skipped_mounts.is_empty() {
+        content.push_str("\nOTHER FILESYSTEMS SKIPPED\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for mount in &scan_stats.skipped_mounts {
+            content.push_str(&format!("{}\n", mount.display()));
+        }
+    }
+
     if !scan_stats.errors.is_empty() {
         content.push_str("\nSCAN ERRORS\n");
         content.push_str(&"─".repeat(70));
         content.push('\n');
+        content.push_str(&error_counts(scan_stats));
         for error in &scan_stats.errors {
             content.push_str(&format!("{}\n", error));
         }
@@ -86,40 +369,112 @@ pub async fn write_inspect_log(
     content.push_str(&"═".repeat(70));
     content.push_str("\nEnd of log\n");
 
-    file.write_all(content.as_bytes()).await?;
-    Ok(log_path)
+    write_log_content(&log_path, &content, options).await
 }
 
+/// Writes the export log to `tap.log` in `dest`, or `tap.log.gz` when compressed.
+///
+/// The log opens with the same version, command line, source, and timestamp
+/// header as the inspection log.
+///
+/// Returns the path where the log file was written.
 pub async fn write_log_file(
     dest: &Path,
     scan_stats: &ScanStats,
     export_stats: &ExportStats,
-) -> color_eyre::Result<()> {
+    options: &LogConfig,
+) -> color_eyre::Result<PathBuf> {
     let log_path = dest.join("tap.log");
-    let mut file = tokio::fs::File::create(&log_path).await?;
 
-    let mut content = String::new();
-    content.push_str("TAP LOG\n");
-    content.push_str(&"═".repeat(70));
-    content.push_str("\n\n");
+    let mut content = log_header("TAP LOG", &[scan_stats.root.as_path()], options);
+
+    if scan_stats.cancelled || export_stats.cancelled {
+        content.push_str("Status: cancelled by user, results are partial\n\n");
+    }
+    if scan_stats.limit_reached {
+        content.push_str(&format!(
+            "Status: scan limit reached after {} files, results are partial\n\n",
+            scan_stats.total_files
+        ));
+    }
+    for warning in &scan_stats.warnings {
+        content.push_str(&format!("WARNING: {}\n\n", warning));
+    }
 
     content.push_str(&format!(
         "Total files scanned: {}\n",
         scan_stats.total_files
     ));
+    if let Some(sample) = scan_stats.sample {
+        content.push_str(&format!(
+            "Sample: up to {} files per category, seed {} (--sample {} --seed {} draws it again)\n",
+            sample.per_category, sample.seed, sample.per_category, sample.seed
+        ));
+    }
     content.push_str(&format!(
-        "Total size: {}\n\n",
+        "Total size: {}\n",
         format_size(scan_stats.total_size)
     ));
+    if scan_stats.sparse_files > 0 {
+        content.push_str(&format!(
+            "Apparent size: {} ({} sparse files)\n",
+            format_size(scan_stats.total_apparent_size),
+            scan_stats.sparse_files
+        ));
+    }
+    if scan_stats.empty_files > 0 {
+        content.push_str(&format!("Empty files: {}\n", scan_stats.empty_files));
+    }
+    content.push_str(&format!(
+        "Total directories: {} ({} empty)\n",
+        scan_stats.total_dirs, scan_stats.empty_dirs
+    ));
+    if scan_stats.excluded_files > 0 || scan_stats.excluded_dirs > 0 {
+        content.push_str(&format!(
+            "Skipped by exclusion rules: {} files ({}), {} directories not walked\n",
+            scan_stats.excluded_files,
+            format_size(scan_stats.excluded_size),
+            scan_stats.excluded_dirs
+        ));
+    }
+    if !scan_stats.special_entries.is_empty() {
+        content.push_str(&format!(
+            "Special entries: {}\n",
+            format_special_entries(scan_stats)
+        ));
+    }
+
+    content.push_str(&format!(
+        "Scan time: {}\n\n",
+        format_duration(scan_stats.duration)
+    ));
 
     content.push_str("FILES BY CATEGORY\n");
     content.push_str(&"─".repeat(70));
     content.push('\n');
+    content.push_str(&category_lines(scan_stats));
 
-    for (category, count, size) in scan_stats.get_summary() {
+    content.push_str("\nFILES BY EXTENSION\n");
+    content.push_str(&"─".repeat(70));
+    content.push('\n');
+
+    for (extension, count, size) in scan_stats.get_extension_summary() {
         content.push_str(&format!(
             "{}: {} files ({})\n",
-            category,
+            extension_label(&extension),
+            count,
+            format_size(size)
+        ));
+    }
+
+    content.push_str("\nFILES BY TOP-LEVEL DIRECTORY\n");
+    content.push_str(&"─".repeat(70));
+    content.push('\n');
+
+    for (directory, count, size) in scan_stats.get_directory_summary() {
+        content.push_str(&format!(
+            "{}: {} files ({})\n",
+            directory_label(&directory),
             count,
             format_size(size)
         ));
@@ -127,17 +482,162 @@ pub async fn write_log_file(
 
     content.push('\n');
     content.push_str(&format!("Files copied: {}\n", export_stats.copied));
+    content.push_str(&format!(
+        "Files already present: {}\n",
+        export_stats.skipped
+    ));
     content.push_str(&format!("Files failed: {}\n", export_stats.failed));
+    content.push_str(&format!("Files retried: {}\n", export_stats.retried.len()));
+    if !export_stats.collided.is_empty() {
+        content.push_str(&format!(
+            "Files skipped on name collision: {}\n",
+            export_stats.collided.len()
+        ));
+    }
+    if !export_stats.overwritten.is_empty() {
+        content.push_str(&format!(
+            "Copies overwritten on name collision: {}\n",
+            export_stats.overwritten.len()
+        ));
+    }
+    if export_stats.verified > 0 {
+        content.push_str(&format!(
+            "Copies verified against their source (--verify-copies): {}\n",
+            export_stats.verified
+        ));
+    }
+    if !export_stats.xattrs_preserved.is_empty() || !export_stats.xattr_failures.is_empty() {
+        content.push_str(&format!(
+            "Files with extended attributes preserved (--preserve-xattrs): {}\n",
+            export_stats.xattrs_preserved.len()
+        ));
+    }
+    if export_stats.truncated > 0 {
+        content.push_str(&format!(
+            "Files truncated (--truncate-to, see truncated_to in manifest.csv): {}\n",
+            export_stats.truncated
+        ));
+    }
+    if export_stats.added + export_stats.changed + export_stats.unchanged > 0 {
+        content.push_str(&format!(
+            "Since previous manifest: {} added, {} changed, {} unchanged (not copied)\n",
+            export_stats.added, export_stats.changed, export_stats.unchanged
+        ));
+    }
+    content.push_str(&format!(
+        "Copy time: {} ({} copied at {})\n",
+        format_duration(export_stats.duration),
+        format_size(export_stats.bytes_copied),
+        format_rate(export_stats.bytes_copied, export_stats.duration)
+    ));
+    content.push_str(&format!(
+        "Data copied: {} of {} scanned ({} failed)\n",
+        format_size(export_stats.bytes_copied),
+        format_size(scan_stats.total_apparent_size),
+        format_size(export_stats.bytes_failed)
+    ));
+
+    if scan_stats.excluded_by_category > 0 {
+        content.push_str(&format!(
+            "\nFiles in excluded categories: {}\n",
+            scan_stats.excluded_by_category
+        ));
+    }
+
+    if scan_stats.excluded_by_date > 0 {
+        content.push_str(&format!(
+            "\nFiles outside the date filter: {}\n",
+            scan_stats.excluded_by_date
+        ));
+    }
+
+    if !scan_stats.sensitive_files.is_empty() {
+        content.push_str("\nSENSITIVE FILES\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for file in &scan_stats.sensitive_files {
+            content.push_str(&format!("{}\n", file.display()));
+        }
+    }
+
+    if !scan_stats.zero_byte_files.is_empty() {
+        content.push_str("\nEMPTY FILES\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for file in &scan_stats.zero_byte_files {
+            content.push_str(&format!("{}\n", file.display()));
+        }
+    }
+
+    content.push_str(&special_entries_section(
+        scan_stats,
+        "SPECIAL ENTRIES (NOT EXPORTED)",
+    ));
+
+    if !scan_stats.skipped_mounts.is_empty() {
+        content.push_str("\nOTHER FILESYSTEMS SKIPPED\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for mount in &scan_stats.skipped_mounts {
+            content.push_str(&format!("{}\n", mount.display()));
+        }
+    }
 
     if !scan_stats.errors.is_empty() {
         content.push_str("\nSCAN ERRORS\n");
         content.push_str(&"─".repeat(70));
         content.push('\n');
+        content.push_str(&error_counts(scan_stats));
         for error in &scan_stats.errors {
             content.push_str(&format!("{}\n", error));
         }
     }
 
+    if !export_stats.retried.is_empty() {
+        content.push_str("\nCOPIED AFTER RETRY\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for path in &export_stats.retried {
+            content.push_str(&format!("{}\n", path.display()));
+        }
+    }
+
+    if !export_stats.xattrs_preserved.is_empty() {
+        content.push_str("\nEXTENDED ATTRIBUTES PRESERVED\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for path in &export_stats.xattrs_preserved {
+            content.push_str(&format!("{}\n", path.display()));
+        }
+    }
+
+    if !export_stats.xattr_failures.is_empty() {
+        content.push_str("\nEXTENDED ATTRIBUTES NOT PRESERVED\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for failure in &export_stats.xattr_failures {
+            content.push_str(&format!("{}\n", failure));
+        }
+    }
+
+    if !export_stats.collided.is_empty() {
+        content.push_str("\nNOT COPIED (NAME ALREADY TAKEN)\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for path in &export_stats.collided {
+            content.push_str(&format!("{}\n", path.display()));
+        }
+    }
+
+    if !export_stats.overwritten.is_empty() {
+        content.push_str("\nOVERWRITTEN BY A LATER FILE WITH THE SAME NAME\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for path in &export_stats.overwritten {
+            content.push_str(&format!("{}\n", path.display()));
+        }
+    }
+
     if !export_stats.errors.is_empty() {
         content.push_str("\nEXPORT ERRORS\n");
         content.push_str(&"─".repeat(70));
@@ -147,6 +647,343 @@ pub async fn write_log_file(
         }
     }
 
-    file.write_all(content.as_bytes()).await?;
-    Ok(())
+    write_log_content(&log_path, &content, options).await
+}
+
+/// Returns the name of a log in the current directory, `tap_<kind>_<source>_<timestamp>.txt`,
+/// with the timestamp suffixed `Z` in UTC.
+fn log_file_name(kind: &str, sources: &[PathBuf], options: &LogConfig) -> PathBuf {
+    let timestamp = if options.utc {
+        chrono::Utc::now().format("%Y%m%d_%H%M%SZ").to_string()
+    } else {
+        chrono::Local::now().format("%Y%m%d_%H%M%S").to_string()
+    };
+    let source_name = sources
+        .first()
+        .and_then(|s| s.file_name())
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown");
+    PathBuf::from(format!("tap_{}_{}_{}.txt", kind, source_name, timestamp))
+}
+
+/// Writes the log of an `inspect --diff` in the current directory.
+///
+/// Lists every file found only in `sources`, only in `other`, and in both
+/// with a different size or hash, each grouped by category. Named and
+/// compressed like the inspection log.
+///
+/// # Returns
+///
+/// The path where the log file was written
+pub async fn write_diff_log(
+    sources: &[PathBuf],
+    other: &Path,
+    diff: &Diff,
+    options: &LogConfig,
+) -> color_eyre::Result<PathBuf> {
+    let log_path = log_file_name("diff", sources, options);
+    let source_paths: Vec<&Path> = sources.iter().map(PathBuf::as_path).collect();
+    let mut content = log_header("TAP DIFF LOG", &source_paths, options);
+    content.push_str(&format!("Compared with: {}\n\n", other.display()));
+
+    content.push_str(&format!("Only in source: {}\n", diff.only_here.len()));
+    content.push_str(&format!(
+        "Only in {}: {}\n",
+        other.display(),
+        diff.only_there.len()
+    ));
+    content.push_str(&format!("Different: {}\n", diff.differing.len()));
+    content.push_str(&format!("Identical: {}\n", diff.same));
+
+    let by_category = diff.by_category();
+    if !by_category.is_empty() {
+        content.push_str("\nDIFFERENCES BY CATEGORY\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for (category, counts) in &by_category {
+            content.push_str(&format!(
+                "{}: {} only in source, {} only in other, {} different\n",
+                category, counts.only_here, counts.only_there, counts.differing
+            ));
+        }
+    }
+
+    let sections = [
+        ("ONLY IN SOURCE", &diff.only_here),
+        ("ONLY IN OTHER", &diff.only_there),
+    ];
+    for (title, entries) in sections {
+        if entries.is_empty() {
+            continue;
+        }
+        content.push_str(&format!("\n{}\n", title));
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for category in by_category.keys() {
+            for entry in entries.iter().filter(|e| e.category == *category) {
+                content.push_str(&format!(
+                    "[{}] {} ({})\n",
+                    category,
+                    entry.path,
+                    format_size(entry.size)
+                ));
+            }
+        }
+    }
+
+    if !diff.differing.is_empty() {
+        content.push_str("\nDIFFERENT\n");
+        content.push_str(&"─".repeat(70));
+        content.push('\n');
+        for category in by_category.keys() {
+            for (here, there) in diff
+                .differing
+                .iter()
+                .filter(|(e, _)| e.category == *category)
+            {
+                content.push_str(&format!(
+                    "[{}] {} ({} vs {}{})\n",
+                    category,
+                    here.path,
+                    format_size(here.size),
+                    format_size(there.size),
+                    if here.size == there.size {
+                        ", SHA-256 differs"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+        }
+    }
+
+    write_log_content(&log_path, &content, options).await
+}
+
+/// Writes finished log text to `path`.
+///
+/// The text is gzip-compressed into `<path>.gz` instead when `options.compress`
+/// is set or it is larger than `options.compress_above`. Returns the path
+/// actually written.
+pub async fn write_log_content(
+    path: &Path,
+    content: &str,
+    options: &LogConfig,
+) -> color_eyre::Result<PathBuf> {
+    let compress = options.compress
+        || options
+            .compress_above
+            .is_some_and(|limit| content.len() as u64 > limit);
+    if !compress {
+        tokio::fs::write(path, content).await?;
+        return Ok(path.to_path_buf());
+    }
+
+    let mut gz_path = path.as_os_str().to_owned();
+    gz_path.push(".gz");
+    let gz_path = PathBuf::from(gz_path);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content.as_bytes())?;
+    tokio::fs::write(&gz_path, encoder.finish()?).await?;
+    Ok(gz_path)
+}
+
+/// Formats one line per category with its file count, total size, and the
+/// average and largest file size.
+///
+/// All sizes are on disk, like the category totals in the summary chart.
+fn category_lines(scan_stats: &ScanStats) -> String {
+    let mut content = String::new();
+    for (category, count, size) in scan_stats.get_summary() {
+        let max = scan_stats
+            .files_by_category
+            .get(&category)
+            .and_then(|files| files.iter().map(|f| f.allocated_size).max())
+            .unwrap_or(0);
+        content.push_str(&format!(
+            "{}: {} files ({}, avg {}, max {})\n",
+            category,
+            count,
+            format_size(size),
+            format_size(size / count.max(1) as u64),
+            format_size(max)
+        ));
+    }
+    content
+}
+
+/// Formats the list of symlinks, device nodes, FIFOs, and sockets under
+/// `title`, or nothing if the scan found none.
+fn special_entries_section(scan_stats: &ScanStats, title: &str) -> String {
+    let mut content = String::new();
+    if scan_stats.special_entries.is_empty() {
+        return content;
+    }
+    content.push_str(&format!("\n{}\n", title));
+    content.push_str(&"─".repeat(70));
+    content.push('\n');
+    for entry in &scan_stats.special_entries {
+        content.push_str(&format!(
+            "{:<18} {}\n",
+            entry.kind.label(),
+            entry.path.display()
+        ));
+    }
+    content
+}
+
+/// Formats one line per error kind with its count, ahead of the full error list.
+fn error_counts(scan_stats: &ScanStats) -> String {
+    let summary = scan_stats.get_error_summary(0);
+    let mut content = String::new();
+    for group in &summary {
+        content.push_str(&format!(
+            "{:<24} {}\n",
+            format!("{}:", group.kind.label()),
+            group.count
+        ));
+    }
+    let classified: usize = summary.iter().map(|group| group.count).sum();
+    if scan_stats.errors.len() > classified {
+        content.push_str(&format!(
+            "{:<24} {}\n",
+            "Other:",
+            scan_stats.errors.len() - classified
+        ));
+    }
+    content.push('\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FileInfo;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[tokio::test]
+    async fn test_write_log_content_compresses() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let path = dir.path().join("tap.log");
+        let content = "TAP LOG\n".repeat(100);
+
+        let plain = write_log_content(&path, &content, &LogConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(plain, path);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+
+        // Over the threshold the log is written compressed next to the plain name
+        let options = LogConfig {
+            compress: false,
+            compress_above: Some(100),
+            ..LogConfig::default()
+        };
+        let compressed = write_log_content(&path, &content, &options).await.unwrap();
+        assert_eq!(compressed, dir.path().join("tap.log.gz"));
+
+        let mut decoded = String::new();
+        GzDecoder::new(std::fs::File::open(&compressed).unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, content);
+
+        let small = LogConfig {
+            compress: false,
+            compress_above: Some(1 << 20),
+            ..LogConfig::default()
+        };
+        let other = dir.path().join("small.log");
+        assert_eq!(
+            write_log_content(&other, &content, &small).await.unwrap(),
+            other
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_log_file_reports_bytes() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let scan_stats = ScanStats {
+            root: PathBuf::from("/mnt/usb"),
+            total_apparent_size: 3 * 1024,
+            ..ScanStats::new()
+        };
+        let export_stats = ExportStats {
+            copied: 1,
+            failed: 1,
+            bytes_copied: 1024,
+            bytes_failed: 2048,
+            ..ExportStats::new()
+        };
+
+        let options = LogConfig {
+            utc: true,
+            ..LogConfig::default()
+        };
+
+        let path = write_log_file(dir.path(), &scan_stats, &export_stats, &options)
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("Data copied: 1.00 KiB of 3.00 KiB scanned (2.00 KiB failed)"));
+        assert!(content.contains(&format!("TAP version: {}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(content.contains("\nCommand: "));
+        assert!(content.contains("Source: /mnt/usb\n"));
+        assert!(content.contains("Z\nTimezone: UTC\n"));
+    }
+
+    #[test]
+    fn test_category_lines_average_and_max() {
+        let mut scan_stats = ScanStats::new();
+        for (name, allocated_size) in [("a.pdf", 1024), ("b.pdf", 3 * 1024)] {
+            scan_stats.add_file(FileInfo {
+                path: PathBuf::from(name),
+                extension: ".pdf".to_string(),
+                size: allocated_size,
+                allocated_size,
+                modified: None,
+                category: "documents".to_string(),
+                mime: None,
+            });
+        }
+
+        assert_eq!(
+            category_lines(&scan_stats),
+            "documents: 2 files (4.00 KiB, avg 2.00 KiB, max 3.00 KiB)\n"
+        );
+    }
+
+    #[test]
+    fn test_quote_args() {
+        let args = ["tap", "export", "/mnt/my drive", "it's"].map(std::ffi::OsString::from);
+        assert_eq!(quote_args(args), "tap export '/mnt/my drive' 'it'\\''s'");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            let args = [
+                std::ffi::OsString::from("tap"),
+                std::ffi::OsString::from_vec(b"/mnt/caf\xe9".to_vec()),
+            ];
+            assert_eq!(quote_args(args), "tap /mnt/caf\u{fffd}");
+        }
+    }
+
+    #[test]
+    fn test_error_echo_throttles() {
+        let echo = ErrorEcho::new(false, &Control::new());
+        let start = Instant::now();
+        for _ in 0..ECHO_PER_SECOND {
+            assert_eq!(echo.admit(start), (true, 0));
+        }
+        assert_eq!(echo.admit(start), (false, 0));
+        assert_eq!(echo.admit(start + Duration::from_millis(500)), (false, 0));
+
+        // The next window reports what was held back, then prints again
+        let later = start + Duration::from_secs(1);
+        assert_eq!(echo.admit(later), (true, 2));
+        assert_eq!(echo.admit(later), (true, 0));
+    }
 }
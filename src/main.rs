@@ -1,58 +1,143 @@
 // src/main.rs
-mod categories;
-mod cli;
-mod config;
-mod device_picker;
-mod export;
-mod inspect;
-mod log;
-mod mount;
-mod scanner;
-mod tui;
-mod zip;
-
 use clap::Parser;
 
-use cli::{Args, Commands};
-use config::Config;
-use device_picker::pick_device;
-use export::handle_export;
-use inspect::handle_inspect;
-use tui::{Mode, UI};
+use tap::categorizer::list_categories;
+use tap::cli::{Args, Commands};
+use tap::config::Config;
+use tap::device_picker::{drive_label, list_devices, pick_device};
+use tap::export::{default_output_dir, handle_export};
+use tap::inspect::{InspectOutputs, handle_inspect};
+use tap::mount::{ensure_mount_privileges, is_device_path, mount_check};
+use tap::tui::{Mode, UI, set_size_base};
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
     // Load configuration
-    let config = Config::load()?;
+    let mut config = Config::load()?;
 
     let args = Args::parse();
 
     match args.command {
-        Commands::Inspect { drive, log } => {
+        Commands::Inspect {
+            drives,
+            log,
+            compress_log,
+            utc,
+            report_html,
+            out_manifest_only,
+            hash,
+            diff,
+            cache,
+            offline,
+            descend_images,
+            scan,
+            mount,
+            display,
+        } => {
+            scan.apply(&mut config);
+            if descend_images {
+                config.scan.descend_images = true;
+            }
+            mount.apply(&mut config);
+            display.apply(&mut config);
+            set_size_base(config.ui.size_base);
+            if compress_log {
+                config.log.compress = true;
+            }
+            if utc {
+                config.log.utc = true;
+            }
+
             // Check terminal size before device picker
             UI::check_terminal_size(&Mode::Inspect, &config.ui.color.theme)?;
 
-            let drive_path = match drive {
-                Some(d) => d,
-                None => pick_device(&config.ui.color.theme)?,
+            // Offline runs read the cache only; with no drives given, any cached sources are shown
+            let drives = if offline {
+                drives
+            } else {
+                // Devices need root to mount, so fail before the picker rather than mid-run
+                if drives.is_empty()
+                    || config.scan.descend_images
+                    || drives.iter().any(|d| is_device_path(d))
+                {
+                    ensure_mount_privileges(&config.ui.color.theme)?;
+                }
+
+                if drives.is_empty() {
+                    vec![pick_device(&config.ui.color.theme)?]
+                } else {
+                    drives
+                }
             };
-            handle_inspect(&drive_path, log, &config).await?;
+            let outputs = InspectOutputs {
+                log,
+                report_html: report_html.as_deref(),
+                manifest: out_manifest_only.as_deref(),
+                hash,
+                diff: diff.as_deref(),
+            };
+            handle_inspect(&drives, outputs, cache.as_deref(), offline, &config).await?;
         }
         Commands::Export {
             drive,
             output_dir,
             zip,
+            report_html,
+            scan,
+            mount,
+            display,
+            export,
         } => {
+            scan.apply(&mut config);
+            mount.apply(&mut config);
+            display.apply(&mut config);
+            set_size_base(config.ui.size_base);
+            export.apply(&mut config);
+
             // Check terminal size before device picker
             UI::check_terminal_size(&Mode::Export, &config.ui.color.theme)?;
 
+            // Devices need root to mount, so fail before the picker rather than mid-run
+            if drive.as_deref().is_none_or(is_device_path) {
+                ensure_mount_privileges(&config.ui.color.theme)?;
+            }
+
             let drive_path = match drive {
                 Some(d) => d,
                 None => pick_device(&config.ui.color.theme)?,
             };
-            handle_export(&drive_path, &output_dir, zip, &config).await?;
+            let output_dir = match output_dir {
+                Some(dir) => dir,
+                None => {
+                    let dir =
+                        default_output_dir(drive_label(&drive_path).as_deref(), config.log.utc);
+                    let white_bold = console::Style::new().white().bold();
+                    println!(
+                        "{}",
+                        white_bold.apply_to(format!("Output directory: {}", dir.display()))
+                    );
+                    dir
+                }
+            };
+            handle_export(
+                &drive_path,
+                &output_dir,
+                zip,
+                report_html.as_deref(),
+                &config,
+            )
+            .await?;
+        }
+        Commands::ListDevices { json } => {
+            list_devices(json, &config.ui.color.theme)?;
+        }
+        Commands::Categories { json } => {
+            list_categories(&config, json)?;
+        }
+        Commands::MountCheck { device } => {
+            mount_check(&device, &config.ui.color.theme)?;
         }
     }
 
@@ -0,0 +1,669 @@
+//! Export manifests.
+//!
+//! This module writes sidecar files into the export directory that record
+//! where each exported file came from, so provenance survives the flat,
+//! categorized layout. It also writes the file listings produced by
+//! `--inventory-only` in place of the copied files, the manifest written by
+//! `inspect --out-manifest-only` without any export, and reads back the
+//! `manifest.csv` of a previous export for `--since-manifest`.
+
+use color_eyre::eyre::eyre;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::Config;
+use crate::control::Control;
+use crate::hash::sha256_file;
+use crate::scanner::{FileInfo, Sample, ScanStats, relative_base};
+
+/// Name of the path map written by `--path-map`.
+pub const PATH_MAP_FILE: &str = "paths.tsv";
+
+/// Name of the CSV inventory written by `--inventory-only`.
+pub const INVENTORY_FILE: &str = "inventory.csv";
+
+/// Name of the manifest written by every export.
+pub const MANIFEST_FILE: &str = "manifest.csv";
+
+const MANIFEST_HEADER: &str = "path,size,modified,category,sha256,exported_path,truncated_to";
+
+/// Escapes backslashes, tabs, and line breaks so each entry stays on one TSV line.
+fn escape_tsv(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quotes a CSV field when it contains a comma, quote, or line break.
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Renders the path map as TSV with a header row, sorted by exported path.
+///
+/// Exported paths use `/` separators regardless of platform; source paths are
+/// made absolute.
+pub fn render_path_map(entries: &[(PathBuf, PathBuf)]) -> String {
+    let mut rows: Vec<(String, String)> = entries
+        .iter()
+        .map(|(exported, source)| {
+            let exported = slash_path(exported);
+            let source = std::path::absolute(source).unwrap_or_else(|_| source.clone());
+            (exported, source.display().to_string())
+        })
+        .collect();
+    rows.sort();
+
+    let mut content = String::from("exported_path\tsource_path\n");
+    for (exported, source) in rows {
+        content.push_str(&escape_tsv(&exported));
+        content.push('\t');
+        content.push_str(&escape_tsv(&source));
+        content.push('\n');
+    }
+    content
+}
+
+/// Writes `paths.tsv` into `dest`, mapping each exported file to its source path.
+///
+/// # Arguments
+///
+/// * `dest` - The export output directory
+/// * `entries` - `(path relative to dest, source path)` pairs
+///
+/// # Returns
+///
+/// The path where the map was written
+pub async fn write_path_map(
+    dest: &Path,
+    entries: &[(PathBuf, PathBuf)],
+) -> color_eyre::Result<PathBuf> {
+    let map_path = dest.join(PATH_MAP_FILE);
+    let mut file = tokio::fs::File::create(&map_path).await?;
+    file.write_all(render_path_map(entries).as_bytes()).await?;
+    Ok(map_path)
+}
+
+/// Returns the absolute source path of a scanned file for listings.
+fn source_path(file: &FileInfo) -> String {
+    std::path::absolute(&file.path)
+        .unwrap_or_else(|_| file.path.clone())
+        .display()
+        .to_string()
+}
+
+/// Renders every scanned file as CSV with a header row, sorted by category then path.
+///
+/// Sizes are in bytes; `allocated_size` is less than `size` for sparse files.
+pub fn render_inventory(scan_stats: &ScanStats) -> String {
+    let mut rows: Vec<(&str, String, &FileInfo)> = scan_stats
+        .files_by_category
+        .iter()
+        .flat_map(|(category, files)| {
+            files
+                .iter()
+                .map(move |file| (category.as_str(), source_path(file), file))
+        })
+        .collect();
+    rows.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+    let mut content = String::from("category,path,extension,size,allocated_size\n");
+    for (category, path, file) in rows {
+        content.push_str(&format!(
+            "{},{},{},{},{}\n",
+            escape_csv(category),
+            escape_csv(&path),
+            escape_csv(&file.extension),
+            file.size,
+            file.allocated_size
+        ));
+    }
+    content
+}
+
+/// Renders one category's files as a sorted list of source paths, one per line.
+pub fn render_file_list(files: &[FileInfo]) -> String {
+    let mut paths: Vec<String> = files.iter().map(source_path).collect();
+    paths.sort();
+
+    let mut content = String::new();
+    for path in paths {
+        content.push_str(&path);
+        content.push('\n');
+    }
+    content
+}
+
+/// Writes the inventory of a scan into `dest` without copying any files.
+///
+/// Each category gets a `<category dir>.txt` listing its source paths, and
+/// `inventory.csv` records every file with its category and sizes.
+///
+/// # Arguments
+///
+/// * `dest` - The export output directory
+/// * `scan_stats` - Statistics from the scan operation
+/// * `config` - Configuration providing the category directory names
+///
+/// # Returns
+///
+/// The path of the CSV inventory
+pub async fn write_inventory(
+    dest: &Path,
+    scan_stats: &ScanStats,
+    config: &Config,
+) -> color_eyre::Result<PathBuf> {
+    tokio::fs::create_dir_all(dest).await?;
+
+    for (category, files) in &scan_stats.files_by_category {
+        let list_path = dest.join(format!("{}.txt", config.category_dir(category)));
+        tokio::fs::write(&list_path, render_file_list(files)).await?;
+    }
+
+    let inventory_path = dest.join(INVENTORY_FILE);
+    tokio::fs::write(&inventory_path, render_inventory(scan_stats)).await?;
+    Ok(inventory_path)
+}
+
+/// Joins the components of a relative path with `/` so manifests compare across platforms.
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Formats a modification time as RFC 3339 in UTC with full precision.
+fn format_modified(modified: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(modified)
+        .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+}
+
+/// Splits CSV content into records, honouring quoted fields that contain
+/// commas, doubled quotes, or line breaks.
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// One file recorded in `manifest.csv`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Path relative to the scanned source root, `/`-separated
+    pub path: String,
+    pub size: u64,
+    /// RFC 3339 modification time, if known
+    pub modified: Option<String>,
+    pub category: String,
+    /// SHA-256 of the source, recorded when `--checksum-source` is set
+    pub sha256: Option<String>,
+    /// Where the file was copied in this export, relative to the output directory
+    pub exported_path: Option<String>,
+    /// Bytes kept when only the head of the file was exported (`--truncate-to`)
+    pub truncated_to: Option<u64>,
+}
+
+impl ManifestEntry {
+    /// Builds the entry for a scanned file, with its path relative to `root`.
+    pub fn new(file: &FileInfo, root: &Path) -> Self {
+        let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+        Self {
+            path: slash_path(relative),
+            size: file.size,
+            modified: file.modified.map(format_modified),
+            category: file.category.clone(),
+            sha256: None,
+            exported_path: None,
+            truncated_to: None,
+        }
+    }
+
+    /// Records where the file was copied, relative to the output directory.
+    pub fn set_exported_path(&mut self, relative: &Path) {
+        self.exported_path = Some(slash_path(relative));
+    }
+}
+
+/// How a file compares with the same path in a previous manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// Not present in the previous manifest
+    Added,
+    /// Present, but the size differs or neither the modification time nor the hash match
+    Changed,
+    /// Same size and either the same modification time or the same hash
+    Unchanged,
+}
+
+/// A previous export's manifest, keyed by source-relative path.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    entries: HashMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    /// Reads a `manifest.csv` written by an earlier export.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or lacks the `path`, `size`,
+    /// and `modified` columns.
+    pub async fn load(path: &Path) -> color_eyre::Result<Self> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| eyre!("Failed to read manifest {}: {}", path.display(), e))?;
+        Self::parse(&content).ok_or_else(|| eyre!("{} is not a TAP manifest", path.display()))
+    }
+
+    /// Parses manifest CSV, locating columns by their header names. Comment
+    /// lines before the header, such as the sample note, are skipped.
+    fn parse(mut content: &str) -> Option<Self> {
+        while content.starts_with('#') {
+            content = content.split_once('\n').map_or("", |(_, rest)| rest);
+        }
+        let mut records = parse_csv(content).into_iter();
+        let header = records.next()?;
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let (path_col, size_col, modified_col) =
+            (column("path")?, column("size")?, column("modified")?);
+        let (category_col, sha256_col) = (column("category"), column("sha256"));
+
+        let mut entries = HashMap::new();
+        for record in records {
+            let field = |col: usize| record.get(col).filter(|f| !f.is_empty()).cloned();
+            let path = field(path_col)?;
+            let entry = ManifestEntry {
+                path: path.clone(),
+                size: field(size_col)?.parse().ok()?,
+                modified: field(modified_col),
+                category: category_col.and_then(field).unwrap_or_default(),
+                sha256: sha256_col.and_then(field),
+                exported_path: None,
+                truncated_to: None,
+            };
+            entries.insert(path, entry);
+        }
+
+        Some(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the manifest's entries, in no particular order.
+    pub fn into_entries(self) -> Vec<ManifestEntry> {
+        self.entries.into_values().collect()
+    }
+
+    /// Compares `entry` with the previous entry for the same path.
+    ///
+    /// When both entries have a SHA-256 the hashes decide, since re-imaged
+    /// drives often keep their timestamps; otherwise the modification times do.
+    pub fn classify(&self, entry: &ManifestEntry) -> Change {
+        let Some(previous) = self.entries.get(&entry.path) else {
+            return Change::Added;
+        };
+        if previous.size != entry.size {
+            return Change::Changed;
+        }
+
+        let unchanged = match (&previous.sha256, &entry.sha256) {
+            (Some(before), Some(now)) => before == now,
+            _ => previous.modified.is_some() && previous.modified == entry.modified,
+        };
+        if unchanged {
+            Change::Unchanged
+        } else {
+            Change::Changed
+        }
+    }
+}
+
+/// Renders manifest entries as CSV with a header row, sorted by path.
+///
+/// A sampled export starts with a `#` comment line giving the sample size and
+/// seed, so the same files can be drawn again.
+pub fn render_manifest(entries: &[ManifestEntry], sample: Option<Sample>) -> String {
+    let mut entries: Vec<&ManifestEntry> = entries.iter().collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut content = String::new();
+    if let Some(sample) = sample {
+        content.push_str(&format!(
+            "# sample: up to {} files per category, seed {} (--sample {} --seed {})\n",
+            sample.per_category, sample.seed, sample.per_category, sample.seed
+        ));
+    }
+    content.push_str(&format!("{}\n", MANIFEST_HEADER));
+    for entry in entries {
+        content.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            escape_csv(&entry.path),
+            entry.size,
+            entry.modified.as_deref().unwrap_or(""),
+            escape_csv(&entry.category),
+            entry.sha256.as_deref().unwrap_or(""),
+            escape_csv(entry.exported_path.as_deref().unwrap_or("")),
+            entry
+                .truncated_to
+                .map(|n| n.to_string())
+                .unwrap_or_default()
+        ));
+    }
+    content
+}
+
+/// Writes `manifest.csv` into `dest`, recording every file considered by the export.
+///
+/// Files skipped as unchanged by `--since-manifest` are listed without an
+/// exported path, so the manifest always describes the whole source and can
+/// seed the next incremental export.
+///
+/// # Returns
+///
+/// The path where the manifest was written
+pub async fn write_manifest(
+    dest: &Path,
+    entries: &[ManifestEntry],
+    sample: Option<Sample>,
+) -> color_eyre::Result<PathBuf> {
+    let manifest_path = dest.join(MANIFEST_FILE);
+    tokio::fs::write(&manifest_path, render_manifest(entries, sample)).await?;
+    Ok(manifest_path)
+}
+
+/// Builds a manifest entry for every scanned file, without exporting anything.
+///
+/// Paths are relative to the source each file was scanned from and no file
+/// has an exported path, so the manifest can seed `export --since-manifest`.
+/// With `hash` set, each file is read in place to record its SHA-256; files
+/// that can no longer be read are left without one. `progress` is called once
+/// per file hashed, and hashing stops early if cancelled through `control`.
+pub async fn scan_manifest<F>(
+    scan_stats: &ScanStats,
+    hash: bool,
+    control: &Control,
+    progress: F,
+) -> color_eyre::Result<Vec<ManifestEntry>>
+where
+    F: Fn(),
+{
+    let mut entries = Vec::with_capacity(scan_stats.total_files);
+    for file in scan_stats.files_by_category.values().flatten() {
+        let root = relative_base(scan_stats.source_of(&file.path));
+        let mut entry = ManifestEntry::new(file, root);
+        if hash && control.wait().await {
+            let path = file.path.clone();
+            entry.sha256 = tokio::task::spawn_blocking(move || sha256_file(&path).ok()).await?;
+            progress();
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Writes the entries of [`scan_manifest`] as CSV to `path`.
+pub async fn write_manifest_to(path: &Path, entries: &[ManifestEntry]) -> color_eyre::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, render_manifest(entries, None)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_tsv() {
+        assert_eq!(escape_tsv("plain.txt"), "plain.txt");
+        assert_eq!(escape_tsv("a\tb\nc\\d"), "a\\tb\\nc\\\\d");
+    }
+
+    // Windows would prefix the source paths with a drive letter
+    #[cfg(unix)]
+    #[test]
+    fn test_render_path_map() {
+        let entries = vec![
+            (
+                PathBuf::from("images").join("photo_1.jpg"),
+                PathBuf::from("/mnt/usb/DCIM/photo.jpg"),
+            ),
+            (
+                PathBuf::from("documents").join("a\tb.txt"),
+                PathBuf::from("/mnt/usb/a\tb.txt"),
+            ),
+        ];
+
+        assert_eq!(
+            render_path_map(&entries),
+            "exported_path\tsource_path\n\
+             documents/a\\tb.txt\t/mnt/usb/a\\tb.txt\n\
+             images/photo_1.jpg\t/mnt/usb/DCIM/photo.jpg\n"
+        );
+    }
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!(escape_csv("plain.txt"), "plain.txt");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_inventory() {
+        let mut stats = ScanStats::new();
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/mnt/usb/b, final.pdf"),
+            extension: ".pdf".to_string(),
+            size: 2048,
+            allocated_size: 4096,
+            modified: None,
+            category: "documents".to_string(),
+            mime: None,
+        });
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/mnt/usb/a.pdf"),
+            extension: ".pdf".to_string(),
+            size: 10,
+            allocated_size: 4096,
+            modified: None,
+            category: "documents".to_string(),
+            mime: None,
+        });
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/mnt/usb/DCIM/photo.jpg"),
+            extension: ".jpg".to_string(),
+            size: 8192,
+            allocated_size: 8192,
+            modified: None,
+            category: "images".to_string(),
+            mime: None,
+        });
+
+        assert_eq!(
+            render_inventory(&stats),
+            "category,path,extension,size,allocated_size\n\
+             documents,/mnt/usb/a.pdf,.pdf,10,4096\n\
+             documents,\"/mnt/usb/b, final.pdf\",.pdf,2048,4096\n\
+             images,/mnt/usb/DCIM/photo.jpg,.jpg,8192,8192\n"
+        );
+        assert_eq!(
+            render_file_list(&stats.files_by_category["documents"]),
+            "/mnt/usb/a.pdf\n/mnt/usb/b, final.pdf\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        assert_eq!(
+            parse_csv("a,\"b,c\",\"say \"\"hi\"\"\"\r\n\"two\nlines\",\n"),
+            vec![
+                vec!["a".to_string(), "b,c".to_string(), "say \"hi\"".to_string()],
+                vec!["two\nlines".to_string(), String::new()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manifest_round_trip_and_classify() {
+        let entry =
+            |path: &str, size, modified: Option<&str>, sha256: Option<&str>| ManifestEntry {
+                path: path.to_string(),
+                size,
+                modified: modified.map(str::to_string),
+                category: "documents".to_string(),
+                sha256: sha256.map(str::to_string),
+                exported_path: Some(format!("documents/{}", path)),
+                truncated_to: None,
+            };
+        let previous = vec![
+            entry("a, b.pdf", 10, Some("2024-01-01T00:00:00.000000000Z"), None),
+            entry(
+                "c.pdf",
+                20,
+                Some("2024-01-01T00:00:00.000000000Z"),
+                Some("ab12"),
+            ),
+        ];
+
+        let manifest = Manifest::parse(&render_manifest(&previous, None)).unwrap();
+        assert_eq!(manifest.len(), 2);
+
+        // The seed of a sampled export is noted without getting in the way
+        let sample = Sample {
+            per_category: 5,
+            seed: 42,
+        };
+        let sampled = render_manifest(&previous, Some(sample));
+        assert!(sampled.starts_with("# sample: up to 5 files per category, seed 42"));
+        assert_eq!(Manifest::parse(&sampled).unwrap().len(), 2);
+
+        let same = entry("a, b.pdf", 10, Some("2024-01-01T00:00:00.000000000Z"), None);
+        assert_eq!(manifest.classify(&same), Change::Unchanged);
+
+        let touched = entry("a, b.pdf", 10, Some("2024-06-01T00:00:00.000000000Z"), None);
+        assert_eq!(manifest.classify(&touched), Change::Changed);
+
+        let grown = entry(
+            "c.pdf",
+            21,
+            Some("2024-01-01T00:00:00.000000000Z"),
+            Some("ab12"),
+        );
+        assert_eq!(manifest.classify(&grown), Change::Changed);
+
+        // A matching hash outweighs a new modification time
+        let rehashed = entry(
+            "c.pdf",
+            20,
+            Some("2024-06-01T00:00:00.000000000Z"),
+            Some("ab12"),
+        );
+        assert_eq!(manifest.classify(&rehashed), Change::Unchanged);
+
+        // A different hash outweighs a restored modification time
+        let reimaged = entry(
+            "c.pdf",
+            20,
+            Some("2024-01-01T00:00:00.000000000Z"),
+            Some("cd34"),
+        );
+        assert_eq!(manifest.classify(&reimaged), Change::Changed);
+
+        // Without a hash on both sides, the modification time decides
+        let unhashed = entry("c.pdf", 20, Some("2024-01-01T00:00:00.000000000Z"), None);
+        assert_eq!(manifest.classify(&unhashed), Change::Unchanged);
+
+        let new = entry("d.pdf", 1, None, None);
+        assert_eq!(manifest.classify(&new), Change::Added);
+    }
+
+    #[test]
+    fn test_manifest_rejects_other_csv() {
+        assert!(Manifest::parse("category,path,extension\n").is_none());
+        assert!(Manifest::parse("path,size,modified\nx,notanumber,\n").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scan_manifest_hashes_in_place() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("docs")).unwrap();
+        std::fs::write(root.path().join("docs/abc.txt"), b"abc").unwrap();
+        std::fs::write(root.path().join("photo.jpg"), b"jpg").unwrap();
+        let scan_stats = crate::scanner::scan_directory(root.path(), &Config::default(), |_| {})
+            .await
+            .unwrap();
+
+        let hashed = std::sync::atomic::AtomicUsize::new(0);
+        let entries = scan_manifest(&scan_stats, true, &Control::new(), || {
+            hashed.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })
+        .await
+        .unwrap();
+        assert_eq!(hashed.into_inner(), 2);
+
+        let path = root.path().join("out/manifest.csv");
+        write_manifest_to(&path, &entries).await.unwrap();
+        let manifest = Manifest::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let abc = &manifest.entries["docs/abc.txt"];
+        assert_eq!(abc.category, "documents");
+        assert_eq!(
+            abc.sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+
+        // Without hashing, nothing is read
+        let entries = scan_manifest(&scan_stats, false, &Control::new(), || {})
+            .await
+            .unwrap();
+        assert!(entries.iter().all(|entry| entry.sha256.is_none()));
+        assert!(entries.iter().all(|entry| entry.exported_path.is_none()));
+    }
+}
@@ -0,0 +1,118 @@
+//! MIME type detection for `scan.detect_mime`.
+//!
+//! With detection on, each file's MIME type is read from its first bytes
+//! where the content is recognizable, and otherwise guessed from its
+//! extension. The type is kept on [`FileInfo::mime`], and `[mime_categories]`
+//! maps types to categories by prefix, so `"image/"` catches every image type
+//! and `"application/pdf"` just one.
+//!
+//! A type detected from content decides the category ahead of the extension,
+//! which catches files with a wrong or missing extension. A type guessed from
+//! the extension is only used for extensions no category lists.
+//!
+//! [`FileInfo::mime`]: crate::scanner::FileInfo::mime
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Where a detected MIME type came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mime {
+    /// Recognized from the file's first bytes
+    Content(String),
+    /// Guessed from the file's extension
+    Extension(String),
+}
+
+impl Mime {
+    /// Returns the MIME type, e.g. `image/jpeg`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Mime::Content(mime) | Mime::Extension(mime) => mime,
+        }
+    }
+
+    /// Returns the MIME type, dropping where it came from.
+    pub fn into_string(self) -> String {
+        match self {
+            Mime::Content(mime) | Mime::Extension(mime) => mime,
+        }
+    }
+}
+
+/// Detects the MIME type of the file at `path`.
+///
+/// Reads at most the first few kilobytes. Returns `None` if neither the
+/// content nor the extension is recognized; unreadable files fall back to
+/// their extension.
+pub fn detect_mime(path: &Path) -> Option<Mime> {
+    if let Ok(Some(kind)) = infer::get_from_path(path) {
+        return Some(Mime::Content(kind.mime_type().to_string()));
+    }
+    mime_guess::from_path(path)
+        .first_raw()
+        .map(|mime| Mime::Extension(mime.to_string()))
+}
+
+/// `[mime_categories]` from the config, matched by longest prefix.
+#[derive(Debug, Clone, Default)]
+pub struct MimeMap {
+    /// `(lowercase prefix, category)`, longest prefix first
+    prefixes: Vec<(String, String)>,
+}
+
+impl MimeMap {
+    pub fn new(mime_categories: &HashMap<String, String>) -> Self {
+        let mut prefixes: Vec<(String, String)> = mime_categories
+            .iter()
+            .map(|(prefix, category)| (prefix.to_lowercase(), category.clone()))
+            .collect();
+        prefixes.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Self { prefixes }
+    }
+
+    /// Returns the category of the longest prefix `mime` starts with, if any.
+    pub fn category(&self, mime: &str) -> Option<&str> {
+        let mime = mime.to_lowercase();
+        self.prefixes
+            .iter()
+            .find(|(prefix, _)| mime.starts_with(prefix.as_str()))
+            .map(|(_, category)| category.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_mime_and_map() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        // A PNG saved with the wrong extension is recognized by its content
+        let png = dir.path().join("scan.txt");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        assert_eq!(
+            detect_mime(&png),
+            Some(Mime::Content("image/png".to_string()))
+        );
+
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, b"plain words").unwrap();
+        assert_eq!(
+            detect_mime(&notes),
+            Some(Mime::Extension("text/plain".to_string()))
+        );
+        std::fs::write(dir.path().join("blob"), b"plain words").unwrap();
+        assert_eq!(detect_mime(&dir.path().join("blob")), None);
+
+        let map = MimeMap::new(
+            &[("image/", "images"), ("image/svg+xml", "diagrams")]
+                .iter()
+                .map(|(prefix, category)| (prefix.to_string(), category.to_string()))
+                .collect(),
+        );
+        assert_eq!(map.category("image/PNG"), Some("images"));
+        assert_eq!(map.category("image/svg+xml"), Some("diagrams"));
+        assert_eq!(map.category("text/plain"), None);
+    }
+}
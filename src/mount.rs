@@ -2,65 +2,207 @@
 //!
 //! This module handles mounting block devices in read-only mode, validating
 //! existing mounts, and safely unmounting drives when operations complete.
+//!
+//! On Linux, mounting relies on `blkid`, `mdadm`, `findmnt`, and `sudo`. On
+//! macOS it is delegated to [`crate::diskutil`]. On other platforms only local
+//! paths are supported.
 
+#[cfg(target_os = "linux")]
+use crate::command::{CommandRunner, SystemCommands};
+use crate::config::MountConfig;
 use crate::tui::UI;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
 use dialoguer::Confirm;
+#[cfg(target_os = "linux")]
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::sync::Mutex;
+
+/// Mount points created by this process, and whether their directory is
+/// removed again after unmounting.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+static TAP_MOUNTS: Mutex<Vec<(PathBuf, bool)>> = Mutex::new(Vec::new());
+
+/// Records a mount point this process created, so only those are unmounted afterwards.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn register_mount(mount_point: PathBuf, remove_dir: bool) {
+    if let Ok(mut mounts) = TAP_MOUNTS.lock() {
+        mounts.push((mount_point, remove_dir));
+    }
+}
 
-/// Detect the filesystem type of a device
-fn get_filesystem_type(device: &str) -> color_eyre::Result<Option<String>> {
-    let output = Command::new("blkid")
-        .args(["-s", "TYPE", "-o", "value", device])
-        .output()?;
-
-    if output.status.success() {
-        let fs_type = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if !fs_type.is_empty() {
-            return Ok(Some(fs_type));
-        }
+/// Forgets a mount point before unmounting it, returning whether its directory
+/// is removed afterwards, or `None` if this process did not create it.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn take_mount(mount_point: &Path) -> Option<bool> {
+    let mut mounts = TAP_MOUNTS.lock().ok()?;
+    let index = mounts.iter().position(|(m, _)| m == mount_point)?;
+    Some(mounts.remove(index).1)
+}
+
+/// Returns true if `drive` names a block device that must be mounted before scanning.
+///
+/// Devices can only be mounted on Linux and macOS; elsewhere every source is
+/// treated as an already-accessible directory. Regular files are never
+/// mounted, even under `/dev` (such as `/dev/shm`), and are scanned as a
+/// single-file source instead.
+pub fn is_device_path(drive: &str) -> bool {
+    cfg!(any(target_os = "linux", target_os = "macos"))
+        && drive.starts_with("/dev/")
+        && !Path::new(drive).is_file()
+}
+
+/// Finds the sources that repeat an earlier source or lie inside another one,
+/// so that no file is scanned twice. Returns their indices.
+pub fn overlapping_sources<P: AsRef<Path>>(sources: &[P]) -> Vec<usize> {
+    let canonical: Vec<PathBuf> = sources
+        .iter()
+        .map(|source| {
+            std::fs::canonicalize(source).unwrap_or_else(|_| source.as_ref().to_path_buf())
+        })
+        .collect();
+    (0..canonical.len())
+        .filter(|&i| {
+            canonical.iter().enumerate().any(|(j, other)| {
+                j != i && canonical[i].starts_with(other) && (canonical[i] != *other || j < i)
+            })
+        })
+        .collect()
+}
+
+/// Checks that mounting at `path` will not hide existing data.
+///
+/// The path must either not exist yet or be an empty directory.
+pub fn check_mount_point(path: &Path) -> color_eyre::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    if !path.is_dir() {
+        return Err(color_eyre::eyre::eyre!(
+            "Mount point {} is not a directory",
+            path.display()
+        ));
     }
+    if std::fs::read_dir(path)?.next().is_some() {
+        return Err(color_eyre::eyre::eyre!(
+            "Mount point {} is not empty",
+            path.display()
+        ));
+    }
+    Ok(())
+}
 
-    Ok(None)
+/// Capacity and usage of the filesystem a source is mounted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriveSpace {
+    /// Total size of the filesystem in bytes
+    pub capacity: u64,
+    /// Bytes in use, including space reserved for root
+    pub used: u64,
+    /// Bytes available to unprivileged users
+    pub free: u64,
 }
 
-/// Check if a device is a RAID member
-fn is_raid_member(device: &str) -> color_eyre::Result<bool> {
-    let output = Command::new("blkid")
-        .args(["-s", "TYPE", "-o", "value", device])
-        .output()?;
-
-    if output.status.success() {
-        let fs_type = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        // Common RAID member types - includes Intel Software RAID (ISW)
-        if fs_type.contains("raid_member")
-            || fs_type.contains("linux_raid_member")
-            || fs_type.contains("isw_raid_member")
-        {
-            return Ok(true);
+/// Returns the capacity, used, and free space of the filesystem mounted at `path`.
+///
+/// Returns `None` unless `path` is itself a mount point, since the figures
+/// would otherwise describe a filesystem that is much larger than the source.
+#[cfg(unix)]
+pub fn drive_space(path: &Path) -> Option<DriveSpace> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    if !is_mount_point(path) {
+        return None;
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a valid out-pointer
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+            return None;
         }
+        stat
+    };
+
+    let block_size = stat.f_frsize as u64;
+    Some(DriveSpace {
+        capacity: stat.f_blocks as u64 * block_size,
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block_size,
+        free: stat.f_bavail as u64 * block_size,
+    })
+}
+
+/// Filesystem usage is not queried on this platform.
+#[cfg(not(unix))]
+pub fn drive_space(_path: &Path) -> Option<DriveSpace> {
+    None
+}
+
+/// Returns true if `path` is the root of a mounted filesystem.
+///
+/// A mount point is on a different device than its parent directory, or is
+/// the same inode as its parent (the filesystem root).
+#[cfg(unix)]
+fn is_mount_point(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_dir() {
+        return false;
     }
 
-    Ok(false)
+    match std::fs::metadata(path.join("..")) {
+        Ok(parent) => parent.dev() != metadata.dev() || parent.ino() == metadata.ino(),
+        Err(_) => false,
+    }
 }
 
-/// Check if a device is an Intel Software RAID (ISW) member
-fn is_isw_raid_member(device: &str) -> color_eyre::Result<bool> {
-    let output = Command::new("blkid")
-        .args(["-s", "TYPE", "-o", "value", device])
-        .output()?;
-
-    if output.status.success() {
-        let fs_type = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if fs_type.contains("isw_raid_member") {
-            return Ok(true);
-        }
+/// Detect the filesystem type of a device
+#[cfg(target_os = "linux")]
+pub(crate) fn get_filesystem_type(
+    runner: &dyn CommandRunner,
+    device: &str,
+) -> color_eyre::Result<Option<String>> {
+    let output = runner.run("blkid", &["-s", "TYPE", "-o", "value", device])?;
+
+    if !output.success {
+        return Ok(None);
     }
 
-    Ok(false)
+    // Only the first reported signature is used
+    Ok(output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string))
+}
+
+/// Check if a device is a RAID member
+#[cfg(target_os = "linux")]
+fn is_raid_member(runner: &dyn CommandRunner, device: &str) -> color_eyre::Result<bool> {
+    // Common RAID member types - includes Intel Software RAID (ISW)
+    Ok(get_filesystem_type(runner, device)?.is_some_and(|fs_type| {
+        fs_type.contains("raid_member")
+            || fs_type.contains("linux_raid_member")
+            || fs_type.contains("isw_raid_member")
+    }))
+}
+
+/// Check if a device is an Intel Software RAID (ISW) member
+#[cfg(target_os = "linux")]
+fn is_isw_raid_member(runner: &dyn CommandRunner, device: &str) -> color_eyre::Result<bool> {
+    Ok(get_filesystem_type(runner, device)?
+        .is_some_and(|fs_type| fs_type.contains("isw_raid_member")))
 }
 
 /// RAID array metadata extracted from mdadm --examine
+#[cfg(target_os = "linux")]
 #[derive(Debug)]
 struct RaidMetadata {
     uuid: Option<String>,
@@ -71,6 +213,7 @@ struct RaidMetadata {
 }
 
 /// Intel RAID (dmraid) metadata
+#[cfg(target_os = "linux")]
 #[derive(Debug)]
 struct DmraidMetadata {
     raid_set_name: Option<String>,
@@ -80,114 +223,119 @@ struct DmraidMetadata {
     total_devices: Option<u32>,
 }
 
+/// Splits a `key : value` line at the first colon, trimming both sides.
+///
+/// Values keep any embedded colons (UUIDs like `a1b2:c3d4:...`, names like
+/// `host:0 (local to host host)`). Lines without a colon or with an empty
+/// value yield `None`.
+#[cfg(target_os = "linux")]
+fn split_field(line: &str) -> Option<(&str, String)> {
+    let mut parts = line.splitn(2, ':');
+    let key = parts.next()?.trim();
+    let value = parts.next()?.trim();
+
+    (!value.is_empty()).then(|| (key, value.to_string()))
+}
+
+/// Parse the RAID set summary printed by `dmraid -s`
+#[cfg(target_os = "linux")]
+fn parse_dmraid_sets(sets_info: &str) -> DmraidMetadata {
+    let mut metadata = DmraidMetadata {
+        raid_set_name: None,
+        raid_type: None,
+        status: None,
+        total_devices: None,
+    };
+
+    for (key, value) in sets_info.lines().filter_map(split_field) {
+        match key {
+            "name" => metadata.raid_set_name = Some(value),
+            "type" => metadata.raid_type = Some(value),
+            "status" => metadata.status = Some(value),
+            _ => {}
+        }
+    }
+
+    metadata
+}
+
 /// Get Intel RAID (dmraid) information for a device
-fn get_dmraid_info(device: &str) -> color_eyre::Result<Option<DmraidMetadata>> {
+#[cfg(target_os = "linux")]
+fn get_dmraid_info(
+    runner: &dyn CommandRunner,
+    device: &str,
+) -> color_eyre::Result<Option<DmraidMetadata>> {
     // Use dmraid to discover RAID sets
-    let output = Command::new("sudo").args(["dmraid", "-s", "-c"]).output()?;
-
-    if output.status.success() {
-        let _info = String::from_utf8_lossy(&output.stdout);
-        let mut metadata = DmraidMetadata {
-            raid_set_name: None,
-            raid_type: None,
-            status: None,
-            total_devices: None,
-        };
+    let output = runner.run("sudo", &["dmraid", "-s", "-c"])?;
+    if !output.success {
+        return Ok(None);
+    }
 
-        // Get detailed info with dmraid -r to see if this device is part of a RAID set
-        let detail_output = Command::new("sudo").args(["dmraid", "-r"]).output()?;
-
-        if detail_output.status.success() {
-            let detail_info = String::from_utf8_lossy(&detail_output.stdout);
-            let device_short = device.trim_start_matches("/dev/");
-
-            // Check if this device is listed in dmraid output
-            if detail_info.contains(device_short) {
-                // Get RAID set info
-                let sets_output = Command::new("sudo").args(["dmraid", "-s"]).output()?;
-
-                if sets_output.status.success() {
-                    let sets_info = String::from_utf8_lossy(&sets_output.stdout);
-
-                    for line in sets_info.lines() {
-                        if line.starts_with("name") && line.contains(':') {
-                            if let Some(name) = line.split(':').nth(1) {
-                                metadata.raid_set_name = Some(name.trim().to_string());
-                            }
-                        } else if line.starts_with("type") && line.contains(':') {
-                            if let Some(raid_type) = line.split(':').nth(1) {
-                                metadata.raid_type = Some(raid_type.trim().to_string());
-                            }
-                        } else if line.starts_with("status") && line.contains(':') {
-                            if let Some(status) = line.split(':').nth(1) {
-                                metadata.status = Some(status.trim().to_string());
-                            }
-                        }
-                    }
-                }
+    // Get detailed info with dmraid -r to see if this device is part of a RAID set
+    let detail_output = runner.run("sudo", &["dmraid", "-r"])?;
+    let device_short = device.trim_start_matches("/dev/");
+    if !detail_output.success || !detail_output.stdout.contains(device_short) {
+        return Ok(None);
+    }
+
+    // Get RAID set info
+    let sets_output = runner.run("sudo", &["dmraid", "-s"])?;
+    if sets_output.success {
+        return Ok(Some(parse_dmraid_sets(&sets_output.stdout)));
+    }
+
+    Ok(Some(parse_dmraid_sets("")))
+}
 
-                return Ok(Some(metadata));
+/// Parse the member superblock printed by `mdadm --examine`
+#[cfg(target_os = "linux")]
+fn parse_mdadm_examine(info: &str) -> RaidMetadata {
+    let mut metadata = RaidMetadata {
+        uuid: None,
+        raid_level: None,
+        raid_devices: None,
+        total_devices: None,
+        name: None,
+    };
+
+    for (key, value) in info.lines().filter_map(split_field) {
+        match key {
+            // 0.90 superblocks print `UUID`, 1.x print `Array UUID`. Drop any
+            // `(local to host ...)` note so the value can be passed to `--uuid`
+            "UUID" | "Array UUID" => {
+                metadata.uuid = value.split_whitespace().next().map(str::to_string)
             }
+            "Raid Level" => metadata.raid_level = Some(value),
+            "Raid Devices" => metadata.raid_devices = value.parse().ok(),
+            "Total Devices" => metadata.total_devices = value.parse().ok(),
+            "Name" | "MD_DEVNAME" => metadata.name = Some(value),
+            _ => {}
         }
     }
 
-    Ok(None)
+    metadata
 }
 
 /// Get RAID array information for a device
-fn get_raid_array_info(device: &str) -> color_eyre::Result<Option<RaidMetadata>> {
+#[cfg(target_os = "linux")]
+fn get_raid_array_info(
+    runner: &dyn CommandRunner,
+    device: &str,
+) -> color_eyre::Result<Option<RaidMetadata>> {
     // Check if mdadm can examine this device
-    let output = Command::new("sudo")
-        .args(["mdadm", "--examine", device])
-        .output()?;
-
-    if output.status.success() {
-        let info = String::from_utf8_lossy(&output.stdout);
-        let mut metadata = RaidMetadata {
-            uuid: None,
-            raid_level: None,
-            raid_devices: None,
-            total_devices: None,
-            name: None,
-        };
-
-        // Parse the mdadm output
-        for line in info.lines() {
-            let trimmed = line.trim();
-
-            if trimmed.starts_with("UUID") && trimmed.contains(':') {
-                if let Some(uuid) = trimmed.split(':').nth(1) {
-                    metadata.uuid = Some(uuid.trim().to_string());
-                }
-            } else if trimmed.starts_with("Raid Level") && trimmed.contains(':') {
-                if let Some(level) = trimmed.split(':').nth(1) {
-                    metadata.raid_level = Some(level.trim().to_string());
-                }
-            } else if trimmed.starts_with("Raid Devices") && trimmed.contains(':') {
-                if let Some(devices) = trimmed.split(':').nth(1) {
-                    metadata.raid_devices = devices.trim().parse().ok();
-                }
-            } else if trimmed.starts_with("Total Devices") && trimmed.contains(':') {
-                if let Some(devices) = trimmed.split(':').nth(1) {
-                    metadata.total_devices = devices.trim().parse().ok();
-                }
-            } else if (trimmed.starts_with("Name") || trimmed.starts_with("MD_DEVNAME"))
-                && trimmed.contains(':')
-            {
-                if let Some(name) = trimmed.split(':').nth(1) {
-                    metadata.name = Some(name.trim().to_string());
-                }
-            }
-        }
+    let output = runner.run("sudo", &["mdadm", "--examine", device])?;
 
-        return Ok(Some(metadata));
+    if output.success {
+        return Ok(Some(parse_mdadm_examine(&output.stdout)));
     }
 
     Ok(None)
 }
 
 /// Activate Intel RAID array using dmraid
+#[cfg(target_os = "linux")]
 fn activate_dmraid_array(
+    runner: &dyn CommandRunner,
     device: &str,
     metadata: &DmraidMetadata,
     theme: &str,
@@ -230,9 +378,9 @@ fn activate_dmraid_array(
         white_bold.apply_to("Activating Intel RAID array with dmraid...")
     );
 
-    let output = Command::new("sudo").args(["dmraid", "-ay"]).output()?;
+    let output = runner.run("sudo", &["dmraid", "-ay"])?;
 
-    if output.status.success() {
+    if output.success {
         println!(
             "{} {}",
             success_style.apply_to("[✓]").bold(),
@@ -247,16 +395,14 @@ fn activate_dmraid_array(
             error_style.apply_to("[!]").bold(),
             white_bold.apply_to("Failed to activate Intel RAID array")
         );
-        println!(
-            "{}",
-            white_bold.apply_to(String::from_utf8_lossy(&output.stderr))
-        );
+        println!("{}", white_bold.apply_to(&output.stderr));
     }
 
     Ok(None)
 }
 
 /// Find the device mapper device for the activated dmraid array
+#[cfg(target_os = "linux")]
 fn find_dmraid_device(
     #[allow(unused_variables)] device: &str,
     metadata: &DmraidMetadata,
@@ -266,18 +412,13 @@ fn find_dmraid_device(
     let white_bold = console::Style::new().white().bold();
 
     // List device mapper devices
-    let output = Command::new("ls").args(["-1", "/dev/mapper"]).output()?;
-
-    if output.status.success() {
-        let devices = String::from_utf8_lossy(&output.stdout);
-
+    if let Ok(devices) = dir_entry_names(Path::new("/dev/mapper")) {
         // Look for the RAID set name in the device mapper devices
         if let Some(ref raid_name) = metadata.raid_set_name {
-            for line in devices.lines() {
-                let line = line.trim();
+            for name in &devices {
                 // dmraid creates devices like /dev/mapper/isw_xxxxx_Volume0
-                if line.contains(raid_name) || line.starts_with("isw_") {
-                    let dm_device = format!("/dev/mapper/{}", line);
+                if name.contains(raid_name) || name.starts_with("isw_") {
+                    let dm_device = format!("/dev/mapper/{}", name);
                     println!(
                         "{} {}",
                         info_style.apply_to("[*]").bold(),
@@ -294,10 +435,9 @@ fn find_dmraid_device(
             warning_style.apply_to("[!]").bold(),
             white_bold.apply_to("Available device mapper devices:")
         );
-        for line in devices.lines() {
-            let line = line.trim();
-            if !line.is_empty() && line != "control" {
-                println!("{}", white_bold.apply_to(format!("  /dev/mapper/{}", line)));
+        for name in &devices {
+            if name != "control" {
+                println!("{}", white_bold.apply_to(format!("  /dev/mapper/{}", name)));
             }
         }
     }
@@ -308,9 +448,12 @@ fn find_dmraid_device(
 }
 
 /// Assemble a RAID array from a member device
+#[cfg(target_os = "linux")]
 fn assemble_raid_array(
+    runner: &dyn CommandRunner,
     device: &str,
     metadata: &RaidMetadata,
+    options: &MountConfig,
     theme: &str,
 ) -> color_eyre::Result<Option<String>> {
     let colorful_theme = UI::get_colorful_theme(theme);
@@ -325,18 +468,16 @@ fn assemble_raid_array(
     );
 
     // First try to assemble normally with scan
-    let output = Command::new("sudo")
-        .args(["mdadm", "--assemble", "--scan", "--readonly"])
-        .output()?;
+    let output = runner.run("sudo", &["mdadm", "--assemble", "--scan", "--readonly"])?;
 
-    if output.status.success() {
+    if output.success {
         println!(
             "{} {}",
             success_style.apply_to("[✓]").bold(),
             white_bold.apply_to("RAID array assembled successfully")
         );
 
-        return find_assembled_array(device, theme);
+        return find_assembled_array(runner, device, theme);
     }
 
     // Normal assembly failed - check if array is degraded
@@ -401,10 +542,21 @@ fn assemble_raid_array(
         );
         println!();
 
-        let should_force = Confirm::with_theme(&colorful_theme)
-            .with_prompt("Attempt to force-assemble degraded RAID array? (read-only)")
-            .default(false)
-            .interact()?;
+        // --assume-yes only covers safe prompts, so it declines this one
+        let should_force = if options.force_degraded {
+            true
+        } else if options.assume_yes {
+            println!(
+                "{}",
+                white_bold.apply_to("  Pass --force-degraded to assemble it without asking.")
+            );
+            false
+        } else {
+            Confirm::with_theme(&colorful_theme)
+                .with_prompt("Attempt to force-assemble degraded RAID array? (read-only)")
+                .default(false)
+                .interact()?
+        };
 
         if !should_force {
             println!("{}", white_bold.apply_to("RAID assembly aborted by user."));
@@ -418,33 +570,43 @@ fn assemble_raid_array(
             white_bold.apply_to("Attempting force assembly of degraded array...")
         );
 
+        // Pick an unused md node so existing arrays are not clobbered
+        let md_device = find_free_md_device()?;
+        println!(
+            "{} {}",
+            info_style.apply_to("[*]").bold(),
+            white_bold.apply_to(format!("Assembling into {}", md_device))
+        );
+
         // Use UUID if available, otherwise try with device
         let force_output = if let Some(ref uuid) = metadata.uuid {
-            Command::new("sudo")
-                .args([
+            runner.run(
+                "sudo",
+                &[
                     "mdadm",
                     "--assemble",
                     "--force",
                     "--readonly",
                     "--uuid",
                     uuid,
-                    "/dev/md127",
-                ])
-                .output()?
+                    &md_device,
+                ],
+            )?
         } else {
-            Command::new("sudo")
-                .args([
+            runner.run(
+                "sudo",
+                &[
                     "mdadm",
                     "--assemble",
                     "--force",
                     "--readonly",
-                    "/dev/md127",
+                    &md_device,
                     device,
-                ])
-                .output()?
+                ],
+            )?
         };
 
-        if force_output.status.success() {
+        if force_output.success {
             println!(
                 "{} {}",
                 success_style.apply_to("[✓]").bold(),
@@ -456,58 +618,138 @@ fn assemble_raid_array(
                 white_bold.apply_to("Note: Array is degraded - some data may be inaccessible")
             );
 
-            return find_assembled_array(device, theme);
+            return find_assembled_array(runner, device, theme);
         } else {
             println!(
                 "{} {}",
                 error_style.apply_to("[!]").bold(),
                 white_bold.apply_to("Failed to force-assemble RAID array")
             );
-            println!(
-                "{}",
-                white_bold.apply_to(String::from_utf8_lossy(&force_output.stderr))
-            );
+            println!("{}", white_bold.apply_to(&force_output.stderr));
         }
     }
 
     Ok(None)
 }
 
+/// List the array devices (e.g. `/dev/md127`) named in `/proc/mdstat`
+#[cfg(target_os = "linux")]
+fn parse_mdstat_arrays(mdstat: &str) -> Vec<String> {
+    mdstat
+        .lines()
+        .filter(|line| line.starts_with("md"))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|md_name| format!("/dev/{}", md_name))
+        .collect()
+}
+
+/// Pick the highest unused `/dev/mdN`, counting down from `md127` as mdadm does.
+///
+/// A number is taken if it appears in `/proc/mdstat` or as an entry in a
+/// `/dev` listing (including named arrays under `/dev/md/`, which are
+/// symlinks and do not reserve a number themselves).
+#[cfg(target_os = "linux")]
+fn first_free_md_device(mdstat: &str, dev_names: &[String]) -> Option<String> {
+    let in_use: HashSet<String> = parse_mdstat_arrays(mdstat)
+        .into_iter()
+        .chain(
+            dev_names
+                .iter()
+                .filter(|name| name.starts_with("md"))
+                .map(|name| format!("/dev/{}", name)),
+        )
+        .collect();
+
+    (0..=127)
+        .rev()
+        .map(|n| format!("/dev/md{}", n))
+        .find(|candidate| !in_use.contains(candidate))
+}
+
+/// Driver used to mount an NTFS volume.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NtfsDriver {
+    /// The FUSE `ntfs-3g` driver, preferred for compatibility
+    Ntfs3g,
+    /// The in-kernel `ntfs3` driver, used when `ntfs-3g` is not installed
+    Ntfs3,
+}
+
+/// Picks `ntfs-3g` when it is installed, otherwise the kernel `ntfs3` driver.
+#[cfg(target_os = "linux")]
+pub(crate) fn ntfs_driver(runner: &dyn CommandRunner) -> NtfsDriver {
+    let installed = runner
+        .run("which", &["ntfs-3g"])
+        .is_ok_and(|output| output.success);
+
+    if installed {
+        NtfsDriver::Ntfs3g
+    } else {
+        NtfsDriver::Ntfs3
+    }
+}
+
+/// Arguments to `sudo` that mount `device` read-only at `mount_point`.
+#[cfg(target_os = "linux")]
+pub(crate) fn mount_command<'a>(
+    ntfs: Option<NtfsDriver>,
+    device: &'a str,
+    mount_point: &'a str,
+) -> Vec<&'a str> {
+    match ntfs {
+        Some(NtfsDriver::Ntfs3g) => vec!["ntfs-3g", "-o", "ro", device, mount_point],
+        Some(NtfsDriver::Ntfs3) => vec!["mount", "-t", "ntfs3", "-o", "ro", device, mount_point],
+        None => vec!["mount", "-o", "ro", device, mount_point],
+    }
+}
+
+/// Names of the entries in `dir`.
+#[cfg(target_os = "linux")]
+fn dir_entry_names(dir: &Path) -> std::io::Result<Vec<String>> {
+    std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// Find an unused md device for force-assembly
+#[cfg(target_os = "linux")]
+fn find_free_md_device() -> color_eyre::Result<String> {
+    // Without md support neither exists, and every number is free
+    let mdstat = std::fs::read_to_string("/proc/mdstat").unwrap_or_default();
+    let dev_names = dir_entry_names(Path::new("/dev"))?;
+
+    first_free_md_device(&mdstat, &dev_names)
+        .ok_or_else(|| color_eyre::eyre::eyre!("No free /dev/mdN device for RAID assembly"))
+}
+
 /// Find the MD device that was assembled for the given physical device
-fn find_assembled_array(device: &str, theme: &str) -> color_eyre::Result<Option<String>> {
+#[cfg(target_os = "linux")]
+fn find_assembled_array(
+    runner: &dyn CommandRunner,
+    device: &str,
+    theme: &str,
+) -> color_eyre::Result<Option<String>> {
     let (info_style, warning_style, _, _) = UI::get_static_status_styles(theme);
     let white_bold = console::Style::new().white().bold();
 
     // Find the assembled array device
-    let list_output = Command::new("cat").arg("/proc/mdstat").output()?;
-
-    if list_output.status.success() {
-        let mdstat = String::from_utf8_lossy(&list_output.stdout);
-        // Parse mdstat to find array that contains this device
-        for line in mdstat.lines() {
-            if line.starts_with("md") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if let Some(md_name) = parts.first() {
-                    let md_device = format!("/dev/{}", md_name);
-                    // Check if this array contains our device
-                    let detail_output = Command::new("sudo")
-                        .args(["mdadm", "--detail", &md_device])
-                        .output()?;
-
-                    if detail_output.status.success() {
-                        let detail = String::from_utf8_lossy(&detail_output.stdout);
-                        let device_short = device.trim_start_matches("/dev/");
-                        if detail.contains(device_short) {
-                            println!(
-                                "{} {}",
-                                info_style.apply_to("[*]").bold(),
-                                white_bold.apply_to(format!("RAID array device: {}", md_device))
-                            );
-                            return Ok(Some(md_device));
-                        }
-                    }
-                }
-            }
+    let arrays = std::fs::read_to_string("/proc/mdstat")
+        .map(|mdstat| parse_mdstat_arrays(&mdstat))
+        .unwrap_or_default();
+
+    // Check each array for our device
+    let device_short = device.trim_start_matches("/dev/");
+    for md_device in &arrays {
+        let detail_output = runner.run("sudo", &["mdadm", "--detail", md_device])?;
+
+        if detail_output.success && detail_output.stdout.contains(device_short) {
+            println!(
+                "{} {}",
+                info_style.apply_to("[*]").bold(),
+                white_bold.apply_to(format!("RAID array device: {}", md_device))
+            );
+            return Ok(Some(md_device.clone()));
         }
     }
 
@@ -518,24 +760,28 @@ fn find_assembled_array(device: &str, theme: &str) -> color_eyre::Result<Option<
         white_bold.apply_to("Array assembled but couldn't determine device name")
     );
     println!("{}", white_bold.apply_to("Available RAID arrays:"));
-
-    let _ = Command::new("sh")
-        .arg("-c")
-        .arg("cat /proc/mdstat | grep '^md'")
-        .status();
+    for md_device in &arrays {
+        println!("{}", white_bold.apply_to(format!("  {}", md_device)));
+    }
 
     Err(color_eyre::eyre::eyre!(
         "Please manually specify the RAID array device (e.g., /dev/md0)"
     ))
 }
 
-pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Result<PathBuf> {
+#[cfg(target_os = "linux")]
+pub async fn mount_drive_readonly(
+    device: &str,
+    options: &MountConfig,
+    theme: &str,
+) -> color_eyre::Result<PathBuf> {
+    let runner = SystemCommands;
     let colorful_theme = UI::get_colorful_theme(theme);
     let (info_style, warning_style, _, success_style) = UI::get_static_status_styles(theme);
     let white_bold = console::Style::new().white().bold();
 
     // Check if this is a RAID member and assemble/activate if needed
-    let actual_device = if is_raid_member(device)? {
+    let actual_device = if is_raid_member(&runner, device)? {
         println!(
             "{} {}",
             info_style.apply_to("[*]").bold(),
@@ -543,15 +789,15 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
         );
 
         // Check if this is an Intel Software RAID (ISW) member
-        if is_isw_raid_member(device)? {
+        if is_isw_raid_member(&runner, device)? {
             println!(
                 "{} {}",
                 info_style.apply_to("[*]").bold(),
                 white_bold.apply_to("Detected Intel Software RAID (ISW) member")
             );
 
-            if let Some(metadata) = get_dmraid_info(device)? {
-                match activate_dmraid_array(device, &metadata, theme)? {
+            if let Some(metadata) = get_dmraid_info(&runner, device)? {
+                match activate_dmraid_array(&runner, device, &metadata, theme)? {
                     Some(dm_device) => dm_device,
                     None => {
                         let (_, _, error_style, _) = UI::get_static_status_styles(theme);
@@ -560,7 +806,7 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                             error_style.apply_to("[!] ERROR:").bold(),
                             white_bold.apply_to("Failed to activate Intel RAID array")
                         );
-                        std::process::exit(1);
+                        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
                     }
                 }
             } else {
@@ -570,11 +816,11 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                     error_style.apply_to("[!] ERROR:").bold(),
                     white_bold.apply_to("Could not read Intel RAID metadata")
                 );
-                std::process::exit(1);
+                return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
             }
         } else {
             // Handle standard Linux RAID with mdadm
-            if let Some(metadata) = get_raid_array_info(device)? {
+            if let Some(metadata) = get_raid_array_info(&runner, device)? {
                 if let Some(ref name) = metadata.name {
                     println!(
                         "{} {}",
@@ -590,7 +836,7 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                     );
                 }
 
-                match assemble_raid_array(device, &metadata, theme)? {
+                match assemble_raid_array(&runner, device, &metadata, options, theme)? {
                     Some(md_device) => md_device,
                     None => {
                         let (_, _, error_style, _) = UI::get_static_status_styles(theme);
@@ -599,7 +845,7 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                             error_style.apply_to("[!] ERROR:").bold(),
                             white_bold.apply_to("Failed to assemble RAID array")
                         );
-                        std::process::exit(1);
+                        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
                     }
                 }
             } else {
@@ -609,7 +855,7 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                     error_style.apply_to("[!] ERROR:").bold(),
                     white_bold.apply_to("Could not read RAID metadata")
                 );
-                std::process::exit(1);
+                return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
             }
         }
     } else {
@@ -619,7 +865,7 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
     let device = actual_device.as_str();
 
     // Check if already mounted
-    if let Some(existing_mount) = get_mount_point(device)? {
+    if let Some(existing_mount) = findmnt_target(&runner, device)? {
         println!(
             "{} {}",
             info_style.apply_to("[*]").bold(),
@@ -628,8 +874,19 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                 existing_mount.display()
             ))
         );
+        if options
+            .mount_point
+            .as_ref()
+            .is_some_and(|requested| *requested != existing_mount)
+        {
+            println!(
+                "{} {}",
+                info_style.apply_to("[*]").bold(),
+                white_bold.apply_to("Using the existing mount instead of --mount-point")
+            );
+        }
 
-        if is_mounted_readonly(&existing_mount)? {
+        if findmnt_readonly(&runner, &existing_mount)? {
             println!(
                 "{} {}",
                 success_style.apply_to("[✓]").bold(),
@@ -647,10 +904,11 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                 white_bold.apply_to("   For safety, the drive should be remounted read-only.")
             );
 
-            let remount = Confirm::with_theme(&colorful_theme)
-                .with_prompt("Remount as read-only?")
-                .default(true)
-                .interact()?;
+            let remount = options.assume_yes
+                || Confirm::with_theme(&colorful_theme)
+                    .with_prompt("Remount as read-only?")
+                    .default(true)
+                    .interact()?;
 
             if !remount {
                 println!(
@@ -667,22 +925,17 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                 info_style.apply_to("[*]").bold(),
                 white_bold.apply_to(format!("Remounting {} as read-only...", device))
             );
-            let output = Command::new("sudo")
-                .args(["mount", "-o", "remount,ro", device])
-                .output()?;
+            let output = runner.run("sudo", &["mount", "-o", "remount,ro", device])?;
 
-            if !output.status.success() {
+            if !output.success {
                 let (_, _, error_style, _) = UI::get_static_status_styles(theme);
                 println!(
                     "{} {}",
                     error_style.apply_to("[!] ERROR:").bold(),
                     white_bold.apply_to("Failed to remount read-only")
                 );
-                println!(
-                    "{}",
-                    white_bold.apply_to(String::from_utf8_lossy(&output.stderr))
-                );
-                std::process::exit(1);
+                println!("{}", white_bold.apply_to(&output.stderr));
+                return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
             }
 
             println!(
@@ -701,10 +954,24 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
         white_bold.apply_to(format!("Drive {} is not mounted", device))
     );
 
-    let should_mount = Confirm::with_theme(&colorful_theme)
-        .with_prompt("Mount as read-only?")
-        .default(true)
-        .interact()?;
+    // A requested mount point must not shadow existing data
+    if let Some(path) = &options.mount_point
+        && let Err(e) = check_mount_point(path)
+    {
+        let (_, _, error_style, _) = UI::get_static_status_styles(theme);
+        println!(
+            "{} {}",
+            error_style.apply_to("[!] ERROR:").bold(),
+            white_bold.apply_to(e)
+        );
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
+    }
+
+    let should_mount = options.assume_yes
+        || Confirm::with_theme(&colorful_theme)
+            .with_prompt("Mount as read-only?")
+            .default(true)
+            .interact()?;
 
     if !should_mount {
         let (_, _, error_style, _) = UI::get_static_status_styles(theme);
@@ -713,11 +980,13 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
             error_style.apply_to("[!] ERROR:").bold(),
             white_bold.apply_to("Drive must be mounted to proceed")
         );
-        std::process::exit(1);
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
     }
 
     // Create mount point
-    let new_mount_point = PathBuf::from(format!("/mnt/tap_{}", device.trim_start_matches("/dev/")));
+    let new_mount_point = device_mount_point(device, options);
+    // An existing empty directory passed as --mount-point is left in place afterwards
+    let remove_dir = options.mount_point.is_none() || !new_mount_point.exists();
 
     println!(
         "{} {}",
@@ -728,35 +997,38 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
         ))
     );
 
-    let output = Command::new("sudo")
-        .args(["mkdir", "-p", new_mount_point.to_str().unwrap()])
-        .output()?;
+    let output = runner.run("sudo", &["mkdir", "-p", new_mount_point.to_str().unwrap()])?;
 
-    if !output.status.success() {
+    if !output.success {
         let (_, _, error_style, _) = UI::get_static_status_styles(theme);
         println!(
             "{} {}",
             error_style.apply_to("[!] ERROR:").bold(),
             white_bold.apply_to("Failed to create mount point")
         );
-        println!(
-            "{}",
-            white_bold.apply_to(String::from_utf8_lossy(&output.stderr))
-        );
-        std::process::exit(1);
+        println!("{}", white_bold.apply_to(&output.stderr));
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
     }
 
     // Detect filesystem type
-    let fs_type = get_filesystem_type(device)?;
-    let use_ntfs3g = fs_type.as_ref().map(|t| t == "ntfs").unwrap_or(false);
+    let fs_type = get_filesystem_type(&runner, device)?;
+    let ntfs = (fs_type.as_deref() == Some("ntfs")).then(|| ntfs_driver(&runner));
 
-    if use_ntfs3g {
-        println!(
+    match ntfs {
+        Some(NtfsDriver::Ntfs3g) => println!(
             "{} {}",
             info_style.apply_to("[*]").bold(),
             white_bold
                 .apply_to("Detected NTFS filesystem - using ntfs-3g for better compatibility")
-        );
+        ),
+        Some(NtfsDriver::Ntfs3) => println!(
+            "{} {}",
+            warning_style.apply_to("[!] WARNING:").bold(),
+            white_bold.apply_to(
+                "Detected NTFS filesystem but ntfs-3g is not installed - using the kernel ntfs3 driver"
+            )
+        ),
+        None => {}
     }
 
     // Mount read-only
@@ -770,41 +1042,19 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
         ))
     );
 
-    let output = if use_ntfs3g {
-        // Use ntfs-3g for NTFS filesystems
-        Command::new("sudo")
-            .args([
-                "ntfs-3g",
-                "-o",
-                "ro",
-                device,
-                new_mount_point.to_str().unwrap(),
-            ])
-            .output()?
-    } else {
-        // Use regular mount for other filesystems
-        Command::new("sudo")
-            .args([
-                "mount",
-                "-o",
-                "ro",
-                device,
-                new_mount_point.to_str().unwrap(),
-            ])
-            .output()?
-    };
+    let output = runner.run(
+        "sudo",
+        &mount_command(ntfs, device, new_mount_point.to_str().unwrap()),
+    )?;
 
-    if !output.status.success() {
+    if !output.success {
         let (_, _, error_style, _) = UI::get_static_status_styles(theme);
         println!(
             "{} {}",
             error_style.apply_to("[!] ERROR:").bold(),
             white_bold.apply_to("Failed to mount drive")
         );
-        println!(
-            "{}",
-            white_bold.apply_to(String::from_utf8_lossy(&output.stderr))
-        );
+        println!("{}", white_bold.apply_to(&output.stderr));
 
         // Try to detect filesystem and suggest mounting
         println!();
@@ -824,16 +1074,25 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
                 device
             ))
         );
-        if use_ntfs3g {
-            println!(
+        match ntfs {
+            Some(NtfsDriver::Ntfs3g) => println!(
                 "{}",
-                white_bold.apply_to("  4. Ensure ntfs-3g is installed: which ntfs-3g")
-            );
+                white_bold.apply_to("  4. Ensure ntfs-3g is working: ntfs-3g --version")
+            ),
+            Some(NtfsDriver::Ntfs3) => println!(
+                "{}",
+                white_bold.apply_to(
+                    "  4. Install ntfs-3g, or check the kernel supports ntfs3: grep ntfs3 /proc/filesystems"
+                )
+            ),
+            None => {}
         }
 
-        std::process::exit(1);
+        return Err(color_eyre::eyre::eyre!("Failed to mount {}", device));
     }
 
+    register_mount(new_mount_point.clone(), remove_dir);
+
     println!(
         "{} {}",
         success_style.apply_to("[✓]").bold(),
@@ -846,37 +1105,542 @@ pub async fn mount_drive_readonly(device: &str, theme: &str) -> color_eyre::Resu
     Ok(new_mount_point)
 }
 
-pub fn get_mount_point(device: &str) -> color_eyre::Result<Option<PathBuf>> {
-    let output = Command::new("findmnt")
-        .args(["-n", "-o", "TARGET", device])
-        .output()?;
-
-    if output.status.success() {
-        let mount_point_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if !mount_point_str.is_empty() {
-            return Ok(Some(PathBuf::from(mount_point_str)));
+/// Where tap mounts `device`: the requested mount point, or `/mnt/tap_<name>`.
+#[cfg(target_os = "linux")]
+fn device_mount_point(device: &str, options: &MountConfig) -> PathBuf {
+    options.mount_point.clone().unwrap_or_else(|| {
+        PathBuf::from(format!("/mnt/tap_{}", device.trim_start_matches("/dev/")))
+    })
+}
+
+/// Mount a device read-only without printing or prompting, for library use.
+///
+/// A device already mounted read-only is used where it is. One mounted
+/// read-write is remounted read-only only with `options.assume_yes`, and is
+/// refused otherwise. RAID members need the assembly steps of
+/// [`mount_drive_readonly`], so they are refused too, as is a run where `sudo`
+/// would ask for a password.
+#[cfg(target_os = "linux")]
+pub fn mount_device(device: &str, options: &MountConfig) -> color_eyre::Result<PathBuf> {
+    mount_device_with(&SystemCommands, device, options)
+}
+
+#[cfg(target_os = "linux")]
+fn mount_device_with(
+    runner: &dyn CommandRunner,
+    device: &str,
+    options: &MountConfig,
+) -> color_eyre::Result<PathBuf> {
+    if !matches!(check_privilege(runner), Privilege::Root | Privilege::Sudo) {
+        return Err(color_eyre::eyre::eyre!(
+            "Mounting {} requires root or passwordless sudo",
+            device
+        ));
+    }
+    if is_raid_member(runner, device)? {
+        return Err(color_eyre::eyre::eyre!(
+            "{} is a RAID member; assemble the array and pass the array device",
+            device
+        ));
+    }
+
+    if let Some(existing_mount) = findmnt_target(runner, device)? {
+        if findmnt_readonly(runner, &existing_mount)? {
+            return Ok(existing_mount);
+        }
+        if !options.assume_yes {
+            return Err(color_eyre::eyre::eyre!(
+                "{} is mounted read-write at {}; remount it read-only or set assume_yes",
+                device,
+                existing_mount.display()
+            ));
+        }
+        let output = runner.run("sudo", &["mount", "-o", "remount,ro", device])?;
+        if !output.success {
+            return Err(color_eyre::eyre::eyre!(
+                "Failed to remount {} read-only: {}",
+                device,
+                output.stderr.trim()
+            ));
+        }
+        return Ok(existing_mount);
+    }
+
+    if let Some(path) = &options.mount_point {
+        check_mount_point(path)?;
+    }
+    let mount_point = device_mount_point(device, options);
+    let remove_dir = options.mount_point.is_none() || !mount_point.exists();
+    let mount_point_str = mount_point.to_string_lossy();
+
+    let output = runner.run("sudo", &["mkdir", "-p", &mount_point_str])?;
+    if !output.success {
+        return Err(color_eyre::eyre::eyre!(
+            "Failed to create mount point {}: {}",
+            mount_point.display(),
+            output.stderr.trim()
+        ));
+    }
+
+    let fs_type = get_filesystem_type(runner, device)?;
+    let ntfs = (fs_type.as_deref() == Some("ntfs")).then(|| ntfs_driver(runner));
+    let output = runner.run("sudo", &mount_command(ntfs, device, &mount_point_str))?;
+    if !output.success {
+        if remove_dir {
+            let _ = runner.run("sudo", &["rmdir", &mount_point_str]);
+        }
+        return Err(color_eyre::eyre::eyre!(
+            "Failed to mount {}: {}",
+            device,
+            output.stderr.trim()
+        ));
+    }
+
+    register_mount(mount_point.clone(), remove_dir);
+    Ok(mount_point)
+}
+
+/// Unmount a device mounted by [`mount_device`] without printing. Mounts tap
+/// did not make are left in place.
+#[cfg(target_os = "linux")]
+pub fn unmount_device(mount_point: &Path) -> color_eyre::Result<()> {
+    unmount_device_with(&SystemCommands, mount_point)
+}
+
+#[cfg(target_os = "linux")]
+fn unmount_device_with(runner: &dyn CommandRunner, mount_point: &Path) -> color_eyre::Result<()> {
+    let Some(remove_dir) = take_mount(mount_point) else {
+        return Ok(());
+    };
+    let mount_point_str = mount_point.to_string_lossy();
+
+    let output = runner.run("sudo", &["umount", &mount_point_str])?;
+    if !output.success {
+        // Still mounted, so a later attempt may unmount it
+        register_mount(mount_point.to_path_buf(), remove_dir);
+        return Err(color_eyre::eyre::eyre!(
+            "Failed to unmount {}: {}",
+            mount_point.display(),
+            output.stderr.trim()
+        ));
+    }
+
+    if remove_dir {
+        let _ = runner.run("sudo", &["rmdir", &mount_point_str]);
+    }
+    Ok(())
+}
+
+/// How tap can obtain the root privileges needed to mount devices.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Privilege {
+    /// Already running as root
+    Root,
+    /// `sudo` works without prompting
+    Sudo,
+    /// `sudo` exists but will ask for a password
+    SudoWithPassword,
+    /// `sudo` is not installed
+    Unavailable,
+}
+
+#[cfg(target_os = "linux")]
+fn check_privilege(runner: &dyn CommandRunner) -> Privilege {
+    if let Ok(output) = runner.run("id", &["-u"])
+        && output.success
+        && output.stdout.trim() == "0"
+    {
+        return Privilege::Root;
+    }
+
+    match runner.run("sudo", &["-n", "true"]) {
+        Ok(output) if output.success => Privilege::Sudo,
+        Ok(_) => Privilege::SudoWithPassword,
+        Err(_) => Privilege::Unavailable,
+    }
+}
+
+/// Make sure devices can be mounted before any work starts.
+///
+/// Exits with an actionable message if `sudo` is missing, and warns if it
+/// will prompt for a password partway through the run.
+#[cfg(target_os = "linux")]
+pub fn ensure_mount_privileges(theme: &str) -> color_eyre::Result<()> {
+    let (_, warning_style, error_style, _) = UI::get_static_status_styles(theme);
+    let white_bold = console::Style::new().white().bold();
+
+    match check_privilege(&SystemCommands) {
+        Privilege::Root | Privilege::Sudo => {}
+        Privilege::SudoWithPassword => {
+            println!(
+                "{} {}",
+                warning_style.apply_to("[!] WARNING:").bold(),
+                white_bold.apply_to("Mounting requires root; sudo will ask for your password")
+            );
+            println!(
+                "{}",
+                white_bold.apply_to(
+                    "   Run tap with sudo, or pre-mount the drive and pass the mount path."
+                )
+            );
+        }
+        Privilege::Unavailable => {
+            println!(
+                "{} {}",
+                error_style.apply_to("[!] ERROR:").bold(),
+                white_bold.apply_to("Mounting requires root, but sudo is not available")
+            );
+            println!(
+                "{}",
+                white_bold.apply_to(
+                    "   Run tap as root, or pre-mount the drive and pass the mount path."
+                )
+            );
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+/// Look up where `device` is mounted with `findmnt`
+#[cfg(target_os = "linux")]
+fn findmnt_target(runner: &dyn CommandRunner, device: &str) -> color_eyre::Result<Option<PathBuf>> {
+    let output = runner.run("findmnt", &["-n", "-o", "TARGET", device])?;
+
+    if output.success {
+        // A device mounted in several places lists one target per line
+        if let Some(mount_point) = output.stdout.lines().map(str::trim).find(|l| !l.is_empty()) {
+            return Ok(Some(PathBuf::from(mount_point)));
         }
     }
 
     Ok(None)
 }
 
-pub fn is_mounted_readonly(path: &Path) -> color_eyre::Result<bool> {
-    let output = Command::new("findmnt")
-        .args(["-n", "-o", "OPTIONS", path.to_str().unwrap()])
-        .output()?;
+/// Check the mount options of `path` with `findmnt`
+#[cfg(target_os = "linux")]
+fn findmnt_readonly(runner: &dyn CommandRunner, path: &Path) -> color_eyre::Result<bool> {
+    let path = path.to_string_lossy();
+    let output = runner.run("findmnt", &["-n", "-o", "OPTIONS", &path])?;
 
-    if output.status.success() {
-        let options = String::from_utf8_lossy(&output.stdout);
+    if output.success {
         // Check if 'ro' is in the mount options
-        return Ok(options.split(',').any(|opt| opt.trim() == "ro"));
+        return Ok(output.stdout.split(',').any(|opt| opt.trim() == "ro"));
     }
 
     Ok(false)
 }
 
-pub fn validate_source_path(drive: &str, theme: &str) -> color_eyre::Result<PathBuf> {
-    let colorful_theme = UI::get_colorful_theme(theme);
+#[cfg(target_os = "linux")]
+pub fn get_mount_point(device: &str) -> color_eyre::Result<Option<PathBuf>> {
+    findmnt_target(&SystemCommands, device)
+}
+
+#[cfg(target_os = "linux")]
+pub fn is_mounted_readonly(path: &Path) -> color_eyre::Result<bool> {
+    findmnt_readonly(&SystemCommands, path)
+}
+
+/// How a `mount-check` finding affects mounting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Nothing stands in the way
+    Ok,
+    /// Worth knowing, neither good nor bad
+    Info,
+    /// Mounting should work, but not without extra steps
+    Warning,
+    /// Mounting will fail until this is fixed
+    Problem,
+}
+
+/// One finding of `tap mount-check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub label: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(label: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            label,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Runs the read-only checks behind `tap mount-check` on `device`.
+///
+/// Nothing is mounted, assembled, or unlocked; the device is only examined
+/// with `blkid`, `mdadm --examine`, and `findmnt`.
+#[cfg(target_os = "linux")]
+fn diagnose_device(runner: &dyn CommandRunner, device: &str) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let fs_type = match get_filesystem_type(runner, device) {
+        Ok(Some(fs_type)) => {
+            checks.push(Check::new("Filesystem", CheckStatus::Ok, fs_type.as_str()));
+            Some(fs_type)
+        }
+        Ok(None) => {
+            checks.push(Check::new(
+                "Filesystem",
+                CheckStatus::Problem,
+                "blkid found no filesystem signature; the partition may be unformatted or damaged",
+            ));
+            None
+        }
+        Err(e) => {
+            checks.push(Check::new(
+                "Filesystem",
+                CheckStatus::Problem,
+                format!("blkid could not be run: {}", e),
+            ));
+            None
+        }
+    };
+
+    let tool = |program: &str| {
+        runner
+            .run("which", &[program])
+            .is_ok_and(|output| output.success)
+    };
+    match fs_type.as_deref() {
+        Some("linux_raid_member") => {
+            checks.push(Check::new(
+                "Container",
+                CheckStatus::Warning,
+                "Linux RAID member; tap assembles the array with mdadm and mounts the array instead",
+            ));
+            if let Ok(Some(metadata)) = get_raid_array_info(runner, device) {
+                checks.push(Check::new(
+                    "RAID array",
+                    CheckStatus::Info,
+                    format!(
+                        "{} ({}, {} of {} devices)",
+                        metadata.name.as_deref().unwrap_or("unnamed"),
+                        metadata.raid_level.as_deref().unwrap_or("unknown level"),
+                        metadata
+                            .total_devices
+                            .map_or("?".to_string(), |n| n.to_string()),
+                        metadata
+                            .raid_devices
+                            .map_or("?".to_string(), |n| n.to_string())
+                    ),
+                ));
+            }
+            checks.push(if tool("mdadm") {
+                Check::new("Helper", CheckStatus::Ok, "mdadm is installed")
+            } else {
+                Check::new(
+                    "Helper",
+                    CheckStatus::Problem,
+                    "mdadm is not installed; install it to assemble the array",
+                )
+            });
+        }
+        Some("isw_raid_member") => {
+            checks.push(Check::new(
+                "Container",
+                CheckStatus::Warning,
+                "Intel Software RAID (ISW) member; tap activates the set with dmraid and mounts it instead",
+            ));
+            checks.push(if tool("dmraid") {
+                Check::new("Helper", CheckStatus::Ok, "dmraid is installed")
+            } else {
+                Check::new(
+                    "Helper",
+                    CheckStatus::Problem,
+                    "dmraid is not installed; install it to activate the set",
+                )
+            });
+        }
+        Some("LVM2_member") => checks.push(Check::new(
+            "Container",
+            CheckStatus::Problem,
+            "LVM physical volume; tap cannot mount it directly. Activate it with `sudo vgchange -ay` and pass a logical volume from /dev/mapper",
+        )),
+        Some("crypto_LUKS") => checks.push(Check::new(
+            "Container",
+            CheckStatus::Problem,
+            format!(
+                "LUKS encrypted; unlock it with `sudo cryptsetup open --readonly {} evidence` and pass /dev/mapper/evidence",
+                device
+            ),
+        )),
+        Some("ntfs") => checks.push(match ntfs_driver(runner) {
+            NtfsDriver::Ntfs3g => Check::new("Helper", CheckStatus::Ok, "ntfs-3g is installed"),
+            NtfsDriver::Ntfs3 if runner.run("modinfo", &["ntfs3"]).is_ok_and(|o| o.success) => {
+                Check::new(
+                    "Helper",
+                    CheckStatus::Ok,
+                    "ntfs-3g is not installed; the kernel ntfs3 driver is used",
+                )
+            }
+            NtfsDriver::Ntfs3 => Check::new(
+                "Helper",
+                CheckStatus::Problem,
+                "neither ntfs-3g nor the kernel ntfs3 driver is available; install ntfs-3g",
+            ),
+        }),
+        _ => {}
+    }
+
+    match findmnt_target(runner, device) {
+        Ok(Some(mount_point)) => {
+            checks.push(Check::new(
+                "Mounted",
+                CheckStatus::Info,
+                format!("at {}", mount_point.display()),
+            ));
+            checks.push(match findmnt_readonly(runner, &mount_point) {
+                Ok(true) => Check::new("Read-only", CheckStatus::Ok, "mounted read-only"),
+                Ok(false) => Check::new(
+                    "Read-only",
+                    CheckStatus::Warning,
+                    "mounted writable; tap asks to remount it read-only before scanning",
+                ),
+                Err(e) => Check::new(
+                    "Read-only",
+                    CheckStatus::Warning,
+                    format!("mount options could not be read: {}", e),
+                ),
+            });
+        }
+        Ok(None) => checks.push(Check::new("Mounted", CheckStatus::Info, "not mounted")),
+        Err(e) => checks.push(Check::new(
+            "Mounted",
+            CheckStatus::Warning,
+            format!("findmnt could not be run: {}", e),
+        )),
+    }
+
+    checks.push(match check_privilege(runner) {
+        Privilege::Root => Check::new("Privileges", CheckStatus::Ok, "running as root"),
+        Privilege::Sudo => Check::new(
+            "Privileges",
+            CheckStatus::Ok,
+            "sudo works without a password",
+        ),
+        Privilege::SudoWithPassword => Check::new(
+            "Privileges",
+            CheckStatus::Warning,
+            "sudo will ask for a password while mounting",
+        ),
+        Privilege::Unavailable => Check::new(
+            "Privileges",
+            CheckStatus::Problem,
+            "mounting needs root, but sudo is not available",
+        ),
+    });
+
+    checks
+}
+
+/// Prints a diagnosis of why `device` would or would not mount, without mounting it.
+pub fn mount_check(device: &str, theme: &str) -> color_eyre::Result<()> {
+    let (info_style, warning_style, error_style, success_style) =
+        UI::get_static_status_styles(theme);
+    let white_bold = console::Style::new().white().bold();
+
+    let checks = if !Path::new(device).exists() {
+        vec![Check::new(
+            "Device",
+            CheckStatus::Problem,
+            format!(
+                "{} does not exist; `tap list-devices` shows the partitions",
+                device
+            ),
+        )]
+    } else if !is_device_path(device) {
+        vec![Check::new(
+            "Device",
+            CheckStatus::Info,
+            format!(
+                "{} is not a block device; tap scans it as a path without mounting",
+                device
+            ),
+        )]
+    } else {
+        #[cfg(target_os = "linux")]
+        {
+            diagnose_device(&SystemCommands, device)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            vec![Check::new(
+                "Device",
+                CheckStatus::Info,
+                format!(
+                    "diagnostics are only available on Linux; try `diskutil info {}`",
+                    device
+                ),
+            )]
+        }
+    };
+
+    println!(
+        "{}",
+        info_style
+            .apply_to(format!("Mount check for {}", device))
+            .bold()
+    );
+    for check in &checks {
+        let tag = match check.status {
+            CheckStatus::Ok => success_style.apply_to("[✓]"),
+            CheckStatus::Info => info_style.apply_to("[*]"),
+            CheckStatus::Warning => warning_style.apply_to("[!]"),
+            CheckStatus::Problem => error_style.apply_to("[x]"),
+        };
+        println!(
+            "{} {} {}",
+            tag.bold(),
+            white_bold.apply_to(format!("{:<11}", format!("{}:", check.label))),
+            check.detail
+        );
+    }
+
+    let problems = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Problem)
+        .count();
+    println!();
+    if problems == 0 {
+        println!(
+            "{}",
+            success_style
+                .apply_to("No problems found; tap should be able to mount this device read-only")
+                .bold()
+        );
+    } else {
+        println!(
+            "{}",
+            error_style
+                .apply_to(format!(
+                    "{} problem(s) would stop tap from mounting this device",
+                    problems
+                ))
+                .bold()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "macos")),
+    allow(unused_variables)
+)]
+pub fn validate_source_path(
+    drive: &str,
+    options: &MountConfig,
+    theme: &str,
+) -> color_eyre::Result<PathBuf> {
     let (_, warning_style, error_style, _) = UI::get_static_status_styles(theme);
     let white_bold = console::Style::new().white().bold();
 
@@ -887,10 +1651,26 @@ pub fn validate_source_path(drive: &str, theme: &str) -> color_eyre::Result<Path
             error_style.apply_to("[!] ERROR:").bold(),
             white_bold.apply_to(format!("Path does not exist: {}", drive))
         );
-        std::process::exit(1);
+        return Err(color_eyre::eyre::eyre!("Invalid source {}", drive));
+    }
+
+    // The read-only check needs findmnt, so other platforms only get a warning
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        println!(
+            "{} {}",
+            warning_style.apply_to("[!] WARNING:").bold(),
+            white_bold.apply_to("Cannot verify read-only status on this platform.")
+        );
+        println!(
+            "{}",
+            white_bold
+                .apply_to("   Make sure the source is write-protected to preserve the evidence.")
+        );
     }
 
     // Warn if not mounted read-only
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
     if !is_mounted_readonly(&path)? {
         println!(
             "{} {}",
@@ -902,27 +1682,45 @@ pub fn validate_source_path(drive: &str, theme: &str) -> color_eyre::Result<Path
             white_bold.apply_to("   This could potentially modify the evidence.")
         );
 
-        let should_continue = Confirm::with_theme(&colorful_theme)
-            .with_prompt("Continue anyway?")
-            .default(false)
-            .interact()?;
+        // --assume-yes only covers safe prompts, so a writable source is refused
+        if options.assume_yes {
+            println!(
+                "{}",
+                white_bold.apply_to(
+                    "   Remount the source read-only, or run without --assume-yes to continue."
+                )
+            );
+        }
+
+        let colorful_theme = UI::get_colorful_theme(theme);
+        let should_continue = !options.assume_yes
+            && Confirm::with_theme(&colorful_theme)
+                .with_prompt("Continue anyway?")
+                .default(false)
+                .interact()?;
 
         if !should_continue {
             println!("{}", white_bold.apply_to("Aborted."));
-            std::process::exit(0);
+            return Err(color_eyre::eyre::eyre!(
+                "{} is not mounted read-only",
+                drive
+            ));
         }
     }
 
     Ok(path)
 }
 
+#[cfg(target_os = "linux")]
 pub fn unmount_drive(mount_point: &Path, _device: &str, theme: &str) -> color_eyre::Result<()> {
+    let runner = SystemCommands;
     let (info_style, warning_style, _, success_style) = UI::get_static_status_styles(theme);
     let white_bold = console::Style::new().white().bold();
 
-    // Only unmount if it's a mount point we created
+    // Only unmount if it's a mount point we created, here or in an earlier run
+    let registered = take_mount(mount_point);
     let mount_point_str = mount_point.to_string_lossy();
-    if !mount_point_str.starts_with("/mnt/tap_") {
+    if registered.is_none() && !mount_point_str.starts_with("/mnt/tap_") {
         println!(
             "{} {}",
             info_style.apply_to("[*]").bold(),
@@ -937,20 +1735,15 @@ pub fn unmount_drive(mount_point: &Path, _device: &str, theme: &str) -> color_ey
         white_bold.apply_to(format!("Unmounting {}...", mount_point.display()))
     );
 
-    let output = Command::new("sudo")
-        .args(["umount", mount_point.to_str().unwrap()])
-        .output()?;
+    let output = runner.run("sudo", &["umount", mount_point.to_str().unwrap()])?;
 
-    if !output.status.success() {
+    if !output.success {
         println!(
             "{} {}",
             warning_style.apply_to("[!] WARNING:").bold(),
             white_bold.apply_to("Failed to unmount drive")
         );
-        println!(
-            "{}",
-            white_bold.apply_to(String::from_utf8_lossy(&output.stderr))
-        );
+        println!("{}", white_bold.apply_to(&output.stderr));
         return Err(color_eyre::eyre::eyre!("Failed to unmount drive"));
     }
 
@@ -960,18 +1753,567 @@ pub fn unmount_drive(mount_point: &Path, _device: &str, theme: &str) -> color_ey
         white_bold.apply_to("Drive unmounted successfully")
     );
 
-    // Try to remove the mount point directory
-    let output = Command::new("sudo")
-        .args(["rmdir", mount_point.to_str().unwrap()])
-        .output()?;
+    // Try to remove the mount point directory, unless it existed before tap mounted there
+    if registered.unwrap_or(true) {
+        let output = runner.run("sudo", &["rmdir", mount_point.to_str().unwrap()])?;
 
-    if output.status.success() {
-        println!(
-            "{} {}",
-            success_style.apply_to("[✓]").bold(),
-            white_bold.apply_to("Mount point removed")
-        );
+        if output.success {
+            println!(
+                "{} {}",
+                success_style.apply_to("[✓]").bold(),
+                white_bold.apply_to("Mount point removed")
+            );
+        }
     }
 
     Ok(())
 }
+
+/// Mount a device read-only using `diskutil`.
+#[cfg(target_os = "macos")]
+pub async fn mount_drive_readonly(
+    device: &str,
+    options: &MountConfig,
+    theme: &str,
+) -> color_eyre::Result<PathBuf> {
+    crate::diskutil::mount_readonly(device, options, theme)
+}
+
+/// Mount a device read-only with `diskutil`, without printing or prompting.
+#[cfg(target_os = "macos")]
+pub fn mount_device(device: &str, options: &MountConfig) -> color_eyre::Result<PathBuf> {
+    crate::diskutil::mount_device(device, options)
+}
+
+/// Unmount a volume mounted by [`mount_device`] without printing.
+#[cfg(target_os = "macos")]
+pub fn unmount_device(mount_point: &Path) -> color_eyre::Result<()> {
+    crate::diskutil::unmount_device(mount_point)
+}
+
+/// Check whether the filesystem containing `path` is mounted read-only.
+#[cfg(target_os = "macos")]
+pub fn is_mounted_readonly(path: &Path) -> color_eyre::Result<bool> {
+    crate::diskutil::is_mounted_readonly(path)
+}
+
+/// Unmount a volume with `diskutil`, if tap mounted it.
+#[cfg(target_os = "macos")]
+pub fn unmount_drive(mount_point: &Path, _device: &str, theme: &str) -> color_eyre::Result<()> {
+    crate::diskutil::unmount(mount_point, theme)
+}
+
+/// Re-checks, after an operation, that a device tap mounted read-only still is.
+///
+/// Returns a warning to show and log if the mount became writable during the
+/// run, or if its state can no longer be read.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn verify_still_readonly(mount_point: &Path, device: &str) -> Option<String> {
+    readonly_warning(is_mounted_readonly(mount_point), mount_point, device)
+}
+
+/// Nothing is ever mounted by tap on this platform, so there is nothing to verify.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn verify_still_readonly(_mount_point: &Path, _device: &str) -> Option<String> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn readonly_warning(
+    readonly: color_eyre::Result<bool>,
+    mount_point: &Path,
+    device: &str,
+) -> Option<String> {
+    match readonly {
+        Ok(true) => None,
+        Ok(false) => Some(format!(
+            "{} at {} is no longer mounted read-only; it was remounted writable during the run, so the source may have changed",
+            device,
+            mount_point.display()
+        )),
+        Err(e) => Some(format!(
+            "Could not confirm that {} at {} is still mounted read-only: {}",
+            device,
+            mount_point.display(),
+            e
+        )),
+    }
+}
+
+/// Only Linux mounts through `sudo`; `diskutil` mounts external volumes without
+/// root and other platforms never mount, so there is nothing to check.
+#[cfg(not(target_os = "linux"))]
+pub fn ensure_mount_privileges(_theme: &str) -> color_eyre::Result<()> {
+    Ok(())
+}
+
+/// Mounting block devices is not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub async fn mount_drive_readonly(
+    device: &str,
+    _options: &MountConfig,
+    _theme: &str,
+) -> color_eyre::Result<PathBuf> {
+    Err(color_eyre::eyre::eyre!(
+        "Mounting {} is not supported on this platform; pass an accessible directory instead",
+        device
+    ))
+}
+
+/// Nothing is ever mounted by tap on this platform, so there is nothing to unmount.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn unmount_drive(_mount_point: &Path, _device: &str, _theme: &str) -> color_eyre::Result<()> {
+    Ok(())
+}
+
+/// Mounting block devices is not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn mount_device(device: &str, _options: &MountConfig) -> color_eyre::Result<PathBuf> {
+    Err(color_eyre::eyre::eyre!(
+        "Mounting {} is not supported on this platform; pass an accessible directory instead",
+        device
+    ))
+}
+
+/// Nothing is ever mounted by tap on this platform, so there is nothing to unmount.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn unmount_device(_mount_point: &Path) -> color_eyre::Result<()> {
+    Ok(())
+}
+
+/// Unmounts a drive tap mounted once an operation is done, or with `--no-unmount`
+/// leaves it in place and prints where it is and how to unmount it later.
+pub fn release_drive(
+    mount_point: &Path,
+    device: &str,
+    options: &MountConfig,
+    theme: &str,
+) -> color_eyre::Result<()> {
+    if !options.no_unmount {
+        return unmount_drive(mount_point, device, theme);
+    }
+
+    let (info_style, ..) = UI::get_static_status_styles(theme);
+    let white_bold = console::Style::new().white().bold();
+    println!(
+        "{} {}",
+        info_style.apply_to("[*]").bold(),
+        white_bold.apply_to(format!(
+            "Leaving {} mounted read-only at {}",
+            device,
+            mount_point.display()
+        ))
+    );
+    println!(
+        "{}",
+        white_bold.apply_to(format!(
+            "    Unmount it when done with: {}",
+            unmount_hint(mount_point)
+        ))
+    );
+    Ok(())
+}
+
+/// The command that unmounts a drive tap left mounted.
+fn unmount_hint(mount_point: &Path) -> String {
+    let program = if cfg!(target_os = "macos") {
+        "diskutil unmount"
+    } else {
+        "sudo umount"
+    };
+    format!("{} {}", program, mount_point.display())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::command::FakeCommands;
+
+    #[test]
+    fn test_release_drive_no_unmount() {
+        let mount_point = Path::new("/mnt/tap_sdz1");
+        let mut options = crate::config::Config::default().mount;
+        options.no_unmount = true;
+
+        // Left in place without running umount, which would fail on this path
+        release_drive(mount_point, "/dev/sdz1", &options, "default").unwrap();
+        assert_eq!(unmount_hint(mount_point), "sudo umount /mnt/tap_sdz1");
+    }
+
+    #[test]
+    fn test_check_privilege() {
+        let root = FakeCommands::new().with("id -u", "0\n");
+        assert_eq!(check_privilege(&root), Privilege::Root);
+
+        let sudo = FakeCommands::new()
+            .with("id -u", "1000\n")
+            .with("sudo -n true", "");
+        assert_eq!(check_privilege(&sudo), Privilege::Sudo);
+
+        let password = FakeCommands::new()
+            .with("id -u", "1000\n")
+            .with_failure("sudo -n true", "sudo: a password is required");
+        assert_eq!(check_privilege(&password), Privilege::SudoWithPassword);
+
+        let missing = FakeCommands::new().with("id -u", "1000\n");
+        assert_eq!(check_privilege(&missing), Privilege::Unavailable);
+    }
+
+    #[test]
+    fn test_diagnose_device() {
+        let ntfs = FakeCommands::new()
+            .with("blkid -s TYPE -o value /dev/sdb1", "ntfs\n")
+            .with_failure("which ntfs-3g", "")
+            .with("modinfo ntfs3", "name: ntfs3\n")
+            .with("findmnt -n -o TARGET /dev/sdb1", "/media/usb\n")
+            .with("findmnt -n -o OPTIONS /media/usb", "rw,relatime\n")
+            .with("id -u", "0\n");
+        let checks = diagnose_device(&ntfs, "/dev/sdb1");
+        let statuses: Vec<_> = checks.iter().map(|c| (c.label, c.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Filesystem", CheckStatus::Ok),
+                ("Helper", CheckStatus::Ok),
+                ("Mounted", CheckStatus::Info),
+                ("Read-only", CheckStatus::Warning),
+                ("Privileges", CheckStatus::Ok),
+            ]
+        );
+        assert!(checks[1].detail.contains("ntfs3"));
+        assert_eq!(checks[2].detail, "at /media/usb");
+
+        // Encrypted and unmounted, with no way to become root
+        let luks = FakeCommands::new()
+            .with("blkid -s TYPE -o value /dev/sdc1", "crypto_LUKS\n")
+            .with_failure("findmnt -n -o TARGET /dev/sdc1", "")
+            .with("id -u", "1000\n");
+        let checks = diagnose_device(&luks, "/dev/sdc1");
+        let problems: Vec<_> = checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Problem)
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(problems, vec!["Container", "Privileges"]);
+        assert!(
+            checks[1]
+                .detail
+                .contains("cryptsetup open --readonly /dev/sdc1")
+        );
+        assert_eq!(checks[2].detail, "not mounted");
+    }
+
+    const MDADM_EXAMINE: &str = "\
+/dev/sdb1:
+          Magic : a92b4efc
+        Version : 1.2
+    Feature Map : 0x0
+     Array UUID : 3f2c9a4e:8b1d7c60:5e4f2a19:0c7b6d83
+           Name : evidence-host:0
+  Creation Time : Tue Mar  5 10:12:44 2024
+     Raid Level : raid1
+   Raid Devices : 2
+";
+
+    const DMRAID_SETS: &str = "\
+*** Group superset isw_cbadgfeh
+--> Subset
+name   : isw_cbadgfeh_Volume0
+size   : 976766976
+stride : 128
+type   : mirror
+status : ok
+";
+
+    #[test]
+    fn test_parse_mdadm_examine() {
+        let metadata = parse_mdadm_examine(MDADM_EXAMINE);
+
+        assert_eq!(
+            metadata.uuid.as_deref(),
+            Some("3f2c9a4e:8b1d7c60:5e4f2a19:0c7b6d83")
+        );
+        assert_eq!(metadata.name.as_deref(), Some("evidence-host:0"));
+        assert_eq!(metadata.raid_level.as_deref(), Some("raid1"));
+        assert_eq!(metadata.raid_devices, Some(2));
+        assert_eq!(metadata.total_devices, None);
+    }
+
+    #[test]
+    fn test_parse_mdadm_examine_v090() {
+        let metadata = parse_mdadm_examine(
+            "\
+          Magic : a92b4efc
+        Version : 0.90.00
+           UUID : a1b2c3d4:e5f60718:293a4b5c:6d7e8f90 (local to host evidence)
+     Raid Level : raid5
+   Raid Devices : 3
+  Total Devices : 2
+           Name : evidence:0  (local to host evidence)
+",
+        );
+
+        assert_eq!(
+            metadata.uuid.as_deref(),
+            Some("a1b2c3d4:e5f60718:293a4b5c:6d7e8f90")
+        );
+        assert_eq!(
+            metadata.name.as_deref(),
+            Some("evidence:0  (local to host evidence)")
+        );
+        assert_eq!(metadata.raid_devices, Some(3));
+        assert_eq!(metadata.total_devices, Some(2));
+    }
+
+    #[test]
+    fn test_split_field() {
+        assert_eq!(
+            split_field("     Array UUID : 3f2c:8b1d:5e4f:0c7b  "),
+            Some(("Array UUID", "3f2c:8b1d:5e4f:0c7b".to_string()))
+        );
+        assert_eq!(split_field("Personalities"), None);
+        assert_eq!(split_field("  State : "), None);
+    }
+
+    #[test]
+    fn test_parse_dmraid_sets() {
+        let metadata = parse_dmraid_sets(DMRAID_SETS);
+
+        assert_eq!(
+            metadata.raid_set_name.as_deref(),
+            Some("isw_cbadgfeh_Volume0")
+        );
+        assert_eq!(metadata.raid_type.as_deref(), Some("mirror"));
+        assert_eq!(metadata.status.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn test_parse_mdstat_arrays() {
+        let mdstat = "\
+Personalities : [raid1]
+md127 : active (read-only) raid1 sdb1[0]
+      976630464 blocks super 1.2 [2/1] [U_]
+
+unused devices: <none>
+";
+        assert_eq!(parse_mdstat_arrays(mdstat), vec!["/dev/md127"]);
+    }
+
+    #[test]
+    fn test_first_free_md_device() {
+        let mdstat = "\
+Personalities : [raid1] [raid5]
+md127 : active raid1 sdb1[0] sdc1[1]
+md126 : inactive sdd1[0](S)
+";
+        let dev_names: Vec<String> = ["md", "md125", "mem", "null", "sda"]
+            .map(String::from)
+            .to_vec();
+
+        assert_eq!(
+            first_free_md_device(mdstat, &dev_names).as_deref(),
+            Some("/dev/md124")
+        );
+        assert_eq!(first_free_md_device("", &[]).as_deref(), Some("/dev/md127"));
+    }
+
+    #[test]
+    fn test_raid_detection_via_blkid() {
+        let runner = FakeCommands::new()
+            .with("blkid -s TYPE -o value /dev/sdb1", "linux_raid_member\n")
+            .with("blkid -s TYPE -o value /dev/sdc1", "isw_raid_member\n")
+            .with("blkid -s TYPE -o value /dev/sdd1", "ntfs\n")
+            .with_failure("blkid -s TYPE -o value /dev/sde1", "");
+
+        assert!(is_raid_member(&runner, "/dev/sdb1").unwrap());
+        assert!(!is_isw_raid_member(&runner, "/dev/sdb1").unwrap());
+        assert!(is_isw_raid_member(&runner, "/dev/sdc1").unwrap());
+        assert!(!is_raid_member(&runner, "/dev/sdd1").unwrap());
+        assert_eq!(
+            get_filesystem_type(&runner, "/dev/sdd1")
+                .unwrap()
+                .as_deref(),
+            Some("ntfs")
+        );
+        assert_eq!(get_filesystem_type(&runner, "/dev/sde1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_raid_info_via_runner() {
+        let runner = FakeCommands::new()
+            .with("sudo mdadm --examine /dev/sdb1", MDADM_EXAMINE)
+            .with_failure("sudo mdadm --examine /dev/sdd1", "No md superblock")
+            .with("sudo dmraid -s -c", "isw_cbadgfeh_Volume0\n")
+            .with(
+                "sudo dmraid -r",
+                "/dev/sdc: isw, \"isw_cbadgfeh\", GROUP, ok\n",
+            )
+            .with("sudo dmraid -s", DMRAID_SETS);
+
+        let md = get_raid_array_info(&runner, "/dev/sdb1").unwrap().unwrap();
+        assert_eq!(md.raid_level.as_deref(), Some("raid1"));
+        assert!(get_raid_array_info(&runner, "/dev/sdd1").unwrap().is_none());
+
+        let dm = get_dmraid_info(&runner, "/dev/sdc").unwrap().unwrap();
+        assert_eq!(dm.raid_type.as_deref(), Some("mirror"));
+        assert!(get_dmraid_info(&runner, "/dev/sdf").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_findmnt_helpers() {
+        let runner = FakeCommands::new()
+            .with("findmnt -n -o TARGET /dev/sdb1", "/media/usb\n/mnt/copy\n")
+            .with_failure("findmnt -n -o TARGET /dev/sdc1", "")
+            .with(
+                "findmnt -n -o OPTIONS /media/usb",
+                "ro,nosuid,nodev,relatime\n",
+            )
+            .with("findmnt -n -o OPTIONS /mnt/rw", "rw,relatime,errors=ro\n");
+
+        assert_eq!(
+            findmnt_target(&runner, "/dev/sdb1").unwrap(),
+            Some(PathBuf::from("/media/usb"))
+        );
+        assert_eq!(findmnt_target(&runner, "/dev/sdc1").unwrap(), None);
+        assert!(findmnt_readonly(&runner, Path::new("/media/usb")).unwrap());
+        assert!(!findmnt_readonly(&runner, Path::new("/mnt/rw")).unwrap());
+    }
+
+    #[test]
+    fn test_readonly_warning() {
+        let runner = FakeCommands::new()
+            .with("findmnt -n -o OPTIONS /mnt/tap_sdb1", "ro,relatime\n")
+            .with("findmnt -n -o OPTIONS /mnt/tap_sdc1", "rw,relatime\n");
+
+        let still = Path::new("/mnt/tap_sdb1");
+        assert_eq!(
+            readonly_warning(findmnt_readonly(&runner, still), still, "/dev/sdb1"),
+            None
+        );
+
+        let remounted = Path::new("/mnt/tap_sdc1");
+        let warning =
+            readonly_warning(findmnt_readonly(&runner, remounted), remounted, "/dev/sdc1").unwrap();
+        assert!(warning.contains("/dev/sdc1"));
+        assert!(warning.contains("no longer mounted read-only"));
+
+        let failed = readonly_warning(Err(color_eyre::eyre::eyre!("boom")), still, "/dev/sdb1");
+        assert!(failed.unwrap().contains("Could not confirm"));
+    }
+
+    #[test]
+    fn test_ntfs_driver_falls_back_to_ntfs3() {
+        let with_ntfs3g = FakeCommands::new().with("which ntfs-3g", "/usr/bin/ntfs-3g\n");
+        let without_ntfs3g = FakeCommands::new().with_failure("which ntfs-3g", "");
+        assert_eq!(ntfs_driver(&with_ntfs3g), NtfsDriver::Ntfs3g);
+        assert_eq!(ntfs_driver(&without_ntfs3g), NtfsDriver::Ntfs3);
+        // `which` itself missing
+        assert_eq!(ntfs_driver(&FakeCommands::new()), NtfsDriver::Ntfs3);
+
+        assert_eq!(
+            mount_command(Some(NtfsDriver::Ntfs3), "/dev/sdb1", "/mnt/tap_sdb1"),
+            vec![
+                "mount",
+                "-t",
+                "ntfs3",
+                "-o",
+                "ro",
+                "/dev/sdb1",
+                "/mnt/tap_sdb1"
+            ]
+        );
+        assert_eq!(
+            mount_command(Some(NtfsDriver::Ntfs3g), "/dev/sdb1", "/mnt/tap_sdb1"),
+            vec!["ntfs-3g", "-o", "ro", "/dev/sdb1", "/mnt/tap_sdb1"]
+        );
+        assert_eq!(
+            mount_command(None, "/dev/sdb1", "/mnt/tap_sdb1"),
+            vec!["mount", "-o", "ro", "/dev/sdb1", "/mnt/tap_sdb1"]
+        );
+    }
+
+    #[test]
+    fn test_check_mount_point() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        assert!(check_mount_point(&root.path().join("case42")).is_ok());
+        assert!(check_mount_point(root.path()).is_ok());
+
+        std::fs::write(root.path().join("evidence.txt"), b"data").unwrap();
+        assert!(check_mount_point(root.path()).is_err());
+        assert!(check_mount_point(&root.path().join("evidence.txt")).is_err());
+    }
+
+    #[test]
+    fn test_mount_device() {
+        let options = crate::config::Config::default().mount;
+        let runner = FakeCommands::new()
+            .with("id -u", "0\n")
+            .with("blkid -s TYPE -o value /dev/sdy1", "ext4\n")
+            .with_failure("findmnt -n -o TARGET /dev/sdy1", "")
+            .with("sudo mkdir -p /mnt/tap_sdy1", "")
+            .with("sudo mount -o ro /dev/sdy1 /mnt/tap_sdy1", "")
+            .with("sudo umount /mnt/tap_sdy1", "")
+            .with("sudo rmdir /mnt/tap_sdy1", "");
+        let mount_point = mount_device_with(&runner, "/dev/sdy1", &options).unwrap();
+        assert_eq!(mount_point, Path::new("/mnt/tap_sdy1"));
+        unmount_device_with(&runner, &mount_point).unwrap();
+        // Only tap's own mounts are unmounted, and only once
+        unmount_device_with(&FakeCommands::new(), &mount_point).unwrap();
+
+        // A read-write mount is only remounted when the caller agreed to it
+        let mut runner = FakeCommands::new()
+            .with("id -u", "0\n")
+            .with("blkid -s TYPE -o value /dev/sdy2", "ext4\n")
+            .with("findmnt -n -o TARGET /dev/sdy2", "/media/usb\n")
+            .with("findmnt -n -o OPTIONS /media/usb", "rw,relatime\n");
+        assert!(mount_device_with(&runner, "/dev/sdy2", &options).is_err());
+        runner = runner.with("sudo mount -o remount,ro /dev/sdy2", "");
+        let mut assume_yes = options.clone();
+        assume_yes.assume_yes = true;
+        assert_eq!(
+            mount_device_with(&runner, "/dev/sdy2", &assume_yes).unwrap(),
+            Path::new("/media/usb")
+        );
+
+        // sudo must never stop to ask for a password
+        let password = FakeCommands::new()
+            .with("id -u", "1000\n")
+            .with_failure("sudo -n true", "a password is required");
+        assert!(mount_device_with(&password, "/dev/sdy1", &options).is_err());
+
+        let raid = FakeCommands::new()
+            .with("id -u", "0\n")
+            .with("blkid -s TYPE -o value /dev/sdy3", "linux_raid_member\n");
+        assert!(mount_device_with(&raid, "/dev/sdy3", &options).is_err());
+    }
+
+    #[test]
+    fn test_overlapping_sources() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let users = root.path().join("Users");
+        std::fs::create_dir_all(users.join("alice")).unwrap();
+        std::fs::create_dir(root.path().join("Users2")).unwrap();
+
+        let sources = [
+            users.clone(),
+            users.join("alice"),
+            root.path().join("Users/../Users"),
+            root.path().join("Users2"),
+        ];
+        assert_eq!(overlapping_sources(&sources), vec![1, 2]);
+        assert!(overlapping_sources(&[users]).is_empty());
+    }
+
+    #[test]
+    fn test_drive_space() {
+        let space = drive_space(Path::new("/")).unwrap();
+        assert!(space.capacity > 0);
+        assert!(space.used <= space.capacity);
+        assert!(space.free <= space.capacity);
+
+        // A plain directory is not a mount point, so it has no drive figures
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir(root.path().join("Users")).unwrap();
+        assert_eq!(drive_space(&root.path().join("Users")), None);
+    }
+}
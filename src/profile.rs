@@ -0,0 +1,172 @@
+//! Built-in category presets.
+//!
+//! A profile restricts a run to the categories that matter for a common kind
+//! of case, without editing the config file. Each preset starts from the
+//! configured `[categories]`, keeps only its own categories, and tunes their
+//! extension lists; every other file is left out of the scan.
+
+use std::collections::HashMap;
+
+use crate::config::Config;
+
+/// Named category preset selected with `--profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Profile {
+    /// Photos, videos, and audio
+    Media,
+    /// Documents, spreadsheets, presentations, and email
+    Office,
+    /// Source code and configuration files
+    Code,
+}
+
+impl Profile {
+    /// Returns the categories for this profile, layered over `base`.
+    pub fn categories(self, base: &HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
+        match self {
+            Profile::Media => media(base),
+            Profile::Office => office(base),
+            Profile::Code => code(base),
+        }
+    }
+
+    /// Replaces the configured categories with this profile's and leaves every
+    /// unrecognized file out of the scan.
+    pub fn apply(self, config: &mut Config) {
+        config.categories = self.categories(&config.categories);
+        config.scan.exclude_categories.push("misc".to_string());
+    }
+}
+
+/// Photo, video, and audio categories, without design files and with extra camera formats.
+fn media(base: &HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
+    let mut categories = keep(base, &["images", "videos", "audio"]);
+    remove(
+        &mut categories,
+        "images",
+        &[".psd", ".ai", ".eps", ".indd", ".xcf", ".sketch", ".fig"],
+    );
+    add(
+        &mut categories,
+        "images",
+        &[
+            ".cr3", ".crw", ".raf", ".srw", ".pef", ".x3f", ".3fr", ".avif", ".jxl",
+        ],
+    );
+    add(
+        &mut categories,
+        "videos",
+        &[".avchd", ".braw", ".r3d", ".insv"],
+    );
+    add(&mut categories, "audio", &[".m4b", ".dsf", ".dff", ".wv"]);
+    categories
+}
+
+/// Office document categories, with Outlook and OpenDocument extras.
+fn office(base: &HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
+    let mut categories = keep(
+        base,
+        &["documents", "spreadsheets", "presentations", "email"],
+    );
+    add(
+        &mut categories,
+        "documents",
+        &[".odm", ".oxps", ".xps", ".epub"],
+    );
+    add(&mut categories, "spreadsheets", &[".fods", ".xlr"]);
+    add(&mut categories, "presentations", &[".fodp", ".sldx"]);
+    add(
+        &mut categories,
+        "email",
+        &[".olm", ".nsf", ".dbx", ".vcf", ".ics"],
+    );
+    categories
+}
+
+/// Source code and configuration categories, with build files and more languages.
+fn code(base: &HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
+    let mut categories = keep(base, &["code", "config"]);
+    add(
+        &mut categories,
+        "code",
+        &[
+            ".zig", ".ex", ".exs", ".hs", ".clj", ".erl", ".jl", ".nim", ".sol", ".ipynb",
+            ".gradle", ".cmake", ".mk",
+        ],
+    );
+    add(
+        &mut categories,
+        "config",
+        &[".tf", ".tfvars", ".hcl", ".proto"],
+    );
+    categories
+}
+
+/// Copies the named categories out of `base`, skipping any that are not defined.
+fn keep(base: &HashMap<String, Vec<String>>, names: &[&str]) -> HashMap<String, Vec<String>> {
+    names
+        .iter()
+        .filter_map(|name| base.get(*name).map(|exts| (name.to_string(), exts.clone())))
+        .collect()
+}
+
+/// Adds extensions to a category, creating it if needed and skipping ones already listed.
+fn add(categories: &mut HashMap<String, Vec<String>>, category: &str, extensions: &[&str]) {
+    let list = categories.entry(category.to_string()).or_default();
+    for ext in extensions {
+        if !list.iter().any(|e| e == ext) {
+            list.push(ext.to_string());
+        }
+    }
+}
+
+/// Removes extensions from a category.
+fn remove(categories: &mut HashMap<String, Vec<String>>, category: &str, extensions: &[&str]) {
+    if let Some(list) = categories.get_mut(category) {
+        list.retain(|e| !extensions.contains(&e.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_restricts_categories() {
+        let mut config = Config::default();
+        Profile::Media.apply(&mut config);
+
+        let mut names: Vec<_> = config.categories.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, vec!["audio", "images", "videos"]);
+        assert!(config.categories["images"].contains(&".cr3".to_string()));
+        assert!(!config.categories["images"].contains(&".psd".to_string()));
+        assert!(config.scan.exclude_categories.contains(&"misc".to_string()));
+
+        // Without the code category, `.ts` is an MPEG transport stream
+        assert_eq!(config.category_map().category(".ts"), "videos");
+        assert_eq!(config.category_map().category(".docx"), "misc");
+    }
+
+    #[test]
+    fn test_profile_layers_over_configured_categories() {
+        let mut base = HashMap::new();
+        base.insert(
+            "code".to_string(),
+            vec![".rs".to_string(), ".zig".to_string()],
+        );
+        base.insert("images".to_string(), vec![".png".to_string()]);
+
+        let categories = Profile::Code.categories(&base);
+        assert!(!categories.contains_key("images"));
+        assert_eq!(
+            categories["code"]
+                .iter()
+                .filter(|e| e.as_str() == ".zig")
+                .count(),
+            1
+        );
+        assert!(categories["code"].contains(&".rs".to_string()));
+        assert!(categories["config"].contains(&".tf".to_string()));
+    }
+}
@@ -0,0 +1,169 @@
+//! Progress heartbeat file.
+//!
+//! `export --progress-file <PATH>` appends one JSON object per line to a file
+//! as the export runs: the current phase, files processed, total, and
+//! percentage. The file does not depend on the terminal, so a long run over
+//! SSH can be followed with `tail -f` from another session, and the last line
+//! still shows how far it got if the session dropped.
+//!
+//! ```text
+//! {"time":"2026-10-17T09:12:03Z","phase":"copy","done":1200,"total":48000,"percent":2.5,"finished":false}
+//! ```
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Least time between two lines written for the same phase.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// One line of the progress file.
+#[derive(Serialize)]
+struct Heartbeat<'a> {
+    /// When the line was written, in RFC 3339 UTC
+    time: String,
+    phase: &'a str,
+    done: u64,
+    total: u64,
+    percent: f64,
+    /// The whole run is over; `phase` says how it ended
+    finished: bool,
+}
+
+struct State {
+    file: File,
+    phase: &'static str,
+    done: u64,
+    total: u64,
+    last_write: Instant,
+}
+
+/// Writes progress heartbeats to a file, or nothing when no path was given.
+///
+/// Updates are cheap to call from progress callbacks: a line is written at
+/// most every [`HEARTBEAT_INTERVAL`], plus one at the start of each phase and
+/// one when the run finishes. Write failures are ignored so a full disk or
+/// removed file never interrupts the run being reported.
+pub struct ProgressFile {
+    state: Option<Mutex<State>>,
+}
+
+impl ProgressFile {
+    /// Creates (or truncates) the file at `path`; `None` disables the heartbeat.
+    pub fn new(path: Option<&Path>) -> color_eyre::Result<Self> {
+        let state = match path {
+            Some(path) => Some(Mutex::new(State {
+                file: File::create(path).map_err(|e| {
+                    color_eyre::eyre::eyre!("Cannot create progress file {}: {}", path.display(), e)
+                })?,
+                phase: "start",
+                done: 0,
+                total: 0,
+                last_write: Instant::now(),
+            })),
+            None => None,
+        };
+        Ok(Self { state })
+    }
+
+    /// Starts a phase of `total` items and records it right away.
+    pub fn start(&self, phase: &'static str, total: u64) {
+        if let Some(state) = &self.state
+            && let Ok(mut state) = state.lock()
+        {
+            state.phase = phase;
+            state.done = 0;
+            state.total = total;
+            state.write(false);
+        }
+    }
+
+    /// Counts one item processed, recording it if the last line is old enough.
+    pub fn inc(&self) {
+        if let Some(state) = &self.state
+            && let Ok(mut state) = state.lock()
+        {
+            state.done += 1;
+            if state.last_write.elapsed() >= HEARTBEAT_INTERVAL {
+                state.write(false);
+            }
+        }
+    }
+
+    /// Records the end of the run, e.g. `"complete"` or `"cancelled"`.
+    pub fn finish(&self, outcome: &'static str) {
+        if let Some(state) = &self.state
+            && let Ok(mut state) = state.lock()
+        {
+            state.phase = outcome;
+            state.write(true);
+        }
+    }
+}
+
+impl State {
+    fn write(&mut self, finished: bool) {
+        let percent = if self.total > 0 {
+            (self.done as f64 / self.total as f64 * 100.0).min(100.0)
+        } else {
+            0.0
+        };
+        let heartbeat = Heartbeat {
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            phase: self.phase,
+            done: self.done,
+            total: self.total,
+            percent: (percent * 10.0).round() / 10.0,
+            finished,
+        };
+        if let Ok(line) = serde_json::to_string(&heartbeat) {
+            let _ = writeln!(self.file, "{}", line);
+            let _ = self.file.flush();
+        }
+        self.last_write = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_file_heartbeats() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let path = dir.path().join("progress.jsonl");
+
+        let progress = ProgressFile::new(Some(&path)).unwrap();
+        progress.start("scan", 4);
+        for _ in 0..3 {
+            progress.inc();
+        }
+        progress.start("copy", 3);
+        progress.inc();
+        progress.finish("complete");
+
+        // Updates within the interval are not written, phase changes and the end are
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["phase"], "scan");
+        assert_eq!(lines[1]["phase"], "copy");
+        assert_eq!(lines[2]["phase"], "complete");
+        assert_eq!(lines[2]["done"], 1);
+        assert_eq!(lines[2]["percent"], 33.3);
+        assert_eq!(lines[2]["finished"], true);
+
+        // Without a path nothing is written and nothing fails
+        let disabled = ProgressFile::new(None).unwrap();
+        disabled.start("scan", 1);
+        disabled.inc();
+        disabled.finish("complete");
+        assert!(ProgressFile::new(Some(&dir.path().join("missing/progress.jsonl"))).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! HTML report generation.
+//!
+//! This module renders scan results into a single self-contained HTML page
+//! with a category bar chart, a statistics table, and the largest files, so
+//! results can be shared without the terminal UI.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::export::ExportStats;
+use crate::scanner::ScanStats;
+use crate::tui::{format_duration, format_size, format_special_entries};
+
+/// Number of entries shown in the largest files table.
+const TOP_FILES: usize = 10;
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 960px; color: #222; }
+h1 { margin-bottom: 0.2rem; }
+.meta { color: #666; margin-top: 0; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2rem; }
+th, td { text-align: left; padding: 0.35rem 0.6rem; border-bottom: 1px solid #ddd; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { background: #eee; height: 1rem; min-width: 12rem; }
+.bar span { display: block; height: 100%; background: #4a7bd0; }";
+
+/// Escapes text for inclusion in HTML element content or attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders the report page for a scan, with copy results when `export_stats` is given.
+///
+/// # Arguments
+///
+/// * `source` - The source path that was scanned
+/// * `scan_stats` - Statistics from the scan operation
+/// * `export_stats` - Statistics from the export, if files were copied
+pub fn render_html_report(
+    source: &Path,
+    scan_stats: &ScanStats,
+    export_stats: Option<&ExportStats>,
+) -> String {
+    let mut categories = scan_stats.get_summary();
+    categories.sort_by_key(|c| std::cmp::Reverse(c.2));
+
+    let mut files = scan_stats.get_all_files();
+    files.sort_by_key(|f| std::cmp::Reverse(f.1));
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>TAP Report</title>\n");
+    let _ = writeln!(html, "<style>\n{}\n</style>\n</head>\n<body>", STYLE);
+    html.push_str("<h1>TAP Report</h1>\n");
+    // Inspecting several sources at once lists all of them
+    let source = if scan_stats.sources.len() > 1 {
+        scan_stats
+            .sources
+            .iter()
+            .map(|s| scan_stats.source_label(s))
+            .collect::<Vec<_>>()
+            .join(", ")
+    } else {
+        source.display().to_string()
+    };
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">Source: {} &middot; Generated {}</p>",
+        escape(&source),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+
+    // Category chart, bars scaled to share of total size
+    html.push_str("<h2>Category Distribution</h2>\n<table>\n");
+    html.push_str("<tr><th>Category</th><th></th><th>Share</th><th>Size</th><th>Files</th></tr>\n");
+    for (category, count, size) in &categories {
+        let share = if scan_stats.total_size > 0 {
+            *size as f64 / scan_stats.total_size as f64 * 100.0
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td><div class=\"bar\"><span style=\"width: {:.2}%\"></span></div></td>\
+             <td class=\"num\">{:.2}%</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape(category),
+            share,
+            share,
+            format_size(*size),
+            count
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Statistics table
+    let average = if scan_stats.total_files > 0 {
+        scan_stats.total_size / scan_stats.total_files as u64
+    } else {
+        0
+    };
+    let mut rows = vec![
+        ("Total files", scan_stats.total_files.to_string()),
+        ("Total size", format_size(scan_stats.total_size)),
+        ("Apparent size", format_size(scan_stats.total_apparent_size)),
+        ("Sparse files", scan_stats.sparse_files.to_string()),
+        ("Empty files", scan_stats.empty_files.to_string()),
+        ("Average file size", format_size(average)),
+        (
+            "Total directories",
+            format!(
+                "{} ({} empty)",
+                scan_stats.total_dirs, scan_stats.empty_dirs
+            ),
+        ),
+        ("Total categories", categories.len().to_string()),
+        (
+            "Skipped by exclusions",
+            format!(
+                "{} files ({}) / {} directories",
+                scan_stats.excluded_files,
+                format_size(scan_stats.excluded_size),
+                scan_stats.excluded_dirs
+            ),
+        ),
+        ("Special entries", format_special_entries(scan_stats)),
+        ("Scan errors", scan_stats.errors.len().to_string()),
+        ("Scan time", format_duration(scan_stats.duration)),
+    ];
+    if let Some(export_stats) = export_stats {
+        rows.push(("Files copied", export_stats.copied.to_string()));
+        rows.push(("Files already present", export_stats.skipped.to_string()));
+        rows.push(("Files failed", export_stats.failed.to_string()));
+        rows.push(("Copy time", format_duration(export_stats.duration)));
+    }
+
+    html.push_str("<h2>Statistics</h2>\n<table>\n");
+    for (label, value) in rows {
+        let _ = writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            label,
+            escape(&value)
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Largest files leaderboard
+    let _ = writeln!(html, "<h2>Top {} Largest Files</h2>\n<table>", TOP_FILES);
+    html.push_str("<tr><th>Rank</th><th>Name</th><th>Size</th><th>Category</th></tr>\n");
+    for (rank, (name, size, apparent_size, category)) in files.iter().take(TOP_FILES).enumerate() {
+        let size = if apparent_size > size {
+            format!(
+                "{} (apparent {})",
+                format_size(*size),
+                format_size(*apparent_size)
+            )
+        } else {
+            format_size(*size)
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"num\">{}</td><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+            rank + 1,
+            escape(name),
+            size,
+            escape(category)
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+/// Writes the HTML report for a scan to `path`.
+///
+/// # Arguments
+///
+/// * `path` - Where to write the report
+/// * `source` - The source path that was scanned
+/// * `scan_stats` - Statistics from the scan operation
+/// * `export_stats` - Statistics from the export, if files were copied
+pub async fn write_html_report(
+    path: &Path,
+    source: &Path,
+    scan_stats: &ScanStats,
+    export_stats: Option<&ExportStats>,
+) -> color_eyre::Result<()> {
+    let html = render_html_report(source, scan_stats, export_stats);
+    tokio::fs::write(path, html).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FileInfo;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_render_html_report() {
+        let mut stats = ScanStats::new();
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/mnt/usb/<script>.pdf"),
+            extension: ".pdf".to_string(),
+            size: 3072,
+            allocated_size: 3072,
+            modified: None,
+            category: "documents".to_string(),
+            mime: None,
+        });
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/mnt/usb/photo.jpg"),
+            extension: ".jpg".to_string(),
+            size: 1024,
+            allocated_size: 1024,
+            modified: None,
+            category: "images".to_string(),
+            mime: None,
+        });
+
+        let html = render_html_report(Path::new("/mnt/usb"), &stats, None);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Source: /mnt/usb"));
+        assert!(html.contains("<span style=\"width: 75.00%\"></span>"));
+        assert!(html.contains("&lt;script&gt;.pdf"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("Files copied"));
+
+        // Documents is larger, so it comes first in both the chart and the leaderboard
+        assert!(html.find("documents").unwrap() < html.find("images").unwrap());
+
+        let export_stats = ExportStats::new();
+        let html = render_html_report(Path::new("/mnt/usb"), &stats, Some(&export_stats));
+        assert!(html.contains("<tr><th>Files copied</th><td>0</td></tr>"));
+    }
+}
@@ -0,0 +1,156 @@
+//! Path-based category rules.
+//!
+//! Rules assign a category by where a file lives rather than by its
+//! extension. They come from `[[rules]]` entries in the config file and from a
+//! `--rules` file, are matched against the path relative to the scan root in
+//! `.tapignore` syntax, and are checked before the extension lookup. The first
+//! matching rule wins; files no rule matches are categorized by extension.
+//!
+//! A rules file holds one `pattern => category` mapping per line:
+//!
+//! ```text
+//! # Everything filed as an exhibit, whatever its type
+//! /Evidence/Exhibits/** => exhibits
+//! *.eml => email
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::ignore::Pattern;
+
+/// A single `pattern => category` mapping.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRule {
+    /// Pattern in `.tapignore` syntax, matched against the path relative to the scan root
+    pub pattern: String,
+    /// Category assigned to matching files
+    pub category: String,
+}
+
+/// Rules read from a `--rules` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RulesFile {
+    pub rules: Vec<CategoryRule>,
+}
+
+/// Parses the contents of a rules file, skipping blank lines and `#` comments.
+pub fn parse_rules(content: &str) -> Result<Vec<CategoryRule>, String> {
+    let mut rules = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (pattern, category) = line
+            .split_once("=>")
+            .map(|(p, c)| (p.trim(), c.trim()))
+            .filter(|(p, c)| !p.is_empty() && !c.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "line {}: expected 'pattern => category', found '{}'",
+                    number + 1,
+                    line
+                )
+            })?;
+
+        rules.push(CategoryRule {
+            pattern: pattern.to_string(),
+            category: category.to_string(),
+        });
+    }
+    Ok(rules)
+}
+
+/// Reads and parses a `--rules` file; used as the clap value parser.
+pub fn load_rules_file(path: &str) -> Result<RulesFile, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    parse_rules(&content).map(|rules| RulesFile { rules })
+}
+
+/// Compiled rules, in the order they are checked.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<(Pattern, String)>,
+}
+
+impl RuleSet {
+    pub fn new(rules: &[CategoryRule]) -> Self {
+        Self {
+            rules: rules
+                .iter()
+                .filter_map(|rule| {
+                    Pattern::parse(&rule.pattern).map(|pattern| (pattern, rule.category.clone()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the category of the first rule matching `relative`, if any.
+    pub fn category(&self, relative: &Path) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.matches(relative, false))
+            .map(|(_, category)| category.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules(
+            "# location implies category\n\
+             \n\
+             /Evidence/Exhibits/** => exhibits\n\
+             *.eml=>email\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                CategoryRule {
+                    pattern: "/Evidence/Exhibits/**".to_string(),
+                    category: "exhibits".to_string(),
+                },
+                CategoryRule {
+                    pattern: "*.eml".to_string(),
+                    category: "email".to_string(),
+                },
+            ]
+        );
+
+        assert!(parse_rules("Evidence/**\n").is_err());
+        assert!(parse_rules("Evidence/** => \n").is_err());
+    }
+
+    #[test]
+    fn test_rule_set_first_match_wins() {
+        let rules = parse_rules(
+            "/Evidence/Exhibits/** => exhibits\n\
+             *.pdf => pdfs\n\
+             **/Exhibits/** => other\n",
+        )
+        .unwrap();
+        let rules = RuleSet::new(&rules);
+
+        assert_eq!(
+            rules.category(Path::new("Evidence/Exhibits/A/photo.jpg")),
+            Some("exhibits")
+        );
+        assert_eq!(
+            rules.category(Path::new("Evidence/Exhibits/scan.pdf")),
+            Some("exhibits")
+        );
+        assert_eq!(rules.category(Path::new("Users/me/scan.pdf")), Some("pdfs"));
+        assert_eq!(
+            rules.category(Path::new("Users/Exhibits/photo.jpg")),
+            Some("other")
+        );
+        assert_eq!(rules.category(Path::new("Evidence/photo.jpg")), None);
+    }
+}
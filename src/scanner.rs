@@ -4,39 +4,249 @@
 //! based on their extensions. It supports parallel processing and progress tracking
 //! for efficient analysis of large file systems.
 
-use std::collections::HashMap;
+use rand::SeedableRng;
+use rand::seq::IndexedRandom;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::task;
 use walkdir::WalkDir;
 
-use crate::categories::{get_category, get_extension};
+use crate::categories::get_extension;
+use crate::categorizer::{Categorizer, ConfigCategorizer};
+use crate::config::Config;
+use crate::control::Control;
+use crate::ignore::{Pattern, load_tapignore};
+use crate::log::ErrorEcho;
+use crate::mime::{Mime, detect_mime};
 
 /// Information about a scanned file.
 ///
 /// Contains metadata about a file discovered during directory scanning,
 /// including its path, size, and categorization.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
     pub path: PathBuf,
-    /// Size of the file in bytes
+    /// Lowercase extension including the leading dot (e.g. ".jpg"), or empty
+    pub extension: String,
+    /// Apparent size of the file in bytes
     pub size: u64,
+    /// Bytes actually allocated on disk; less than `size` for sparse files
+    pub allocated_size: u64,
+    /// Last modification time, if the filesystem reports one
+    pub modified: Option<SystemTime>,
     /// The category this file belongs to (e.g., "images", "documents")
     pub category: String,
+    /// MIME type detected with `scan.detect_mime`, e.g. "image/jpeg"
+    #[serde(default)]
+    pub mime: Option<String>,
+}
+
+impl FileInfo {
+    /// Returns true if the file occupies less disk space than its apparent size.
+    pub fn is_sparse(&self) -> bool {
+        self.allocated_size < self.size
+    }
+}
+
+/// What a walked entry is when it is neither a regular file nor a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SpecialKind {
+    /// A symbolic link whose target exists; links are never followed
+    Symlink,
+    /// A symbolic link whose target does not exist
+    BrokenSymlink,
+    BlockDevice,
+    CharDevice,
+    Fifo,
+    Socket,
+}
+
+impl SpecialKind {
+    /// Classifies a walked entry, or returns `None` for files and directories.
+    fn of(entry: &walkdir::DirEntry) -> Option<Self> {
+        let file_type = entry.file_type();
+        if file_type.is_symlink() {
+            return Some(if std::fs::metadata(entry.path()).is_ok() {
+                SpecialKind::Symlink
+            } else {
+                SpecialKind::BrokenSymlink
+            });
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+
+            if file_type.is_block_device() {
+                return Some(SpecialKind::BlockDevice);
+            }
+            if file_type.is_char_device() {
+                return Some(SpecialKind::CharDevice);
+            }
+            if file_type.is_fifo() {
+                return Some(SpecialKind::Fifo);
+            }
+            if file_type.is_socket() {
+                return Some(SpecialKind::Socket);
+            }
+        }
+
+        None
+    }
+
+    /// Human-readable name used in the statistics and log.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpecialKind::Symlink => "symlink",
+            SpecialKind::BrokenSymlink => "broken symlink",
+            SpecialKind::BlockDevice => "block device",
+            SpecialKind::CharDevice => "character device",
+            SpecialKind::Fifo => "FIFO",
+            SpecialKind::Socket => "socket",
+        }
+    }
+}
+
+/// A symlink, device node, FIFO, or socket found during a scan.
+///
+/// These are counted but never recorded as files, so they are not exported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecialEntry {
+    pub kind: SpecialKind,
+    pub path: PathBuf,
+}
+
+/// Why a path could not be scanned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorKind {
+    /// Access was refused; for a directory, nothing below it was walked
+    PermissionDenied,
+    /// Reading the path failed with another I/O error
+    Unreadable,
+    /// The path was removed while the scan was running
+    Vanished,
+}
+
+impl ErrorKind {
+    /// Classifies an I/O error.
+    pub fn from_io(kind: std::io::ErrorKind) -> Self {
+        match kind {
+            std::io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+            std::io::ErrorKind::NotFound => ErrorKind::Vanished,
+            _ => ErrorKind::Unreadable,
+        }
+    }
+
+    /// Human-readable name used in the summary and log.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorKind::PermissionDenied => "Permission denied",
+            ErrorKind::Unreadable => "Unreadable",
+            ErrorKind::Vanished => "Vanished during scan",
+        }
+    }
+}
+
+/// A path that could not be scanned, with the class of error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanError {
+    pub kind: ErrorKind,
+    pub path: PathBuf,
+}
+
+/// The errors of one kind, as summarized by [`ScanStats::get_error_summary`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorGroup {
+    pub kind: ErrorKind,
+    pub count: usize,
+    /// Offending paths with how many errors each accounts for, most first
+    pub paths: Vec<(PathBuf, usize)>,
 }
 
 /// Statistics collected during a directory scan.
 ///
 /// Aggregates information about all files discovered during a scan,
 /// organized by category, along with error information.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ScanStats {
+    /// The directory that was scanned; the first one when several scans were merged
+    pub root: PathBuf,
+    /// Every scanned directory, in order; more than one after [`ScanStats::merge`]
+    pub sources: Vec<PathBuf>,
+    /// Names for sources that are mounted partitions of disk images, shown instead of
+    /// their mount points; see [`ScanStats::source_label`]
+    #[serde(default)]
+    pub source_labels: HashMap<PathBuf, String>,
     pub files_by_category: HashMap<String, Vec<FileInfo>>,
     pub total_files: usize,
+    /// Bytes allocated on disk across all files
     pub total_size: u64,
+    /// Sum of apparent file sizes, which exceeds `total_size` when sparse files are present
+    pub total_apparent_size: u64,
+    /// Number of files that allocate less than their apparent size
+    pub sparse_files: usize,
+    /// Number of files with an apparent size of zero
+    #[serde(default)]
+    pub empty_files: usize,
+    /// Number of directories below the scan root
+    pub total_dirs: usize,
+    /// Directories with no file descendants
+    pub empty_dirs: usize,
+    /// Mount points below the root skipped because they are on another filesystem
+    /// or hold a pseudo-filesystem such as `/proc`
+    pub skipped_mounts: Vec<PathBuf>,
+    /// Files left out because their category is in `scan.exclude_categories`
+    pub excluded_by_category: usize,
+    /// Files left out by `scan.modified_after` or `scan.modified_before`
+    #[serde(default)]
+    pub excluded_by_date: usize,
+    /// Files skipped by exclusion rules (hidden entries, `node_modules`, ...)
+    pub excluded_files: usize,
+    /// Directories skipped by exclusion rules; their contents are not walked
+    pub excluded_dirs: usize,
+    /// Apparent size of `excluded_files`, not including the contents of `excluded_dirs`
+    pub excluded_size: u64,
     pub errors: Vec<String>,
+    /// The paths behind `errors` that could be classified, in the order they failed
+    pub error_paths: Vec<ScanError>,
+    /// Problems found around the scan that affect how far its results can be trusted
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Wall-clock time spent walking the tree
+    pub duration: Duration,
+    /// The scan was cancelled before the whole tree was walked
+    pub cancelled: bool,
+    /// The scan stopped early because it reached `scan.max_files`
+    pub limit_reached: bool,
+    /// Files matching `scan.sensitive_patterns`, sorted; only filled when `scan.flag_sensitive` is set
+    pub sensitive_files: Vec<PathBuf>,
+    /// Zero-byte files, sorted; only filled when `scan.report_empty` is set
+    #[serde(default)]
+    pub zero_byte_files: Vec<PathBuf>,
+    /// Symlinks, device nodes, FIFOs, and sockets, sorted by path
+    #[serde(default)]
+    pub special_entries: Vec<SpecialEntry>,
+    /// How the files were sampled with [`ScanStats::sampled`], if they were
+    #[serde(default)]
+    pub sample: Option<Sample>,
 }
 
+/// A random per-category sample, recorded so it can be drawn again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Most files kept from each category
+    pub per_category: usize,
+    /// Seed of the random choice
+    pub seed: u64,
+}
+
+/// Number of size ranges in [`ScanStats::get_size_histogram`].
+pub const SIZE_BUCKETS: usize = 7;
+
 impl Default for ScanStats {
     fn default() -> Self {
         Self::new()
@@ -47,24 +257,54 @@ impl ScanStats {
     /// Creates a new empty `ScanStats` instance.
     pub fn new() -> Self {
         Self {
+            root: PathBuf::new(),
+            sources: Vec::new(),
+            source_labels: HashMap::new(),
             files_by_category: HashMap::new(),
             total_files: 0,
             total_size: 0,
+            total_apparent_size: 0,
+            sparse_files: 0,
+            empty_files: 0,
+            total_dirs: 0,
+            empty_dirs: 0,
+            skipped_mounts: Vec::new(),
+            excluded_by_category: 0,
+            excluded_by_date: 0,
+            excluded_files: 0,
+            excluded_dirs: 0,
+            excluded_size: 0,
             errors: Vec::new(),
+            error_paths: Vec::new(),
+            warnings: Vec::new(),
+            duration: Duration::ZERO,
+            cancelled: false,
+            limit_reached: false,
+            sensitive_files: Vec::new(),
+            zero_byte_files: Vec::new(),
+            special_entries: Vec::new(),
+            sample: None,
         }
     }
 
     /// Adds a file to the statistics.
     ///
-    /// Updates the total file count, total size, and adds the file to its
-    /// corresponding category.
+    /// Updates the total file count, allocated and apparent sizes, the sparse
+    /// file count, and adds the file to its corresponding category.
     ///
     /// # Arguments
     ///
     /// * `file_info` - Information about the file to add
     pub fn add_file(&mut self, file_info: FileInfo) {
         self.total_files += 1;
-        self.total_size += file_info.size;
+        self.total_size += file_info.allocated_size;
+        self.total_apparent_size += file_info.size;
+        if file_info.is_sparse() {
+            self.sparse_files += 1;
+        }
+        if file_info.size == 0 {
+            self.empty_files += 1;
+        }
 
         self.files_by_category
             .entry(file_info.category.clone())
@@ -72,6 +312,136 @@ impl ScanStats {
             .push(file_info);
     }
 
+    /// Adds the results of another scan, for inspecting several sources at once.
+    ///
+    /// Totals are summed and lists concatenated. Files keep their full paths,
+    /// so [`ScanStats::get_source_summary`] can attribute them to a source.
+    pub fn merge(&mut self, other: ScanStats) {
+        if self.sources.is_empty() {
+            self.root = other.root;
+        }
+        self.sources.extend(other.sources);
+        self.source_labels.extend(other.source_labels);
+        for (category, files) in other.files_by_category {
+            self.files_by_category
+                .entry(category)
+                .or_default()
+                .extend(files);
+        }
+        self.total_files += other.total_files;
+        self.total_size += other.total_size;
+        self.total_apparent_size += other.total_apparent_size;
+        self.sparse_files += other.sparse_files;
+        self.empty_files += other.empty_files;
+        self.total_dirs += other.total_dirs;
+        self.empty_dirs += other.empty_dirs;
+        self.skipped_mounts.extend(other.skipped_mounts);
+        self.excluded_by_category += other.excluded_by_category;
+        self.excluded_by_date += other.excluded_by_date;
+        self.excluded_files += other.excluded_files;
+        self.excluded_dirs += other.excluded_dirs;
+        self.excluded_size += other.excluded_size;
+        self.errors.extend(other.errors);
+        self.error_paths.extend(other.error_paths);
+        self.warnings.extend(other.warnings);
+        self.duration += other.duration;
+        self.cancelled |= other.cancelled;
+        self.limit_reached |= other.limit_reached;
+        self.sensitive_files.extend(other.sensitive_files);
+        self.sensitive_files.sort();
+        self.zero_byte_files.extend(other.zero_byte_files);
+        self.zero_byte_files.sort();
+        self.special_entries.extend(other.special_entries);
+        self.special_entries.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    /// Counts `special_entries` per kind, in [`SpecialKind`] order.
+    pub fn get_special_summary(&self) -> Vec<(SpecialKind, usize)> {
+        let mut counts: HashMap<SpecialKind, usize> = HashMap::new();
+        for entry in &self.special_entries {
+            *counts.entry(entry.kind).or_default() += 1;
+        }
+        let mut summary: Vec<_> = counts.into_iter().collect();
+        summary.sort();
+        summary
+    }
+
+    /// Returns the results narrowed to the files in `categories`.
+    ///
+    /// File totals are recomputed from the kept files; the sources, errors, and
+    /// warnings of the scan are kept as they are.
+    pub fn with_categories(&self, categories: &[String]) -> ScanStats {
+        let mut narrowed = self.without_files();
+        for category in categories {
+            for file in self.files_by_category.get(category).into_iter().flatten() {
+                narrowed.add_file(file.clone());
+            }
+        }
+        narrowed
+    }
+
+    /// Returns the results narrowed to at most `per_category` randomly chosen
+    /// files from each category, recording the sample in `sample`.
+    ///
+    /// Files are ordered by path before the choice, so the same files and
+    /// `seed` always give the same sample. Totals are recomputed as for
+    /// [`ScanStats::with_categories`].
+    pub fn sampled(&self, per_category: usize, seed: u64) -> ScanStats {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut sampled = ScanStats {
+            sample: Some(Sample { per_category, seed }),
+            ..self.without_files()
+        };
+        let mut categories: Vec<_> = self.files_by_category.iter().collect();
+        categories.sort_by_key(|(category, _)| *category);
+        for (_, files) in categories {
+            let mut files: Vec<&FileInfo> = files.iter().collect();
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            let mut chosen: Vec<&FileInfo> = files
+                .choose_multiple(&mut rng, per_category)
+                .copied()
+                .collect();
+            chosen.sort_by(|a, b| a.path.cmp(&b.path));
+            for file in chosen {
+                sampled.add_file(file.clone());
+            }
+        }
+        sampled
+    }
+
+    /// Returns the sources, errors, warnings, and sample of the scan without any files.
+    fn without_files(&self) -> ScanStats {
+        ScanStats {
+            root: self.root.clone(),
+            sources: self.sources.clone(),
+            source_labels: self.source_labels.clone(),
+            errors: self.errors.clone(),
+            error_paths: self.error_paths.clone(),
+            warnings: self.warnings.clone(),
+            duration: self.duration,
+            limit_reached: self.limit_reached,
+            sample: self.sample,
+            ..ScanStats::new()
+        }
+    }
+
+    /// Returns the scanned source a file belongs to, or `root` if none match.
+    pub fn source_of(&self, path: &Path) -> &Path {
+        self.sources
+            .iter()
+            .filter(|source| path.starts_with(source))
+            .max_by_key(|source| source.components().count())
+            .unwrap_or(&self.root)
+    }
+
+    /// Returns how to show `source`: its label from `source_labels`, or else its path.
+    pub fn source_label(&self, source: &Path) -> String {
+        self.source_labels
+            .get(source)
+            .cloned()
+            .unwrap_or_else(|| source.display().to_string())
+    }
+
     /// Records an error encountered during scanning.
     ///
     /// # Arguments
@@ -81,10 +451,61 @@ impl ScanStats {
         self.errors.push(error);
     }
 
+    /// Records an error for `path`, classified by the I/O error behind it.
+    pub fn add_path_error(&mut self, path: &Path, kind: std::io::ErrorKind, error: String) {
+        self.errors.push(error);
+        self.error_paths.push(ScanError {
+            kind: ErrorKind::from_io(kind),
+            path: path.to_path_buf(),
+        });
+    }
+
+    /// Summarizes `error_paths` per kind, most frequent kind first.
+    ///
+    /// Each kind lists up to `limit` offending paths with their error counts.
+    /// Paths that failed alongside siblings are grouped under their parent
+    /// directory, so one inaccessible tree shows as a single line rather than
+    /// hundreds.
+    pub fn get_error_summary(&self, limit: usize) -> Vec<ErrorGroup> {
+        let mut by_kind: HashMap<ErrorKind, HashMap<&Path, Vec<&Path>>> = HashMap::new();
+        for error in &self.error_paths {
+            let parent = error.path.parent().unwrap_or(&error.path);
+            by_kind
+                .entry(error.kind)
+                .or_default()
+                .entry(parent)
+                .or_default()
+                .push(&error.path);
+        }
+
+        let mut summary: Vec<_> = by_kind
+            .into_iter()
+            .map(|(kind, groups)| {
+                let count: usize = groups.values().map(Vec::len).sum();
+                let mut paths: Vec<(PathBuf, usize)> = groups
+                    .into_iter()
+                    .map(|(parent, paths)| match paths.as_slice() {
+                        [path] => (path.to_path_buf(), 1),
+                        _ => (parent.to_path_buf(), paths.len()),
+                    })
+                    .collect();
+                paths.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                paths.truncate(limit);
+                ErrorGroup { kind, count, paths }
+            })
+            .collect();
+        summary.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.kind.label().cmp(b.kind.label()))
+        });
+        summary
+    }
+
     /// Generates a summary of files by category.
     ///
-    /// Returns a vector of tuples containing category name, file count, and total size.
-    /// The results are sorted by file count in descending order.
+    /// Returns a vector of tuples containing category name, file count, and total
+    /// allocated size. The results are sorted by file count in descending order.
     ///
     /// # Returns
     ///
@@ -95,12 +516,138 @@ impl ScanStats {
             .iter()
             .map(|(category, files)| {
                 let count = files.len();
-                let size: u64 = files.iter().map(|f| f.size).sum();
+                let size: u64 = files.iter().map(|f| f.allocated_size).sum();
                 (category.clone(), count, size)
             })
             .collect();
 
-        summary.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by count descending
+        summary.sort_by_key(|s| std::cmp::Reverse(s.1)); // Sort by count descending
+        summary
+    }
+
+    /// Counts files by apparent size in [`SIZE_BUCKETS`] buckets, a factor of
+    /// ten apart: under `unit`, `unit` to 10 × `unit`, ..., and 100 × `unit`²
+    /// and over. `unit` is 1024 or 1000, matching the size units on display.
+    ///
+    /// # Returns
+    ///
+    /// `(file_count, total_size)` for each bucket, smallest sizes first
+    pub fn get_size_histogram(&self, unit: u64) -> [(usize, u64); SIZE_BUCKETS] {
+        let bounds = [
+            unit,
+            10 * unit,
+            100 * unit,
+            unit * unit,
+            10 * unit * unit,
+            100 * unit * unit,
+        ];
+        let mut buckets = [(0, 0); SIZE_BUCKETS];
+        for file in self.files_by_category.values().flatten() {
+            let bucket = bounds.iter().filter(|&&bound| file.size >= bound).count();
+            buckets[bucket].0 += 1;
+            buckets[bucket].1 += file.size;
+        }
+        buckets
+    }
+
+    /// Generates a summary of files by extension across all categories.
+    ///
+    /// Extensions are lowercase with a leading dot (e.g. `".pdf"`); files
+    /// without one are grouped under an empty string. The results are sorted
+    /// by file count in descending order, then by extension.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(extension, file_count, total_size)` tuples
+    pub fn get_extension_summary(&self) -> Vec<(String, usize, u64)> {
+        let mut by_extension: HashMap<String, (usize, u64)> = HashMap::new();
+        for file in self.files_by_category.values().flatten() {
+            let entry = by_extension.entry(file.extension.clone()).or_default();
+            entry.0 += 1;
+            entry.1 += file.allocated_size;
+        }
+
+        let mut summary: Vec<_> = by_extension
+            .into_iter()
+            .map(|(extension, (count, size))| (extension, count, size))
+            .collect();
+
+        summary.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        summary
+    }
+
+    /// Generates a summary of files by the top-level directory they live under.
+    ///
+    /// Each file is grouped by the first path component below `root`; files
+    /// directly in the root are grouped under an empty string. After a merge,
+    /// directories are named `<source name>/<directory>` so sources stay apart.
+    /// The results are sorted by total allocated size in descending order,
+    /// then by name.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(directory, file_count, total_size)` tuples
+    pub fn get_directory_summary(&self) -> Vec<(String, usize, u64)> {
+        let mut by_directory: HashMap<String, (usize, u64)> = HashMap::new();
+        for file in self.files_by_category.values().flatten() {
+            let source = self.source_of(&file.path);
+            let relative = file
+                .path
+                .strip_prefix(relative_base(source))
+                .unwrap_or(&file.path);
+            let mut components = relative.components().filter_map(|c| match c {
+                std::path::Component::Normal(name) => Some(name),
+                _ => None,
+            });
+            let first = components.next();
+            let mut directory = match (first, components.next()) {
+                (Some(name), Some(_)) => name.to_string_lossy().into_owned(),
+                _ => String::new(),
+            };
+            if self.sources.len() > 1 {
+                let name = source.file_name().map_or_else(
+                    || source.display().to_string(),
+                    |n| n.to_string_lossy().into_owned(),
+                );
+                directory = if directory.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", name, directory)
+                };
+            }
+
+            let entry = by_directory.entry(directory).or_default();
+            entry.0 += 1;
+            entry.1 += file.allocated_size;
+        }
+
+        let mut summary: Vec<_> = by_directory
+            .into_iter()
+            .map(|(directory, (count, size))| (directory, count, size))
+            .collect();
+
+        summary.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        summary
+    }
+
+    /// Generates a summary of files by scanned source, in scan order.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(source, file_count, total_size)` tuples
+    pub fn get_source_summary(&self) -> Vec<(PathBuf, usize, u64)> {
+        let mut summary: Vec<_> = self
+            .sources
+            .iter()
+            .map(|source| (source.clone(), 0, 0))
+            .collect();
+        for file in self.files_by_category.values().flatten() {
+            let source = self.source_of(&file.path);
+            if let Some(entry) = summary.iter_mut().find(|(s, _, _)| s == source) {
+                entry.1 += 1;
+                entry.2 += file.allocated_size;
+            }
+        }
         summary
     }
 
@@ -108,8 +655,8 @@ impl ScanStats {
     ///
     /// # Returns
     ///
-    /// A vector of `(filename, size, category)` tuples for all files
-    pub fn get_all_files(&self) -> Vec<(String, u64, String)> {
+    /// A vector of `(filename, allocated_size, apparent_size, category)` tuples for all files
+    pub fn get_all_files(&self) -> Vec<(String, u64, u64, String)> {
         self.files_by_category
             .iter()
             .flat_map(|(category, files)| {
@@ -120,22 +667,375 @@ impl ScanStats {
                         .and_then(|s| s.to_str())
                         .unwrap_or("unknown")
                         .to_string();
-                    (name, f.size, category.clone())
+                    (name, f.allocated_size, f.size, category.clone())
                 })
             })
             .collect()
     }
 }
 
+/// Why an entry was left out of the walk.
+enum Skip {
+    /// A hidden (dot) entry, walked only with `include_hidden`
+    Hidden,
+    /// Matched a built-in exclusion (system entries) or an exclusion pattern
+    Excluded,
+    /// Lives on a different filesystem than the scan root
+    OtherFilesystem,
+    /// A bundle directory, recorded as a single file instead of being walked
+    Bundle,
+}
+
+/// Decides which entries the walker visits, shared by the counting and scanning passes.
+struct EntryFilter {
+    root: PathBuf,
+    /// Device ID of the scan root when `same_filesystem` is enabled
+    root_device: Option<u64>,
+    /// Mount points of `scan.pseudo_filesystems` below the root, relative to it
+    pseudo_mounts: HashSet<PathBuf>,
+    /// Walk entries whose name starts with a dot
+    include_hidden: bool,
+    categorizer: Arc<dyn Categorizer>,
+    /// Detect MIME types for the categorizer and `FileInfo::mime`, from `scan.detect_mime`
+    detect_mime: bool,
+    /// Categories whose files are left out entirely
+    excluded_categories: HashSet<String>,
+    /// Extensions from `scan.exclude_extensions`, lowercase with a leading dot
+    excluded_extensions: HashSet<String>,
+    /// Extensions from `scan.include_only_extensions`; when not empty, all others are skipped
+    only_extensions: HashSet<String>,
+    /// Patterns from `scan.exclude_patterns`, `--exclude`, and the root's `.tapignore`
+    patterns: Vec<Pattern>,
+    /// Patterns from `scan.sensitive_patterns`, empty unless `scan.flag_sensitive` is set
+    sensitive: Vec<Pattern>,
+    /// Extensions from `scan.bundle_extensions`, empty unless `scan.bundles_as_files` is set
+    bundle_extensions: HashSet<String>,
+    /// Files must be modified after this time, from `scan.modified_after`
+    modified_after: Option<SystemTime>,
+    /// Files must be modified before this time, from `scan.modified_before`
+    modified_before: Option<SystemTime>,
+    /// Files without a modification time fail the date filters, from `scan.exclude_unknown_dates`
+    exclude_unknown_dates: bool,
+}
+
+impl EntryFilter {
+    fn new(root: &Path, config: &Config, categorizer: Arc<dyn Categorizer>) -> Self {
+        let root = relative_base(root);
+        let root_device = if config.scan.same_filesystem {
+            device_id(root)
+        } else {
+            None
+        };
+
+        // Hidden entries are governed by `include_hidden`, so the default `.*` pattern
+        // must not undo `--include-hidden`
+        let mut patterns: Vec<Pattern> = config
+            .scan
+            .exclude_patterns
+            .iter()
+            .filter(|p| !(config.scan.include_hidden && p.as_str() == ".*"))
+            .filter_map(|p| Pattern::parse(p))
+            .collect();
+        patterns.extend(load_tapignore(root));
+
+        let sensitive = if config.scan.flag_sensitive {
+            config
+                .scan
+                .sensitive_patterns
+                .iter()
+                .filter_map(|p| Pattern::parse(&p.to_lowercase()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let bundle_extensions = if config.scan.bundles_as_files {
+            config
+                .scan
+                .bundle_extensions
+                .iter()
+                .map(|ext| ext.to_lowercase())
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        Self {
+            root: root.to_path_buf(),
+            root_device,
+            pseudo_mounts: pseudo_mounts(root, &config.scan.pseudo_filesystems),
+            include_hidden: config.scan.include_hidden,
+            categorizer,
+            detect_mime: config.scan.detect_mime,
+            excluded_categories: config.scan.exclude_categories.iter().cloned().collect(),
+            excluded_extensions: normalize_extensions(&config.scan.exclude_extensions),
+            only_extensions: normalize_extensions(&config.scan.include_only_extensions),
+            patterns,
+            sensitive,
+            bundle_extensions,
+            modified_after: config.scan.modified_after,
+            modified_before: config.scan.modified_before,
+            exclude_unknown_dates: config.scan.exclude_unknown_dates,
+        }
+    }
+
+    /// Returns true if no date filter is set.
+    fn all_dates(&self) -> bool {
+        self.modified_after.is_none() && self.modified_before.is_none()
+    }
+
+    /// Returns true if a file modified at `modified` passes the date filters.
+    ///
+    /// Some network and FUSE filesystems have no modification times; those
+    /// files pass unless `exclude_unknown_dates` is set.
+    fn in_date_range(&self, modified: Option<SystemTime>) -> bool {
+        if self.all_dates() {
+            return true;
+        }
+        match modified {
+            Some(time) => {
+                self.modified_after.is_none_or(|after| time > after)
+                    && self.modified_before.is_none_or(|before| time < before)
+            }
+            None => !self.exclude_unknown_dates,
+        }
+    }
+
+    /// Returns true if the file at `path` matches a sensitive pattern, ignoring case.
+    fn is_sensitive(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let relative = PathBuf::from(relative.to_string_lossy().to_lowercase());
+        self.sensitive.iter().any(|p| p.matches(&relative, false))
+    }
+
+    /// Sensitive files at or below a hidden entry the walk leaves out, so that
+    /// keys and credentials in dotfiles are reported even when not counted.
+    fn hidden_sensitive(&self, entry: &walkdir::DirEntry) -> Vec<PathBuf> {
+        if self.sensitive.is_empty() {
+            return Vec::new();
+        }
+        WalkDir::new(entry.path())
+            .same_file_system(self.root_device.is_some())
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file() && self.is_sensitive(e.path()))
+            .map(walkdir::DirEntry::into_path)
+            .collect()
+    }
+
+    /// Returns true if some categories are excluded, so files must be categorized to count them.
+    fn excludes_categories(&self) -> bool {
+        !self.excluded_categories.is_empty()
+    }
+
+    /// Returns the category and, with `scan.detect_mime`, the MIME type of a
+    /// file, or `None` if that category is excluded.
+    fn category(
+        &self,
+        path: &Path,
+        metadata: &std::fs::Metadata,
+    ) -> Option<(String, Option<String>)> {
+        let mime = if self.detect_mime && metadata.is_file() {
+            detect_mime(path)
+        } else {
+            None
+        };
+        let category = match &mime {
+            Some(mime) => self.categorizer.categorize_with_mime(path, metadata, mime),
+            None => self.categorizer.categorize(path, metadata),
+        };
+        (!self.excluded_categories.contains(&category))
+            .then(|| (category, mime.map(Mime::into_string)))
+    }
+
+    /// Returns the reason to skip `entry`, or `None` if it should be walked.
+    fn check(&self, entry: &walkdir::DirEntry) -> Option<Skip> {
+        // The root was chosen explicitly, so it is walked even if it is hidden
+        if entry.depth() == 0 {
+            return None;
+        }
+
+        let file_name = entry.file_name().to_string_lossy();
+        if file_name.starts_with('.') && !self.include_hidden {
+            return Some(Skip::Hidden);
+        }
+        if file_name == "System Volume Information"
+            || file_name == "$RECYCLE.BIN"
+            || file_name == "node_modules"
+        {
+            return Some(Skip::Excluded);
+        }
+
+        if let Ok(relative) = entry.path().strip_prefix(&self.root) {
+            let is_dir = entry.file_type().is_dir();
+            if self.patterns.iter().any(|p| p.matches(relative, is_dir)) {
+                return Some(Skip::Excluded);
+            }
+        }
+
+        if entry.file_type().is_dir()
+            && !self.pseudo_mounts.is_empty()
+            && entry
+                .path()
+                .strip_prefix(&self.root)
+                .is_ok_and(|relative| self.pseudo_mounts.contains(relative))
+        {
+            return Some(Skip::OtherFilesystem);
+        }
+
+        // Mount points are always directories, so only those need a device lookup
+        if let Some(root_device) = self.root_device
+            && entry.file_type().is_dir()
+            && device_id(entry.path()).is_some_and(|dev| dev != root_device)
+        {
+            return Some(Skip::OtherFilesystem);
+        }
+
+        let extension = get_extension(entry.path());
+        let is_bundle = entry.file_type().is_dir() && self.bundle_extensions.contains(&extension);
+
+        // Only files and bundles have an extension in this sense, not ordinary directories
+        if (is_bundle || !entry.file_type().is_dir())
+            && (self.excluded_extensions.contains(&extension)
+                || !(self.only_extensions.is_empty() || self.only_extensions.contains(&extension)))
+        {
+            return Some(Skip::Excluded);
+        }
+
+        if is_bundle {
+            return Some(Skip::Bundle);
+        }
+
+        None
+    }
+}
+
+/// Lowercases extensions given with or without a leading dot, e.g. `"ISO"` to `".iso"`.
+fn normalize_extensions(extensions: &[String]) -> HashSet<String> {
+    extensions
+        .iter()
+        .map(|ext| format!(".{}", ext.trim().trim_start_matches('.').to_lowercase()))
+        .collect()
+}
+
+/// Returns the mount points of `types` filesystems strictly below `root`,
+/// relative to it, from `/proc/mounts`.
+///
+/// Empty where there is no `/proc/mounts`, as on macOS and Windows.
+fn pseudo_mounts(root: &Path, types: &[String]) -> HashSet<PathBuf> {
+    if types.is_empty() {
+        return HashSet::new();
+    }
+    match (
+        std::fs::read_to_string("/proc/mounts"),
+        std::fs::canonicalize(root),
+    ) {
+        (Ok(table), Ok(root)) => mounts_below(&table, &root, types),
+        _ => HashSet::new(),
+    }
+}
+
+/// Returns the mount points of `types` filesystems in a `/proc/mounts` table
+/// that lie strictly below the absolute `root`, relative to it.
+fn mounts_below(table: &str, root: &Path, types: &[String]) -> HashSet<PathBuf> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_, target, fstype) = (fields.next()?, fields.next()?, fields.next()?);
+            if !types.iter().any(|t| t == fstype) {
+                return None;
+            }
+            let target = PathBuf::from(unescape_mount_path(target));
+            let relative = target.strip_prefix(root).ok()?;
+            (!relative.as_os_str().is_empty()).then(|| relative.to_path_buf())
+        })
+        .collect()
+}
+
+/// Decodes the octal escapes the mount table uses in paths, such as `\040` for a space.
+fn unescape_mount_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && let Some(code) = path
+                .get(i + 1..i + 4)
+                .filter(|digits| digits.bytes().all(|b| matches!(b, b'0'..=b'7')))
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        {
+            decoded.push(code);
+            i += 4;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the ID of the device containing `path`.
+#[cfg(unix)]
+fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::symlink_metadata(path).ok().map(|m| m.dev())
+}
+
+/// Returns the bytes allocated on disk for a file, from `st_blocks` (always 512-byte units).
+#[cfg(unix)]
+fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks() * 512
+}
+
+/// Allocation is not exposed on this platform, so the apparent size is used.
+#[cfg(not(unix))]
+fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Device IDs are not available on this platform, so filesystem boundaries are not detected.
+#[cfg(not(unix))]
+fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Returns the directory that paths under a scanned `source` are relative to.
+///
+/// This is `source` itself, or its parent when a single file was scanned, so
+/// the file keeps its name in relative paths, manifests, and exclusion matching.
+pub fn relative_base(source: &Path) -> &Path {
+    match source.parent() {
+        Some(parent) if source.is_file() => parent,
+        _ => source,
+    }
+}
+
+/// Returns the apparent and allocated size of every file inside a bundle directory.
+pub fn bundle_size(path: &Path) -> (u64, u64) {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .fold((0, 0), |(size, allocated), metadata| {
+            (size + metadata.len(), allocated + allocated_size(&metadata))
+        })
+}
+
 /// Counts the number of files in a directory tree.
 ///
 /// Performs a fast count of all files in the given path, excluding system
-/// directories and hidden files. This is useful for displaying progress bars
-/// with accurate total counts.
+/// directories and, unless `scan.include_hidden` is set, hidden files. This is
+/// useful for displaying progress bars with accurate total counts.
 ///
 /// # Arguments
 ///
-/// * `path` - The root directory to count files in
+/// * `path` - The root directory to count files in, or a single file
+/// * `config` - Configuration controlling which entries are walked
 ///
 /// # Returns
 ///
@@ -145,29 +1045,66 @@ impl ScanStats {
 ///
 /// ```no_run
 /// use std::path::Path;
+/// use tap::config::Config;
 /// use tap::scanner::count_files;
 ///
 /// # async fn example() {
-/// let count = count_files(Path::new("/mnt/evidence")).await;
+/// let config = Config::default();
+/// let count = count_files(Path::new("/mnt/evidence"), &config).await;
 /// println!("Found {} files", count);
 /// # }
 /// ```
-pub async fn count_files(path: &Path) -> u64 {
+pub async fn count_files(path: &Path, config: &Config) -> u64 {
+    let categorizer = Arc::new(ConfigCategorizer::new(path, config));
+    count_files_with_categorizer(path, config, categorizer).await
+}
+
+/// Counts files like [`count_files`], leaving out those `categorizer` puts in
+/// an excluded category.
+///
+/// Files are only categorized when `scan.exclude_categories` is not empty.
+pub async fn count_files_with_categorizer(
+    path: &Path,
+    config: &Config,
+    categorizer: Arc<dyn Categorizer>,
+) -> u64 {
+    let filter = EntryFilter::new(path, config, categorizer);
+    let max_files = config.scan.max_files;
     let result: Result<u64, tokio::task::JoinError> = task::spawn_blocking({
         let path = path.to_path_buf();
         move || -> u64 {
-            WalkDir::new(&path)
+            // Bundles are not walked, but each counts as one file
+            let limit = max_files.unwrap_or(u64::MAX);
+            let mut bundles = 0;
+            let files = WalkDir::new(&path)
                 .into_iter()
-                .filter_entry(|e| {
-                    let file_name = e.file_name().to_string_lossy();
-                    !file_name.starts_with('.')
-                        && file_name != "System Volume Information"
-                        && file_name != "$RECYCLE.BIN"
-                        && file_name != "node_modules"
+                .filter_entry(|e| match filter.check(e) {
+                    None => true,
+                    Some(Skip::Bundle) => {
+                        if !filter.excludes_categories()
+                            || std::fs::metadata(e.path())
+                                .is_ok_and(|m| filter.category(e.path(), &m).is_some())
+                        {
+                            bundles += 1;
+                        }
+                        false
+                    }
+                    Some(_) => false,
                 })
                 .filter_map(|e: Result<walkdir::DirEntry, walkdir::Error>| e.ok())
                 .filter(|e| e.file_type().is_file())
-                .count() as u64
+                .filter(|e| {
+                    !filter.excludes_categories()
+                        || std::fs::metadata(e.path())
+                            .is_ok_and(|m| filter.category(e.path(), &m).is_some())
+                })
+                .filter(|e| {
+                    filter.all_dates()
+                        || filter.in_date_range(e.metadata().ok().and_then(|m| m.modified().ok()))
+                })
+                .take(usize::try_from(limit).unwrap_or(usize::MAX))
+                .count() as u64;
+            (files + bundles).min(limit)
         }
     })
     .await;
@@ -178,11 +1115,13 @@ pub async fn count_files(path: &Path) -> u64 {
 /// Scans a directory and categorizes all files.
 ///
 /// Walks through the directory tree, categorizes each file based on its extension,
-/// and collects statistics. System directories and hidden files are automatically excluded.
+/// and collects statistics. System directories are always excluded, and hidden files are
+/// excluded unless `scan.include_hidden` is set.
 ///
 /// # Arguments
 ///
-/// * `path` - The root directory to scan
+/// * `path` - The root directory to scan, or a single file to categorize on its own
+/// * `config` - Configuration controlling which entries are walked
 /// * `progress_callback` - A function called for each file processed, receives the file path as a string
 ///
 /// # Returns
@@ -198,10 +1137,12 @@ pub async fn count_files(path: &Path) -> u64 {
 ///
 /// ```no_run
 /// use std::path::Path;
+/// use tap::config::Config;
 /// use tap::scanner::scan_directory;
 ///
 /// # async fn example() -> color_eyre::Result<()> {
-/// let stats = scan_directory(Path::new("/mnt/evidence"), |path| {
+/// let config = Config::default();
+/// let stats = scan_directory(Path::new("/mnt/evidence"), &config, |path| {
 ///     println!("Processing: {}", path);
 /// }).await?;
 ///
@@ -210,65 +1151,356 @@ pub async fn count_files(path: &Path) -> u64 {
 /// # Ok(())
 /// # }
 /// ```
-pub async fn scan_directory<F>(path: &Path, progress_callback: F) -> color_eyre::Result<ScanStats>
+pub async fn scan_directory<F>(
+    path: &Path,
+    config: &Config,
+    progress_callback: F,
+) -> color_eyre::Result<ScanStats>
 where
     F: Fn(String) + Send + Sync + 'static,
 {
+    scan_directory_with_control(path, config, &Control::new(), progress_callback).await
+}
+
+/// Scans a directory like [`scan_directory`], honouring pause and cancel requests.
+///
+/// The walk waits between files while `control` is paused. Once cancelled it
+/// stops and returns the partial results with `ScanStats.cancelled` set.
+pub async fn scan_directory_with_control<F>(
+    path: &Path,
+    config: &Config,
+    control: &Control,
+    progress_callback: F,
+) -> color_eyre::Result<ScanStats>
+where
+    F: Fn(String) + Send + Sync + 'static,
+{
+    let categorizer = Arc::new(ConfigCategorizer::new(path, config));
+    scan_directory_with_categorizer(path, config, control, categorizer, progress_callback).await
+}
+
+/// Scans a directory like [`scan_directory_with_control`], with `categorizer`
+/// deciding each file's category instead of the configured rules and extensions.
+///
+/// The exclusion patterns, date filters, and `scan.exclude_categories` still
+/// apply; only the classification is replaced.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::Metadata;
+/// use std::path::Path;
+/// use std::sync::Arc;
+/// use tap::categorizer::Categorizer;
+/// use tap::config::Config;
+/// use tap::control::Control;
+/// use tap::scanner::scan_directory_with_categorizer;
+///
+/// struct BySize;
+///
+/// impl Categorizer for BySize {
+///     fn categorize(&self, _path: &Path, meta: &Metadata) -> String {
+///         if meta.len() > 1 << 30 { "large" } else { "small" }.to_string()
+///     }
+/// }
+///
+/// # async fn example() -> color_eyre::Result<()> {
+/// let config = Config::default();
+/// let path = Path::new("/mnt/evidence");
+/// let stats =
+///     scan_directory_with_categorizer(path, &config, &Control::new(), Arc::new(BySize), |_| {})
+///         .await?;
+/// println!("{} large files", stats.files_by_category.get("large").map_or(0, Vec::len));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn scan_directory_with_categorizer<F>(
+    path: &Path,
+    config: &Config,
+    control: &Control,
+    categorizer: Arc<dyn Categorizer>,
+    progress_callback: F,
+) -> color_eyre::Result<ScanStats>
+where
+    F: Fn(String) + Send + Sync + 'static,
+{
+    let started = Instant::now();
     let stats = Arc::new(Mutex::new(ScanStats::new()));
     let callback = Arc::new(progress_callback);
+    let filter = EntryFilter::new(path, config, categorizer);
+    let max_files = config.scan.max_files;
+    let live_root = !config.scan.same_filesystem
+        && std::fs::canonicalize(path).is_ok_and(|p| p.parent().is_none());
+    let report_empty = config.scan.report_empty;
+    let echo = ErrorEcho::new(config.ui.verbose, control);
 
     let path = path.to_path_buf();
     let stats_clone = Arc::clone(&stats);
     let callback_clone = Arc::clone(&callback);
+    let control = control.clone();
 
     task::spawn_blocking(move || {
-        for entry in WalkDir::new(&path).into_iter().filter_entry(|e| {
-            let file_name = e.file_name().to_string_lossy();
-            !file_name.starts_with('.')
-                && file_name != "System Volume Information"
-                && file_name != "$RECYCLE.BIN"
-                && file_name != "node_modules"
-        }) {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        let mut non_empty_dirs: HashSet<PathBuf> = HashSet::new();
+        let mut skipped_mounts: Vec<PathBuf> = Vec::new();
+        let mut excluded_by_category = 0;
+        let mut excluded_by_date = 0;
+        // Files the date filters could not check because they have no modification time
+        let mut unknown_dates = 0;
+        let mut sensitive_files: Vec<PathBuf> = Vec::new();
+        // Sensitive files found below hidden entries, which are not walked
+        let mut hidden_sensitive: Vec<PathBuf> = Vec::new();
+        let mut bundles: Vec<PathBuf> = Vec::new();
+        let mut special_entries: Vec<SpecialEntry> = Vec::new();
+        let (mut excluded_files, mut excluded_dirs, mut excluded_size) = (0, 0, 0);
+        let mut cancelled = false;
+        // Files recorded so far, checked against `max_files`
+        let mut recorded = 0u64;
+        let mut limit_reached = false;
+
+        for entry in WalkDir::new(&path)
+            .into_iter()
+            .filter_entry(|e| match filter.check(e) {
+                None => true,
+                Some(Skip::OtherFilesystem) => {
+                    skipped_mounts.push(e.path().to_path_buf());
+                    false
+                }
+                Some(Skip::Bundle) => {
+                    bundles.push(e.path().to_path_buf());
+                    false
+                }
+                Some(skip @ (Skip::Hidden | Skip::Excluded)) => {
+                    if matches!(skip, Skip::Hidden) {
+                        hidden_sensitive.extend(filter.hidden_sensitive(e));
+                    }
+                    if e.file_type().is_dir() {
+                        excluded_dirs += 1;
+                    } else {
+                        excluded_files += 1;
+                        excluded_size += e.metadata().map(|m| m.len()).unwrap_or(0);
+                    }
+                    false
+                }
+            })
+        {
+            if !control.wait_blocking() {
+                cancelled = true;
+                break;
+            }
+
             match entry {
+                Ok(entry) if entry.file_type().is_dir() => {
+                    if entry.depth() > 0 {
+                        dirs.push(entry.into_path());
+                    }
+                }
                 Ok(entry) if entry.file_type().is_file() => {
                     let path = entry.path();
-                    let extension = get_extension(path);
-                    let category = get_category(&extension);
-
-                    match std::fs::metadata(path) {
-                        Ok(metadata) => {
-                            let file_info = FileInfo {
-                                path: path.to_path_buf(),
-                                size: metadata.len(),
-                                category: category.to_string(),
-                            };
 
-                            // Callback with current file
-                            callback_clone(path.display().to_string());
-
-                            // add to stats
-                            let mut stats = stats_clone.lock().unwrap();
-                            stats.add_file(file_info);
+                    // Mark every ancestor up to the root as containing a file
+                    for ancestor in path.ancestors().skip(1) {
+                        if !non_empty_dirs.insert(ancestor.to_path_buf()) {
+                            break;
                         }
+                    }
+
+                    let extension = get_extension(path);
+                    let metadata = match std::fs::metadata(path) {
+                        Ok(metadata) => metadata,
                         Err(e) => {
+                            let message = format!("Error reading {}: {}", path.display(), e);
+                            echo.echo(&message);
                             let mut stats = stats_clone.lock().unwrap();
-                            stats.add_error(format!("Error reading {}: {}", path.display(), e));
+                            stats.add_path_error(path, e.kind(), message);
+                            continue;
+                        }
+                    };
+                    let Some((category, mime)) = filter.category(path, &metadata) else {
+                        excluded_by_category += 1;
+                        continue;
+                    };
+
+                    let modified = metadata.modified().ok();
+                    if modified.is_none() && !filter.all_dates() {
+                        unknown_dates += 1;
+                    }
+                    if !filter.in_date_range(modified) {
+                        excluded_by_date += 1;
+                        continue;
+                    }
+
+                    // Stop at the first file past the limit, so an exact fit is not flagged
+                    if max_files.is_some_and(|max| recorded >= max) {
+                        limit_reached = true;
+                        break;
+                    }
+
+                    let file_info = FileInfo {
+                        path: path.to_path_buf(),
+                        extension,
+                        size: metadata.len(),
+                        allocated_size: allocated_size(&metadata),
+                        modified,
+                        category,
+                        mime,
+                    };
+
+                    if filter.is_sensitive(path) {
+                        sensitive_files.push(path.to_path_buf());
+                    }
+
+                    // Callback with current file
+                    callback_clone(path.display().to_string());
+
+                    // add to stats
+                    let mut stats = stats_clone.lock().unwrap();
+                    stats.add_file(file_info);
+                    recorded += 1;
+                }
+                Ok(entry) => {
+                    if let Some(kind) = SpecialKind::of(&entry) {
+                        special_entries.push(SpecialEntry {
+                            kind,
+                            path: entry.into_path(),
+                        });
+                    }
+                }
+                Err(e) => {
+                    let message = format!("Error walking directory: {}", e);
+                    echo.echo(&message);
+                    let mut stats = stats_clone.lock().unwrap();
+                    match e.path() {
+                        Some(path) => {
+                            let kind = e
+                                .io_error()
+                                .map_or(std::io::ErrorKind::Other, |io| io.kind());
+                            stats.add_path_error(path, kind, message);
                         }
+                        None => stats.add_error(message),
                     }
                 }
+            }
+        }
+
+        // Bundles found during the walk are recorded whole, sized by their contents
+        for bundle in bundles {
+            if cancelled || limit_reached {
+                break;
+            }
+            if !control.wait_blocking() {
+                cancelled = true;
+                break;
+            }
+
+            for ancestor in bundle.ancestors().skip(1) {
+                if !non_empty_dirs.insert(ancestor.to_path_buf()) {
+                    break;
+                }
+            }
+
+            let extension = get_extension(&bundle);
+            let metadata = match std::fs::metadata(&bundle) {
+                Ok(metadata) => metadata,
                 Err(e) => {
+                    let message = format!("Error reading {}: {}", bundle.display(), e);
+                    echo.echo(&message);
                     let mut stats = stats_clone.lock().unwrap();
-                    stats.add_error(format!("Error walking directory: {}", e));
+                    stats.add_path_error(&bundle, e.kind(), message);
+                    continue;
                 }
-                _ => {}
+            };
+            let Some((category, mime)) = filter.category(&bundle, &metadata) else {
+                excluded_by_category += 1;
+                continue;
+            };
+            let modified = metadata.modified().ok();
+            if modified.is_none() && !filter.all_dates() {
+                unknown_dates += 1;
+            }
+            if !filter.in_date_range(modified) {
+                excluded_by_date += 1;
+                continue;
+            }
+            if max_files.is_some_and(|max| recorded >= max) {
+                limit_reached = true;
+                break;
+            }
+
+            let (size, allocated) = bundle_size(&bundle);
+            let file_info = FileInfo {
+                extension,
+                size,
+                allocated_size: allocated,
+                modified,
+                category,
+                mime,
+                path: bundle,
+            };
+            if filter.is_sensitive(&file_info.path) {
+                sensitive_files.push(file_info.path.clone());
             }
+            callback_clone(file_info.path.display().to_string());
+            stats_clone.lock().unwrap().add_file(file_info);
+            recorded += 1;
+        }
+
+        let mut stats = stats_clone.lock().unwrap();
+        stats.total_dirs = dirs.len();
+        stats.skipped_mounts = skipped_mounts;
+        stats.sources = vec![path.clone()];
+        stats.root = path;
+        stats.excluded_by_category = excluded_by_category;
+        stats.excluded_by_date = excluded_by_date;
+        if unknown_dates > 0 {
+            let warning = format!(
+                "{} file(s) in {} have no modification time; {} by the date filter",
+                unknown_dates,
+                stats.root.display(),
+                if filter.exclude_unknown_dates {
+                    "left out (scan.exclude_unknown_dates)"
+                } else {
+                    "included anyway (set scan.exclude_unknown_dates to leave them out)"
+                }
+            );
+            stats.warnings.push(warning);
         }
+        if live_root {
+            let warning = format!(
+                "Scanned the root of a running system ({}); other mounted filesystems were walked too. Use --same-filesystem to stay on one",
+                stats.root.display()
+            );
+            stats.warnings.push(warning);
+        }
+        stats.excluded_files = excluded_files;
+        stats.excluded_dirs = excluded_dirs;
+        stats.excluded_size = excluded_size;
+        stats.cancelled = cancelled;
+        stats.limit_reached = limit_reached;
+        sensitive_files.extend(hidden_sensitive);
+        sensitive_files.sort();
+        stats.sensitive_files = sensitive_files;
+        special_entries.sort_by(|a, b| a.path.cmp(&b.path));
+        stats.special_entries = special_entries;
+        if report_empty {
+            let mut zero_byte_files: Vec<PathBuf> = stats
+                .files_by_category
+                .values()
+                .flatten()
+                .filter(|file| file.size == 0)
+                .map(|file| file.path.clone())
+                .collect();
+            zero_byte_files.sort();
+            stats.zero_byte_files = zero_byte_files;
+        }
+        stats.empty_dirs = dirs.iter().filter(|d| !non_empty_dirs.contains(*d)).count();
     })
     .await?;
 
-    let stats = Arc::try_unwrap(stats)
+    let mut stats = Arc::try_unwrap(stats)
         .map_err(|_| color_eyre::eyre::eyre!("Failed to unwrap stats"))?
         .into_inner()?;
+    stats.duration = started.elapsed();
 
     Ok(stats)
 }
@@ -281,8 +1513,12 @@ mod tests {
     fn test_file_info_creation() {
         let file_info = FileInfo {
             path: PathBuf::from("/test/file.txt"),
+            extension: ".txt".to_string(),
             size: 1024,
+            allocated_size: 1024,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         };
 
         assert_eq!(file_info.path, PathBuf::from("/test/file.txt"));
@@ -290,12 +1526,123 @@ mod tests {
         assert_eq!(file_info.category, "documents");
     }
 
+    #[test]
+    fn test_get_extension_summary() {
+        let mut stats = ScanStats::new();
+        for (path, size, category) in [
+            ("/a/report.PDF", 300, "documents"),
+            ("/a/letter.docx", 100, "documents"),
+            ("/a/scan.pdf", 200, "documents"),
+            ("/a/Makefile", 50, "misc"),
+        ] {
+            stats.add_file(FileInfo {
+                path: PathBuf::from(path),
+                extension: get_extension(Path::new(path)),
+                size,
+                allocated_size: size,
+                modified: None,
+                category: category.to_string(),
+                mime: None,
+            });
+        }
+
+        let summary = stats.get_extension_summary();
+        assert_eq!(
+            summary,
+            vec![
+                (".pdf".to_string(), 2, 500),
+                ("".to_string(), 1, 50),
+                (".docx".to_string(), 1, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_directory_summary() {
+        let mut stats = ScanStats {
+            root: PathBuf::from("/mnt/usb"),
+            ..ScanStats::new()
+        };
+        for (path, size) in [
+            ("/mnt/usb/Users/bob/Videos/trip.mp4", 800),
+            ("/mnt/usb/Users/alice/notes.txt", 100),
+            ("/mnt/usb/Backups/old.zip", 300),
+            ("/mnt/usb/readme.txt", 10),
+        ] {
+            stats.add_file(FileInfo {
+                path: PathBuf::from(path),
+                extension: get_extension(Path::new(path)),
+                size,
+                allocated_size: size,
+                modified: None,
+                category: "misc".to_string(),
+                mime: None,
+            });
+        }
+
+        assert_eq!(
+            stats.get_directory_summary(),
+            vec![
+                ("Users".to_string(), 2, 900),
+                ("Backups".to_string(), 1, 300),
+                ("".to_string(), 1, 10),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_stats_merge() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let first = root.path().join("sda1");
+        let second = root.path().join("sdb1");
+        std::fs::create_dir_all(first.join("Users")).unwrap();
+        std::fs::create_dir_all(second.join("Backups")).unwrap();
+        std::fs::write(first.join("Users/report.pdf"), b"report").unwrap();
+        std::fs::write(first.join("readme.txt"), b"readme").unwrap();
+        std::fs::write(second.join("Backups/photo.jpg"), b"photo").unwrap();
+
+        let config = Config::default();
+        let mut stats = scan_directory(&first, &config, |_| {}).await.unwrap();
+        stats.merge(scan_directory(&second, &config, |_| {}).await.unwrap());
+
+        assert_eq!(stats.root, first);
+        assert_eq!(stats.sources, vec![first.clone(), second.clone()]);
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.total_dirs, 2);
+
+        let by_source: Vec<_> = stats
+            .get_source_summary()
+            .into_iter()
+            .map(|(source, count, _)| (source, count))
+            .collect();
+        assert_eq!(by_source, vec![(first.clone(), 2), (second.clone(), 1)]);
+
+        // A partition of a disk image is shown by its label
+        let mut image = ScanStats::new();
+        image
+            .source_labels
+            .insert(second.clone(), "disk.vmdk › p1".to_string());
+        stats.merge(image);
+        assert_eq!(stats.source_label(&second), "disk.vmdk › p1");
+        assert_eq!(stats.source_label(&first), first.display().to_string());
+
+        let mut directories: Vec<_> = stats
+            .get_directory_summary()
+            .into_iter()
+            .map(|(directory, _, _)| directory)
+            .collect();
+        directories.sort();
+        assert_eq!(directories, vec!["sda1", "sda1/Users", "sdb1/Backups"]);
+    }
+
     #[test]
     fn test_scan_stats_new() {
         let stats = ScanStats::new();
 
         assert_eq!(stats.total_files, 0);
         assert_eq!(stats.total_size, 0);
+        assert_eq!(stats.total_dirs, 0);
+        assert_eq!(stats.empty_dirs, 0);
         assert!(stats.files_by_category.is_empty());
         assert!(stats.errors.is_empty());
     }
@@ -306,8 +1653,12 @@ mod tests {
 
         let file_info = FileInfo {
             path: PathBuf::from("/test/file.txt"),
+            extension: ".txt".to_string(),
             size: 1024,
+            allocated_size: 1024,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         };
 
         stats.add_file(file_info);
@@ -324,20 +1675,32 @@ mod tests {
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/file1.txt"),
+            extension: ".txt".to_string(),
             size: 1024,
+            allocated_size: 1024,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         });
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/file2.jpg"),
+            extension: ".jpg".to_string(),
             size: 2048,
+            allocated_size: 2048,
+            modified: None,
             category: "images".to_string(),
+            mime: None,
         });
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/file3.txt"),
+            extension: ".txt".to_string(),
             size: 512,
+            allocated_size: 512,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         });
 
         assert_eq!(stats.total_files, 3);
@@ -358,26 +1721,80 @@ mod tests {
         assert_eq!(stats.errors[1], "Another error");
     }
 
+    #[test]
+    fn test_scan_stats_error_summary() {
+        let mut stats = ScanStats::new();
+        let denied = std::io::ErrorKind::PermissionDenied;
+        for user in ["alice", "bob", "carol"] {
+            let path = PathBuf::from(format!("/home/{}/.cache", user));
+            stats.add_path_error(&path, denied, format!("denied {}", user));
+        }
+        for name in ["a", "b"] {
+            let path = PathBuf::from("/srv/locked").join(name);
+            stats.add_path_error(&path, denied, format!("denied {}", name));
+        }
+        let gone = PathBuf::from("/tmp/build/out.o");
+        stats.add_path_error(&gone, std::io::ErrorKind::NotFound, "gone".to_string());
+        stats.add_error("Loop detected".to_string());
+
+        let summary = stats.get_error_summary(5);
+        assert_eq!(stats.errors.len(), 7);
+        assert_eq!(summary.len(), 2);
+
+        // Sibling failures are grouped under their parent; lone ones keep their path
+        assert_eq!(summary[0].kind, ErrorKind::PermissionDenied);
+        assert_eq!(summary[0].count, 5);
+        assert_eq!(
+            summary[0].paths,
+            vec![
+                (PathBuf::from("/srv/locked"), 2),
+                (PathBuf::from("/home/alice/.cache"), 1),
+                (PathBuf::from("/home/bob/.cache"), 1),
+                (PathBuf::from("/home/carol/.cache"), 1),
+            ]
+        );
+        assert_eq!(
+            summary[1],
+            ErrorGroup {
+                kind: ErrorKind::Vanished,
+                count: 1,
+                paths: vec![(gone, 1)],
+            }
+        );
+    }
+
     #[test]
     fn test_scan_stats_get_summary() {
         let mut stats = ScanStats::new();
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/file1.txt"),
+            extension: ".txt".to_string(),
             size: 1024,
+            allocated_size: 1024,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         });
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/file2.txt"),
+            extension: ".txt".to_string(),
             size: 512,
+            allocated_size: 512,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         });
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/file3.jpg"),
+            extension: ".jpg".to_string(),
             size: 2048,
+            allocated_size: 2048,
+            modified: None,
             category: "images".to_string(),
+            mime: None,
         });
 
         let summary = stats.get_summary();
@@ -405,14 +1822,22 @@ mod tests {
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/document.txt"),
+            extension: ".txt".to_string(),
             size: 1024,
+            allocated_size: 1024,
+            modified: None,
             category: "documents".to_string(),
+            mime: None,
         });
 
         stats.add_file(FileInfo {
             path: PathBuf::from("/test/image.jpg"),
+            extension: ".jpg".to_string(),
             size: 2048,
+            allocated_size: 2048,
+            modified: None,
             category: "images".to_string(),
+            mime: None,
         });
 
         let all_files = stats.get_all_files();
@@ -420,10 +1845,715 @@ mod tests {
         assert_eq!(all_files.len(), 2);
 
         // Check that filenames are extracted correctly
-        let has_document = all_files.iter().any(|(name, _, _)| name == "document.txt");
-        let has_image = all_files.iter().any(|(name, _, _)| name == "image.jpg");
+        let has_document = all_files
+            .iter()
+            .any(|(name, _, _, _)| name == "document.txt");
+        let has_image = all_files.iter().any(|(name, _, _, _)| name == "image.jpg");
 
         assert!(has_document);
         assert!(has_image);
     }
+
+    #[tokio::test]
+    async fn test_scan_directory_counts_dirs() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("docs/reports")).unwrap();
+        std::fs::create_dir_all(root.path().join("empty/nested")).unwrap();
+        std::fs::write(root.path().join("docs/reports/q1.pdf"), b"pdf").unwrap();
+
+        let stats = scan_directory(root.path(), &Config::default(), |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(stats.total_files, 1);
+        // docs, docs/reports, empty, empty/nested
+        assert_eq!(stats.total_dirs, 4);
+        // empty and empty/nested have no file descendants
+        assert_eq!(stats.empty_dirs, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_hidden_entries() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".ssh")).unwrap();
+        std::fs::create_dir_all(root.path().join("System Volume Information")).unwrap();
+        std::fs::write(root.path().join(".ssh/id_ed25519"), b"key").unwrap();
+        std::fs::write(root.path().join(".bash_history"), b"ls").unwrap();
+        std::fs::write(
+            root.path().join("System Volume Information/tracking.log"),
+            b"x",
+        )
+        .unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"notes").unwrap();
+
+        let mut config = Config::default();
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(count_files(root.path(), &config).await, 1);
+        assert_eq!(stats.excluded_files, 1);
+        assert_eq!(stats.excluded_dirs, 2);
+        assert_eq!(stats.excluded_size, 2);
+
+        // Named exclusions still apply when hidden entries are included
+        config.scan.include_hidden = true;
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(count_files(root.path(), &config).await, 3);
+        assert_eq!(stats.excluded_files, 0);
+        assert_eq!(stats.excluded_dirs, 1);
+
+        // Volume metadata at the root stays excluded with hidden entries included,
+        // but only at the root
+        for dir in [
+            "lost+found",
+            ".Trash-1000",
+            ".Spotlight-V100",
+            ".fseventsd",
+            "docs/lost+found",
+        ] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+            std::fs::write(root.path().join(dir).join("entry"), b"x").unwrap();
+        }
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 4);
+        assert_eq!(count_files(root.path(), &config).await, 4);
+        assert_eq!(stats.excluded_dirs, 5);
+
+        // A hidden root is still walked without the flag
+        config.scan.include_hidden = false;
+        let stats = scan_directory(&root.path().join(".ssh"), &config, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(stats.total_files, 1);
+    }
+
+    #[test]
+    fn test_scan_stats_sparse_accounting() {
+        let mut stats = ScanStats::new();
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/vm/disk.qcow2"),
+            extension: ".qcow2".to_string(),
+            size: 10 * 1024 * 1024,
+            allocated_size: 4096,
+            modified: None,
+            category: "virtual".to_string(),
+            mime: None,
+        });
+        stats.add_file(FileInfo {
+            path: PathBuf::from("/vm/notes.txt"),
+            extension: ".txt".to_string(),
+            size: 100,
+            allocated_size: 4096,
+            modified: None,
+            category: "documents".to_string(),
+            mime: None,
+        });
+
+        assert_eq!(stats.total_size, 8192);
+        assert_eq!(stats.total_apparent_size, 10 * 1024 * 1024 + 100);
+        assert_eq!(stats.sparse_files, 1);
+
+        let summary = stats.get_summary();
+        assert!(summary.contains(&("virtual".to_string(), 1, 4096)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allocated_size_of_sparse_file() {
+        let dir = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let path = dir.path().join("sparse.img");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert!(allocated_size(&metadata) < metadata.len());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_scan_directory_special_entries() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::write(root.path().join("report.pdf"), b"pdf").unwrap();
+        std::os::unix::fs::symlink("report.pdf", root.path().join("link.pdf")).unwrap();
+        std::os::unix::fs::symlink("missing.pdf", root.path().join("broken.pdf")).unwrap();
+        let _socket =
+            std::os::unix::net::UnixListener::bind(root.path().join("agent.sock")).unwrap();
+
+        let stats = scan_directory(root.path(), &Config::default(), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(
+            stats.get_special_summary(),
+            vec![
+                (SpecialKind::Symlink, 1),
+                (SpecialKind::BrokenSymlink, 1),
+                (SpecialKind::Socket, 1),
+            ]
+        );
+        assert_eq!(
+            stats.special_entries[0].path,
+            root.path().join("agent.sock")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_excludes_categories() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::write(root.path().join("report.pdf"), b"pdf").unwrap();
+        std::fs::write(root.path().join("blob.xyz123"), b"??").unwrap();
+        std::fs::write(root.path().join("README"), b"hi").unwrap();
+
+        let mut config = Config::default();
+        config.scan.exclude_categories.push("misc".to_string());
+
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.excluded_by_category, 2);
+        assert!(!stats.files_by_category.contains_key("misc"));
+        assert_eq!(count_files(root.path(), &config).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_with_control_cancelled() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::write(root.path().join("a.txt"), b"a").unwrap();
+
+        let control = Control::new();
+        control.cancel();
+        let stats = scan_directory_with_control(root.path(), &Config::default(), &control, |_| {})
+            .await
+            .unwrap();
+        assert!(stats.cancelled);
+        assert_eq!(stats.total_files, 0);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_flags_sensitive_files() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".ssh")).unwrap();
+        std::fs::create_dir_all(root.path().join("project")).unwrap();
+        std::fs::write(root.path().join(".ssh/id_rsa"), b"key").unwrap();
+        std::fs::write(root.path().join("project/.env"), b"TOKEN=1").unwrap();
+        std::fs::write(root.path().join("Passwords.kdbx"), b"db").unwrap();
+        std::fs::write(root.path().join("SERVER.KEY"), b"key").unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"notes").unwrap();
+        let expected = vec![
+            root.path().join(".ssh/id_rsa"),
+            root.path().join("Passwords.kdbx"),
+            root.path().join("SERVER.KEY"),
+            root.path().join("project/.env"),
+        ];
+
+        let mut config = Config::default();
+        config.scan.include_hidden = true;
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert!(stats.sensitive_files.is_empty());
+
+        config.scan.flag_sensitive = true;
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 5);
+        assert_eq!(stats.sensitive_files, expected);
+
+        // Hidden files are reported without being counted
+        config.scan.include_hidden = false;
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.sensitive_files, expected);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_reports_empty_files() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("downloads")).unwrap();
+        std::fs::write(root.path().join("downloads/video.mp4"), b"").unwrap();
+        std::fs::write(root.path().join("placeholder.txt"), b"").unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"notes").unwrap();
+
+        let mut config = Config::default();
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.empty_files, 2);
+        assert!(stats.zero_byte_files.is_empty());
+
+        config.scan.report_empty = true;
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.empty_files, 2);
+        assert_eq!(
+            stats.zero_byte_files,
+            vec![
+                root.path().join("downloads/video.mp4"),
+                root.path().join("placeholder.txt"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_stats_with_categories() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::write(root.path().join("report.pdf"), b"report").unwrap();
+        std::fs::write(root.path().join("photo.jpg"), b"photo").unwrap();
+        std::fs::write(root.path().join("empty.jpg"), b"").unwrap();
+
+        let stats = scan_directory(root.path(), &Config::default(), |_| {})
+            .await
+            .unwrap();
+        let images = stats.with_categories(&["images".to_string(), "unknown".to_string()]);
+        assert_eq!(images.root, stats.root);
+        assert_eq!(images.total_files, 2);
+        assert_eq!(images.total_apparent_size, 5);
+        assert_eq!(images.empty_files, 1);
+        assert_eq!(
+            images.files_by_category.keys().collect::<Vec<_>>(),
+            vec!["images"]
+        );
+        assert_eq!(stats.with_categories(&[]).total_files, 0);
+    }
+
+    #[test]
+    fn test_scan_stats_sampled() {
+        let scanned = |reverse: bool| {
+            let mut stats = ScanStats::new();
+            for i in 0..20 {
+                for (extension, category) in [(".jpg", "images"), (".pdf", "documents")] {
+                    stats.add_file(FileInfo {
+                        path: PathBuf::from(format!("/src/{}{}", i, extension)),
+                        extension: extension.to_string(),
+                        size: 1,
+                        allocated_size: 1,
+                        modified: None,
+                        category: category.to_string(),
+                        mime: None,
+                    });
+                }
+            }
+            if reverse {
+                stats
+                    .files_by_category
+                    .values_mut()
+                    .for_each(|f| f.reverse());
+            }
+            stats
+        };
+        let stats = scanned(false);
+        let paths = |stats: &ScanStats| -> Vec<PathBuf> {
+            let mut paths: Vec<_> = stats
+                .files_by_category
+                .values()
+                .flatten()
+                .map(|f| f.path.clone())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let sample = stats.sampled(3, 7);
+        assert_eq!(sample.total_files, 6);
+        assert_eq!(sample.files_by_category["images"].len(), 3);
+        assert_eq!(
+            sample.sample,
+            Some(Sample {
+                per_category: 3,
+                seed: 7
+            })
+        );
+
+        // The same seed gives the same files whatever order they were scanned in
+        assert_eq!(paths(&scanned(true).sampled(3, 7)), paths(&sample));
+        assert_ne!(paths(&stats.sampled(3, 8)), paths(&sample));
+        assert_eq!(stats.sampled(50, 7).total_files, 40);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_exclude_extensions() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("mirror.iso")).unwrap();
+        std::fs::write(root.path().join("mirror.iso/notes.txt"), b"notes").unwrap();
+        std::fs::write(root.path().join("install.ISO"), b"iso").unwrap();
+        std::fs::write(root.path().join("disk.vmdk"), b"vmdk").unwrap();
+        std::fs::write(root.path().join("backup.zip"), b"zip").unwrap();
+
+        let mut config = Config::default();
+        config.scan.exclude_extensions = vec!["iso".to_string(), ".VMDK".to_string()];
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+
+        let mut paths: Vec<_> = stats
+            .files_by_category
+            .values()
+            .flatten()
+            .map(|file| file.path.clone())
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                root.path().join("backup.zip"),
+                root.path().join("mirror.iso/notes.txt"),
+            ]
+        );
+        assert_eq!(stats.excluded_files, 2);
+        assert_eq!(count_files(root.path(), &config).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_include_only_extensions() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("docs")).unwrap();
+        std::fs::write(root.path().join("docs/report.PDF"), b"pdf").unwrap();
+        std::fs::write(root.path().join("docs/letter.docx"), b"docx").unwrap();
+        std::fs::write(root.path().join("photo.jpg"), b"jpg").unwrap();
+        std::fs::write(root.path().join("README"), b"readme").unwrap();
+        std::fs::write(root.path().join("mystery.xyz"), b"xyz").unwrap();
+
+        let mut config = Config::default();
+        config.scan.include_only_extensions = vec!["pdf".to_string(), ".docx".to_string()];
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+
+        // Other files are skipped before categorizing, so nothing lands in misc
+        let mut paths: Vec<_> = stats
+            .files_by_category
+            .values()
+            .flatten()
+            .map(|file| file.path.clone())
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                root.path().join("docs/letter.docx"),
+                root.path().join("docs/report.PDF"),
+            ]
+        );
+        assert!(!stats.files_by_category.contains_key("misc"));
+        assert_eq!(stats.excluded_files, 3);
+        assert_eq!(count_files(root.path(), &config).await, 2);
+    }
+
+    #[test]
+    fn test_scan_stats_size_histogram() {
+        let mut stats = ScanStats::new();
+        for size in [0, 999, 1000, 20_000, 5_000_000, 100_000_000, 3_000_000_000] {
+            stats.add_file(FileInfo {
+                path: PathBuf::from(format!("/test/{}.bin", size)),
+                extension: ".bin".to_string(),
+                size,
+                allocated_size: size,
+                modified: None,
+                category: "misc".to_string(),
+                mime: None,
+            });
+        }
+
+        let histogram = stats.get_size_histogram(1000);
+        assert_eq!(
+            histogram,
+            [
+                (2, 999),
+                (1, 1000),
+                (1, 20_000),
+                (0, 0),
+                (1, 5_000_000),
+                (0, 0),
+                (2, 3_100_000_000),
+            ]
+        );
+        // Binary units move the 1000-byte file below the first bound
+        assert_eq!(stats.get_size_histogram(1024)[0], (3, 1999));
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_date_filters() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let backup = SystemTime::now() - 30 * day;
+        for (name, modified) in [
+            ("old.txt", backup - day),
+            ("new.txt", backup + day),
+            ("newer.txt", SystemTime::now()),
+        ] {
+            let file = std::fs::File::create(root.path().join(name)).unwrap();
+            file.set_modified(modified).unwrap();
+        }
+
+        let mut config = Config::default();
+        config.scan.modified_after = Some(backup);
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.excluded_by_date, 1);
+        assert_eq!(count_files(root.path(), &config).await, 2);
+
+        config.scan.modified_before = Some(SystemTime::now() - day);
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        let files: Vec<_> = stats.files_by_category.values().flatten().collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, root.path().join("new.txt"));
+        assert_eq!(stats.excluded_by_date, 2);
+        assert!(stats.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_date_filter_unknown_modified_time() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let mut config = Config::default();
+        let filter = |config: &Config| {
+            EntryFilter::new(
+                root.path(),
+                config,
+                Arc::new(ConfigCategorizer::new(root.path(), config)),
+            )
+        };
+        assert!(filter(&config).in_date_range(None));
+
+        // Without a time to compare, a file is kept unless configured otherwise
+        config.scan.modified_after = Some(SystemTime::UNIX_EPOCH);
+        assert!(filter(&config).in_date_range(None));
+        config.scan.exclude_unknown_dates = true;
+        assert!(!filter(&config).in_date_range(None));
+        assert!(filter(&config).in_date_range(Some(SystemTime::now())));
+    }
+
+    #[test]
+    fn test_pseudo_filesystem_mounts() {
+        let table = "/dev/sda2 / ext4 rw,relatime 0 0\n\
+                     proc /proc proc rw,nosuid,nodev,noexec 0 0\n\
+                     sysfs /sys sysfs rw 0 0\n\
+                     tmpfs /run tmpfs rw 0 0\n\
+                     proc /srv/my\\040jail/proc proc rw 0 0\n";
+        let types = Config::default().scan.pseudo_filesystems;
+
+        let below_root = mounts_below(table, Path::new("/"), &types);
+        assert_eq!(below_root.len(), 3);
+        assert!(below_root.contains(Path::new("proc")));
+        assert!(below_root.contains(Path::new("sys")));
+        assert!(below_root.contains(Path::new("srv/my jail/proc")));
+
+        // A pseudo-filesystem chosen as the root itself is walked
+        let below_proc = mounts_below(table, Path::new("/proc"), &types);
+        assert!(below_proc.is_empty());
+        assert!(mounts_below(table, Path::new("/"), &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_exclude_patterns() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("project/build/out")).unwrap();
+        std::fs::write(root.path().join("project/build/out/app.bin"), b"bin").unwrap();
+        std::fs::write(root.path().join("project/main.rs"), b"fn main() {}").unwrap();
+        std::fs::write(root.path().join("scratch.tmp"), b"tmp").unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"notes").unwrap();
+        std::fs::write(
+            root.path().join(".tapignore"),
+            "# owner exclusions\n**/build/\n",
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.scan.exclude_patterns.push("*.tmp".to_string());
+
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(count_files(root.path(), &config).await, 2);
+        // .tapignore (hidden) and scratch.tmp, plus the build directory
+        assert_eq!(stats.excluded_files, 2);
+        assert_eq!(stats.excluded_dirs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scan_single_file_source() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let file = root.path().join("report.pdf");
+        std::fs::write(&file, b"report").unwrap();
+        std::fs::write(root.path().join("other.txt"), b"other").unwrap();
+
+        let config = Config {
+            scan: crate::config::ScanConfig {
+                flag_sensitive: true,
+                sensitive_patterns: vec!["report.*".to_string()],
+                ..Config::default().scan
+            },
+            ..Config::default()
+        };
+        assert_eq!(relative_base(&file), root.path());
+        assert_eq!(relative_base(root.path()), root.path());
+        assert_eq!(count_files(&file, &config).await, 1);
+
+        let stats = scan_directory(&file, &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.total_dirs, 0);
+        assert_eq!(stats.root, file);
+        assert_eq!(stats.files_by_category["documents"][0].path, file);
+        assert_eq!(stats.sensitive_files, vec![file.clone()]);
+        assert_eq!(
+            stats.get_directory_summary(),
+            vec![(String::new(), 1, stats.total_size)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_bundles_as_files() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let app = root.path().join("Applications/Editor.app");
+        std::fs::create_dir_all(app.join("Contents/MacOS")).unwrap();
+        std::fs::write(app.join("Contents/Info.plist"), b"<plist/>").unwrap();
+        std::fs::write(app.join("Contents/MacOS/Editor"), b"binary").unwrap();
+        std::fs::create_dir_all(root.path().join("Notes.rtfd")).unwrap();
+        std::fs::write(root.path().join("Notes.rtfd/TXT.rtf"), b"{\\rtf1}").unwrap();
+        std::fs::write(root.path().join("report.pdf"), b"report").unwrap();
+
+        // By default bundles are walked like any other directory
+        let config = Config::default();
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 4);
+
+        let config = Config {
+            scan: crate::config::ScanConfig {
+                bundles_as_files: true,
+                ..Config::default().scan
+            },
+            ..Config::default()
+        };
+        assert_eq!(count_files(root.path(), &config).await, 3);
+
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.total_dirs, 1);
+        let bundle = &stats.files_by_category["executables"][0];
+        assert_eq!(bundle.path, app);
+        assert_eq!(bundle.extension, ".app");
+        assert_eq!(bundle.size, 14);
+        assert_eq!(
+            stats.files_by_category["documents"]
+                .iter()
+                .filter(|f| f.extension == ".rtfd")
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_max_files() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            std::fs::write(root.path().join(name), name).unwrap();
+        }
+
+        let limited = |max| Config {
+            scan: crate::config::ScanConfig {
+                max_files: Some(max),
+                ..Config::default().scan
+            },
+            ..Config::default()
+        };
+
+        let config = limited(2);
+        assert_eq!(count_files(root.path(), &config).await, 2);
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert!(stats.limit_reached);
+
+        // A limit the tree fits in exactly is not reported as reached
+        let stats = scan_directory(root.path(), &limited(4), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(stats.total_files, 4);
+        assert!(!stats.limit_reached);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_category_rules() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("Evidence/Exhibits")).unwrap();
+        std::fs::write(root.path().join("Evidence/Exhibits/photo.jpg"), b"jpg").unwrap();
+        std::fs::write(root.path().join("Evidence/Exhibits/ledger.xlsx"), b"xlsx").unwrap();
+        std::fs::write(root.path().join("Evidence/photo.jpg"), b"jpg").unwrap();
+
+        let config = Config {
+            rules: crate::rules::parse_rules("/Evidence/Exhibits/** => exhibits\n").unwrap(),
+            ..Config::default()
+        };
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        assert_eq!(stats.files_by_category["exhibits"].len(), 2);
+        assert_eq!(stats.files_by_category["images"].len(), 1);
+
+        // Rule categories can still be excluded
+        let config = Config {
+            scan: crate::config::ScanConfig {
+                exclude_categories: vec!["exhibits".to_string()],
+                ..config.scan.clone()
+            },
+            ..config
+        };
+        assert_eq!(count_files(root.path(), &config).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_detect_mime() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::write(
+            root.path().join("scan.txt"),
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+        )
+        .unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"plain words").unwrap();
+        std::fs::write(
+            root.path().join("build.py"),
+            b"#!/usr/bin/env python3\nprint(1)\n",
+        )
+        .unwrap();
+
+        let stats = scan_directory(root.path(), &Config::default(), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(stats.files_by_category["documents"].len(), 2);
+        assert!(stats.files_by_category["documents"][0].mime.is_none());
+
+        let mut config = Config::default();
+        config.scan.detect_mime = true;
+        let stats = scan_directory(root.path(), &config, |_| {}).await.unwrap();
+        let images = &stats.files_by_category["images"];
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].mime.as_deref(), Some("image/png"));
+        let documents = &stats.files_by_category["documents"];
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].mime.as_deref(), Some("text/plain"));
+        // A script's text type does not move it out of its extension's category
+        let code = &stats.files_by_category["code"];
+        assert_eq!(code.len(), 1);
+        assert_eq!(code[0].mime.as_deref(), Some("text/x-shellscript"));
+    }
+
+    #[tokio::test]
+    async fn test_scan_directory_with_categorizer() {
+        struct BySize;
+
+        impl Categorizer for BySize {
+            fn categorize(&self, _path: &Path, meta: &std::fs::Metadata) -> String {
+                if meta.len() > 4 { "large" } else { "small" }.to_string()
+            }
+        }
+
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        std::fs::write(root.path().join("report.pdf"), b"a long report").unwrap();
+        std::fs::write(root.path().join("photo.jpg"), b"jpg").unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"ok").unwrap();
+
+        let mut config = Config::default();
+        let stats = scan_directory_with_categorizer(
+            root.path(),
+            &config,
+            &Control::new(),
+            Arc::new(BySize),
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.files_by_category["large"].len(), 1);
+        assert_eq!(stats.files_by_category["small"].len(), 2);
+
+        // Excluded categories apply to whatever the categorizer returns
+        config.scan.exclude_categories.push("small".to_string());
+        assert_eq!(
+            count_files_with_categorizer(root.path(), &config, Arc::new(BySize)).await,
+            1
+        );
+    }
 }
@@ -5,9 +5,18 @@
 
 use console::Term;
 use dialoguer::theme::{ColorfulTheme, Theme};
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::style::ProgressTracker;
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 use std::collections::VecDeque;
+use std::fmt;
 use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::{ChartBy, SizeBase};
+use crate::mount::DriveSpace;
+use crate::scanner::{SIZE_BUCKETS, ScanStats, relative_base};
 
 pub const BANNER: &str = r#"
       ░██                               
@@ -46,6 +55,18 @@ pub struct UI {
     recent_files: VecDeque<String>,
     pub max_recent: usize,
     pub color_theme: String,
+    /// Categories with fewer files are folded into "other" in the category chart
+    pub min_category_files: usize,
+    /// What the category chart bars are proportional to
+    pub chart_by: ChartBy,
+    /// Time between spinner redraws; zero redraws only when progress is made
+    pub tick_interval: std::time::Duration,
+    /// Use ASCII spinner and bar characters for terminals without Unicode glyphs
+    pub ascii_progress: bool,
+    /// Show the interactive summary screens; off with `--no-summary`
+    pub show_summary: bool,
+    /// Print the one-line category summary at the end; off with `--no-compact-summary`
+    pub show_compact_summary: bool,
 }
 
 impl UI {
@@ -56,6 +77,12 @@ impl UI {
             recent_files: VecDeque::with_capacity(3),
             max_recent: 3,
             color_theme: "default".to_string(),
+            min_category_files: 0,
+            chart_by: ChartBy::Size,
+            tick_interval: std::time::Duration::from_millis(100),
+            ascii_progress: false,
+            show_summary: true,
+            show_compact_summary: true,
         })
     }
 
@@ -64,6 +91,48 @@ impl UI {
         self
     }
 
+    pub fn with_min_category_files(mut self, min_files: usize) -> Self {
+        self.min_category_files = min_files;
+        self
+    }
+
+    pub fn with_chart_by(mut self, chart_by: ChartBy) -> Self {
+        self.chart_by = chart_by;
+        self
+    }
+
+    pub fn with_progress_style(mut self, tick_ms: u64, ascii: bool) -> Self {
+        self.tick_interval = std::time::Duration::from_millis(tick_ms);
+        self.ascii_progress = ascii;
+        self
+    }
+
+    pub fn with_summary(mut self, show: bool) -> Self {
+        self.show_summary = show;
+        self
+    }
+
+    pub fn with_compact_summary(mut self, show: bool) -> Self {
+        self.show_compact_summary = show;
+        self
+    }
+
+    /// Spinner frames and bar fill characters for the configured progress style
+    fn progress_chars(&self) -> (&'static str, &'static str) {
+        if self.ascii_progress {
+            ("-\\|/", "# ")
+        } else {
+            ("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏", "█ ")
+        }
+    }
+
+    /// Starts redrawing `pb` on its own at the configured interval, unless disabled
+    fn start_ticking(&self, pb: &ProgressBar) {
+        if !self.tick_interval.is_zero() {
+            pb.enable_steady_tick(self.tick_interval);
+        }
+    }
+
     /// Get the console::Style for the configured theme
     fn get_style(&self) -> console::Style {
         use console::Style;
@@ -510,33 +579,75 @@ impl UI {
     pub fn create_spinner(&self, message: &str) -> ProgressBar {
         let pb = ProgressBar::new_spinner();
         let spinner_color = self.get_spinner_color();
+        let (tick_chars, _) = self.progress_chars();
         pb.set_style(
             ProgressStyle::default_spinner()
                 .template(&format!("{{spinner:{}}} {{msg}}", spinner_color))
                 .unwrap()
-                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
+                .tick_chars(tick_chars),
         );
         pb.set_message(message.to_string());
-        pb.enable_steady_tick(std::time::Duration::from_millis(100));
+        self.start_ticking(&pb);
         pb
     }
 
-    /// create a progess bar with known total
+    /// create a progess bar with known total, showing the current files per second
     pub fn create_progress_bar(&self, total: u64, message: &str) -> ProgressBar {
+        self.progress_bar(total, message, None)
+    }
+
+    /// Creates a progress bar for a scan: with a known total when the files
+    /// were counted, otherwise an open-ended one showing the files so far.
+    pub fn create_scan_bar(&self, total: Option<u64>, message: &str) -> ProgressBar {
+        let Some(total) = total else {
+            let pb = ProgressBar::no_length();
+            let spinner_color = self.get_spinner_color();
+            let (tick_chars, _) = self.progress_chars();
+            pb.set_style(
+                ProgressStyle::default_spinner()
+                    .template(&format!(
+                        "{{spinner:{}}} {{pos}} files {{rate}} {{msg}}",
+                        spinner_color
+                    ))
+                    .unwrap()
+                    .with_key("rate", RateTracker::new(None))
+                    .tick_chars(tick_chars),
+            );
+            pb.set_message(message.to_string());
+            self.start_ticking(&pb);
+            return pb;
+        };
+        self.create_progress_bar(total, message)
+    }
+
+    /// Creates a progress bar that also shows the bytes per second added to the returned [`Throughput`].
+    pub fn create_progress_bar_with_throughput(
+        &self,
+        total: u64,
+        message: &str,
+    ) -> (ProgressBar, Throughput) {
+        let throughput = Throughput::default();
+        let pb = self.progress_bar(total, message, Some(throughput.clone()));
+        (pb, throughput)
+    }
+
+    fn progress_bar(&self, total: u64, message: &str, bytes: Option<Throughput>) -> ProgressBar {
         let pb = ProgressBar::new(total);
         let (spinner_color, bar_color) = self.get_bar_colors();
+        let (tick_chars, progress_chars) = self.progress_chars();
         pb.set_style(
             ProgressStyle::default_bar()
                 .template(&format!(
-                    "{{spinner:{}}} {{bar:40.{}/{}}} {{pos}}/{{len}} ({{percent}}%) {{msg}}",
+                    "{{spinner:{}}} {{bar:40.{}/{}}} {{pos}}/{{len}} ({{percent}}%) {{rate}} {{msg}}",
                     spinner_color, bar_color, bar_color
                 ))
                 .unwrap()
-                .progress_chars("█ ")
-                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
+                .with_key("rate", RateTracker::new(bytes))
+                .progress_chars(progress_chars)
+                .tick_chars(tick_chars),
         );
         pb.set_message(message.to_string());
-        pb.enable_steady_tick(std::time::Duration::from_millis(100));
+        self.start_ticking(&pb);
         pb
     }
 
@@ -601,16 +712,30 @@ impl UI {
         Ok(())
     }
 
-    /// Print a summary section with navigation
+    /// Print a summary section with navigation, unless turned off with `--no-summary`
     pub fn print_summary(
         &self,
         mode: &Mode,
         title: &str,
-        stats: &[(String, usize, u64)],
-        all_files: &[(String, u64, String)], // (name, size, category)
-        total_drive_size: Option<u64>,
+        scan_stats: &ScanStats,
+        drive: Option<DriveSpace>,
         _clear_before: bool,
     ) -> io::Result<()> {
+        if !self.show_summary {
+            return Ok(());
+        }
+        let stats = scan_stats.get_summary();
+        let stats = stats.as_slice();
+        let all_files = scan_stats.get_all_files();
+        let all_files = all_files.as_slice();
+        let extension_stats = scan_stats.get_extension_summary();
+        let directory_stats = scan_stats.get_directory_summary();
+        let source_stats: Vec<_> = scan_stats
+            .get_source_summary()
+            .into_iter()
+            .map(|(source, count, size)| (scan_stats.source_label(&source), count, size))
+            .collect();
+
         let mut total_files = 0;
         let mut total_size = 0u64;
 
@@ -620,7 +745,25 @@ impl UI {
         }
 
         // Start navigation system
-        let sections = ["Categories", "Statistics", "Largest Files"];
+        let mut sections = vec![
+            "Categories",
+            "Statistics",
+            "Extensions",
+            "Directories",
+            "Largest Files",
+        ];
+        if source_stats.len() > 1 {
+            sections.insert(4, "Sources");
+        }
+        if !scan_stats.sensitive_files.is_empty() {
+            sections.push("Sensitive Files");
+        }
+        if !scan_stats.zero_byte_files.is_empty() {
+            sections.push("Empty Files");
+        }
+        if !scan_stats.errors.is_empty() {
+            sections.push("Errors");
+        }
         let mut current_section = 0;
 
         loop {
@@ -646,6 +789,9 @@ impl UI {
                     .italic()
             );
             println!("{}", white_bold.apply_to("=".repeat(70)));
+            if scan_stats.limit_reached {
+                self.print_warning("Scan limit reached; results are partial")?;
+            }
             println!();
 
             // Display current section
@@ -653,8 +799,13 @@ impl UI {
                 "Categories" => {
                     println!("{}", style.apply_to("CATEGORY DISTRIBUTION").bold());
                     println!();
-                    let pie_chart =
-                        create_fixed_pie_chart(stats, total_drive_size, &self.color_theme);
+                    let chart_stats = fold_small_categories(stats, self.min_category_files);
+                    let pie_chart = create_fixed_pie_chart(
+                        &chart_stats,
+                        drive.map(|d| d.capacity),
+                        self.chart_by,
+                        &self.color_theme,
+                    );
                     for line in pie_chart {
                         println!("  {}", line);
                     }
@@ -663,10 +814,112 @@ impl UI {
                 "Statistics" => {
                     println!("{}", style.apply_to("STATISTICS").bold());
                     println!();
-                    let statistics = create_statistics_summary(stats, total_files, total_size);
+                    let statistics = create_statistics_summary(
+                        stats,
+                        total_files,
+                        total_size,
+                        scan_stats.total_dirs,
+                        scan_stats.empty_dirs,
+                    );
                     for line in statistics {
                         println!("  {}", line);
                     }
+                    if scan_stats.sparse_files > 0 {
+                        println!(
+                            "  {} {} ({} sparse files)",
+                            white_bold.apply_to("Apparent size:           "),
+                            white_bold
+                                .apply_to(format_size(scan_stats.total_apparent_size))
+                                .italic(),
+                            white_bold
+                                .apply_to(format!("{}", scan_stats.sparse_files))
+                                .italic()
+                        );
+                    }
+                    if scan_stats.empty_files > 0 {
+                        println!(
+                            "  {} {}",
+                            white_bold.apply_to("Empty files:             "),
+                            white_bold
+                                .apply_to(format!("{}", scan_stats.empty_files))
+                                .italic()
+                        );
+                    }
+                    if scan_stats.excluded_files > 0 || scan_stats.excluded_dirs > 0 {
+                        println!(
+                            "  {} {} files ({}) / {} directories",
+                            white_bold.apply_to("Skipped by exclusions:   "),
+                            white_bold
+                                .apply_to(format!("{}", scan_stats.excluded_files))
+                                .italic(),
+                            white_bold
+                                .apply_to(format_size(scan_stats.excluded_size))
+                                .italic(),
+                            white_bold
+                                .apply_to(format!("{}", scan_stats.excluded_dirs))
+                                .italic()
+                        );
+                    }
+                    if scan_stats.excluded_by_date > 0 {
+                        println!(
+                            "  {} {}",
+                            white_bold.apply_to("Outside date filter:     "),
+                            white_bold
+                                .apply_to(format!("{}", scan_stats.excluded_by_date))
+                                .italic()
+                        );
+                    }
+                    if !scan_stats.special_entries.is_empty() {
+                        println!(
+                            "  {} {}",
+                            white_bold.apply_to("Special entries:         "),
+                            white_bold
+                                .apply_to(format_special_entries(scan_stats))
+                                .italic()
+                        );
+                    }
+                    if let Some(drive) = drive {
+                        println!(
+                            "  {} {} capacity, {} used, {} free",
+                            white_bold.apply_to("Drive:                   "),
+                            white_bold.apply_to(format_size(drive.capacity)).italic(),
+                            white_bold.apply_to(format_size(drive.used)).italic(),
+                            white_bold.apply_to(format_size(drive.free)).italic()
+                        );
+                    }
+                    println!();
+                    println!("{}", style.apply_to("SIZE DISTRIBUTION").bold());
+                    println!();
+                    for line in create_size_histogram(scan_stats) {
+                        println!("  {}", line);
+                    }
+                    println!();
+                }
+                "Extensions" => {
+                    println!("{}", style.apply_to("EXTENSION DISTRIBUTION").bold());
+                    println!();
+                    let chart = create_extension_chart(&extension_stats, total_files);
+                    for line in chart {
+                        println!("  {}", line);
+                    }
+                    println!();
+                }
+                "Directories" => {
+                    println!("{}", style.apply_to("TOP-LEVEL DIRECTORIES").bold());
+                    println!();
+                    let chart = create_directory_chart(&directory_stats, total_size);
+                    for line in chart {
+                        println!("  {}", line);
+                    }
+                    println!();
+                }
+                "Sources" => {
+                    println!("{}", style.apply_to("SOURCES").bold());
+                    println!();
+                    let chart = create_directory_chart(&source_stats, total_size);
+                    for line in chart {
+                        println!("  {}", line);
+                    }
                     println!();
                 }
                 "Largest Files" => {
@@ -678,42 +931,71 @@ impl UI {
                     }
                     println!();
                 }
-                _ => {}
-            }
-
-            // Show navigation prompt
-            let nav_choice = self.show_navigation_prompt(
-                current_section,
-                sections.len(),
-                sections[current_section],
-            )?;
-
-            match nav_choice.as_str() {
-                "next" => {
-                    if current_section < sections.len() - 1 {
-                        current_section += 1;
+                "Sensitive Files" => {
+                    println!("{}", style.apply_to("SENSITIVE FILES").bold());
+                    println!();
+                    let list = create_path_list(
+                        &scan_stats.sensitive_files,
+                        relative_base(&scan_stats.root),
+                        &format!(
+                            "{} files match the sensitive name patterns",
+                            scan_stats.sensitive_files.len()
+                        ),
+                    );
+                    for line in list {
+                        println!("  {}", line);
                     }
+                    println!();
                 }
-                "back" => {
-                    current_section = current_section.saturating_sub(1);
+                "Empty Files" => {
+                    println!("{}", style.apply_to("EMPTY FILES").bold());
+                    println!();
+                    let list = create_path_list(
+                        &scan_stats.zero_byte_files,
+                        relative_base(&scan_stats.root),
+                        &format!(
+                            "{} files are zero bytes (failed downloads, placeholders, ...)",
+                            scan_stats.zero_byte_files.len()
+                        ),
+                    );
+                    for line in list {
+                        println!("  {}", line);
+                    }
+                    println!();
                 }
-                "exit" => {
-                    break;
+                "Errors" => {
+                    println!("{}", style.apply_to("SCAN ERRORS").bold());
+                    println!();
+                    let list = create_error_list(
+                        &scan_stats.get_error_summary(5),
+                        scan_stats.errors.len(),
+                    );
+                    for line in list {
+                        println!("  {}", line);
+                    }
+                    println!();
                 }
-                _ => break,
+                _ => {}
+            }
+
+            // Show navigation prompt
+            match self.show_navigation_prompt(current_section, &sections)? {
+                Some(section) => current_section = section,
+                None => break,
             }
         }
 
         Ok(())
     }
 
-    /// Show navigation prompt with options
+    /// Show navigation prompt listing every section.
+    ///
+    /// Returns the index of the section to show next, or `None` to leave the summary.
     fn show_navigation_prompt(
         &self,
         current_section: usize,
-        total_sections: usize,
-        section_name: &str,
-    ) -> io::Result<String> {
+        sections: &[&str],
+    ) -> io::Result<Option<usize>> {
         use console::Style;
         use dialoguer::Select;
 
@@ -727,43 +1009,38 @@ impl UI {
                 .apply_to(format!(
                     "Section {}/{}:",
                     current_section + 1,
-                    total_sections
+                    sections.len()
                 ))
                 .bold(),
-            white_bold.apply_to(section_name)
+            white_bold.apply_to(sections[current_section])
         );
         println!();
 
-        let mut options = Vec::new();
-
-        // Add "Next →" first when available (since it's the default)
-        if current_section < total_sections - 1 {
-            options.push("Next →");
-        }
-
-        if current_section > 0 {
-            options.push("← Previous");
-        }
-
-        options.push("Continue");
+        // Every section can be reached directly; the current one is marked
+        let mut options: Vec<String> = sections
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                if i == current_section {
+                    format!("{} (current)", name)
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        options.push("Quit".to_string());
 
-        // Default to "Next →" if it exists in options, otherwise default to first option
-        let default_index = options.iter().position(|&opt| opt == "Next →").unwrap_or(0);
+        // Default to the next section, or to Quit from the last one
+        let default_index = current_section + 1;
 
         let theme = self.get_theme();
         let selection = Select::with_theme(theme.as_ref())
-            .with_prompt("Navigate")
+            .with_prompt("Jump to")
             .items(&options)
             .default(default_index)
             .interact_on(&self.term)?;
 
-        let choice = options[selection];
-        match choice {
-            "← Previous" => Ok("back".to_string()),
-            "Next →" => Ok("next".to_string()),
-            "Continue" => Ok("exit".to_string()),
-            _ => Ok("exit".to_string()),
-        }
+        Ok((selection < sections.len()).then_some(selection))
     }
 
     /// Print an info message
@@ -805,6 +1082,29 @@ impl UI {
         Ok(())
     }
 
+    /// Print the one-line category summary, left in the scrollback once the run is over.
+    ///
+    /// Category names take the theme color, which console drops when stdout is not a
+    /// terminal or `CLICOLOR=0` is set, so piped output stays plain.
+    pub fn print_compact_summary(&self, stats: &ScanStats) -> io::Result<()> {
+        if !self.show_compact_summary {
+            return Ok(());
+        }
+        let parts = compact_summary_parts(stats);
+        if parts.is_empty() {
+            println!("no files");
+            return Ok(());
+        }
+        let style = self.get_style().bold();
+        let line = parts
+            .iter()
+            .map(|(category, rest)| format!("{} {}", style.apply_to(category), rest))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        println!("{}", line);
+        Ok(())
+    }
+
     /// Print a warning message
     pub fn print_warning(&self, message: &str) -> io::Result<()> {
         use console::Style;
@@ -864,17 +1164,40 @@ impl Drop for UI {
     }
 }
 
+/// Folds categories with fewer than `min_files` files into a single `other` row.
+///
+/// Totals are unchanged; the row is only added when something was folded.
+fn fold_small_categories(
+    stats: &[(String, usize, u64)],
+    min_files: usize,
+) -> Vec<(String, usize, u64)> {
+    let (kept, folded): (Vec<_>, Vec<_>) = stats
+        .iter()
+        .cloned()
+        .partition(|(_, count, _)| *count >= min_files);
+
+    let mut result = kept;
+    if !folded.is_empty() {
+        let count = folded.iter().map(|(_, count, _)| count).sum();
+        let size = folded.iter().map(|(_, _, size)| size).sum();
+        result.push((format!("other ({})", folded.len()), count, size));
+    }
+    result
+}
+
 // Helper function to create fixed-size pie chart showing folder sizes and percentages
 fn create_fixed_pie_chart(
     stats: &[(String, usize, u64)],
     total_drive_size: Option<u64>,
-    _theme: &str,
+    chart_by: ChartBy,
+    theme: &str,
 ) -> Vec<String> {
     let mut lines = Vec::new();
 
-    // Calculate total scanned size
+    // Calculate total scanned size and file count
     let total_scanned: u64 = stats.iter().map(|(_, _, size)| size).sum();
-    if total_scanned == 0 {
+    let total_count: usize = stats.iter().map(|(_, count, _)| count).sum();
+    if total_count == 0 || (total_scanned == 0 && chart_by == ChartBy::Size) {
         use console::Style;
         let white_bold = Style::new().white().bold();
         lines.push(format!("{}", white_bold.apply_to("No data to display")));
@@ -882,33 +1205,52 @@ fn create_fixed_pie_chart(
     }
 
     // Use drive size if provided, otherwise use scanned size
-    let reference_size = total_drive_size.unwrap_or(total_scanned);
+    let reference_size = total_drive_size.unwrap_or(total_scanned).max(1);
 
     use console::Style;
 
     let white_bold = Style::new().white().bold();
     let char = "█";
+    let count_char = "▒";
 
-    // Sort categories by size descending
+    // Sort categories by what the bars show, descending
     let mut sorted_stats: Vec<_> = stats.iter().collect();
-    sorted_stats.sort_by(|a, b| b.2.cmp(&a.2));
+    if chart_by == ChartBy::Count {
+        sorted_stats.sort_by_key(|s| std::cmp::Reverse(s.1));
+    } else {
+        sorted_stats.sort_by_key(|s| std::cmp::Reverse(s.2));
+    }
+
+    // Each category keeps its color whichever order the bars are in
+    let palette = chart_palette(theme);
+    let mut names: Vec<&str> = stats
+        .iter()
+        .map(|(name, _, _)| name.as_str())
+        .filter(|name| !name.starts_with("other ("))
+        .collect();
+    names.sort_unstable();
+    let category_style = |category: &str| match names.binary_search(&category) {
+        Ok(index) => palette[index % palette.len()].clone(),
+        // The folded "other (N)" row is set apart from every real category
+        Err(_) => Style::new().white().dim(),
+    };
 
     // Fixed bar width
     const BAR_WIDTH: usize = 40;
 
-    for (category, count, size) in sorted_stats.iter() {
-        let percentage_of_drive = (*size as f64 / reference_size as f64) * 100.0;
-        let bar_length = ((*size as f64 / reference_size as f64) * BAR_WIDTH as f64) as usize;
-
-        // Build the bar
-        let bar = if bar_length > 0 {
-            char.repeat(bar_length)
+    let bar = |fraction: f64, fill: &str| -> (String, usize) {
+        let bar_length = (fraction * BAR_WIDTH as f64) as usize;
+        if bar_length > 0 {
+            (fill.repeat(bar_length), bar_length)
         } else {
-            " ".to_string()
-        };
+            (" ".to_string(), 1)
+        }
+    };
 
+    for (category, count, size) in sorted_stats.iter() {
         // Format category name with fixed width
         let category_label = format!("{}:", category);
+        let color = category_style(category);
 
         // Calculate average file size for this category
         let avg_size = if *count > 0 {
@@ -917,34 +1259,120 @@ fn create_fixed_pie_chart(
             0
         };
 
-        // Apply white bold to text, italicize important numbers
-        let line = format!(
-            "{} {:<15} {}{} {} {} ({} files, avg: {})",
-            char,
-            category_label,
-            bar,
-            " ".repeat(BAR_WIDTH.saturating_sub(bar_length)),
-            white_bold
-                .apply_to(format!("{:>6.2}%", percentage_of_drive))
-                .italic(),
-            white_bold
-                .apply_to(format!("{:>12}", format_size(*size)))
-                .italic(),
-            count,
-            white_bold.apply_to(format_size(avg_size)).italic()
-        );
+        if chart_by != ChartBy::Count {
+            let fraction = *size as f64 / reference_size as f64;
+            let (size_bar, bar_length) = bar(fraction, char);
+
+            // Color the bar by category, apply white bold to text, italicize important numbers
+            let line = format!(
+                "{} {} {}{} {} {} {}{}{}",
+                color.apply_to(char),
+                white_bold.apply_to(format!("{:<15}", category_label)),
+                color.apply_to(size_bar),
+                " ".repeat(BAR_WIDTH.saturating_sub(bar_length)),
+                white_bold
+                    .apply_to(format!("{:>6.2}%", fraction * 100.0))
+                    .italic(),
+                white_bold
+                    .apply_to(format!("{:>12}", format_size(*size)))
+                    .italic(),
+                white_bold.apply_to(format!("({} files, avg: ", count)),
+                white_bold.apply_to(format_size(avg_size)).italic(),
+                white_bold.apply_to(")")
+            );
+            lines.push(line);
+        }
 
-        lines.push(format!("{}", white_bold.apply_to(line)));
+        if chart_by != ChartBy::Size {
+            let fraction = *count as f64 / total_count as f64;
+            let (count_bar, bar_length) = bar(fraction, count_char);
+
+            // Under a size bar the category is already named
+            let (marker, label) = if chart_by == ChartBy::Both {
+                (" ", String::new())
+            } else {
+                (char, category_label)
+            };
+            let line = format!(
+                "{} {} {}{} {} {} {}{}{}",
+                color.apply_to(marker),
+                white_bold.apply_to(format!("{:<15}", label)),
+                color.apply_to(count_bar),
+                " ".repeat(BAR_WIDTH.saturating_sub(bar_length)),
+                white_bold
+                    .apply_to(format!("{:>6.2}%", fraction * 100.0))
+                    .italic(),
+                white_bold.apply_to(format!("{:>6} files", count)).italic(),
+                white_bold.apply_to(format!("({}, avg: ", format_size(*size))),
+                white_bold.apply_to(format_size(avg_size)).italic(),
+                white_bold.apply_to(")")
+            );
+            lines.push(line);
+        }
+    }
+
+    if chart_by == ChartBy::Both {
+        lines.push(String::new());
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!(
+                "{} share of bytes   {} share of files",
+                char, count_char
+            ))
+        ));
+    }
+
+    if chart_by != ChartBy::Count
+        && let Some(capacity) = total_drive_size
+    {
+        lines.push(String::new());
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!(
+                "Size percentages are of the {} drive; scanned files take {}",
+                format_size(capacity),
+                format_size(total_scanned)
+            ))
+        ));
     }
 
     lines
 }
 
+/// Hues far apart in the 256-color cube, each named after the theme it matches.
+const CHART_HUES: [(&str, u8); 8] = [
+    ("blue", 33),
+    ("yellow", 220),
+    ("green", 40),
+    ("magenta", 170),
+    ("cyan", 44),
+    ("red", 196),
+    ("orange", 208),
+    ("purple", 99),
+];
+
+/// Styles used to tell chart categories apart: the theme's own color from
+/// [`UI::get_static_status_styles`] first, then clearly different hues,
+/// cycling when there are more categories.
+fn chart_palette(theme: &str) -> Vec<console::Style> {
+    let (_, theme_style, _, _) = UI::get_static_status_styles(theme);
+    std::iter::once(theme_style.bold())
+        .chain(
+            CHART_HUES
+                .iter()
+                .filter(|(name, _)| *name != theme)
+                .map(|(_, hue)| console::Style::new().color256(*hue).bold()),
+        )
+        .collect()
+}
+
 // Helper function to create statistics summary
 fn create_statistics_summary(
     stats: &[(String, usize, u64)],
     total_files: usize,
     total_size: u64,
+    total_dirs: usize,
+    empty_dirs: usize,
 ) -> Vec<String> {
     use console::Style;
     let white_bold = Style::new().white().bold();
@@ -993,6 +1421,12 @@ fn create_statistics_summary(
         white_bold.apply_to("Median file size:        "),
         white_bold.apply_to(format_size(median)).italic()
     ));
+    lines.push(format!(
+        "{} {} ({} empty)",
+        white_bold.apply_to("Total directories:       "),
+        white_bold.apply_to(format!("{}", total_dirs)).italic(),
+        white_bold.apply_to(format!("{}", empty_dirs)).italic()
+    ));
     lines.push(format!(
         "{} {}",
         white_bold.apply_to("Total categories:        "),
@@ -1031,8 +1465,200 @@ fn create_statistics_summary(
     lines
 }
 
+/// Labels for the buckets of [`ScanStats::get_size_histogram`] in the configured units.
+fn size_bucket_labels() -> [String; SIZE_BUCKETS] {
+    let (k, m) = if DECIMAL_SIZES.load(Ordering::Relaxed) {
+        ("KB", "MB")
+    } else {
+        ("KiB", "MiB")
+    };
+    [
+        format!("< 1 {}", k),
+        format!("1-10 {}", k),
+        format!("10-100 {}", k),
+        format!("100 {}-1 {}", k, m),
+        format!("1-10 {}", m),
+        format!("10-100 {}", m),
+        format!(">= 100 {}", m),
+    ]
+}
+
+// Helper function to create the file size histogram, with bars sized by file count
+fn create_size_histogram(scan_stats: &ScanStats) -> Vec<String> {
+    use console::Style;
+    let white_bold = Style::new().white().bold();
+
+    let unit = if DECIMAL_SIZES.load(Ordering::Relaxed) {
+        1000
+    } else {
+        1024
+    };
+    let buckets = scan_stats.get_size_histogram(unit);
+    let total_files: usize = buckets.iter().map(|(count, _)| count).sum();
+    if total_files == 0 {
+        return vec![format!("{}", white_bold.apply_to("No data to display"))];
+    }
+
+    let char = "█";
+    const BAR_WIDTH: usize = 30;
+
+    size_bucket_labels()
+        .iter()
+        .zip(buckets)
+        .map(|(label, (count, size))| {
+            let share = count as f64 / total_files as f64;
+            let bar_length = (share * BAR_WIDTH as f64) as usize;
+            let line = format!(
+                "{} {:<15} {}{} {} {} ({} files)",
+                char,
+                label,
+                char.repeat(bar_length),
+                " ".repeat(BAR_WIDTH.saturating_sub(bar_length)),
+                white_bold
+                    .apply_to(format!("{:>6.2}%", share * 100.0))
+                    .italic(),
+                white_bold
+                    .apply_to(format!("{:>12}", format_size(size)))
+                    .italic(),
+                count
+            );
+            format!("{}", white_bold.apply_to(line))
+        })
+        .collect()
+}
+
+/// Display name for an extension key, showing files without one as `(none)`.
+pub fn extension_label(extension: &str) -> &str {
+    if extension.is_empty() {
+        "(none)"
+    } else {
+        extension
+    }
+}
+
+// Helper function to create extension bar chart, sized by file count
+fn create_extension_chart(ext_stats: &[(String, usize, u64)], total_files: usize) -> Vec<String> {
+    use console::Style;
+    let white_bold = Style::new().white().bold();
+    let mut lines = Vec::new();
+
+    if total_files == 0 {
+        lines.push(format!("{}", white_bold.apply_to("No data to display")));
+        return lines;
+    }
+
+    let char = "█";
+    const BAR_WIDTH: usize = 30;
+    const MAX_ROWS: usize = 15;
+
+    for (extension, count, size) in ext_stats.iter().take(MAX_ROWS) {
+        let share = *count as f64 / total_files as f64;
+        let bar_length = (share * BAR_WIDTH as f64) as usize;
+
+        let line = format!(
+            "{} {:<12} {}{} {} {} ({} files)",
+            char,
+            format!("{}:", extension_label(extension)),
+            char.repeat(bar_length),
+            " ".repeat(BAR_WIDTH.saturating_sub(bar_length)),
+            white_bold
+                .apply_to(format!("{:>6.2}%", share * 100.0))
+                .italic(),
+            white_bold
+                .apply_to(format!("{:>12}", format_size(*size)))
+                .italic(),
+            count
+        );
+
+        lines.push(format!("{}", white_bold.apply_to(line)));
+    }
+
+    if ext_stats.len() > MAX_ROWS {
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!(
+                "... and {} more extensions (see log for the full list)",
+                ext_stats.len() - MAX_ROWS
+            ))
+        ));
+    }
+
+    lines
+}
+
+/// Display name for a top-level directory key, showing files directly in the root as `(root)`.
+pub fn directory_label(directory: &str) -> &str {
+    if directory.is_empty() {
+        "(root)"
+    } else {
+        directory
+    }
+}
+
+// Helper function to create top-level directory bar chart, sized by allocated size
+fn create_directory_chart(dir_stats: &[(String, usize, u64)], total_size: u64) -> Vec<String> {
+    use console::Style;
+    let white_bold = Style::new().white().bold();
+    let mut lines = Vec::new();
+
+    if dir_stats.is_empty() {
+        lines.push(format!("{}", white_bold.apply_to("No data to display")));
+        return lines;
+    }
+
+    let char = "█";
+    const BAR_WIDTH: usize = 30;
+    const MAX_ROWS: usize = 15;
+
+    for (directory, count, size) in dir_stats.iter().take(MAX_ROWS) {
+        let share = if total_size > 0 {
+            *size as f64 / total_size as f64
+        } else {
+            0.0
+        };
+        let bar_length = (share * BAR_WIDTH as f64) as usize;
+
+        // Long directory names would push the bars out of line
+        let label = directory_label(directory);
+        let label = if label.chars().count() > 19 {
+            format!("{}...", label.chars().take(16).collect::<String>())
+        } else {
+            label.to_string()
+        };
+
+        let line = format!(
+            "{} {:<20} {}{} {} {} ({} files)",
+            char,
+            format!("{}:", label),
+            char.repeat(bar_length),
+            " ".repeat(BAR_WIDTH.saturating_sub(bar_length)),
+            white_bold
+                .apply_to(format!("{:>6.2}%", share * 100.0))
+                .italic(),
+            white_bold
+                .apply_to(format!("{:>12}", format_size(*size)))
+                .italic(),
+            count
+        );
+
+        lines.push(format!("{}", white_bold.apply_to(line)));
+    }
+
+    if dir_stats.len() > MAX_ROWS {
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!(
+                "... and {} more directories (see log for the full list)",
+                dir_stats.len() - MAX_ROWS
+            ))
+        ));
+    }
+
+    lines
+}
+
 // Helper function to create top 10 largest files leaderboard
-fn create_leaderboard(all_files: &[(String, u64, String)]) -> Vec<String> {
+fn create_leaderboard(all_files: &[(String, u64, u64, String)]) -> Vec<String> {
     use console::Style;
     let white_bold = Style::new().white().bold();
     let mut lines = Vec::new();
@@ -1044,7 +1670,7 @@ fn create_leaderboard(all_files: &[(String, u64, String)]) -> Vec<String> {
 
     // Sort by size descending and take top 10
     let mut sorted_files: Vec<_> = all_files.iter().collect();
-    sorted_files.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted_files.sort_by_key(|f| std::cmp::Reverse(f.1));
     let top_files: Vec<_> = sorted_files.iter().take(10).collect();
 
     // Header
@@ -1058,7 +1684,7 @@ fn create_leaderboard(all_files: &[(String, u64, String)]) -> Vec<String> {
     lines.push(format!("{}", white_bold.apply_to("-".repeat(68))));
 
     // Top 10 files - italicize important data (rank, size)
-    for (rank, (name, size, category)) in top_files.iter().enumerate() {
+    for (rank, (name, size, apparent_size, category)) in top_files.iter().enumerate() {
         // Truncate long file names
         let display_name = if name.len() > 35 {
             format!("{}...", &name[..32])
@@ -1074,22 +1700,525 @@ fn create_leaderboard(all_files: &[(String, u64, String)]) -> Vec<String> {
             category
         );
 
+        // Sparse files take less space than they claim, so show both
+        let line = if apparent_size > size {
+            format!("{} (apparent {})", line, format_size(*apparent_size))
+        } else {
+            line
+        };
+
         lines.push(format!("{}", white_bold.apply_to(line)));
     }
 
     lines
 }
 
-// Helper function to format file sizes
+// Helper function to list files relative to the scan root under a heading
+fn create_path_list(
+    files: &[std::path::PathBuf],
+    root: &std::path::Path,
+    heading: &str,
+) -> Vec<String> {
+    use console::Style;
+    let white_bold = Style::new().white().bold();
+    let mut lines = Vec::new();
+    const MAX_ROWS: usize = 20;
+
+    lines.push(format!("{}", white_bold.apply_to(heading)));
+    lines.push(format!("{}", white_bold.apply_to("-".repeat(68))));
+
+    for file in files.iter().take(MAX_ROWS) {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(safe_truncate_path(&relative.display().to_string(), 66))
+        ));
+    }
+
+    if files.len() > MAX_ROWS {
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!(
+                "... and {} more (see log for the full list)",
+                files.len() - MAX_ROWS
+            ))
+        ));
+    }
+
+    lines
+}
+
+// Helper function to list error counts per kind with the paths causing most of them
+fn create_error_list(summary: &[crate::scanner::ErrorGroup], total_errors: usize) -> Vec<String> {
+    use console::Style;
+    let white_bold = Style::new().white().bold();
+    let mut lines = Vec::new();
+
+    lines.push(format!(
+        "{}",
+        white_bold.apply_to(format!("{} paths could not be scanned", total_errors))
+    ));
+    lines.push(format!("{}", white_bold.apply_to("-".repeat(68))));
+
+    for group in summary {
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!(
+                "{:<24} {}",
+                format!("{}:", group.kind.label()),
+                group.count
+            ))
+        ));
+        for (path, errors) in &group.paths {
+            let entry = if *errors > 1 {
+                format!("{} ({} errors)", path.display(), errors)
+            } else {
+                path.display().to_string()
+            };
+            lines.push(format!(
+                "  {}",
+                white_bold.apply_to(safe_truncate_path(&entry, 64))
+            ));
+        }
+    }
+
+    let classified: usize = summary.iter().map(|group| group.count).sum();
+    if total_errors > classified {
+        lines.push(format!(
+            "{}",
+            white_bold.apply_to(format!("{:<24} {}", "Other:", total_errors - classified))
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "{}",
+        white_bold.apply_to("See log for every error message")
+    ));
+
+    lines
+}
+
+/// Whether `format_size` uses decimal units; binary is the default.
+/// How far back the rates shown next to progress bars look.
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Bytes processed so far by the phase a progress bar tracks.
+///
+/// Cloned into the code doing the work, which calls [`Throughput::add`] as
+/// each file finishes; the bar shows the resulting rate.
+#[derive(Debug, Clone, Default)]
+pub struct Throughput(Arc<AtomicU64>);
+
+impl Throughput {
+    pub fn add(&self, bytes: u64) {
+        self.0.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits how often the recent-files list is redrawn.
+///
+/// Checked by scan, copy, and zip callbacks for every file, so the display
+/// keeps moving through a few huge files without redrawing for each of
+/// millions of tiny ones. The first file is always shown.
+#[derive(Debug)]
+pub struct RedrawThrottle {
+    start: Instant,
+    interval_ms: u64,
+    /// Milliseconds after `start` when the next redraw is due
+    next_ms: AtomicU64,
+}
+
+impl RedrawThrottle {
+    /// Allows a redraw at most every `interval_ms` milliseconds; 0 allows every one.
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            start: Instant::now(),
+            interval_ms,
+            next_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns true if a redraw is due, and if so starts the next interval.
+    ///
+    /// When several threads ask at once only one of them gets true.
+    pub fn ready(&self) -> bool {
+        let now = self.start.elapsed().as_millis() as u64;
+        let next = self.next_ms.load(Ordering::Relaxed);
+        now >= next
+            && self
+                .next_ms
+                .compare_exchange(
+                    next,
+                    now + self.interval_ms,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+    }
+}
+
+/// The `{rate}` progress bar key: files and, if tracked, bytes per second
+/// over the last [`RATE_WINDOW`], so a slowdown shows within seconds rather
+/// than being averaged over the whole run.
+#[derive(Clone)]
+struct RateTracker {
+    bytes: Option<Throughput>,
+    /// `(time, files, bytes)` samples, oldest first
+    samples: VecDeque<(Instant, u64, u64)>,
+}
+
+impl RateTracker {
+    fn new(bytes: Option<Throughput>) -> Self {
+        Self {
+            bytes,
+            samples: VecDeque::new(),
+        }
+    }
+
+    fn record(&mut self, now: Instant, files: u64) {
+        let bytes = self.bytes.as_ref().map_or(0, Throughput::bytes);
+        self.samples.push_back((now, files, bytes));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _, _)| now.duration_since(*time) > RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Files and bytes per second between the oldest and newest sample.
+    ///
+    /// `None` until samples span at least a second.
+    fn rates(&self) -> Option<(f64, f64)> {
+        let ((start, files0, bytes0), (end, files1, bytes1)) =
+            (self.samples.front()?, self.samples.back()?);
+        let seconds = end.duration_since(*start).as_secs_f64();
+        if seconds < 1.0 {
+            return None;
+        }
+        Some((
+            files1.saturating_sub(*files0) as f64 / seconds,
+            bytes1.saturating_sub(*bytes0) as f64 / seconds,
+        ))
+    }
+}
+
+impl ProgressTracker for RateTracker {
+    fn clone_box(&self) -> Box<dyn ProgressTracker> {
+        Box::new(self.clone())
+    }
+
+    fn tick(&mut self, state: &ProgressState, now: Instant) {
+        self.record(now, state.pos());
+    }
+
+    fn reset(&mut self, _: &ProgressState, _: Instant) {
+        self.samples.clear();
+    }
+
+    fn write(&self, _: &ProgressState, w: &mut dyn fmt::Write) {
+        let Some((files, bytes)) = self.rates() else {
+            return;
+        };
+        let _ = write!(w, "{:.1} files/s", files);
+        if self.bytes.is_some() {
+            let _ = write!(w, ", {}/s", format_size(bytes as u64));
+        }
+    }
+}
+
+static DECIMAL_SIZES: AtomicBool = AtomicBool::new(false);
+
+/// Sets the unit convention used by `format_size` for the rest of the run.
+pub fn set_size_base(base: SizeBase) {
+    DECIMAL_SIZES.store(base == SizeBase::Decimal, Ordering::Relaxed);
+}
+
+/// One line with each category's file count and size, most files first, e.g.
+/// `documents 4312 (1.20 GiB) | images 9871 (3.40 GiB)`.
+pub fn compact_summary(stats: &ScanStats) -> String {
+    let parts = compact_summary_parts(stats);
+    if parts.is_empty() {
+        return "no files".to_string();
+    }
+    parts
+        .iter()
+        .map(|(category, rest)| format!("{} {}", category, rest))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Category names and their `count (size)` text for [`compact_summary`].
+fn compact_summary_parts(stats: &ScanStats) -> Vec<(String, String)> {
+    stats
+        .get_summary()
+        .into_iter()
+        .map(|(category, count, size)| (category, format!("{} ({})", count, format_size(size))))
+        .collect()
+}
+
+/// Formats a byte count using the configured unit convention (see `set_size_base`).
 pub fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let base = if DECIMAL_SIZES.load(Ordering::Relaxed) {
+        SizeBase::Decimal
+    } else {
+        SizeBase::Binary
+    };
+    format_size_in(bytes, base)
+}
+
+/// Formats a byte count in the given unit convention, e.g. `"1.50 GiB"` or `"1.61 GB"`.
+pub fn format_size_in(bytes: u64, base: SizeBase) -> String {
+    let (step, units): (f64, &[&str]) = match base {
+        SizeBase::Binary => (1024.0, &["B", "KiB", "MiB", "GiB", "TiB"]),
+        SizeBase::Decimal => (1000.0, &["B", "KB", "MB", "GB", "TB"]),
+    };
     let mut size = bytes as f64;
     let mut unit_index = 0;
 
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
+    while size >= step && unit_index < units.len() - 1 {
+        size /= step;
         unit_index += 1;
     }
 
-    format!("{:.2} {}", size, UNITS[unit_index])
+    format!("{:.2} {}", size, units[unit_index])
+}
+
+/// Formats the special entry counts of a scan, e.g. `"4 (symlink: 2, broken symlink: 1, FIFO: 1)"`.
+pub fn format_special_entries(scan_stats: &ScanStats) -> String {
+    if scan_stats.special_entries.is_empty() {
+        return "0".to_string();
+    }
+    let kinds: Vec<String> = scan_stats
+        .get_special_summary()
+        .into_iter()
+        .map(|(kind, count)| format!("{}: {}", kind.label(), count))
+        .collect();
+    format!(
+        "{} ({})",
+        scan_stats.special_entries.len(),
+        kinds.join(", ")
+    )
+}
+
+/// Formats an elapsed time for display, e.g. `"850ms"`, `"42.3s"`, `"14m 05s"`, `"2h 03m 10s"`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!(
+            "{}h {:02}m {:02}s",
+            secs / 3600,
+            (secs / 60) % 60,
+            secs % 60
+        )
+    }
+}
+
+/// Formats a transfer rate as bytes per second, e.g. `"85.00 MiB/s"`.
+pub fn format_rate(bytes: u64, duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs <= 0.0 {
+        return "n/a".to_string();
+    }
+    format!("{}/s", format_size((bytes as f64 / secs) as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_tracker_uses_recent_samples() {
+        let throughput = Throughput::default();
+        let mut tracker = RateTracker::new(Some(throughput.clone()));
+        let start = Instant::now();
+
+        // A fast start that has left the window no longer counts
+        tracker.record(start, 0);
+        assert_eq!(tracker.rates(), None);
+        throughput.add(100 << 20);
+        tracker.record(start + Duration::from_secs(2), 1000);
+        throughput.add(2 << 20);
+        tracker.record(start + Duration::from_secs(8), 1010);
+        throughput.add(2 << 20);
+        tracker.record(start + Duration::from_secs(10), 1020);
+        assert_eq!(tracker.rates(), Some((5.0, (1 << 20) as f64)));
+
+        let mut files_only = RateTracker::new(None);
+        files_only.record(start, 0);
+        files_only.record(start + Duration::from_secs(4), 10);
+        assert_eq!(files_only.rates(), Some((2.5, 0.0)));
+    }
+
+    #[test]
+    fn test_redraw_throttle() {
+        // The first file is shown, then nothing until the interval has passed
+        let throttle = RedrawThrottle::new(60_000);
+        assert!(throttle.ready());
+        assert!(!throttle.ready());
+
+        let every_file = RedrawThrottle::new(0);
+        assert!((0..5).all(|_| every_file.ready()));
+
+        let short = RedrawThrottle::new(20);
+        assert!(short.ready());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(short.ready());
+    }
+
+    #[test]
+    fn test_no_count_and_no_summary() {
+        let ui = UI::new().unwrap().with_summary(false);
+        assert_eq!(ui.create_scan_bar(Some(10), "Analyzing").length(), Some(10));
+        let open_ended = ui.create_scan_bar(None, "Analyzing");
+        open_ended.inc(3);
+        assert_eq!(open_ended.length(), None);
+        assert_eq!(open_ended.position(), 3);
+
+        // Returns at once rather than waiting for a key
+        let stats = ScanStats::new();
+        ui.print_summary(&Mode::Inspect, "DONE", &stats, None, false)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_compact_summary() {
+        use crate::scanner::FileInfo;
+        use std::path::PathBuf;
+
+        let mut stats = ScanStats::new();
+        assert_eq!(compact_summary(&stats), "no files");
+
+        for (name, category, size) in [
+            ("a.txt", "documents", 100),
+            ("b.jpg", "images", 300),
+            ("c.png", "images", 200),
+        ] {
+            stats.add_file(FileInfo {
+                path: PathBuf::from(name),
+                extension: String::new(),
+                size,
+                allocated_size: size,
+                modified: None,
+                category: category.to_string(),
+                mime: None,
+            });
+        }
+        // Sizes under 1000 bytes read the same in either unit convention
+        assert_eq!(
+            compact_summary(&stats),
+            "images 2 (500.00 B) | documents 1 (100.00 B)"
+        );
+    }
+
+    #[test]
+    fn test_fold_small_categories() {
+        let stats = vec![
+            ("images".to_string(), 120, 5000),
+            ("fonts".to_string(), 1, 40),
+            ("torrents".to_string(), 2, 10),
+        ];
+
+        assert_eq!(fold_small_categories(&stats, 0), stats);
+        assert_eq!(
+            fold_small_categories(&stats, 3),
+            vec![
+                ("images".to_string(), 120, 5000),
+                ("other (2)".to_string(), 3, 50),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pie_chart_by_count() {
+        let stats = vec![
+            ("videos".to_string(), 2, 9000),
+            ("documents".to_string(), 6, 1000),
+        ];
+
+        let by_size = create_fixed_pie_chart(&stats, None, ChartBy::Size, "default");
+        assert_eq!(by_size.len(), 2);
+        assert!(console::strip_ansi_codes(&by_size[0]).contains("videos:"));
+        assert!(console::strip_ansi_codes(&by_size[0]).contains("90.00%"));
+
+        let by_count = create_fixed_pie_chart(&stats, None, ChartBy::Count, "default");
+        let first = console::strip_ansi_codes(&by_count[0]).to_string();
+        assert!(first.contains("documents:"));
+        assert!(first.contains("75.00%"));
+
+        // A size line and a count line per category, plus the legend
+        let both = create_fixed_pie_chart(&stats, Some(20_000), ChartBy::Both, "default");
+        assert_eq!(both.len(), 4 + 2 + 2);
+        assert!(console::strip_ansi_codes(&both[1]).contains("25.00%"));
+    }
+
+    #[test]
+    fn test_chart_palette() {
+        for theme in [
+            "default", "cyan", "magenta", "yellow", "green", "red", "blue", "white",
+        ] {
+            let palette = chart_palette(theme);
+            for (i, style) in palette.iter().enumerate() {
+                assert!(
+                    !palette[i + 1..].contains(style),
+                    "{} repeats a color",
+                    theme
+                );
+            }
+        }
+
+        // The theme's color leads, and no other bar shares its hue
+        let (_, cyan, _, _) = UI::get_static_status_styles("cyan");
+        assert_eq!(chart_palette("cyan")[0], cyan.bold());
+        assert_eq!(chart_palette("cyan").len(), 8);
+        assert_eq!(chart_palette("default").len(), 9);
+        assert_eq!(chart_palette("unknown"), chart_palette("white"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(42_300)), "42.3s");
+        assert_eq!(format_duration(Duration::from_secs(14 * 60 + 5)), "14m 05s");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 3600 + 3 * 60 + 10)),
+            "2h 03m 10s"
+        );
+    }
+
+    #[test]
+    fn test_format_size_in() {
+        assert_eq!(format_size_in(512, SizeBase::Binary), "512.00 B");
+        assert_eq!(format_size_in(1536, SizeBase::Binary), "1.50 KiB");
+        assert_eq!(
+            format_size_in(500_000_000_000, SizeBase::Binary),
+            "465.66 GiB"
+        );
+        assert_eq!(
+            format_size_in(500_000_000_000, SizeBase::Decimal),
+            "500.00 GB"
+        );
+        assert_eq!(format_size_in(1000, SizeBase::Decimal), "1.00 KB");
+    }
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(
+            format_rate(170 * 1024 * 1024, Duration::from_secs(2)),
+            "85.00 MiB/s"
+        );
+        assert_eq!(format_rate(1024, Duration::ZERO), "n/a");
+    }
 }
@@ -0,0 +1,231 @@
+//! Terminal-free workflows for using TAP as a library.
+//!
+//! The `inspect` and `export` commands wrap these steps in the terminal UI.
+//! Here each step takes a [`Config`], a [`Control`] for pausing or cancelling,
+//! and a progress callback, and returns the statistics without printing,
+//! prompting, or exiting the process.
+//!
+//! Sources must be paths. A device is mounted read-only with [`mount`], which
+//! returns an error where the terminal UI would ask, its mount point is
+//! scanned or exported, and [`unmount`] undoes the mount afterwards.
+
+use color_eyre::eyre::eyre;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::{Config, MountConfig};
+use crate::control::Control;
+use crate::export::{
+    ExportOutputs, ExportStats, export_files, sample_scanned, write_export_outputs,
+};
+use crate::manifest::Manifest;
+use crate::mount::{is_device_path, mount_device, unmount_device};
+use crate::scanner::{ScanStats, scan_directory_with_control};
+
+/// Mounts `device` read-only and returns its mount point.
+///
+/// Nothing is printed and nothing is asked: where `tap` would prompt, the
+/// answer comes from `options` (`assume_yes` to remount a read-write mount
+/// read-only) or the step fails. RAID members must be assembled first.
+pub fn mount(device: &str, options: &MountConfig) -> color_eyre::Result<PathBuf> {
+    mount_device(device, options)
+}
+
+/// Unmounts a mount point returned by [`mount`], if the mount was made there.
+/// A device that was already mounted is left as it was.
+pub fn unmount(mount_point: &Path) -> color_eyre::Result<()> {
+    unmount_device(mount_point)
+}
+
+/// Checks that `source` is an existing path rather than a device to mount.
+pub fn check_source(source: &Path) -> color_eyre::Result<()> {
+    if is_device_path(&source.to_string_lossy()) {
+        return Err(eyre!(
+            "{} is a device; mount it first with workflow::mount and pass the mount point",
+            source.display()
+        ));
+    }
+    if !source.exists() {
+        return Err(eyre!("Source path does not exist: {}", source.display()));
+    }
+    Ok(())
+}
+
+/// Scans `sources` one after another into one set of results.
+///
+/// `progress` is called with the path of each file as it is recorded. Stops
+/// after the first source that is cancelled through `control`.
+pub async fn scan_sources<F>(
+    sources: &[PathBuf],
+    config: &Config,
+    control: &Control,
+    progress: F,
+) -> color_eyre::Result<ScanStats>
+where
+    F: Fn(String) + Clone + Send + Sync + 'static,
+{
+    let mut scan_stats = ScanStats::new();
+    for source in sources {
+        let source_stats =
+            scan_directory_with_control(source, config, control, progress.clone()).await?;
+        scan_stats.merge(source_stats);
+        if scan_stats.cancelled {
+            break;
+        }
+    }
+    Ok(scan_stats)
+}
+
+/// Copies already scanned files into `output_dir`, then writes the manifest,
+/// path map, and log as the `export` command does.
+///
+/// With `export.inventory_only` set, or a cancelled scan, nothing is copied.
+/// `previous` is the manifest of an earlier export; files unchanged since it
+/// are not copied again.
+pub async fn export_scanned<F>(
+    scan_stats: &ScanStats,
+    output_dir: &Path,
+    config: &Config,
+    previous: Option<&Manifest>,
+    control: &Control,
+    progress: F,
+) -> color_eyre::Result<(ExportStats, ExportOutputs)>
+where
+    F: Fn(String) + Send + Sync + 'static,
+{
+    let export_stats = if scan_stats.cancelled {
+        ExportStats {
+            cancelled: true,
+            ..ExportStats::new()
+        }
+    } else if config.export.inventory_only {
+        ExportStats::new()
+    } else {
+        let progress = Arc::new(progress);
+        export_files(
+            scan_stats,
+            output_dir,
+            config,
+            previous,
+            control,
+            None,
+            move |path| {
+                let progress = Arc::clone(&progress);
+                async move { progress(path) }
+            },
+        )
+        .await?
+    };
+
+    let outputs = write_export_outputs(output_dir, scan_stats, &export_stats, config).await?;
+    Ok((export_stats, outputs))
+}
+
+/// Scans `sources` and returns their combined results.
+///
+/// The library counterpart of `tap inspect`.
+pub async fn inspect<F>(
+    sources: &[PathBuf],
+    config: &Config,
+    control: &Control,
+    progress: F,
+) -> color_eyre::Result<ScanStats>
+where
+    F: Fn(String) + Clone + Send + Sync + 'static,
+{
+    for source in sources {
+        check_source(source)?;
+    }
+    scan_sources(sources, config, control, progress).await
+}
+
+/// Scans `source` and exports its files to `output_dir`.
+///
+/// The library counterpart of `tap export` without `--zip`. `progress` is
+/// called for each file scanned and again for each file copied; when
+/// `export.since_manifest` is set, only files changed since it are copied,
+/// and with `export.sample_per_category` only a random sample is.
+pub async fn export<F>(
+    source: &Path,
+    output_dir: &Path,
+    config: &Config,
+    control: &Control,
+    progress: F,
+) -> color_eyre::Result<ExportStats>
+where
+    F: Fn(String) + Clone + Send + Sync + 'static,
+{
+    check_source(source)?;
+    let previous = match &config.export.since_manifest {
+        Some(path) => Some(Manifest::load(path).await?),
+        None => None,
+    };
+
+    let scan_stats = scan_directory_with_control(source, config, control, progress.clone()).await?;
+    let scan_stats = sample_scanned(scan_stats, config);
+    let (export_stats, _) = export_scanned(
+        &scan_stats,
+        output_dir,
+        config,
+        previous.as_ref(),
+        control,
+        progress,
+    )
+    .await?;
+    Ok(export_stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_export_without_terminal() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("source");
+        std::fs::create_dir_all(source.join("docs")).unwrap();
+        std::fs::write(source.join("docs/report.pdf"), b"report").unwrap();
+        std::fs::write(source.join("photo.jpg"), b"photo").unwrap();
+        let output = root.path().join("out");
+
+        let config = Config::default();
+        let control = Control::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let progress = {
+            let calls = Arc::clone(&calls);
+            move |_: String| {
+                calls.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+
+        let scan_stats = inspect(
+            std::slice::from_ref(&source),
+            &config,
+            &control,
+            progress.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(scan_stats.total_files, 2);
+
+        let export_stats = export(&source, &output, &config, &control, progress)
+            .await
+            .unwrap();
+        assert_eq!(export_stats.copied, 2);
+        assert_eq!(export_stats.bytes_copied, 11);
+        assert_eq!(export_stats.bytes_failed, 0);
+        assert!(output.join("documents/report.pdf").is_file());
+        assert!(output.join("manifest.csv").is_file());
+        assert!(output.join("tap.log").is_file());
+        // Two scans and one copy per file
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+
+        let missing = root.path().join("missing");
+        assert!(
+            inspect(&[missing], &config, &control, |_| {})
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,74 +1,697 @@
 //! Archive creation utilities.
 //!
 //! This module provides functionality for creating ZIP archives from directories,
-//! with progress tracking and optimized compression settings.
+//! with progress tracking and optimized compression settings. Archives are
+//! written under a `.partial` name and only renamed into place once their
+//! contents have been checked, so a finished archive is always complete.
+//!
+//! With `--zip-split <SIZE>` the archive is written as numbered volumes
+//! (`export.zip.001`, `export.zip.002`, ...) of at most that size, for media
+//! with a file size limit such as FAT32. The volumes are plain byte ranges of
+//! one ordinary archive, so joining them in order (`cat export.zip.??? >
+//! export.zip`, or `copy /b` on Windows) gives a ZIP any tool can open.
 
+use color_eyre::eyre::eyre;
 use indicatif::ProgressBar;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::task;
 use walkdir::WalkDir;
-use zip::ZipWriter;
 use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::config::ZipConfig;
+use crate::control::Control;
+use crate::throttle::parse_size;
+use crate::tui::Throughput;
+
+/// Smallest volume size accepted by `--zip-split`.
+pub const MIN_SPLIT_SIZE: u64 = 64 * 1024;
+
+/// Parses a `--zip-split` volume size such as `4GB`; used as the clap value parser.
+pub fn parse_split_size(value: &str) -> Result<u64, String> {
+    let size = parse_size(value)?;
+    check_split_size(size)?;
+    Ok(size)
+}
+
+/// Checks that `size` is large enough to be a volume size.
+pub fn check_split_size(size: u64) -> Result<(), String> {
+    if size < MIN_SPLIT_SIZE {
+        return Err(format!(
+            "volume size must be at least {} bytes, found {}",
+            MIN_SPLIT_SIZE, size
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the path of volume `index` (counting from 1) of a split archive: `export.zip.001`.
+pub fn volume_path(archive_path: &Path, index: usize) -> PathBuf {
+    let mut name = archive_path.as_os_str().to_owned();
+    name.push(format!(".{:03}", index));
+    PathBuf::from(name)
+}
+
+/// Returns a shell command that joins the volumes of `archive_path` back into one archive.
+pub fn join_volumes_command(archive_path: &Path) -> String {
+    let archive = archive_path.display();
+    if cfg!(windows) {
+        format!("copy /b \"{}.???\" \"{}\"", archive, archive)
+    } else {
+        format!("cat '{}'.??? > '{}'", archive, archive)
+    }
+}
+
+/// Removes volumes of `archive_path` from `first` on, stopping at the first that is missing.
+fn remove_volumes(archive_path: &Path, first: usize) {
+    let mut index = first;
+    while std::fs::remove_file(volume_path(archive_path, index)).is_ok() {
+        index += 1;
+    }
+}
+
+/// Returns where the archive for `output_dir` is written.
+///
+/// Uses `--zip-path` when given, otherwise `<output dir name>.zip` inside
+/// `zip.output_dir`, otherwise a `.zip` next to `output_dir`.
+pub fn archive_path(output_dir: &Path, config: &ZipConfig) -> PathBuf {
+    if let Some(path) = &config.archive_path {
+        return path.clone();
+    }
 
+    match (&config.output_dir, output_dir.file_name()) {
+        (Some(dir), Some(name)) => dir.join(name).with_extension("zip"),
+        _ => output_dir.with_extension("zip"),
+    }
+}
+
+/// Returns the temporary name an archive is written under until it is verified.
+fn partial_path(archive_path: &Path) -> PathBuf {
+    let mut name = archive_path.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// Compresses `source_dir` into an archive at `archive_path`.
+///
+/// The archive is written to a `.partial` file, checked against the files that
+/// were added, and only then renamed to `archive_path`. On any failure the
+/// partial file is removed and `source_dir` is left untouched.
+///
+/// With a `volume_size`, the archive is split into volumes of that many bytes
+/// named as by [`volume_path`], and older volumes beyond the new last one are
+/// removed.
+///
+/// Returns the files written, in order, or `None` if `control` was cancelled
+/// part way through.
+///
+/// # Errors
+///
+/// Returns an error if writing fails, if `source_dir` contains no files, or if
+/// the finished archive does not list every file at its full size.
 pub async fn zip_directory<F>(
     source_dir: &Path,
+    archive_path: &Path,
+    volume_size: Option<u64>,
     pb: ProgressBar,
+    throughput: Option<Throughput>,
+    control: &Control,
     progress_callback: F,
-) -> color_eyre::Result<PathBuf>
+) -> color_eyre::Result<Option<Vec<PathBuf>>>
 where
     F: Fn(String) + Send + Sync + 'static,
 {
     let source_dir = source_dir.to_path_buf();
+    let archive_path = archive_path.to_path_buf();
+    let control = control.clone();
     let pb = Arc::new(pb);
     let progress_callback = Arc::new(progress_callback);
 
     // Run the blocking zip operation in a separate thread pool
-    let zip_path = task::spawn_blocking(move || -> color_eyre::Result<PathBuf> {
-        // Create zip file path
-        let zip_path = source_dir.with_extension("zip");
-        let file = File::create(&zip_path)?;
-        let file = BufWriter::with_capacity(256 * 1024, file); // 256KB buffer
-        let mut zip = ZipWriter::new(file);
-
-        // Use faster compression with level 6 (good balance of speed/compression)
-        let options = FileOptions::default()
-            .compression_method(zip::CompressionMethod::Deflated)
-            .compression_level(Some(6))
-            .unix_permissions(0o755);
-
-        // Walk through the directory
-        for entry in WalkDir::new(&source_dir).into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path();
-            let name = path.strip_prefix(&source_dir)?;
-
-            if path.is_file() {
-                // Call callback with file path
-                progress_callback(path.display().to_string());
-
-                zip.start_file(name.to_string_lossy().to_string(), options)?;
-
-                // Use buffered reader for better I/O performance
-                let f = File::open(path)?;
-                let mut f = BufReader::with_capacity(128 * 1024, f); // 128KB buffer
-                std::io::copy(&mut f, &mut zip)?;
-
-                // Update progress
-                pb.inc(1);
-            } else if !name.as_os_str().is_empty() {
-                // Add directory entry
-                zip.add_directory(name.to_string_lossy().to_string(), options)?;
-            }
-        }
+    let zip_paths = task::spawn_blocking(move || -> color_eyre::Result<Option<Vec<PathBuf>>> {
+        let partial = partial_path(&archive_path);
 
-        zip.finish()?;
+        let result = write_archive(
+            &source_dir,
+            &partial,
+            volume_size,
+            &pb,
+            throughput.as_ref(),
+            &control,
+            &*progress_callback,
+        )
+        .and_then(|written| match written {
+            Some((volumes, written)) => {
+                match volume_size {
+                    Some(size) => verify_entries(SplitFile::open(&partial, size)?, &written)?,
+                    None => verify_archive(&partial, &written)?,
+                }
+                Ok(Some(volumes))
+            }
+            None => Ok(None),
+        });
         pb.finish_and_clear();
 
-        Ok(zip_path)
+        let remove_partial = || match volume_size {
+            Some(_) => remove_volumes(&partial, 1),
+            None => {
+                let _ = std::fs::remove_file(&partial);
+            }
+        };
+        match result {
+            Ok(Some(_)) if volume_size.is_none() => {
+                if let Err(e) = std::fs::rename(&partial, &archive_path) {
+                    remove_partial();
+                    return Err(e.into());
+                }
+                Ok(Some(vec![archive_path]))
+            }
+            Ok(Some(volumes)) => {
+                let mut paths = Vec::with_capacity(volumes);
+                for index in 1..=volumes {
+                    let path = volume_path(&archive_path, index);
+                    if let Err(e) = std::fs::rename(volume_path(&partial, index), &path) {
+                        // Half the volumes under each name would join into neither archive
+                        remove_volumes(&archive_path, 1);
+                        remove_volumes(&partial, index);
+                        return Err(e.into());
+                    }
+                    paths.push(path);
+                }
+                // Volumes left by an earlier, larger archive would corrupt the joined file
+                remove_volumes(&archive_path, volumes + 1);
+                Ok(Some(paths))
+            }
+            Ok(None) => {
+                remove_partial();
+                Ok(None)
+            }
+            Err(e) => {
+                remove_partial();
+                Err(e)
+            }
+        }
     })
     .await??;
 
-    Ok(zip_path)
+    Ok(zip_paths)
+}
+
+/// Writes every file under `source_dir` into a new archive at `dest`, split
+/// into volumes of `volume_size` bytes if given.
+///
+/// Returns the number of files written and the `(entry name, size)` of each
+/// file added, or `None` if cancelled.
+#[allow(clippy::type_complexity)]
+fn write_archive(
+    source_dir: &Path,
+    dest: &Path,
+    volume_size: Option<u64>,
+    pb: &ProgressBar,
+    throughput: Option<&Throughput>,
+    control: &Control,
+    progress_callback: &dyn Fn(String),
+) -> color_eyre::Result<Option<(usize, Vec<(String, u64)>)>> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    match volume_size {
+        None => {
+            let file = BufWriter::with_capacity(256 * 1024, File::create(dest)?); // 256KB buffer
+            let Some((file, written)) =
+                add_files(source_dir, file, pb, throughput, control, progress_callback)?
+            else {
+                return Ok(None);
+            };
+            let file = file.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            Ok(Some((1, written)))
+        }
+        Some(size) => {
+            // Volumes left by a crashed run would be read back as part of this archive
+            remove_volumes(dest, 1);
+            let volumes = BufWriter::with_capacity(256 * 1024, SplitFile::create(dest, size));
+            let Some((volumes, written)) = add_files(
+                source_dir,
+                volumes,
+                pb,
+                throughput,
+                control,
+                progress_callback,
+            )?
+            else {
+                return Ok(None);
+            };
+            let volumes = volumes.into_inner().map_err(|e| e.into_error())?;
+            volumes.sync_all()?;
+            Ok(Some((volumes.files.len(), written)))
+        }
+    }
+}
+
+/// Adds every file under `source_dir` to a new archive written to `writer`.
+///
+/// Returns the finished writer and the `(entry name, size)` of each file
+/// added, or `None` if cancelled.
+#[allow(clippy::type_complexity)]
+fn add_files<W: Write + Seek>(
+    source_dir: &Path,
+    writer: W,
+    pb: &ProgressBar,
+    throughput: Option<&Throughput>,
+    control: &Control,
+    progress_callback: &dyn Fn(String),
+) -> color_eyre::Result<Option<(W, Vec<(String, u64)>)>> {
+    let mut zip = ZipWriter::new(writer);
+
+    // Use faster compression with level 6 (good balance of speed/compression)
+    let options = FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .compression_level(Some(6))
+        .unix_permissions(0o755);
+
+    let mut written = Vec::new();
+
+    // Walk through the directory
+    for entry in WalkDir::new(source_dir) {
+        // An unreadable entry would be silently missing from the archive
+        let entry = entry?;
+        let path = entry.path();
+        let name = path.strip_prefix(source_dir)?;
+
+        if !control.wait_blocking() {
+            return Ok(None);
+        }
+
+        if entry.file_type().is_file() {
+            // Call callback with file path
+            progress_callback(path.display().to_string());
+
+            let name = name.to_string_lossy().to_string();
+            zip.start_file(name.clone(), options)?;
+
+            // Use buffered reader for better I/O performance
+            let f = File::open(path)?;
+            let mut f = BufReader::with_capacity(128 * 1024, f); // 128KB buffer
+            let size = std::io::copy(&mut f, &mut zip)?;
+            written.push((name, size));
+
+            // Update progress
+            if let Some(throughput) = throughput {
+                throughput.add(size);
+            }
+            pb.inc(1);
+        } else if !name.as_os_str().is_empty() {
+            // Add directory entry
+            zip.add_directory(name.to_string_lossy().to_string(), options)?;
+        }
+    }
+
+    Ok(Some((zip.finish()?, written)))
+}
+
+/// Checks that the archive at `path` lists exactly the `expected` files at their full sizes.
+fn verify_archive(path: &Path, expected: &[(String, u64)]) -> color_eyre::Result<()> {
+    verify_entries(File::open(path)?, expected)
+}
+
+/// Checks that the archive read from `reader` lists exactly the `expected` files at their full sizes.
+fn verify_entries<R: Read + Seek>(reader: R, expected: &[(String, u64)]) -> color_eyre::Result<()> {
+    if expected.is_empty() {
+        return Err(eyre!("no files to archive"));
+    }
+
+    let mut archive = ZipArchive::new(reader)?;
+    let mut entries = HashMap::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        if !file.is_dir() {
+            entries.insert(file.name().to_string(), file.size());
+        }
+    }
+
+    if entries.len() != expected.len() {
+        return Err(eyre!(
+            "archive lists {} files but {} were added",
+            entries.len(),
+            expected.len()
+        ));
+    }
+    for (name, size) in expected {
+        if entries.get(name) != Some(size) {
+            return Err(eyre!("archive entry {} is missing or truncated", name));
+        }
+    }
+
+    Ok(())
+}
+
+/// One archive stored as consecutive volumes of a fixed size.
+///
+/// Byte `n` of the archive lives at offset `n % volume_size` of volume
+/// `n / volume_size`, so the volumes joined in order are the archive itself.
+/// Volumes are created as writes reach them.
+struct SplitFile {
+    base: PathBuf,
+    volume_size: u64,
+    files: Vec<File>,
+    pos: u64,
+    len: u64,
+}
+
+impl SplitFile {
+    /// Starts a new split archive whose volumes are named after `base`.
+    fn create(base: &Path, volume_size: u64) -> Self {
+        Self {
+            base: base.to_path_buf(),
+            volume_size,
+            files: Vec::new(),
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Opens the existing volumes of `base` for reading.
+    fn open(base: &Path, volume_size: u64) -> io::Result<Self> {
+        let mut split = Self::create(base, volume_size);
+        while let Ok(file) = File::open(volume_path(base, split.files.len() + 1)) {
+            split.len += file.metadata()?.len();
+            split.files.push(file);
+        }
+        Ok(split)
+    }
+
+    /// Returns the volume holding the current position and the offset within it.
+    fn locate(&self) -> (usize, u64) {
+        (
+            (self.pos / self.volume_size) as usize,
+            self.pos % self.volume_size,
+        )
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.files.iter().try_for_each(File::sync_all)
+    }
+}
+
+impl Write for SplitFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let (index, offset) = self.locate();
+        while self.files.len() <= index {
+            let path = volume_path(&self.base, self.files.len() + 1);
+            self.files.push(File::create(path)?);
+        }
+
+        let room = (self.volume_size - offset).min(buf.len() as u64) as usize;
+        let file = &mut self.files[index];
+        file.seek(SeekFrom::Start(offset))?;
+        let n = file.write(&buf[..room])?;
+        self.pos += n as u64;
+        self.len = self.len.max(self.pos);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.files.iter_mut().try_for_each(|file| file.flush())
+    }
+}
+
+impl Read for SplitFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let (index, offset) = self.locate();
+        let room = (self.volume_size - offset)
+            .min(self.len - self.pos)
+            .min(buf.len() as u64) as usize;
+        let file = &mut self.files[index];
+        file.seek(SeekFrom::Start(offset))?;
+        let n = file.read(&mut buf[..room])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for SplitFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "seek before start of archive")
+        })?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_path() {
+        let mut config = ZipConfig {
+            enabled: false,
+            compression_level: 6,
+            buffer_size_kb: 256,
+            output_dir: None,
+            archive_path: None,
+            split_size: None,
+        };
+        let output_dir = Path::new("exports").join("case42");
+        assert_eq!(
+            archive_path(&output_dir, &config),
+            Path::new("exports").join("case42.zip")
+        );
+
+        config.output_dir = Some(PathBuf::from("archives"));
+        assert_eq!(
+            archive_path(&output_dir, &config),
+            Path::new("archives").join("case42.zip")
+        );
+
+        config.archive_path = Some(PathBuf::from("evidence.zip"));
+        assert_eq!(
+            archive_path(&output_dir, &config),
+            PathBuf::from("evidence.zip")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zip_directory_writes_verified_archive() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("export");
+        std::fs::create_dir_all(source.join("documents")).unwrap();
+        std::fs::write(source.join("documents/report.pdf"), b"report").unwrap();
+        std::fs::write(source.join("tap.log"), b"log").unwrap();
+
+        let archive = root.path().join("export.zip");
+        let zip_path = zip_directory(
+            &source,
+            &archive,
+            None,
+            ProgressBar::hidden(),
+            None,
+            &Control::new(),
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(zip_path, Some(vec![archive.clone()]));
+        assert!(!partial_path(&archive).exists());
+        assert!(source.join("tap.log").exists());
+
+        let archive = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        assert_eq!(archive.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_zip_directory_failure_keeps_source() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("export");
+        std::fs::create_dir_all(source.join("documents")).unwrap();
+        for name in ["a.pdf", "b.pdf", "c.pdf"] {
+            std::fs::write(source.join("documents").join(name), b"report").unwrap();
+        }
+
+        // The archive cannot be written: its directory is a regular file
+        std::fs::write(root.path().join("not_a_dir"), b"").unwrap();
+        let archive = root.path().join("not_a_dir/export.zip");
+        let result = zip_directory(
+            &source,
+            &archive,
+            None,
+            ProgressBar::hidden(),
+            None,
+            &Control::new(),
+            |_| {},
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(!archive.exists());
+        assert!(!partial_path(&archive).exists());
+        for name in ["a.pdf", "b.pdf", "c.pdf"] {
+            let path = source.join("documents").join(name);
+            assert_eq!(std::fs::read(path).unwrap(), b"report");
+        }
+        let left = std::fs::read_dir(source.join("documents")).unwrap().count();
+        assert_eq!(left, 3);
+
+        // The archive is written in full but cannot take its final name
+        let archive = root.path().join("taken.zip");
+        std::fs::create_dir_all(archive.join("inside")).unwrap();
+        let result = zip_directory(
+            &source,
+            &archive,
+            None,
+            ProgressBar::hidden(),
+            None,
+            &Control::new(),
+            |_| {},
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(!partial_path(&archive).exists());
+        let left = std::fs::read_dir(source.join("documents")).unwrap().count();
+        assert_eq!(left, 3);
+    }
+
+    #[tokio::test]
+    async fn test_zip_directory_refuses_empty_source() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("export");
+        std::fs::create_dir_all(source.join("documents")).unwrap();
+
+        let archive = root.path().join("export.zip");
+        let result = zip_directory(
+            &source,
+            &archive,
+            None,
+            ProgressBar::hidden(),
+            None,
+            &Control::new(),
+            |_| {},
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(!archive.exists());
+        assert!(!partial_path(&archive).exists());
+    }
+
+    #[tokio::test]
+    async fn test_zip_directory_splits_into_volumes() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let source = root.path().join("export");
+        std::fs::create_dir_all(&source).unwrap();
+        // Incompressible data, so the archive spans several volumes
+        let mut seed = 42u32;
+        let data: Vec<u8> = (0..200 * 1024)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        std::fs::write(source.join("image.bin"), &data).unwrap();
+        std::fs::write(source.join("tap.log"), b"log").unwrap();
+
+        // Stale volumes from an earlier, larger archive and a crashed run are removed
+        let archive = root.path().join("export.zip");
+        for index in 1..=6 {
+            std::fs::write(volume_path(&archive, index), b"stale").unwrap();
+            std::fs::write(volume_path(&partial_path(&archive), index), b"stale").unwrap();
+        }
+        let volumes = zip_directory(
+            &source,
+            &archive,
+            Some(MIN_SPLIT_SIZE),
+            ProgressBar::hidden(),
+            None,
+            &Control::new(),
+            |_| {},
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(volumes.len(), 4);
+        assert_eq!(volumes[0], volume_path(&archive, 1));
+        assert!(!volume_path(&archive, 5).exists());
+        assert!(!volume_path(&archive, 6).exists());
+        assert!(!volume_path(&partial_path(&archive), 1).exists());
+        for volume in &volumes[..3] {
+            assert_eq!(std::fs::metadata(volume).unwrap().len(), MIN_SPLIT_SIZE);
+        }
+
+        // The volumes joined in order are an ordinary archive
+        let mut joined = Vec::new();
+        for volume in &volumes {
+            joined.extend(std::fs::read(volume).unwrap());
+        }
+        let mut reader = ZipArchive::new(std::io::Cursor::new(joined)).unwrap();
+        let mut contents = Vec::new();
+        reader
+            .by_name("image.bin")
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        assert_eq!(contents, data);
+
+        // A volume that cannot be renamed into place leaves no volumes under either name
+        for volume in &volumes {
+            std::fs::remove_file(volume).unwrap();
+        }
+        std::fs::create_dir_all(volume_path(&archive, 2).join("occupied")).unwrap();
+        assert!(
+            zip_directory(
+                &source,
+                &archive,
+                Some(MIN_SPLIT_SIZE),
+                ProgressBar::hidden(),
+                None,
+                &Control::new(),
+                |_| {},
+            )
+            .await
+            .is_err()
+        );
+        assert!(!volume_path(&archive, 1).exists());
+        for index in 1..=4 {
+            assert!(!volume_path(&partial_path(&archive), index).exists());
+        }
+
+        assert!(parse_split_size("4GB").is_ok());
+        assert!(parse_split_size("1KiB").is_err());
+    }
+
+    #[test]
+    fn test_verify_archive_detects_truncation() {
+        let root = tempfile::Builder::new().prefix("tap").tempdir().unwrap();
+        let path = root.path().join("test.zip");
+
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("a.txt", FileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut zip, b"abc").unwrap();
+        zip.finish().unwrap();
+
+        assert!(verify_archive(&path, &[("a.txt".to_string(), 3)]).is_ok());
+        assert!(verify_archive(&path, &[("a.txt".to_string(), 4)]).is_err());
+        assert!(
+            verify_archive(&path, &[("a.txt".to_string(), 3), ("b.txt".to_string(), 1)]).is_err()
+        );
+    }
 }